@@ -1,22 +1,33 @@
 use axum::{
     body::Body,
+    extract::DefaultBodyLimit,
     http::{header, HeaderValue, StatusCode},
     middleware::{from_fn, Next},
-    routing::{get, post},
+    routing::{get, post, put},
     Router,
 };
 use std::{convert::Infallible, env, sync::Arc};
 use tower_http::cors::{Any, CorsLayer};
 
-use crate::api::{error::trace_id_middleware, middleware::auth_middleware};
+use crate::api::{error::trace_id_middleware, middleware::auth_middleware, state::AppState};
 use crate::routes::{
     apps::{
-        create_app, delete_app, get_app, get_app_logs, get_app_status, list_apps, recreate_app,
-        start_app, stop_app,
+        create_app, delete_app, get_app, get_app_logs, get_app_stats, get_app_status, kill_app,
+        list_apps, pause_app, recreate_app, rename_app, restart_app, start_app, stop_app,
+        unpause_app, wait_app,
     },
-    health, metrics, version,
+    archive::{get_app_archive, put_app_archive},
+    builds::{create_build, get_build, list_builds},
+    exec::{exec_app, exec_app_ws},
+    health,
+    images::{delete_image, get_image, list_images, prune_images, pull_image},
+    metrics,
+    networks::{create_network, delete_network, get_network, list_networks},
+    registries::{delete_registry, list_registries, put_registry},
+    system_info, version,
+    volumes::{create_volume, delete_volume, get_volume, list_volumes},
 };
-use crate::services::docker::{DockerClient, ShellDockerClient};
+use crate::services::docker::{client_from_env, DockerClient};
 
 #[cfg(all(feature = "openapi", debug_assertions))]
 use crate::openapi::ApiDoc;
@@ -25,9 +36,38 @@ use utoipa::OpenApi;
 #[cfg(all(feature = "openapi", debug_assertions))]
 use utoipa_swagger_ui::SwaggerUi;
 
-/// Builds the API router mounted at `/api/v1`.
+/// Default size limit of uploaded build contexts and archives, in MiB.
+const DEFAULT_UPLOAD_LIMIT_MB: usize = 256;
+
+/// Size limit in bytes of an upload, set in MiB by the `var` environment variable.
+fn upload_limit(var: &str) -> usize {
+    env::var(var)
+        .ok()
+        .and_then(|mb| mb.parse::<usize>().ok())
+        .filter(|mb| *mb > 0)
+        .unwrap_or(DEFAULT_UPLOAD_LIMIT_MB)
+        * 1024
+        * 1024
+}
+
+/// Builds the API router mounted at `/api/v1`, over the client of [`client_from_env`].
+///
+/// # Panics
+/// If the Docker client configuration is invalid, such as an unknown
+/// `DOCKER_BACKEND`; the daemon binary reports it as a startup error instead.
 pub fn router() -> Router {
-    let docker: Arc<dyn DockerClient> = Arc::new(ShellDockerClient);
+    let docker =
+        client_from_env().unwrap_or_else(|e| panic!("Invalid Docker client configuration: {e}"));
+    router_with_client(docker)
+}
+
+/// Builds the API router backed by the given Docker client, without a container cache.
+pub fn router_with_client(docker: Arc<dyn DockerClient>) -> Router {
+    router_with_state(AppState::new(docker))
+}
+
+/// Builds the API router around the given state.
+pub fn router_with_state(state: AppState) -> Router {
     let allowed_origins = env::var("ALLOWED_ORIGINS")
         .ok()
         .map(|val| {
@@ -55,13 +95,53 @@ pub fn router() -> Router {
         .route("/apps/:name/start", post(start_app))
         .route("/apps/:name/stop", post(stop_app))
         .route("/apps/:name/recreate", post(recreate_app))
+        .route("/apps/:name/pause", post(pause_app))
+        .route("/apps/:name/unpause", post(unpause_app))
+        .route("/apps/:name/restart", post(restart_app))
+        .route("/apps/:name/kill", post(kill_app))
+        .route("/apps/:name/rename", post(rename_app))
+        .route("/apps/:name/wait", post(wait_app))
         .route("/apps/:name/logs", get(get_app_logs))
+        .route("/apps/:name/exec", post(exec_app))
+        .route("/apps/:name/exec/ws", get(exec_app_ws))
+        .route("/apps/:name/stats", get(get_app_stats))
         .route("/apps/:name/status", get(get_app_status))
+        .route(
+            "/apps/:name/archive",
+            get(get_app_archive)
+                .put(put_app_archive)
+                .layer(DefaultBodyLimit::max(upload_limit(
+                    "ARCHIVE_UPLOAD_LIMIT_MB",
+                ))),
+        )
+        .route("/images", get(list_images))
+        .route("/images/pull", post(pull_image))
+        .route("/images/prune", post(prune_images))
+        .route("/images/:name", get(get_image).delete(delete_image))
+        .route("/networks", get(list_networks).post(create_network))
+        .route("/networks/:name", get(get_network).delete(delete_network))
+        .route("/volumes", get(list_volumes).post(create_volume))
+        .route("/volumes/:name", get(get_volume).delete(delete_volume))
+        .route(
+            "/builds",
+            get(list_builds)
+                .post(create_build)
+                .layer(DefaultBodyLimit::max(upload_limit(
+                    "BUILD_CONTEXT_LIMIT_MB",
+                ))),
+        )
+        .route("/builds/:id", get(get_build))
+        .route("/registries", get(list_registries))
+        .route(
+            "/registries/:registry",
+            put(put_registry).delete(delete_registry),
+        )
         .route("/health", get(health))
         .route("/version", get(version))
         .route("/metrics", get(metrics))
+        .route("/system/info", get(system_info))
         .layer(cors)
-        .with_state(docker.clone());
+        .with_state(state.clone());
 
     let api = if let Some(origins) = allowed_origins {
         api.layer(from_fn(
@@ -86,7 +166,7 @@ pub fn router() -> Router {
         api
     };
 
-    let app = Router::new().nest("/api/v1", api).with_state(docker);
+    let app = Router::new().nest("/api/v1", api).with_state(state);
     let app = app
         .layer(from_fn(trace_id_middleware))
         .layer(from_fn(auth_middleware));
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+
+use tokio::sync::mpsc;
+
+use crate::{
+    docker::{self, AppInstance, ContainerState, RegistryAuth},
+    errors::Error,
+    services::{
+        builds::{BuildStatus, BuildStore},
+        cache::ContainerCache,
+        credentials::CredentialStore,
+        docker::DockerClient,
+        health::HealthRestarts,
+        locks::{AppLock, AppLocks},
+    },
+};
+
+/// Shared state of the API router.
+///
+/// Reads go through the container cache when one is attached and populated,
+/// and straight to the Docker client otherwise. Registry credentials and
+/// builds are kept in memory unless stores are attached with
+/// [`AppState::with_credentials`] and [`AppState::with_builds`]. Mutations
+/// of an app are serialized through [`AppState::lock`]. Restarts of unhealthy
+/// apps are read from the health supervisor's [`HealthRestarts`].
+#[derive(Clone)]
+pub struct AppState {
+    pub docker: Arc<dyn DockerClient>,
+    pub cache: Option<Arc<ContainerCache>>,
+    pub credentials: Arc<CredentialStore>,
+    pub builds: Arc<BuildStore>,
+    pub locks: Arc<AppLocks>,
+    pub health_restarts: Arc<HealthRestarts>,
+}
+
+impl AppState {
+    /// State without a cache: every read is a Docker call.
+    pub fn new(docker: Arc<dyn DockerClient>) -> Self {
+        Self {
+            docker,
+            cache: None,
+            credentials: Arc::new(CredentialStore::in_memory()),
+            builds: Arc::new(BuildStore::in_memory()),
+            locks: Arc::new(AppLocks::default()),
+            health_restarts: Arc::new(HealthRestarts::default()),
+        }
+    }
+
+    /// State serving reads from `cache`.
+    pub fn with_cache(docker: Arc<dyn DockerClient>, cache: Arc<ContainerCache>) -> Self {
+        Self {
+            cache: Some(cache),
+            ..Self::new(docker)
+        }
+    }
+
+    /// Uses `credentials` for registry logins.
+    pub fn with_credentials(mut self, credentials: Arc<CredentialStore>) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Records builds in `builds`.
+    pub fn with_builds(mut self, builds: Arc<BuildStore>) -> Self {
+        self.builds = builds;
+        self
+    }
+
+    /// Serializes mutations through `locks`.
+    pub fn with_locks(mut self, locks: Arc<AppLocks>) -> Self {
+        self.locks = locks;
+        self
+    }
+
+    /// Reads restarts of unhealthy apps from `restarts`, shared with the health supervisor.
+    pub fn with_health_restarts(mut self, restarts: Arc<HealthRestarts>) -> Self {
+        self.health_restarts = restarts;
+        self
+    }
+
+    /// Waits for the lock of app `name` before running `operation` on it.
+    pub async fn lock(&self, name: &str, operation: &'static str) -> Result<AppLock<'_>, Error> {
+        self.locks.acquire(name, operation).await
+    }
+
+    fn ready_cache(&self) -> Option<&ContainerCache> {
+        self.cache.as_deref().filter(|cache| cache.is_ready())
+    }
+
+    /// Running containers, as listed by `GET /apps`.
+    pub async fn running_apps(&self) -> Result<Vec<AppInstance>, Error> {
+        match self.ready_cache() {
+            Some(cache) => Ok(cache.running()),
+            None => docker::get_running_containers(self.docker.as_ref()).await,
+        }
+    }
+
+    /// A single container, as returned by `GET /apps/{name}`.
+    pub async fn app(&self, name: &str) -> Result<Option<AppInstance>, Error> {
+        match self.ready_cache() {
+            Some(cache) => Ok(cache.get(name)),
+            None => docker::get_container_by_name(self.docker.as_ref(), name).await,
+        }
+    }
+
+    /// State of a container (`running`, `exited`, ...).
+    pub async fn status(&self, name: &str) -> Result<String, Error> {
+        match self.ready_cache() {
+            Some(cache) => cache.status(name).ok_or(Error::ContainerNotFound),
+            None => docker::get_container_status(self.docker.as_ref(), name).await,
+        }
+    }
+
+    /// Runtime state of a container, health included.
+    pub async fn container_state(&self, name: &str) -> Result<ContainerState, Error> {
+        match self.ready_cache() {
+            Some(cache) => cache.state(name).ok_or(Error::ContainerNotFound),
+            None => docker::get_container_state(self.docker.as_ref(), name).await,
+        }
+    }
+
+    /// Credentials `namespace` presents when pulling `image`.
+    pub fn registry_auth(&self, namespace: &str, image: &str) -> Option<RegistryAuth> {
+        self.credentials.auth_for(namespace, image)
+    }
+
+    /// Pulls `image` with the namespace's credentials when it is missing locally.
+    ///
+    /// Without credentials for its registry, the image is left to the
+    /// anonymous pull the backend performs when creating the container.
+    pub async fn ensure_image(&self, namespace: &str, image: &str) -> Result<(), Error> {
+        let Some(auth) = self.registry_auth(namespace, image) else {
+            return Ok(());
+        };
+        match self.docker.inspect_image(image).await {
+            Err(Error::ImageNotFound) => {
+                let (tx, _) = mpsc::channel(1);
+                self.docker.pull_image(image, Some(&auth), tx).await
+            }
+            Err(e) => Err(e),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    /// Image of a succeeded build of `namespace`, by build id.
+    pub fn built_image(&self, namespace: &str, build: &str) -> Result<String, Error> {
+        let record = self.builds.get(namespace, build)?;
+        match record.status {
+            BuildStatus::Succeeded => Ok(record.tag),
+            BuildStatus::Running => Err(Error::Conflict(format!("Build {build} is still running"))),
+            BuildStatus::Failed => Err(Error::Conflict(format!(
+                "Build {build} failed: {}",
+                record.error.unwrap_or_default()
+            ))),
+        }
+    }
+
+    /// Refreshes the cached entry after a mutation, so that the caller
+    /// reads its own writes without waiting for the events stream.
+    pub async fn refresh(&self, name: &str) {
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.refresh(name).await {
+                tracing::debug!(error = %e, container = name, "Failed to refresh cached container");
+            }
+        }
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn DockerClient> {
+    fn from_ref(state: &AppState) -> Self {
+        state.docker.clone()
+    }
+}
@@ -1,62 +1,45 @@
-use std::{collections::HashMap, process::Command};
+use std::collections::HashMap;
 
 use crate::{errors::Error, services::docker::DockerClient};
 
 use super::{
-    models::{AppInstance, AppStatus},
+    models::{
+        sort_ports, AppInstance, AppStatus, ContainerExit, ContainerInspect, ContainerState,
+        HealthCheck, HealthStatus, NetworkAttachment, PortMapping, ProcessOverrides,
+        ResourceLimits, SecurityOptions, WaitCondition, DEFAULT_NETWORKS,
+    },
     ContainerConfig,
 };
 
 /// Recreates a Docker container by name: stops, deletes, and restarts it with same config.
 ///
+/// Port mappings are kept as requested, host addresses and protocols included.
+/// Network attachments are kept, aliases included; a container only on the
+/// default network lands back on it. Resource limits, the health check, the
+/// command, entrypoint, working directory, user and hostname overrides, and the
+/// security options are kept as well; the seccomp profile is the daemon's
+/// current one, and whatever was not overridden follows the image.
+///
 /// # Arguments
 /// - `name`: The container to recreate
 ///
 /// # Returns
 /// - `Ok(container_id)` if successful
 /// - `Err(Error)` if failed
-pub fn recreate_container(client: &dyn DockerClient, name: &str) -> Result<String, Error> {
-    let output = client.inspect(name)?;
-    let container: Vec<serde_json::Value> =
-        serde_json::from_str(&output).map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+pub async fn recreate_container(client: &dyn DockerClient, name: &str) -> Result<String, Error> {
+    let cfg = client.inspect(name).await?;
 
-    if container.is_empty() {
-        return Err(Error::ContainerNotFound);
+    let image = cfg.config.image.as_str();
+    if image.is_empty() {
+        return Err(Error::DockerOutputParse("Missing image".into()));
     }
 
-    let cfg = &container[0];
-
-    let image = cfg["Config"]["Image"]
-        .as_str()
-        .ok_or_else(|| Error::DockerOutputParse("Missing image".into()))?;
-
-    let ports = cfg["NetworkSettings"]["Ports"]
-        .as_object()
-        .ok_or_else(|| Error::DockerOutputParse("Missing ports".into()))?;
+    let ports = port_mappings(&cfg);
 
-    let container_port = ports
-        .keys()
-        .filter_map(|k| k.split('/').next())
-        .filter_map(|p| p.parse::<u16>().ok())
-        .next()
-        .ok_or_else(|| Error::DockerOutputParse("No container port found".into()))?;
-
-    let host_ports: Vec<u16> = ports
-        .values()
-        .filter_map(|v| v.as_array())
-        .flatten()
-        .filter_map(|binding| binding["HostPort"].as_str()?.parse().ok())
-        .collect();
-
-    let labels = cfg["Config"]["Labels"].as_object().map(|map| {
-        map.iter()
-            .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
-            .collect::<HashMap<String, String>>()
-    });
+    let labels = cfg.config.labels.clone();
 
-    let env_vars = cfg["Config"]["Env"].as_array().map(|vars| {
+    let env_vars = cfg.config.env.as_ref().map(|vars| {
         vars.iter()
-            .filter_map(|v| v.as_str())
             .filter_map(|kv| {
                 let mut split = kv.splitn(2, '=');
                 let k = split.next()?;
@@ -66,70 +49,109 @@ pub fn recreate_container(client: &dyn DockerClient, name: &str) -> Result<Strin
             .collect::<HashMap<String, String>>()
     });
 
-    let volumes = cfg["HostConfig"]["Binds"].as_array().map(|items| {
-        items
-            .iter()
-            .filter_map(|v| v.as_str())
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>()
-    });
-
-    let restart_policy = cfg["HostConfig"]["RestartPolicy"]["Name"]
-        .as_str()
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string());
+    let volumes = cfg.host_config.binds.clone();
+
+    let restart_policy =
+        Some(cfg.host_config.restart_policy.name.as_str()).filter(|s| !s.is_empty());
+
+    let networks = network_attachments(&cfg);
+
+    let limits = ResourceLimits::from_host_config(&cfg.host_config);
+    let healthcheck = HealthCheck::from_config(&cfg.config);
+    let process = ProcessOverrides::from_inspect(&cfg);
+    let security = SecurityOptions::from_host_config(&cfg.host_config);
+
+    client.remove(name).await?;
+
+    client
+        .run(ContainerConfig {
+            name,
+            image,
+            ports: &ports,
+            labels: labels.as_ref(),
+            env: env_vars.as_ref(),
+            volumes: volumes.as_ref(),
+            restart_policy,
+            networks: &networks,
+            limits: Some(&limits).filter(|limits| !limits.is_empty()),
+            healthcheck: healthcheck.as_ref(),
+            process: Some(&process).filter(|process| !process.is_empty()),
+            security: Some(&security).filter(|security| !security.is_empty()),
+        })
+        .await
+}
 
-    remove_container(name)?;
+/// User-defined networks of an inspected container, sorted by name.
+///
+/// The short container id, which engines before API 1.44 add to the aliases, is left out.
+pub fn network_attachments(container: &ContainerInspect) -> Vec<NetworkAttachment> {
+    let mut networks: Vec<NetworkAttachment> = container
+        .network_settings
+        .networks
+        .iter()
+        .flatten()
+        .filter(|(network, _)| !DEFAULT_NETWORKS.contains(&network.as_str()))
+        .map(|(network, endpoint)| NetworkAttachment {
+            name: network.clone(),
+            aliases: endpoint
+                .aliases
+                .iter()
+                .flatten()
+                .filter(|alias| !(alias.len() == 12 && container.id.starts_with(alias.as_str())))
+                .cloned()
+                .collect(),
+        })
+        .collect();
+    networks.sort_by(|a, b| a.name.cmp(&b.name));
+    networks
+}
 
-    client.run(ContainerConfig {
-        name,
-        image,
-        host_ports: &host_ports,
-        container_port,
-        labels: labels.as_ref(),
-        env: env_vars.as_ref(),
-        volumes: volumes.as_ref(),
-        restart_policy: restart_policy.as_deref(),
-    })
+/// Published ports of an inspected container, sorted.
+///
+/// Read from the requested bindings, or from the network settings for
+/// engines that do not report them, where the wildcard addresses Docker
+/// binds for "every interface" are folded back into an unset `host_ip`.
+pub fn port_mappings(container: &ContainerInspect) -> Vec<PortMapping> {
+    if let Some(bindings) = &container.host_config.port_bindings {
+        return PortMapping::from_bindings(bindings);
+    }
+    let Some(bindings) = &container.network_settings.ports else {
+        return vec![];
+    };
+    let mut mappings = PortMapping::from_bindings(bindings);
+    for mapping in &mut mappings {
+        if matches!(mapping.host_ip.as_deref(), Some("0.0.0.0" | "::")) {
+            mapping.host_ip = None;
+        }
+    }
+    sort_ports(&mut mappings);
+    mappings
 }
 
-/// Lists running Docker containers using `docker ps`.
+/// Lists running containers through the Docker client.
 ///
 /// # Returns
 /// - `Ok(Vec<AppInstance>)` containing all running containers
-/// - `Err(Error)` if the Docker command fails
-pub fn get_running_containers() -> Result<Vec<AppInstance>, Error> {
-    let output = Command::new("docker")
-        .args([
-            "ps",
-            "--format",
-            "{{.ID}};{{.Names}};{{.Image}};{{.Status}};{{.Ports}}",
-        ])
-        .output()
-        .map_err(|_| Error::DockerCommandFailed)?;
-
-    if !output.status.success() {
-        return Err(Error::DockerCommandFailed);
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let containers = stdout
-        .lines()
+/// - `Err(Error)` if the Docker call fails
+pub async fn get_running_containers(client: &dyn DockerClient) -> Result<Vec<AppInstance>, Error> {
+    let containers = client
+        .list(false)
+        .await?
+        .into_iter()
         .enumerate()
-        .filter_map(|(idx, line)| {
-            let parts: Vec<&str> = line.split(';').collect();
-            if parts.len() < 5 {
-                return None;
-            }
-
-            Some(AppInstance {
-                id: idx as u32 + 1,
-                name: parts[1].to_string(),
-                image: parts[2].to_string(),
-                status: parse_status(parts[3]),
-                ports: parse_ports(parts[4]),
-                created_at: "".to_string(),
-            })
+        .map(|(idx, c)| AppInstance {
+            id: idx as u32 + 1,
+            name: c.name,
+            image: c.image,
+            status: AppStatus::from_state(&c.state),
+            ports: c.ports,
+            created_at: "".to_string(),
+            limits: ResourceLimits::default(),
+            health: None,
+            port_mappings: vec![],
+            process: ProcessOverrides::default(),
+            security: SecurityOptions::default(),
+            seccomp: None,
         })
         .collect();
 
@@ -145,59 +167,49 @@ pub fn get_running_containers() -> Result<Vec<AppInstance>, Error> {
 /// - `Ok(Some(AppInstance))` if found
 /// - `Ok(None)` if not found
 /// - `Err(Error)` if an error occurred
-pub fn get_container_by_name(
+pub async fn get_container_by_name(
     client: &dyn DockerClient,
     name: &str,
 ) -> Result<Option<AppInstance>, Error> {
-    let output = match client.inspect(name) {
-        Ok(o) => o,
-        Err(Error::ContainerNotFound) => return Ok(None),
-        Err(e) => return Err(e),
-    };
-
-    let containers: Vec<serde_json::Value> =
-        serde_json::from_str(&output).map_err(|e| Error::DockerOutputParse(e.to_string()))?;
-
-    if containers.is_empty() {
-        return Ok(None);
+    match client.inspect(name).await {
+        Ok(container) => Ok(Some(app_from_inspect(&container))),
+        Err(Error::ContainerNotFound) => Ok(None),
+        Err(e) => Err(e),
     }
+}
 
-    let container = &containers[0];
-    let name = container["Name"]
-        .as_str()
-        .unwrap_or_default()
-        .trim_start_matches('/')
-        .to_string();
-    let image = container["Config"]["Image"]
-        .as_str()
-        .unwrap_or_default()
-        .to_string();
-    let created_at = container["Created"]
-        .as_str()
-        .unwrap_or_default()
-        .to_string();
-
-    let ports = if let Some(ports) = container["NetworkSettings"]["Ports"].as_object() {
-        ports
-            .keys()
-            .filter_map(|k| {
-                k.split('/')
-                    .next()
-                    .and_then(|port| port.parse::<u16>().ok())
-            })
-            .collect()
-    } else {
-        vec![]
-    };
+/// Builds the `GET /apps/{name}` view of an inspected container.
+///
+/// `ports` lists the container ports and `port_mappings` how they are
+/// published; `id` is left at 0. Limits are the ones the engine enforces, and
+/// the process settings are the overrides recorded at creation.
+pub fn app_from_inspect(container: &ContainerInspect) -> AppInstance {
+    let ports = container
+        .network_settings
+        .ports
+        .iter()
+        .flat_map(|ports| ports.keys())
+        .filter_map(|k| {
+            k.split('/')
+                .next()
+                .and_then(|port| port.parse::<u16>().ok())
+        })
+        .collect();
 
-    Ok(Some(AppInstance {
+    AppInstance {
         id: 0,
-        name,
-        status: AppStatus::Running,
-        image,
+        name: container.name.trim_start_matches('/').to_string(),
+        status: AppStatus::from_state(&container.state.status),
+        image: container.config.image.clone(),
         ports,
-        created_at,
-    }))
+        created_at: container.created.clone(),
+        limits: ResourceLimits::from_host_config(&container.host_config),
+        health: HealthStatus::from_state(&container.state),
+        port_mappings: port_mappings(container),
+        process: ProcessOverrides::from_inspect(container),
+        security: SecurityOptions::from_host_config(&container.host_config),
+        seccomp: SecurityOptions::seccomp(&container.host_config),
+    }
 }
 
 /// Returns the status of a container by name using `docker inspect`.
@@ -208,99 +220,48 @@ pub fn get_container_by_name(
 /// # Returns
 /// - `Ok(status)` if found (e.g., "running", "exited", etc.)
 /// - `Err(ContainerNotFound)` if not found
-pub fn get_container_status(client: &dyn DockerClient, name: &str) -> Result<String, Error> {
-    let output = client.inspect(name)?;
-    let containers: Vec<serde_json::Value> =
-        serde_json::from_str(&output).map_err(|e| Error::DockerOutputParse(e.to_string()))?;
-
-    if containers.is_empty() {
-        return Err(Error::ContainerNotFound);
-    }
-
-    let status = containers[0]["State"]["Status"]
-        .as_str()
-        .unwrap_or_default()
-        .to_string();
-    Ok(status)
+pub async fn get_container_status(client: &dyn DockerClient, name: &str) -> Result<String, Error> {
+    Ok(client.inspect(name).await?.state.status)
 }
 
-/// Removes a Docker container by name.
-///
-/// # Arguments
-/// - `name`: The container name to remove.
+/// Returns the runtime state of a container by name, health included.
 ///
 /// # Returns
-/// - `Ok(())` if deleted successfully
-/// - `Err(Error)` if failed
-pub fn remove_container(name: &str) -> Result<(), Error> {
-    let output = Command::new("docker")
-        .args(["rm", "-f", name])
-        .output()
-        .map_err(|_| Error::DockerCommandFailed)?;
-
-    let stderr = String::from_utf8_lossy(&output.stderr);
-
-    if stderr.contains("No such container") || stderr.contains("Error: No such container") {
-        return Err(Error::ContainerNotFound);
-    }
-
-    if output.status.success() {
-        Ok(())
-    } else {
-        Err(Error::Unexpected(stderr.trim().to_string()))
-    }
+/// - `Ok(state)` if found
+/// - `Err(ContainerNotFound)` if not found
+pub async fn get_container_state(
+    client: &dyn DockerClient,
+    name: &str,
+) -> Result<ContainerState, Error> {
+    Ok(client.inspect(name).await?.state)
 }
 
-/// Fetch the logs of a container using `docker logs`.
-///
-/// # Arguments
-/// - `name`: Container name.
+/// Waits for a container to meet `condition`, then reports how it ended.
 ///
 /// # Returns
-/// - `Ok(logs)` if successful.
-/// - `Err(Error)` if failed.
-pub fn get_container_logs(name: &str) -> Result<String, Error> {
-    let output = Command::new("docker")
-        .args(["logs", name])
-        .output()
-        .map_err(|_| Error::DockerCommandFailed)?;
-
-    if output.status.success() {
-        let logs = String::from_utf8_lossy(&output.stdout).to_string();
-        Ok(logs)
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
-        if stderr.contains("no such container") {
-            Err(Error::ContainerNotFound)
-        } else {
-            Err(Error::Unexpected(stderr.trim().to_string()))
+/// - `Ok(exit)` with the exit code, OOM flag and finish time
+/// - `Err(ContainerNotFound)` if the container doesn't exist
+pub async fn wait_container(
+    client: &dyn DockerClient,
+    name: &str,
+    condition: WaitCondition,
+) -> Result<ContainerExit, Error> {
+    let exit_code = client.wait(name, condition).await?;
+    // A removed container cannot be inspected anymore.
+    let state = match client.inspect(name).await {
+        Ok(container) => container.state,
+        Err(Error::ContainerNotFound) => {
+            return Ok(ContainerExit {
+                exit_code,
+                ..Default::default()
+            })
         }
-    }
-}
-
-/// Parses the status string from `docker ps` into an `AppStatus`.
-fn parse_status(status: &str) -> AppStatus {
-    if status.contains("Up") {
-        AppStatus::Running
-    } else if status.contains("Exited") {
-        AppStatus::Stopped
-    } else {
-        AppStatus::Error
-    }
-}
-
-/// Parses the ports string from `docker ps` into a list of `u16` host ports.
-fn parse_ports(ports_info: &str) -> Vec<u16> {
-    ports_info
-        .split(',')
-        .filter_map(|entry| {
-            let parts: Vec<&str> = entry.trim().split("->").collect();
-            if !parts.is_empty() {
-                if let Some(port_part) = parts[0].split(':').next_back() {
-                    return port_part.parse::<u16>().ok();
-                }
-            }
-            None
-        })
-        .collect()
+        Err(e) => return Err(e),
+    };
+    Ok(ContainerExit {
+        exit_code,
+        oom_killed: state.oom_killed,
+        finished_at: Some(state.finished_at)
+            .filter(|at| !at.is_empty() && !at.starts_with("0001-")),
+    })
 }
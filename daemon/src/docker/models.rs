@@ -1,17 +1,940 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use serde::{Deserialize, Serialize};
 
+use crate::errors::Error;
+
+/// Restart policies accepted by the Docker engine.
+pub const RESTART_POLICIES: [&str; 4] = ["no", "always", "on-failure", "unless-stopped"];
+
+/// Networks containers land on when created without any, as named by Docker and Podman.
+pub const DEFAULT_NETWORKS: [&str; 2] = ["bridge", "podman"];
+
+/// Oldest Engine API version the daemon talks to.
+pub const MIN_API_VERSION: &str = "1.41";
+
 /// Container creation parameters
+#[derive(Default, Serialize)]
 pub struct ContainerConfig<'a> {
     pub name: &'a str,
     pub image: &'a str,
-    pub host_ports: &'a [u16],
-    pub container_port: u16,
+    /// Published ports; nothing is published when empty.
+    pub ports: &'a [PortMapping],
     pub labels: Option<&'a HashMap<String, String>>,
     pub env: Option<&'a HashMap<String, String>>,
     pub volumes: Option<&'a Vec<String>>,
     pub restart_policy: Option<&'a str>,
+    /// Networks to attach, the default network when empty.
+    pub networks: &'a [NetworkAttachment],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits: Option<&'a ResourceLimits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<&'a HealthCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process: Option<&'a ProcessOverrides>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security: Option<&'a SecurityOptions>,
+}
+
+impl ContainerConfig<'_> {
+    /// Checks the parameters that every backend relies on before
+    /// anything is sent to the container engine.
+    pub fn validate(&self) -> Result<(), Error> {
+        for (i, port) in self.ports.iter().enumerate() {
+            port.validate()?;
+            let clash = self.ports[..i].iter().any(|other| {
+                other.host == port.host
+                    && other.protocol == port.protocol
+                    && (other.host_ip.is_none()
+                        || port.host_ip.is_none()
+                        || other.host_ip == port.host_ip)
+            });
+            if clash {
+                return Err(Error::BadRequest(format!(
+                    "Host port {}/{} is published twice",
+                    port.host,
+                    port.protocol.as_str()
+                )));
+            }
+        }
+
+        if let Some(vols) = self.volumes {
+            for v in vols {
+                if !v.contains(':') || v.starts_with(':') || v.ends_with(':') {
+                    return Err(Error::BadRequest(format!("Invalid volume format: '{v}'")));
+                }
+            }
+        }
+
+        if let Some(policy) = self.restart_policy {
+            if !RESTART_POLICIES.contains(&policy) {
+                return Err(Error::InvalidRequest(format!(
+                    "Invalid restart policy: '{policy}'"
+                )));
+            }
+        }
+
+        for (i, network) in self.networks.iter().enumerate() {
+            network.validate()?;
+            if self.networks[..i].iter().any(|n| n.name == network.name) {
+                return Err(Error::BadRequest(format!(
+                    "Network '{}' is listed twice",
+                    network.name
+                )));
+            }
+        }
+
+        if let Some(limits) = self.limits {
+            limits.validate()?;
+        }
+
+        if let Some(healthcheck) = self.healthcheck {
+            healthcheck.validate()?;
+        }
+
+        if let Some(process) = self.process {
+            process.validate()?;
+        }
+
+        if let Some(security) = self.security {
+            security.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Smallest memory limit the engine accepts, in bytes.
+pub const MIN_MEMORY: u64 = 6 * 1024 * 1024;
+
+/// Largest CPU weight the kernel accepts.
+const MAX_CPU_SHARES: u64 = 262_144;
+
+/// CPU, memory and process limits of an app; unset limits are unlimited.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ResourceLimits {
+    /// CPUs the app may use, possibly fractional (`1.5`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    /// CPU weight relative to the other apps under contention (2 to 262144, 1024 by default).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<u64>,
+    /// Hard memory limit, in bytes; the app is OOM-killed past it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<u64>,
+    /// Soft memory limit, in bytes, enforced when the host runs short of memory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reservation: Option<u64>,
+    /// Memory plus swap, in bytes, or `-1` for unlimited swap; requires `memory`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_swap: Option<i64>,
+    /// Maximum number of processes in the container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pids_limit: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// `true` when no limit is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Rejects limits the engine would refuse, or that contradict each other.
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(cpus) = self.cpus {
+            if !cpus.is_finite() || cpus < 0.01 {
+                return Err(Error::BadRequest(format!(
+                    "Invalid cpus {cpus}: must be at least 0.01"
+                )));
+            }
+        }
+        if let Some(shares) = self.cpu_shares {
+            if !(2..=MAX_CPU_SHARES).contains(&shares) {
+                return Err(Error::BadRequest(format!(
+                    "Invalid cpu_shares {shares}: must be between 2 and {MAX_CPU_SHARES}"
+                )));
+            }
+        }
+        for (field, value) in [
+            ("memory", self.memory),
+            ("memory_reservation", self.memory_reservation),
+        ] {
+            if let Some(bytes) = value.filter(|bytes| *bytes < MIN_MEMORY) {
+                return Err(Error::BadRequest(format!(
+                    "Invalid {field} {bytes}: must be at least {MIN_MEMORY} bytes"
+                )));
+            }
+        }
+        if let (Some(memory), Some(reservation)) = (self.memory, self.memory_reservation) {
+            if reservation > memory {
+                return Err(Error::BadRequest(
+                    "memory_reservation must not exceed memory".into(),
+                ));
+            }
+        }
+        if let Some(swap) = self.memory_swap {
+            let Some(memory) = self.memory else {
+                return Err(Error::BadRequest("memory_swap requires memory".into()));
+            };
+            if swap != -1 && (swap < 0 || (swap as u64) < memory) {
+                return Err(Error::BadRequest(format!(
+                    "Invalid memory_swap {swap}: must be -1 or at least memory"
+                )));
+            }
+        }
+        if self.pids_limit == Some(0) {
+            return Err(Error::BadRequest(
+                "Invalid pids_limit 0: must be at least 1".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Limits an inspected container runs with; the engine reports unset ones as `0`.
+    ///
+    /// Podman reports CPU limits as a quota over a period rather than nano CPUs.
+    pub fn from_host_config(host: &HostConfig) -> Self {
+        let positive = |value: i64| u64::try_from(value).ok().filter(|v| *v > 0);
+        let cpus = if host.nano_cpus > 0 {
+            Some(host.nano_cpus as f64 / 1e9)
+        } else if host.cpu_quota > 0 && host.cpu_period > 0 {
+            Some(host.cpu_quota as f64 / host.cpu_period as f64)
+        } else {
+            None
+        };
+        Self {
+            cpus,
+            cpu_shares: positive(host.cpu_shares),
+            memory: positive(host.memory),
+            memory_reservation: positive(host.memory_reservation),
+            memory_swap: Some(host.memory_swap).filter(|swap| *swap != 0),
+            pids_limit: host.pids_limit.and_then(positive),
+        }
+    }
+
+    /// Nano CPUs, the unit the Engine API takes CPU limits in.
+    pub fn nano_cpus(&self) -> Option<i64> {
+        self.cpus.map(|cpus| (cpus * 1e9).round() as i64)
+    }
+}
+
+/// How the process of an app is started, overriding the image defaults when set.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProcessOverrides {
+    /// Arguments replacing the image's `CMD`, passed to the entrypoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+    /// Executable and arguments replacing the image's `ENTRYPOINT`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<Vec<String>>,
+    /// Absolute directory the process starts in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    /// `user`, `user:group`, `uid` or `uid:gid` the process runs as.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Hostname of the container, its short id by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+}
+
+impl ProcessOverrides {
+    /// `true` when nothing is overridden.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Rejects empty commands, relative working directories and invalid hostnames.
+    pub fn validate(&self) -> Result<(), Error> {
+        for (field, args) in [("command", &self.command), ("entrypoint", &self.entrypoint)] {
+            if args.as_ref().is_some_and(|args| args.is_empty()) {
+                return Err(Error::BadRequest(format!("{field} must not be empty")));
+            }
+        }
+        if let Some(dir) = self
+            .working_dir
+            .as_deref()
+            .filter(|dir| !dir.starts_with('/'))
+        {
+            return Err(Error::BadRequest(format!(
+                "Invalid working_dir '{dir}': must be an absolute path"
+            )));
+        }
+        if let Some(user) = self.user.as_deref() {
+            if user.is_empty() || user.contains(char::is_whitespace) {
+                return Err(Error::BadRequest(format!("Invalid user '{user}'")));
+            }
+        }
+        if let Some(hostname) = self.hostname.as_deref() {
+            let valid_label = |label: &str| {
+                (1..=63).contains(&label.len())
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            };
+            if hostname.len() > 253 || !hostname.split('.').all(valid_label) {
+                return Err(Error::BadRequest(format!("Invalid hostname '{hostname}'")));
+            }
+        }
+        Ok(())
+    }
+
+    /// Value of the [`PROCESS_LABEL`] recording these overrides.
+    pub fn to_label(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Overrides an inspected container was created with, read from its [`PROCESS_LABEL`].
+    ///
+    /// Engines report the image's `CMD`, `ENTRYPOINT`, `WORKDIR` and `USER`
+    /// merged with the overrides, so the inspected values cannot tell them
+    /// apart. Containers without the label are taken to override nothing.
+    pub fn from_inspect(container: &ContainerInspect) -> Self {
+        container
+            .config
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(PROCESS_LABEL))
+            .and_then(|label| serde_json::from_str(label).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Label recording, as JSON, the process overrides an app was created with.
+pub const PROCESS_LABEL: &str = "lightshuttle.process";
+
+/// Security options of an app, on top of the daemon-wide seccomp profile.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SecurityOptions {
+    /// Capabilities added to the engine's default set, such as `NET_ADMIN`, or `ALL`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cap_add: Vec<String>,
+    /// Capabilities removed from the engine's default set, or `ALL`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cap_drop: Vec<String>,
+    /// Mounts the root filesystem read-only.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    /// Keeps processes from gaining privileges, through setuid binaries for instance.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_new_privileges: bool,
+    /// tmpfs mounts by absolute path, with their mount options (`size=64m,mode=1777`) or `""`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tmpfs: BTreeMap<String, String>,
+    /// AppArmor profile, the engine's default when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apparmor: Option<String>,
+}
+
+impl SecurityOptions {
+    /// `true` when no option is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Capabilities spelled as the engine reports them: upper case, `CAP_` prefixed.
+    pub fn normalized(&self) -> Self {
+        let normalize = |caps: &[String]| {
+            caps.iter()
+                .map(|cap| {
+                    let cap = cap.to_ascii_uppercase();
+                    if cap == "ALL" || cap.starts_with("CAP_") {
+                        cap
+                    } else {
+                        format!("CAP_{cap}")
+                    }
+                })
+                .collect()
+        };
+        Self {
+            cap_add: normalize(&self.cap_add),
+            cap_drop: normalize(&self.cap_drop),
+            ..self.clone()
+        }
+    }
+
+    /// Rejects malformed capabilities, tmpfs mounts and AppArmor profiles.
+    pub fn validate(&self) -> Result<(), Error> {
+        for cap in self.cap_add.iter().chain(&self.cap_drop) {
+            let name = cap.strip_prefix("CAP_").unwrap_or(cap);
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(Error::BadRequest(format!("Invalid capability '{cap}'")));
+            }
+        }
+        for (path, options) in &self.tmpfs {
+            if !path.starts_with('/') || path.contains(':') {
+                return Err(Error::BadRequest(format!(
+                    "Invalid tmpfs path '{path}': must be an absolute path"
+                )));
+            }
+            if options.contains(char::is_whitespace) {
+                return Err(Error::BadRequest(format!(
+                    "Invalid tmpfs options '{options}' for {path}"
+                )));
+            }
+        }
+        if let Some(profile) = &self.apparmor {
+            if profile.is_empty() || profile.contains(|c: char| c.is_whitespace() || c == '=') {
+                return Err(Error::BadRequest(format!(
+                    "Invalid apparmor profile '{profile}'"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Options an inspected container runs with.
+    ///
+    /// Read from the capabilities, the root filesystem mode, the tmpfs
+    /// mounts and the `apparmor` and `no-new-privileges` security options;
+    /// the seccomp profile is the daemon's, not the app's.
+    pub fn from_host_config(host: &HostConfig) -> Self {
+        let mut security = Self {
+            cap_add: host.cap_add.clone().unwrap_or_default(),
+            cap_drop: host.cap_drop.clone().unwrap_or_default(),
+            read_only: host.readonly_rootfs,
+            tmpfs: host
+                .tmpfs
+                .iter()
+                .flatten()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            ..Default::default()
+        };
+        // Docker takes `key=value` as well as the older `key:value`.
+        for option in host.security_opt.iter().flatten() {
+            let (key, value) = option
+                .split_once(['=', ':'])
+                .map_or((option.as_str(), None), |(k, v)| (k, Some(v)));
+            match key {
+                "no-new-privileges" => security.no_new_privileges = value != Some("false"),
+                "apparmor" => security.apparmor = value.map(String::from),
+                _ => {}
+            }
+        }
+        security.normalized()
+    }
+
+    /// Seccomp profile an inspected container runs with: `unconfined`,
+    /// `custom`, or unset for the engine's default.
+    pub fn seccomp(host: &HostConfig) -> Option<String> {
+        let profile = host.security_opt.iter().flatten().find_map(|option| {
+            option
+                .strip_prefix("seccomp=")
+                .or_else(|| option.strip_prefix("seccomp:"))
+        })?;
+        let mode = match profile {
+            "unconfined" => "unconfined",
+            _ => "custom",
+        };
+        Some(mode.to_string())
+    }
+}
+
+/// Label set on apps created with a health check, recording after how many
+/// failed checks the daemon restarts them when unhealthy.
+pub const HEALTH_RESTART_LABEL: &str = "lightshuttle.health.restart-after";
+
+/// Failed checks in a row before an app turns unhealthy, unless set otherwise.
+pub const DEFAULT_HEALTH_RETRIES: u32 = 3;
+
+/// Health check of an app, run inside the container by the engine.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HealthCheck {
+    /// Command run by the container's shell; exiting with 0 means healthy.
+    pub command: String,
+    /// Seconds between two checks (30 by default).
+    pub interval: Option<u64>,
+    /// Seconds a check may run before it counts as failed (30 by default).
+    pub timeout: Option<u64>,
+    /// Failed checks in a row before the app turns unhealthy (3 by default).
+    pub retries: Option<u32>,
+    /// Seconds after start during which failed checks do not count (0 by default).
+    pub start_period: Option<u64>,
+    /// Failed checks in a row after which the daemon restarts the unhealthy
+    /// app (`retries` by default); `0` never restarts it.
+    pub restart_after: Option<u32>,
+}
+
+impl HealthCheck {
+    /// Rejects an empty command and zero durations or retries.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.command.trim().is_empty() {
+            return Err(Error::BadRequest(
+                "healthcheck command must not be empty".into(),
+            ));
+        }
+        for (field, value) in [("interval", self.interval), ("timeout", self.timeout)] {
+            if value == Some(0) {
+                return Err(Error::BadRequest(format!(
+                    "Invalid healthcheck {field} 0: must be at least 1 second"
+                )));
+            }
+        }
+        if self.retries == Some(0) {
+            return Err(Error::BadRequest(
+                "Invalid healthcheck retries 0: must be at least 1".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Failed checks in a row after which the daemon restarts the app, `0` for never.
+    pub fn restart_after(&self) -> u32 {
+        self.restart_after
+            .or(self.retries)
+            .unwrap_or(DEFAULT_HEALTH_RETRIES)
+    }
+
+    /// Engine form of the check, with durations in nanoseconds; `0` keeps the engine default.
+    pub fn to_config(&self) -> HealthConfig {
+        let nanos = |secs: Option<u64>| secs.map_or(0, |secs| secs as i64 * 1_000_000_000);
+        HealthConfig {
+            test: Some(vec!["CMD-SHELL".to_string(), self.command.clone()]),
+            interval: nanos(self.interval),
+            timeout: nanos(self.timeout),
+            start_period: nanos(self.start_period),
+            retries: self.retries.map_or(0, i64::from),
+        }
+    }
+
+    /// Health check an inspected container was created with, if any.
+    ///
+    /// Inspect reports the image's `HEALTHCHECK` in the same shape as one set
+    /// at creation, so only containers carrying the [`HEALTH_RESTART_LABEL`]
+    /// are taken to have their own; the image applies its check again on its own.
+    pub fn from_config(config: &ContainerInspectConfig) -> Option<Self> {
+        let restart_after = config
+            .labels
+            .as_ref()?
+            .get(HEALTH_RESTART_LABEL)?
+            .parse()
+            .ok();
+        let healthcheck = config.healthcheck.as_ref()?;
+        let command = match healthcheck.test.as_deref()? {
+            [kind, command] if kind == "CMD-SHELL" => command.clone(),
+            _ => return None,
+        };
+        let secs = |nanos: i64| u64::try_from(nanos / 1_000_000_000).ok().filter(|s| *s > 0);
+        Some(Self {
+            command,
+            interval: secs(healthcheck.interval),
+            timeout: secs(healthcheck.timeout),
+            retries: u32::try_from(healthcheck.retries).ok().filter(|r| *r > 0),
+            start_period: secs(healthcheck.start_period),
+            restart_after,
+        })
+    }
+}
+
+/// Health of a container with a health check.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// No check has succeeded yet.
+    Starting,
+    Healthy,
+    /// The last `retries` checks failed.
+    Unhealthy,
+}
+
+impl HealthStatus {
+    /// Maps the engine health status; `none` and unknown values mean no health check.
+    pub fn from_state(state: &ContainerState) -> Option<Self> {
+        match state.health.as_ref()?.status.as_str() {
+            "starting" => Some(Self::Starting),
+            "healthy" => Some(Self::Healthy),
+            "unhealthy" => Some(Self::Unhealthy),
+            _ => None,
+        }
+    }
+}
+
+/// Network an app is attached to, with the extra names it answers to on it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NetworkAttachment {
+    /// Network name or id.
+    pub name: String,
+    /// DNS names of the app on this network, besides its container name.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl NetworkAttachment {
+    /// Rejects empty network names and aliases that are not DNS names.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::BadRequest("Network name must not be empty".into()));
+        }
+        if let Some(alias) = self.aliases.iter().find(|alias| !is_dns_name(alias)) {
+            return Err(Error::BadRequest(format!(
+                "Invalid alias '{alias}' on network '{}'",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Checks a new container name, made of letters, digits, `-`, `_` and `.`.
+pub fn validate_container_name(name: &str) -> Result<(), Error> {
+    if is_dns_name(name) {
+        Ok(())
+    } else {
+        Err(Error::BadRequest(format!(
+            "Invalid container name '{name}'"
+        )))
+    }
+}
+
+/// Checks a signal given by name (`SIGHUP`, `HUP`, `SIGRTMIN+3`) or number (`9`).
+pub fn validate_signal(signal: &str) -> Result<(), Error> {
+    let valid = !signal.is_empty()
+        && signal.len() <= 32
+        && signal
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::BadRequest(format!("Invalid signal '{signal}'")))
+    }
+}
+
+/// `true` for names made of letters, digits, `-`, `_` and `.`, starting with a letter or digit.
+fn is_dns_name(name: &str) -> bool {
+    name.len() <= 253
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Transport protocol of a published port.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum PortProtocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl PortProtocol {
+    /// Name used by the engines (`tcp`, `udp`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PortProtocol::Tcp => "tcp",
+            PortProtocol::Udp => "udp",
+        }
+    }
+}
+
+/// Container port published on a host port.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PortMapping {
+    pub host: u16,
+    /// Port the app listens on inside the container.
+    pub container: u16,
+    /// `tcp` by default.
+    #[serde(default)]
+    pub protocol: PortProtocol,
+    /// Host address to bind, every interface when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_ip: Option<String>,
+}
+
+impl PortMapping {
+    /// TCP mapping of `host` to `container` on every interface.
+    pub const fn tcp(host: u16, container: u16) -> Self {
+        Self {
+            host,
+            container,
+            protocol: PortProtocol::Tcp,
+            host_ip: None,
+        }
+    }
+
+    /// `<container>/<protocol>`, the key engines group port bindings by.
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.container, self.protocol.as_str())
+    }
+
+    /// Rejects port 0 and host addresses that are not IP addresses.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.host == 0 || self.container == 0 {
+            return Err(Error::BadRequest(format!(
+                "Invalid port mapping {}:{}: ports must be between 1 and 65535",
+                self.host, self.container
+            )));
+        }
+        if let Some(ip) = &self.host_ip {
+            if ip.parse::<std::net::IpAddr>().is_err() {
+                return Err(Error::BadRequest(format!("Invalid host_ip '{ip}'")));
+            }
+        }
+        Ok(())
+    }
+
+    /// Mappings of engine port bindings keyed by `<port>/<protocol>`, sorted.
+    ///
+    /// Exposed but unpublished ports, and protocols other than TCP and UDP,
+    /// are left out; an empty host address means every interface.
+    pub fn from_bindings(bindings: &HashMap<String, Option<Vec<PortBinding>>>) -> Vec<Self> {
+        let mut mappings: Vec<Self> = bindings
+            .iter()
+            .filter_map(|(key, bindings)| {
+                let (port, protocol) = key.split_once('/').unwrap_or((key, "tcp"));
+                let container = port.parse::<u16>().ok()?;
+                let protocol = match protocol {
+                    "tcp" => PortProtocol::Tcp,
+                    "udp" => PortProtocol::Udp,
+                    _ => return None,
+                };
+                Some(bindings.iter().flatten().filter_map(move |binding| {
+                    Some(Self {
+                        host: binding.host_port.parse().ok()?,
+                        container,
+                        protocol,
+                        host_ip: Some(binding.host_ip.clone()).filter(|ip| !ip.is_empty()),
+                    })
+                }))
+            })
+            .flatten()
+            .collect();
+        sort_ports(&mut mappings);
+        mappings
+    }
+}
+
+/// Sorts mappings by container port, protocol, host address and host port, without duplicates.
+pub fn sort_ports(mappings: &mut Vec<PortMapping>) {
+    mappings.sort_by(|a, b| {
+        (a.container, a.protocol, &a.host_ip, a.host).cmp(&(
+            b.container,
+            b.protocol,
+            &b.host_ip,
+            b.host,
+        ))
+    });
+    mappings.dedup();
+}
+
+/// Published port of an app: a mapping, or a bare host port forwarded to
+/// the request's `container_port` over TCP.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(untagged)]
+pub enum PortSpec {
+    Host(u16),
+    Mapping(PortMapping),
+}
+
+impl PortSpec {
+    /// Mappings of `specs`; bare host ports require `container_port`.
+    pub fn resolve(
+        specs: &[PortSpec],
+        container_port: Option<u16>,
+    ) -> Result<Vec<PortMapping>, Error> {
+        specs
+            .iter()
+            .map(|spec| match spec {
+                PortSpec::Mapping(mapping) => Ok(mapping.clone()),
+                PortSpec::Host(host) => container_port
+                    .map(|container| PortMapping::tcp(*host, container))
+                    .ok_or_else(|| {
+                        Error::BadRequest(format!(
+                            "Host port {host} needs container_port, or a {{host, container}} mapping"
+                        ))
+                    }),
+            })
+            .collect()
+    }
+}
+
+/// Named volume mounted by an app, as an alternative to a `source:target[:ro]` string.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(untagged)]
+pub enum VolumeMount {
+    /// `host_path:target[:options]` or `volume:target[:options]`.
+    Bind(String),
+    Volume {
+        /// Name of the volume, created on first use.
+        volume: String,
+        /// Absolute path inside the container.
+        target: String,
+        #[serde(default)]
+        read_only: bool,
+    },
+}
+
+impl VolumeMount {
+    /// `source:target[:ro]` form understood by every backend.
+    pub fn to_bind(&self) -> Result<String, Error> {
+        match self {
+            VolumeMount::Bind(bind) => Ok(bind.clone()),
+            VolumeMount::Volume {
+                volume,
+                target,
+                read_only,
+            } => {
+                if !is_dns_name(volume) {
+                    return Err(Error::BadRequest(format!("Invalid volume name '{volume}'")));
+                }
+                if !target.starts_with('/') || target.contains(':') {
+                    return Err(Error::BadRequest(format!(
+                        "Invalid mount target '{target}': expected an absolute path"
+                    )));
+                }
+                let options = if *read_only { ":ro" } else { "" };
+                Ok(format!("{volume}:{target}{options}"))
+            }
+        }
+    }
+}
+
+/// Name of the named volume a `source:target[:options]` mount refers to;
+/// `None` for bind mounts of host paths.
+///
+/// # Examples
+/// ```
+/// use lightshuttle_core::docker::volume_name;
+///
+/// assert_eq!(volume_name("pgdata:/var/lib/postgresql/data:ro"), Some("pgdata"));
+/// assert_eq!(volume_name("/srv/www:/usr/share/nginx/html"), None);
+/// ```
+pub fn volume_name(mount: &str) -> Option<&str> {
+    let source = mount.split(':').next()?;
+    is_dns_name(source).then_some(source)
+}
+
+/// Parameters of a named volume.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct VolumeConfig {
+    pub name: String,
+    /// Volume driver; `local` when unset.
+    pub driver: Option<String>,
+    /// Options passed to the driver, such as `type`, `device` and `o` for `local`.
+    pub driver_opts: BTreeMap<String, String>,
+    pub labels: BTreeMap<String, String>,
+}
+
+impl VolumeConfig {
+    /// Rejects names, drivers, options and labels no backend accepts.
+    pub fn validate(&self) -> Result<(), Error> {
+        if !is_dns_name(&self.name) {
+            return Err(Error::BadRequest(format!(
+                "Invalid volume name '{}'",
+                self.name
+            )));
+        }
+        if let Some(driver) = &self.driver {
+            if driver.is_empty() || !driver.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(Error::BadRequest(format!("Invalid driver '{driver}'")));
+            }
+        }
+        let invalid_key = |key: &String| key.is_empty() || key.contains(['=', ',']);
+        if let Some(key) = self.driver_opts.keys().find(|k| invalid_key(k)) {
+            return Err(Error::BadRequest(format!("Invalid driver option '{key}'")));
+        }
+        if let Some(key) = self.labels.keys().find(|k| invalid_key(k)) {
+            return Err(Error::BadRequest(format!("Invalid label '{key}'")));
+        }
+        Ok(())
+    }
+}
+
+/// Typed subset of the `docker volume inspect` / `GET /volumes/{name}` output,
+/// which libpod shares.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct VolumeInfo {
+    pub name: String,
+    pub driver: String,
+    /// Path of the volume data on the host.
+    pub mountpoint: String,
+    /// Creation time, RFC 3339.
+    #[serde(rename = "CreatedAt")]
+    pub created: String,
+    pub labels: Option<BTreeMap<String, String>>,
+    /// Driver options.
+    pub options: Option<BTreeMap<String, String>>,
+}
+
+/// Disk usage of a named volume and the containers mounting it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VolumeUsage {
+    /// Size on disk in bytes, when the engine computed it.
+    pub size: Option<u64>,
+    /// Names of the containers, in any state, mounting the volume.
+    pub apps: Vec<String>,
+}
+
+/// Parameters of a user-defined network.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct NetworkConfig {
+    pub name: String,
+    /// Network driver; the engine default (`bridge`) when unset.
+    pub driver: Option<String>,
+    /// IPv4 or IPv6 subnet in CIDR notation; allocated by the engine when unset.
+    pub subnet: Option<String>,
+    /// Cut the network off from the outside world.
+    pub internal: bool,
+}
+
+impl NetworkConfig {
+    /// Rejects names, drivers and subnets no backend accepts.
+    pub fn validate(&self) -> Result<(), Error> {
+        if !is_dns_name(&self.name) {
+            return Err(Error::BadRequest(format!(
+                "Invalid network name '{}'",
+                self.name
+            )));
+        }
+        if let Some(driver) = &self.driver {
+            if driver.is_empty() || !driver.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(Error::BadRequest(format!("Invalid driver '{driver}'")));
+            }
+        }
+        if let Some(subnet) = &self.subnet {
+            let valid = subnet.split_once('/').is_some_and(|(ip, prefix)| {
+                let max = match ip.parse::<std::net::IpAddr>() {
+                    Ok(std::net::IpAddr::V4(_)) => 32,
+                    Ok(std::net::IpAddr::V6(_)) => 128,
+                    Err(_) => return false,
+                };
+                prefix.parse::<u8>().is_ok_and(|p| p <= max)
+            });
+            if !valid {
+                return Err(Error::BadRequest(format!(
+                    "Invalid subnet '{subnet}': expected CIDR notation"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// User-defined or built-in network of the engine.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NetworkInfo {
+    pub id: String,
+    pub name: String,
+    pub driver: String,
+    /// First subnet of the network, in CIDR notation.
+    pub subnet: Option<String>,
+    pub internal: bool,
+    /// Creation time, RFC 3339.
+    pub created: String,
 }
 
 /// Represents an application instance (a running Docker container).
@@ -24,6 +947,24 @@ pub struct AppInstance {
     pub image: String,
     pub ports: Vec<u16>,
     pub created_at: String,
+    /// Effective resource limits, when known.
+    #[serde(flatten)]
+    pub limits: ResourceLimits,
+    /// Set when the app has a health check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthStatus>,
+    /// Published ports, when known.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub port_mappings: Vec<PortMapping>,
+    /// Command, entrypoint, working directory, user and hostname, when known.
+    #[serde(flatten)]
+    pub process: ProcessOverrides,
+    /// Capabilities, read-only root filesystem, tmpfs mounts and the like, when known.
+    #[serde(flatten)]
+    pub security: SecurityOptions,
+    /// Seccomp profile: `custom` for the daemon's, `unconfined`, or unset for the engine's default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seccomp: Option<String>,
 }
 
 /// Represents the status of an application.
@@ -35,3 +976,714 @@ pub enum AppStatus {
     Stopped,
     Error,
 }
+
+impl AppStatus {
+    /// Maps a Docker container state (`running`, `exited`, ...) to an `AppStatus`.
+    pub fn from_state(state: &str) -> Self {
+        match state {
+            "running" | "restarting" | "paused" => AppStatus::Running,
+            "created" | "exited" => AppStatus::Stopped,
+            _ => AppStatus::Error,
+        }
+    }
+}
+
+/// Container entry returned by `DockerClient::list`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ContainerSummary {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    /// Machine-readable state (`running`, `exited`, ...).
+    pub state: String,
+    /// Human-readable status (`Up 5 minutes`, ...).
+    pub status: String,
+    /// Published host ports.
+    pub ports: Vec<u16>,
+    pub labels: HashMap<String, String>,
+    /// Names of the attached networks.
+    pub networks: Vec<String>,
+}
+
+/// Resource usage snapshot of a container.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ContainerStats {
+    /// CPU usage, 100 = one full core.
+    pub cpu_percent: f64,
+    /// Memory in use, page cache excluded (bytes).
+    pub memory_usage: u64,
+    /// Memory limit (bytes); the host memory when unlimited.
+    pub memory_limit: u64,
+    /// Bytes received over all interfaces.
+    pub network_rx: u64,
+    /// Bytes sent over all interfaces.
+    pub network_tx: u64,
+    /// Bytes read from block devices.
+    pub block_read: u64,
+    /// Bytes written to block devices.
+    pub block_write: u64,
+    /// Number of processes and threads.
+    pub pids: u64,
+}
+
+/// Command run inside an existing container.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ExecConfig {
+    /// Program and arguments; not interpreted by a shell.
+    pub cmd: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub working_dir: Option<String>,
+    pub user: Option<String>,
+    /// Allocate a pseudo-terminal (stdout and stderr are then merged).
+    pub tty: bool,
+}
+
+impl ExecConfig {
+    /// Rejects an empty command line.
+    pub fn validate(&self) -> Result<(), Error> {
+        match self.cmd.first() {
+            Some(program) if !program.trim().is_empty() => Ok(()),
+            _ => Err(Error::BadRequest("cmd must not be empty".into())),
+        }
+    }
+
+    /// `KEY=value` pairs, sorted for stable output.
+    pub fn env_pairs(&self) -> Vec<String> {
+        let mut pairs: Vec<String> = self.env.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        pairs.sort();
+        pairs
+    }
+}
+
+/// Result of a non-interactive exec.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: i64,
+}
+
+/// Image stored by the engine.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ImageSummary {
+    pub id: String,
+    /// `repository:tag` references; empty for dangling images.
+    pub tags: Vec<String>,
+    /// Size in bytes.
+    pub size: u64,
+    /// Creation time, RFC 3339.
+    pub created: String,
+}
+
+impl ImageSummary {
+    /// Whether a container image reference (tag, digest or id) designates this image.
+    ///
+    /// Short names are compared in their canonical form, so `nginx`,
+    /// `nginx:latest` and `docker.io/library/nginx:latest` all match.
+    pub fn matches(&self, reference: &str) -> bool {
+        let id = self.id.trim_start_matches("sha256:");
+        let reference_id = reference.trim_start_matches("sha256:");
+        if reference_id.len() >= 12 && id.starts_with(reference_id) {
+            return true;
+        }
+        let reference = canonical_reference(reference);
+        self.tags
+            .iter()
+            .any(|tag| canonical_reference(tag) == reference)
+    }
+}
+
+/// Expands an image reference to `registry/repository:tag` form.
+pub fn canonical_reference(reference: &str) -> String {
+    let (name, suffix) = match reference.split_once('@') {
+        Some((name, digest)) => (name, format!("@{digest}")),
+        None => match reference.rsplit_once(':') {
+            Some((name, tag)) if !tag.contains('/') => (name, format!(":{tag}")),
+            _ => (reference, ":latest".to_string()),
+        },
+    };
+    let first = name.split('/').next().unwrap_or_default();
+    let has_registry =
+        name.contains('/') && (first.contains('.') || first.contains(':') || first == "localhost");
+    let name = match (has_registry, name.contains('/')) {
+        (true, _) => name.to_string(),
+        (false, true) => format!("docker.io/{name}"),
+        (false, false) => format!("docker.io/library/{name}"),
+    };
+    format!("{name}{suffix}")
+}
+
+/// Registry host of an image reference, `docker.io` for Docker Hub images.
+pub fn registry_of(reference: &str) -> String {
+    canonical_reference(reference)
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Credentials presented to a registry when pulling.
+///
+/// `Debug` never prints the password.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RegistryAuth {
+    pub username: String,
+    pub password: String,
+    /// Registry host, `ghcr.io` or `registry.example.com:5000`.
+    pub server_address: String,
+}
+
+impl std::fmt::Debug for RegistryAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegistryAuth")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .field("server_address", &self.server_address)
+            .finish()
+    }
+}
+
+impl RegistryAuth {
+    /// Value of the `X-Registry-Auth` header: base64url-encoded JSON.
+    pub fn header(&self) -> String {
+        use base64::Engine as _;
+        let json = serde_json::json!({
+            "username": self.username,
+            "password": self.password,
+            "serveraddress": self.server_address,
+        });
+        base64::engine::general_purpose::URL_SAFE.encode(json.to_string())
+    }
+
+    /// `auths` entry of a Docker CLI `config.json`: base64 of `user:password`.
+    pub fn basic(&self) -> String {
+        use base64::Engine as _;
+        base64::engine::general_purpose::STANDARD
+            .encode(format!("{}:{}", self.username, self.password))
+    }
+
+    /// Key of the registry in CLI and build configs; Docker Hub keeps its legacy index URL.
+    pub fn config_key(&self) -> &str {
+        match self.server_address.as_str() {
+            "docker.io" => "https://index.docker.io/v1/",
+            other => other,
+        }
+    }
+
+    /// Value of the `X-Registry-Config` build header: base64url-encoded JSON
+    /// of every login, keyed by registry.
+    pub fn config_header(auths: &[RegistryAuth]) -> String {
+        use base64::Engine as _;
+        let config: serde_json::Map<String, serde_json::Value> = auths
+            .iter()
+            .map(|auth| {
+                let login = serde_json::json!({
+                    "username": auth.username,
+                    "password": auth.password,
+                    "serveraddress": auth.server_address,
+                });
+                (auth.config_key().to_string(), login)
+            })
+            .collect();
+        base64::engine::general_purpose::URL_SAFE
+            .encode(serde_json::Value::Object(config).to_string())
+    }
+}
+
+/// Typed subset of the `docker image inspect` / `GET /images/{name}/json` output.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ImageInspect {
+    pub id: String,
+    pub repo_tags: Option<Vec<String>>,
+    pub repo_digests: Option<Vec<String>>,
+    pub created: String,
+    pub size: u64,
+    pub architecture: String,
+    pub os: String,
+    pub config: Option<ImageInspectConfig>,
+}
+
+/// Default container configuration baked into an image.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ImageInspectConfig {
+    pub env: Option<Vec<String>>,
+    pub cmd: Option<Vec<String>>,
+    pub entrypoint: Option<Vec<String>>,
+    pub working_dir: String,
+    pub user: String,
+    pub exposed_ports: Option<HashMap<String, serde_json::Value>>,
+    pub labels: Option<HashMap<String, String>>,
+}
+
+impl ImageInspect {
+    /// Summary view of the inspected image; `<none>` tags are dropped.
+    pub fn summary(&self) -> ImageSummary {
+        ImageSummary {
+            id: self.id.clone(),
+            tags: self
+                .repo_tags
+                .iter()
+                .flatten()
+                .filter(|tag| !tag.contains("<none>"))
+                .cloned()
+                .collect(),
+            size: self.size,
+            created: self.created.clone(),
+        }
+    }
+}
+
+/// Images removed by a prune.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PruneReport {
+    /// Ids and references of the removed images.
+    pub deleted: Vec<String>,
+    /// Disk space freed, in bytes.
+    pub space_reclaimed: u64,
+}
+
+/// Progress message emitted while pulling an image.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PullProgress {
+    /// Layer the message is about, if any.
+    pub layer: Option<String>,
+    /// `Downloading`, `Extracting`, `Pull complete`, ...
+    pub status: String,
+    /// Bytes transferred so far for the layer.
+    pub current: Option<u64>,
+    /// Total bytes of the layer.
+    pub total: Option<u64>,
+}
+
+impl PullProgress {
+    /// Parses a line of the Engine API pull stream.
+    ///
+    /// Returns `Err` with the message when the line reports a failure.
+    pub fn from_message(line: &str) -> Option<Result<Self, String>> {
+        let event: serde_json::Value = serde_json::from_str(line).ok()?;
+        if let Some(err) = event["error"].as_str().filter(|e| !e.is_empty()) {
+            return Some(Err(err.to_string()));
+        }
+        let status = event["status"].as_str()?;
+        let detail = &event["progressDetail"];
+        Some(Ok(Self {
+            layer: event["id"].as_str().map(str::to_string),
+            status: status.to_string(),
+            current: detail["current"].as_u64(),
+            total: detail["total"].as_u64(),
+        }))
+    }
+}
+
+/// Version and capabilities of the container engine.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EngineInfo {
+    /// Engine release, e.g. `27.3.1`.
+    pub version: String,
+    /// Highest Engine API version the engine speaks, e.g. `1.47`.
+    pub api_version: String,
+    pub os: String,
+    pub arch: String,
+    /// Storage driver of images and containers, e.g. `overlay2`.
+    pub storage_driver: String,
+    /// `1` or `2`; `null` when the engine does not report it.
+    pub cgroup_version: Option<String>,
+    /// Whether the engine runs without root privileges.
+    pub rootless: bool,
+    /// Security features enabled on the engine: `seccomp`, `apparmor`, `selinux`, ...
+    pub security_options: Vec<String>,
+}
+
+impl EngineInfo {
+    /// Builds the info from the bodies of the Engine API `/version` and `/info`
+    /// endpoints, which `docker version` and `docker info` print as JSON too.
+    pub fn from_json(version: &serde_json::Value, info: &serde_json::Value) -> Self {
+        let text = |value: &serde_json::Value| value.as_str().unwrap_or_default().to_string();
+        let security_options: Vec<String> = info["SecurityOptions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|option| option.as_str())
+            .map(|option| {
+                // `name=seccomp,profile=builtin`, or a bare name before API 1.24.
+                option
+                    .split(',')
+                    .find_map(|field| field.strip_prefix("name="))
+                    .unwrap_or(option)
+                    .to_string()
+            })
+            .collect();
+        Self {
+            version: text(&version["Version"]),
+            api_version: text(&version["ApiVersion"]),
+            os: text(&version["Os"]),
+            arch: text(&version["Arch"]),
+            storage_driver: text(&info["Driver"]),
+            cgroup_version: info["CgroupVersion"]
+                .as_str()
+                .filter(|v| !v.is_empty())
+                .map(str::to_string),
+            rootless: security_options.iter().any(|option| option == "rootless"),
+            security_options,
+        }
+    }
+
+    /// Whether the engine speaks [`MIN_API_VERSION`]; unparsable versions are not.
+    pub fn is_supported(&self) -> bool {
+        let parse = |version: &str| {
+            let (major, minor) = version.trim_start_matches('v').split_once('.')?;
+            Some((major.parse::<u32>().ok()?, minor.parse::<u32>().ok()?))
+        };
+        match (parse(&self.api_version), parse(MIN_API_VERSION)) {
+            (Some(version), Some(min)) => version >= min,
+            _ => false,
+        }
+    }
+}
+
+/// Parameters of an image build, besides its context.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct BuildOptions {
+    /// Reference the built image is tagged with.
+    pub tag: String,
+    /// Path of the Dockerfile inside the context.
+    pub dockerfile: String,
+    /// Stage of a multi-stage Dockerfile to build; the last one by default.
+    pub target: Option<String>,
+    /// `ARG` values, by name.
+    pub build_args: BTreeMap<String, String>,
+}
+
+impl BuildOptions {
+    /// Rejects tags, Dockerfile paths and argument names no backend accepts.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.tag.is_empty() || self.tag.contains(char::is_whitespace) {
+            return Err(Error::BadRequest(format!("Invalid tag '{}'", self.tag)));
+        }
+        let dockerfile = std::path::Path::new(&self.dockerfile);
+        if self.dockerfile.is_empty()
+            || dockerfile.is_absolute()
+            || dockerfile
+                .components()
+                .any(|c| c == std::path::Component::ParentDir)
+        {
+            return Err(Error::BadRequest(format!(
+                "Invalid dockerfile '{}': expected a path inside the context",
+                self.dockerfile
+            )));
+        }
+        if self.target.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            return Err(Error::BadRequest("target must not be empty".into()));
+        }
+        if let Some(name) = self
+            .build_args
+            .keys()
+            .find(|name| name.is_empty() || name.contains(|c: char| c == '=' || c.is_whitespace()))
+        {
+            return Err(Error::BadRequest(format!(
+                "Invalid build arg name '{name}'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Value of the Engine API `buildargs` parameter: a JSON object.
+    pub fn build_args_json(&self) -> String {
+        serde_json::to_string(&self.build_args).unwrap_or_default()
+    }
+}
+
+/// Message of the Engine API and libpod build streams.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildMessage {
+    /// Build output, possibly several lines.
+    Output(String),
+    /// Id of the built image, sent once the build succeeded.
+    ImageId(String),
+}
+
+impl BuildMessage {
+    /// Parses a line of a build stream.
+    ///
+    /// Returns `Err` with the message when the line reports a failure.
+    ///
+    /// # Examples
+    /// ```
+    /// use lightshuttle_core::docker::BuildMessage;
+    ///
+    /// let line = r#"{"aux":{"ID":"sha256:1f2e"}}"#;
+    /// assert_eq!(
+    ///     BuildMessage::from_message(line),
+    ///     Some(Ok(BuildMessage::ImageId("sha256:1f2e".into())))
+    /// );
+    /// ```
+    pub fn from_message(line: &str) -> Option<Result<Self, String>> {
+        let message: serde_json::Value = serde_json::from_str(line).ok()?;
+        if let Some(err) = message["error"].as_str().filter(|e| !e.is_empty()) {
+            return Some(Err(err.trim().to_string()));
+        }
+        if let Some(id) = message["aux"]["ID"].as_str() {
+            return Some(Ok(Self::ImageId(id.to_string())));
+        }
+        message["stream"]
+            .as_str()
+            .map(|output| Ok(Self::Output(output.to_string())))
+    }
+
+    /// Non-blank lines of an output message, without trailing whitespace.
+    pub fn lines(output: &str) -> impl Iterator<Item = &str> {
+        output
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.trim().is_empty())
+    }
+}
+
+/// State a container is waited for.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum WaitCondition {
+    /// Not running, which is immediate for a container that already exited.
+    #[default]
+    NotRunning,
+    /// Exited after the wait started.
+    NextExit,
+    /// Removed.
+    Removed,
+}
+
+impl WaitCondition {
+    /// Value of the Engine API `condition` parameter.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WaitCondition::NotRunning => "not-running",
+            WaitCondition::NextExit => "next-exit",
+            WaitCondition::Removed => "removed",
+        }
+    }
+}
+
+/// How a waited container ended.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ContainerExit {
+    /// Exit code of the main process.
+    pub exit_code: i64,
+    /// Whether the kernel killed the container for running out of memory.
+    pub oom_killed: bool,
+    /// Time the container exited, RFC 3339; `null` once it has been removed.
+    pub finished_at: Option<String>,
+}
+
+/// Container lifecycle event from the engine events stream.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContainerEvent {
+    pub id: String,
+    pub name: String,
+    /// Engine action (`create`, `start`, `die`, `destroy`, `rename`, ...).
+    pub action: String,
+    /// Previous name, set on `rename` events.
+    pub old_name: Option<String>,
+    /// Exit code of the main process, set on `die` events.
+    pub exit_code: Option<i64>,
+}
+
+impl ContainerEvent {
+    /// Parses a Docker-style event message (`{"Type", "Action", "Actor"}`).
+    ///
+    /// The Engine API, libpod and `docker events --format '{{json .}}'` all
+    /// emit this shape. Returns `None` for non-container events.
+    ///
+    /// # Examples
+    /// ```
+    /// use lightshuttle_core::docker::ContainerEvent;
+    ///
+    /// let line = r#"{"Type":"container","Action":"die","Actor":{"ID":"abc","Attributes":{"name":"web"}}}"#;
+    /// let event = ContainerEvent::from_message(line).unwrap();
+    /// assert_eq!((event.name.as_str(), event.action.as_str()), ("web", "die"));
+    /// ```
+    pub fn from_message(line: &str) -> Option<Self> {
+        let message: serde_json::Value = serde_json::from_str(line).ok()?;
+        if message["Type"] != "container" {
+            return None;
+        }
+        let attributes = &message["Actor"]["Attributes"];
+        Some(Self {
+            id: message["Actor"]["ID"].as_str()?.to_string(),
+            name: attributes["name"].as_str().unwrap_or_default().to_string(),
+            action: message["Action"].as_str()?.to_string(),
+            old_name: attributes["oldName"]
+                .as_str()
+                .map(|name| name.trim_start_matches('/').to_string()),
+            exit_code: attributes["exitCode"]
+                .as_str()
+                .and_then(|code| code.parse().ok()),
+        })
+    }
+}
+
+/// Typed subset of the `docker inspect` / `GET /containers/{id}/json` output.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ContainerInspect {
+    pub id: String,
+    pub name: String,
+    pub created: String,
+    pub state: ContainerState,
+    pub config: ContainerInspectConfig,
+    pub host_config: HostConfig,
+    pub network_settings: NetworkSettings,
+}
+
+/// Runtime state of an inspected container.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ContainerState {
+    pub status: String,
+    pub running: bool,
+    pub exit_code: i64,
+    #[serde(rename = "OOMKilled")]
+    pub oom_killed: bool,
+    /// RFC 3339; `0001-01-01T00:00:00Z` until the container first exits.
+    pub finished_at: String,
+    /// Set when the container has a health check.
+    pub health: Option<ContainerHealth>,
+}
+
+/// Health check results of an inspected container.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ContainerHealth {
+    /// `starting`, `healthy` or `unhealthy`.
+    pub status: String,
+    /// Failed checks in a row.
+    pub failing_streak: u32,
+    /// Latest checks, oldest first.
+    pub log: Option<Vec<HealthProbe>>,
+}
+
+/// Outcome of a single health check.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct HealthProbe {
+    /// RFC 3339.
+    pub start: String,
+    pub end: String,
+    pub exit_code: i64,
+    pub output: String,
+}
+
+/// Health check as configured on the engine, durations in nanoseconds.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct HealthConfig {
+    /// `["CMD-SHELL", command]`, `["CMD", args...]` or `["NONE"]`.
+    pub test: Option<Vec<String>>,
+    pub interval: i64,
+    pub timeout: i64,
+    pub start_period: i64,
+    pub retries: i64,
+}
+
+/// Configuration an inspected container was created with.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ContainerInspectConfig {
+    pub image: String,
+    pub env: Option<Vec<String>>,
+    pub labels: Option<HashMap<String, String>>,
+    pub healthcheck: Option<HealthConfig>,
+    pub cmd: Option<Vec<String>>,
+    /// A single string on libpod before 5.0, split on whitespace.
+    #[serde(deserialize_with = "string_or_list")]
+    pub entrypoint: Option<Vec<String>>,
+    pub working_dir: String,
+    pub user: String,
+    pub hostname: String,
+}
+
+/// Reads a list of strings also sent as one space-separated string.
+fn string_or_list<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        String(String),
+        List(Vec<String>),
+    }
+    Ok(match Option::<StringOrList>::deserialize(deserializer)? {
+        Some(StringOrList::String(s)) => Some(s.split_whitespace().map(String::from).collect()),
+        Some(StringOrList::List(list)) => Some(list),
+        None => None,
+    })
+}
+
+/// Host-side configuration of an inspected container.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct HostConfig {
+    pub binds: Option<Vec<String>>,
+    /// Requested bindings, keyed by `<port>/<protocol>`; unlike the network
+    /// settings, the host address is empty when every interface was asked for.
+    pub port_bindings: Option<HashMap<String, Option<Vec<PortBinding>>>>,
+    pub restart_policy: RestartPolicy,
+    pub nano_cpus: i64,
+    pub cpu_shares: i64,
+    pub cpu_quota: i64,
+    pub cpu_period: i64,
+    pub memory: i64,
+    pub memory_reservation: i64,
+    pub memory_swap: i64,
+    /// `null`, `0` or `-1` when unlimited, depending on the engine.
+    pub pids_limit: Option<i64>,
+    pub cap_add: Option<Vec<String>>,
+    pub cap_drop: Option<Vec<String>>,
+    pub readonly_rootfs: bool,
+    pub tmpfs: Option<HashMap<String, String>>,
+    /// `apparmor=...`, `no-new-privileges`, `seccomp=...` and the like.
+    pub security_opt: Option<Vec<String>>,
+}
+
+/// Restart policy as reported by the engine.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct RestartPolicy {
+    pub name: String,
+}
+
+/// Network settings of an inspected container.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct NetworkSettings {
+    /// Keyed by `<port>/<protocol>`; a `null` value means the port is exposed but not published.
+    pub ports: Option<HashMap<String, Option<Vec<PortBinding>>>>,
+    /// Attached networks, by name.
+    pub networks: Option<HashMap<String, EndpointSettings>>,
+}
+
+/// Attachment of an inspected container to a network.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct EndpointSettings {
+    /// Engines before API 1.44 also list the short container id here.
+    pub aliases: Option<Vec<String>>,
+}
+
+/// Host binding of a container port.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct PortBinding {
+    pub host_ip: String,
+    pub host_port: String,
+}
@@ -12,6 +12,12 @@ pub enum Error {
     #[error("Container not found")]
     ContainerNotFound,
 
+    #[error("Image not found")]
+    ImageNotFound,
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Docker output parsing failed: {0}")]
     DockerOutputParse(String),
 
@@ -23,6 +29,16 @@ pub enum Error {
 
     #[error("Invalid input: {0}")]
     BadRequest(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    /// A Docker operation outlived its deadline; holds the operation name.
+    #[error("Docker operation timed out: {0}")]
+    Timeout(String),
 }
 
 impl IntoResponse for Error {
@@ -33,6 +49,10 @@ impl IntoResponse for Error {
                 "Container not found".to_string(),
                 None,
             ),
+            Error::ImageNotFound => (StatusCode::NOT_FOUND, "Image not found".to_string(), None),
+            Error::NotFound(detail) => {
+                (StatusCode::NOT_FOUND, "Not found".to_string(), Some(detail))
+            }
             Error::DockerCommandFailed => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Docker command execution failed".to_string(),
@@ -58,6 +78,15 @@ impl IntoResponse for Error {
                 "Invalid input".to_string(),
                 Some(detail),
             ),
+            Error::Conflict(detail) => (StatusCode::CONFLICT, "Conflict".to_string(), Some(detail)),
+            Error::Forbidden(detail) => {
+                (StatusCode::FORBIDDEN, "Forbidden".to_string(), Some(detail))
+            }
+            Error::Timeout(operation) => (
+                StatusCode::GATEWAY_TIMEOUT,
+                "Docker operation timed out".to_string(),
+                Some(operation),
+            ),
         };
 
         let trace_id = TRACE_ID.with(|id| id.clone());
@@ -1,5 +1,15 @@
-use lightshuttle_core::api::routes::router;
-use std::net::SocketAddr;
+use lightshuttle_core::{
+    api::{routes::router_with_state, state::AppState},
+    services::{
+        builds::BuildStore,
+        cache::{ContainerCache, DEFAULT_RESYNC_INTERVAL},
+        credentials::CredentialStore,
+        docker::{check_engine, client_from_env},
+        health::{HealthRestarts, HealthSupervisor},
+        locks::AppLocks,
+    },
+};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[cfg(unix)]
@@ -34,6 +44,49 @@ async fn main() {
         std::process::exit(1);
     });
 
+    let docker = client_from_env().unwrap_or_else(|e| {
+        tracing::error!("Invalid Docker client configuration: {e}");
+        std::process::exit(1);
+    });
+
+    // Refuse engines older than the API the backends speak, unless told to only warn
+    let strict = std::env::var("DOCKER_API_CHECK").map_or(true, |mode| mode != "warn");
+    if let Err(e) = check_engine(docker.as_ref(), strict).await {
+        tracing::error!("{e}; set DOCKER_API_CHECK=warn to start anyway");
+        std::process::exit(1);
+    }
+
+    // Serve app listings from an index fed by the Docker events stream
+    let resync = match std::env::var("CONTAINER_CACHE_RESYNC_SECS") {
+        Ok(secs) => match secs.parse::<u64>() {
+            Ok(secs) if secs > 0 => Duration::from_secs(secs),
+            _ => {
+                tracing::warn!("Invalid CONTAINER_CACHE_RESYNC_SECS: {secs}; using the default");
+                DEFAULT_RESYNC_INTERVAL
+            }
+        },
+        Err(_) => DEFAULT_RESYNC_INTERVAL,
+    };
+    let cache = ContainerCache::spawn(docker.clone(), resync);
+
+    // Registry credentials, encrypted at rest
+    let credentials = CredentialStore::from_env().unwrap_or_else(|e| {
+        tracing::error!("Failed to open the registry credentials store: {e}");
+        std::process::exit(1);
+    });
+
+    // Build history, referenced when creating apps from a build
+    let builds = BuildStore::from_env().unwrap_or_else(|e| {
+        tracing::error!("Failed to open the build store: {e}");
+        std::process::exit(1);
+    });
+
+    // Restart apps that stay unhealthy, under the same locks as the API
+    let locks = Arc::new(AppLocks::from_env());
+    let health_restarts = Arc::new(HealthRestarts::default());
+    HealthSupervisor::new(docker.clone(), locks.clone(), health_restarts.clone())
+        .spawn(HealthSupervisor::interval_from_env());
+
     tracing::info!("LightShuttle API starting on http://{addr}");
 
     // Bind TCP listener
@@ -45,7 +98,18 @@ async fn main() {
         });
 
     // Start serving using axum
-    axum::serve(listener, router()).await.unwrap_or_else(|e| {
+    axum::serve(
+        listener,
+        router_with_state(
+            AppState::with_cache(docker, cache)
+                .with_credentials(Arc::new(credentials))
+                .with_builds(Arc::new(builds))
+                .with_locks(locks)
+                .with_health_restarts(health_restarts),
+        ),
+    )
+    .await
+    .unwrap_or_else(|e| {
         tracing::error!("Server crashed: {e}");
         std::process::exit(1);
     });
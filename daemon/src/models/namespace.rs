@@ -1,5 +1,7 @@
 use serde::Deserialize;
 
+use crate::{errors::Error, services::credentials::DEFAULT_NAMESPACE};
+
 /// Logical grouping of resources with associated permissions.
 #[derive(Clone, Debug, Deserialize)]
 #[allow(dead_code)]
@@ -10,6 +12,9 @@ pub struct Namespace {
     pub read: bool,
     /// Permission to perform write operations.
     pub write: bool,
+    /// Permission to run commands inside containers; off unless granted.
+    #[serde(default)]
+    pub exec: bool,
 }
 
 #[allow(dead_code)]
@@ -23,4 +28,40 @@ impl Namespace {
     pub fn can_write(&self) -> bool {
         self.write
     }
+
+    /// Fails with `403 Forbidden` unless write operations are permitted.
+    ///
+    /// `action` completes "Namespace X is not allowed to ..." in the error.
+    pub fn require_write(&self, action: &str) -> Result<(), Error> {
+        if self.can_write() {
+            return Ok(());
+        }
+        Err(Error::Forbidden(format!(
+            "Namespace {} is not allowed to {action}",
+            self.name
+        )))
+    }
+
+    /// Returns `true` if commands may be executed inside containers.
+    pub fn can_exec(&self) -> bool {
+        self.exec
+    }
+
+    /// Fails with `403 Forbidden` unless commands may be executed inside containers.
+    ///
+    /// Like [`Self::require_write`], `action` completes the error message.
+    pub fn require_exec(&self, action: &str) -> Result<(), Error> {
+        if self.can_exec() {
+            return Ok(());
+        }
+        Err(Error::Forbidden(format!(
+            "Namespace {} is not allowed to {action}",
+            self.name
+        )))
+    }
+
+    /// Name scoping the resources of a request, `default` when it carries no namespace.
+    pub fn scope(namespace: Option<&Namespace>) -> &str {
+        namespace.map_or(DEFAULT_NAMESPACE, |ns| ns.name.as_str())
+    }
 }
@@ -2,16 +2,34 @@ use utoipa::OpenApi;
 
 use crate::{
     api::error::ApiError,
-    docker::models::{AppInstance, AppStatus},
+    docker::models::{
+        AppInstance, AppStatus, ContainerExit, ContainerStats, EngineInfo, ExecOutput, HealthCheck,
+        HealthStatus, NetworkAttachment, PortMapping, PortProtocol, PortSpec, ProcessOverrides,
+        PruneReport, PullProgress, ResourceLimits, SecurityOptions, VolumeMount, WaitCondition,
+    },
     routes::{
-        apps,
+        apps, archive, builds, exec,
         health::{self, HealthResponse},
+        images,
         metrics::{self, MetricsResponse},
         models::{
-            AppListResponse, ContainerIdResponse, CreateAppRequest, CreateAppResponse, Pagination,
-            StatusResponse,
+            AppListResponse, ArchiveQuery, BuildQuery, BuildRequest, ContainerIdResponse,
+            CreateAppRequest, CreateAppResponse, CreateNetworkRequest, CreateVolumeRequest,
+            ExecRequest, ExecSessionQuery, ImageDetailsResponse, ImageResponse, KillQuery,
+            NetworkResponse, Pagination, PruneQuery, PullImageRequest, RegistryCredentialRequest,
+            RenameAppRequest, RestartQuery, StatsQuery, StatusResponse, VolumeResponse, WaitQuery,
         },
+        networks, registries,
+        system::{self, SystemInfoResponse},
         version::{self, VersionResponse},
+        volumes,
+    },
+    services::{
+        builds::{BuildRecord, BuildStatus},
+        credentials::RegistryCredential,
+        docker::BackendInfo,
+        endpoint::EndpointInfo,
+        health::HealthRestart,
     },
 };
 
@@ -23,34 +41,114 @@ use crate::{
         apps::start_app,
         apps::stop_app,
         apps::recreate_app,
+        apps::pause_app,
+        apps::unpause_app,
+        apps::restart_app,
+        apps::kill_app,
+        apps::rename_app,
+        apps::wait_app,
         apps::list_apps,
         apps::get_app,
         apps::get_app_logs,
+        apps::get_app_stats,
+        exec::exec_app,
+        exec::exec_app_ws,
+        archive::get_app_archive,
+        archive::put_app_archive,
         apps::get_app_status,
         apps::delete_app,
+        images::list_images,
+        images::get_image,
+        images::delete_image,
+        images::prune_images,
+        images::pull_image,
+        networks::list_networks,
+        networks::create_network,
+        networks::get_network,
+        networks::delete_network,
+        volumes::list_volumes,
+        volumes::create_volume,
+        volumes::get_volume,
+        volumes::delete_volume,
+        builds::create_build,
+        builds::list_builds,
+        builds::get_build,
+        registries::list_registries,
+        registries::put_registry,
+        registries::delete_registry,
         health::health,
         metrics::metrics,
         version::version,
+        system::system_info,
     ),
     components(schemas(
         CreateAppRequest,
         Pagination,
         AppListResponse,
+        ResourceLimits,
+        HealthCheck,
+        PortSpec,
+        PortMapping,
+        PortProtocol,
+        ProcessOverrides,
+        SecurityOptions,
+        HealthStatus,
+        HealthRestart,
         CreateAppResponse,
         ContainerIdResponse,
         StatusResponse,
+        StatsQuery,
+        RestartQuery,
+        KillQuery,
+        RenameAppRequest,
+        WaitQuery,
+        WaitCondition,
+        ContainerExit,
+        ContainerStats,
+        ExecRequest,
+        ExecSessionQuery,
+        ExecOutput,
+        ArchiveQuery,
+        ImageResponse,
+        ImageDetailsResponse,
+        PullImageRequest,
+        PullProgress,
+        PruneQuery,
+        PruneReport,
+        NetworkResponse,
+        CreateNetworkRequest,
+        NetworkAttachment,
+        VolumeResponse,
+        CreateVolumeRequest,
+        VolumeMount,
+        BuildQuery,
+        BuildRequest,
+        BuildRecord,
+        BuildStatus,
+        RegistryCredentialRequest,
+        RegistryCredential,
         HealthResponse,
         MetricsResponse,
         VersionResponse,
+        SystemInfoResponse,
+        BackendInfo,
+        EngineInfo,
+        EndpointInfo,
         AppInstance,
         AppStatus,
         ApiError,
     )),
     tags(
         (name = "Apps", description = "Application management"),
+        (name = "Images", description = "Image management"),
+        (name = "Networks", description = "Network management"),
+        (name = "Volumes", description = "Named volume management"),
+        (name = "Builds", description = "Image builds"),
+        (name = "Registries", description = "Private registry credentials"),
         (name = "Health", description = "Health check"),
         (name = "Metrics", description = "Service metrics"),
         (name = "Version", description = "Service version"),
+        (name = "System", description = "Daemon and Docker backend information"),
     )
 )]
 pub struct ApiDoc;
@@ -1,24 +1,36 @@
+use std::{collections::HashMap, convert::Infallible, time::Duration};
+
 use axum::{
-    extract::{Json, Path, Query, State},
+    extract::{Extension, Json, Path, Query, State},
     http::{header, StatusCode},
-    response::{IntoResponse, Response},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
 };
+use futures_util::stream;
 
 use crate::{
-    docker::{self, ContainerConfig},
+    api::state::AppState,
+    docker::{
+        self, validate_container_name, validate_signal, ContainerConfig, HealthStatus, PortSpec,
+        HEALTH_RESTART_LABEL, PROCESS_LABEL,
+    },
     errors::Error,
+    models::namespace::Namespace,
     services::docker::DockerClient,
 };
 use std::sync::Arc;
 
 use super::{
-    AppListResponse, ContainerIdResponse, CreateAppRequest, CreateAppResponse, Pagination,
-    StatusResponse,
+    AppListResponse, ContainerIdResponse, CreateAppRequest, CreateAppResponse, KillQuery,
+    Pagination, RenameAppRequest, RestartQuery, StatsQuery, StatusResponse, WaitQuery,
 };
 
 /// Handles POST /apps
 ///
-/// Launches a new container based on the provided configuration.
+/// Launches a new container based on the provided configuration, from an
+/// image reference or the image of a succeeded build of the namespace.
 ///
 /// # Arguments
 /// - `payload`: JSON body containing app creation parameters.
@@ -26,6 +38,11 @@ use super::{
 /// # Returns
 /// - `201 Created` with container ID if successful.
 /// - `400 Bad Request` with error message if failed.
+/// - `403 Forbidden` if the API key lacks the `write` permission.
+/// - `404 Not Found` if the referenced build doesn't exist.
+/// - `409 Conflict` if the referenced build is running or failed, or the
+///   app is still busy with another operation after the wait limit.
+/// - `504 Gateway Timeout` if Docker did not answer in time.
 #[cfg_attr(feature = "openapi", utoipa::path(
     post,
     path = "/apps",
@@ -34,25 +51,79 @@ use super::{
     responses(
         (status = 201, description = "App created", body = CreateAppResponse),
         (status = 400, description = "Bad request", body = crate::api::error::ApiError),
-        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "Build not found", body = crate::api::error::ApiError),
+        (status = 409, description = "Build not succeeded, or app busy", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError),
+        (status = 504, description = "Docker operation timed out", body = crate::api::error::ApiError)
     )
 ))]
 pub async fn create_app(
-    State(docker): State<Arc<dyn DockerClient>>,
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
     Json(payload): Json<CreateAppRequest>,
 ) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("create apps")?;
+    }
+    let scope = Namespace::scope(namespace.as_deref());
+    let image = match (&payload.build, payload.image.trim()) {
+        (Some(_), image) if !image.is_empty() => {
+            return Err(Error::BadRequest(
+                "image and build are mutually exclusive".into(),
+            ))
+        }
+        (Some(build), _) => state.built_image(scope, build)?,
+        (None, "") => return Err(Error::BadRequest("image or build is required".into())),
+        (None, image) => {
+            // Private images are pulled up front with the namespace's registry credentials.
+            state.ensure_image(scope, image).await?;
+            image.to_string()
+        }
+    };
+
+    let volumes = payload
+        .volumes
+        .as_ref()
+        .map(|mounts| mounts.iter().map(|m| m.to_bind()).collect())
+        .transpose()?;
+    let ports = PortSpec::resolve(&payload.ports, payload.container_port)?;
+    // Checked as sent, before capabilities are spelled the engine's way.
+    payload.security.validate()?;
+    let security = payload.security.normalized();
+
+    // The health supervisor finds the apps it restarts by label.
+    let mut labels = payload.labels.clone();
+    if let Some(healthcheck) = &payload.healthcheck {
+        labels.get_or_insert_with(HashMap::new).insert(
+            HEALTH_RESTART_LABEL.to_string(),
+            healthcheck.restart_after().to_string(),
+        );
+    }
+    // Inspect merges the overrides with the image defaults, so they are kept apart.
+    if !payload.process.is_empty() {
+        labels
+            .get_or_insert_with(HashMap::new)
+            .insert(PROCESS_LABEL.to_string(), payload.process.to_label());
+    }
     let config = ContainerConfig {
         name: &payload.name,
-        image: &payload.image,
-        host_ports: &payload.ports,
-        container_port: payload.container_port,
-        labels: payload.labels.as_ref(),
+        image: &image,
+        ports: &ports,
+        labels: labels.as_ref(),
         env: payload.env.as_ref(),
-        volumes: payload.volumes.as_ref(),
+        volumes: volumes.as_ref(),
         restart_policy: payload.restart_policy.as_deref(),
+        networks: payload.networks.as_deref().unwrap_or_default(),
+        limits: Some(&payload.limits).filter(|limits| !limits.is_empty()),
+        healthcheck: payload.healthcheck.as_ref(),
+        process: Some(&payload.process).filter(|process| !process.is_empty()),
+        security: Some(&security).filter(|security| !security.is_empty()),
     };
 
-    let container_id = docker.run(config)?;
+    let _lock = state.lock(&payload.name, "create").await?;
+    let container_id = state.docker.run(config).await?;
+    state.refresh(&payload.name).await;
     Ok((
         StatusCode::CREATED,
         Json(CreateAppResponse {
@@ -68,7 +139,9 @@ pub async fn create_app(
 ///
 /// # Returns
 /// - `200 OK` if the container was started
+/// - `403 Forbidden` if the API key lacks the `write` permission
 /// - `404 Not Found` if the container doesn't exist
+/// - `409 Conflict` if the app is still busy with another operation after the wait limit
 /// - `500 Internal Server Error` otherwise
 #[cfg_attr(feature = "openapi", utoipa::path(
     post,
@@ -77,15 +150,23 @@ pub async fn create_app(
     params(("name", Path, description = "Container name")),
     responses(
         (status = 200, description = "App started"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
         (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 409, description = "App busy with another operation", body = crate::api::error::ApiError),
         (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
     )
 ))]
 pub async fn start_app(
-    State(docker): State<Arc<dyn DockerClient>>,
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
     Path(name): Path<String>,
 ) -> Result<impl IntoResponse, Error> {
-    docker.start(&name)?;
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("start apps")?;
+    }
+    let _lock = state.lock(&name, "start").await?;
+    state.docker.start(&name).await?;
+    state.refresh(&name).await;
     Ok(StatusCode::OK)
 }
 
@@ -95,8 +176,11 @@ pub async fn start_app(
 ///
 /// # Returns
 /// - `200 OK` if the container was stopped
+/// - `403 Forbidden` if the API key lacks the `write` permission
 /// - `404 Not Found` if the container doesn't exist
+/// - `409 Conflict` if the app is still busy with another operation after the wait limit
 /// - `500 Internal Server Error` otherwise
+/// - `504 Gateway Timeout` if Docker did not answer in time
 #[cfg_attr(feature = "openapi", utoipa::path(
     post,
     path = "/apps/{name}/stop",
@@ -104,15 +188,24 @@ pub async fn start_app(
     params(("name", Path, description = "Container name")),
     responses(
         (status = 200, description = "App stopped"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
         (status = 404, description = "App not found", body = crate::api::error::ApiError),
-        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+        (status = 409, description = "App busy with another operation", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError),
+        (status = 504, description = "Docker operation timed out", body = crate::api::error::ApiError)
     )
 ))]
 pub async fn stop_app(
-    State(docker): State<Arc<dyn DockerClient>>,
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
     Path(name): Path<String>,
 ) -> Result<impl IntoResponse, Error> {
-    docker.stop(&name)?;
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("stop apps")?;
+    }
+    let _lock = state.lock(&name, "stop").await?;
+    state.docker.stop(&name).await?;
+    state.refresh(&name).await;
     Ok(StatusCode::OK)
 }
 
@@ -122,8 +215,11 @@ pub async fn stop_app(
 ///
 /// # Returns
 /// - `200 OK` with new container ID
+/// - `403 Forbidden` if the API key lacks the `write` permission
 /// - `404 Not Found` if container doesn't exist
+/// - `409 Conflict` if the app is still busy with another operation after the wait limit
 /// - `500 Internal Server Error` otherwise
+/// - `504 Gateway Timeout` if Docker did not answer in time
 #[cfg_attr(feature = "openapi", utoipa::path(
     post,
     path = "/apps/{name}/recreate",
@@ -131,18 +227,282 @@ pub async fn stop_app(
     params(("name", Path, description = "Container name")),
     responses(
         (status = 200, description = "App recreated", body = ContainerIdResponse),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
         (status = 404, description = "App not found", body = crate::api::error::ApiError),
-        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+        (status = 409, description = "App busy with another operation", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError),
+        (status = 504, description = "Docker operation timed out", body = crate::api::error::ApiError)
     )
 ))]
 pub async fn recreate_app(
-    State(docker): State<Arc<dyn DockerClient>>,
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
     Path(name): Path<String>,
 ) -> Result<impl IntoResponse, Error> {
-    let container_id = docker::recreate_container(docker.as_ref(), &name)?;
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("recreate apps")?;
+    }
+    let _lock = state.lock(&name, "recreate").await?;
+    let container_id = docker::recreate_container(state.docker.as_ref(), &name).await?;
+    state.refresh(&name).await;
     Ok((StatusCode::OK, Json(ContainerIdResponse { container_id })))
 }
 
+/// Handles POST /apps/:name/pause
+///
+/// Freezes every process of a running container.
+///
+/// # Returns
+/// - `200 OK` if the container was paused
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the container doesn't exist
+/// - `409 Conflict` if the container is not running, or the app is still busy
+///   with another operation after the wait limit
+/// - `500 Internal Server Error` otherwise
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/apps/{name}/pause",
+    tag = "Apps",
+    params(("name", Path, description = "Container name")),
+    responses(
+        (status = 200, description = "App paused"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 409, description = "App not running, or busy", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn pause_app(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("pause apps")?;
+    }
+    let _lock = state.lock(&name, "pause").await?;
+    state.docker.pause(&name).await?;
+    state.refresh(&name).await;
+    Ok(StatusCode::OK)
+}
+
+/// Handles POST /apps/:name/unpause
+///
+/// Resumes a paused container.
+///
+/// # Returns
+/// - `200 OK` if the container was resumed
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the container doesn't exist
+/// - `409 Conflict` if the container is not paused, or the app is still busy
+///   with another operation after the wait limit
+/// - `500 Internal Server Error` otherwise
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/apps/{name}/unpause",
+    tag = "Apps",
+    params(("name", Path, description = "Container name")),
+    responses(
+        (status = 200, description = "App resumed"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 409, description = "App not paused, or busy", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn unpause_app(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("unpause apps")?;
+    }
+    let _lock = state.lock(&name, "unpause").await?;
+    state.docker.unpause(&name).await?;
+    state.refresh(&name).await;
+    Ok(StatusCode::OK)
+}
+
+/// Handles POST /apps/:name/restart
+///
+/// Stops then starts a container, killing it if it does not stop within
+/// `timeout` seconds.
+///
+/// # Returns
+/// - `200 OK` if the container was restarted
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the container doesn't exist
+/// - `409 Conflict` if the app is still busy with another operation after the wait limit
+/// - `500 Internal Server Error` otherwise
+/// - `504 Gateway Timeout` if Docker did not answer in time
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/apps/{name}/restart",
+    tag = "Apps",
+    params(("name", Path, description = "Container name"), RestartQuery),
+    responses(
+        (status = 200, description = "App restarted"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 409, description = "App busy with another operation", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError),
+        (status = 504, description = "Docker operation timed out", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn restart_app(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+    Query(query): Query<RestartQuery>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("restart apps")?;
+    }
+    let _lock = state.lock(&name, "restart").await?;
+    state.docker.restart(&name, query.timeout).await?;
+    state.refresh(&name).await;
+    Ok(StatusCode::OK)
+}
+
+/// Handles POST /apps/:name/kill
+///
+/// Sends a signal to the main process of a running container, `SIGKILL`
+/// unless `signal` names another one, such as `SIGHUP` to reload a config.
+///
+/// # Returns
+/// - `200 OK` if the signal was sent
+/// - `400 Bad Request` if the signal is invalid
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the container doesn't exist
+/// - `409 Conflict` if the container is not running, or the app is still busy
+///   with another operation after the wait limit
+/// - `500 Internal Server Error` otherwise
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/apps/{name}/kill",
+    tag = "Apps",
+    params(("name", Path, description = "Container name"), KillQuery),
+    responses(
+        (status = 200, description = "Signal sent"),
+        (status = 400, description = "Invalid signal", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 409, description = "App not running, or busy", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn kill_app(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+    Query(query): Query<KillQuery>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("kill apps")?;
+    }
+    let signal = query.signal.as_deref().unwrap_or("SIGKILL");
+    validate_signal(signal)?;
+    let _lock = state.lock(&name, "kill").await?;
+    state.docker.kill(&name, signal).await?;
+    state.refresh(&name).await;
+    Ok(StatusCode::OK)
+}
+
+/// Handles POST /apps/:name/rename
+///
+/// Gives a container a new name. Both names are locked for the duration of
+/// the rename.
+///
+/// # Returns
+/// - `200 OK` if the container was renamed
+/// - `400 Bad Request` if the new name is invalid
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the container doesn't exist
+/// - `409 Conflict` if the new name is taken, or either app is still busy
+///   with another operation after the wait limit
+/// - `500 Internal Server Error` otherwise
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/apps/{name}/rename",
+    tag = "Apps",
+    params(("name", Path, description = "Container name")),
+    request_body = RenameAppRequest,
+    responses(
+        (status = 200, description = "App renamed"),
+        (status = 400, description = "Invalid name", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 409, description = "Name already in use, or app busy", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn rename_app(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+    Json(payload): Json<RenameAppRequest>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("rename apps")?;
+    }
+    validate_container_name(&payload.name)?;
+    if payload.name == name {
+        return Ok(StatusCode::OK);
+    }
+    // Locks are always taken in name order, so that crossed renames cannot deadlock.
+    let (first, second) = if name < payload.name {
+        (&name, &payload.name)
+    } else {
+        (&payload.name, &name)
+    };
+    let _first = state.lock(first, "rename").await?;
+    let _second = state.lock(second, "rename").await?;
+    state.docker.rename(&name, &payload.name).await?;
+    state.refresh(&name).await;
+    state.refresh(&payload.name).await;
+    Ok(StatusCode::OK)
+}
+
+/// Handles POST /apps/:name/wait
+///
+/// Long-polls until the container meets `condition`, `not-running` by
+/// default, and reports how it ended. Meant for one-shot apps such as
+/// migrations; no lock is taken, so the app can be stopped meanwhile.
+///
+/// # Returns
+/// - `200 OK` with the exit code, OOM flag and finish time
+/// - `404 Not Found` if the container doesn't exist
+/// - `500 Internal Server Error` otherwise
+/// - `504 Gateway Timeout` if the condition was not met within `timeout` seconds
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/apps/{name}/wait",
+    tag = "Apps",
+    params(("name", Path, description = "Container name"), WaitQuery),
+    responses(
+        (status = 200, description = "App exited", body = crate::docker::models::ContainerExit),
+        (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError),
+        (status = 504, description = "Condition not met in time", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn wait_app(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<WaitQuery>,
+) -> Result<impl IntoResponse, Error> {
+    let condition = query.condition.unwrap_or_default();
+    let wait = docker::wait_container(state.docker.as_ref(), &name, condition);
+    let exit = match query.timeout {
+        Some(secs) => tokio::time::timeout(Duration::from_secs(secs), wait)
+            .await
+            .map_err(|_| Error::Timeout("wait".into()))??,
+        None => wait.await?,
+    };
+    Ok((StatusCode::OK, Json(exit)))
+}
+
 /// Handles GET /apps
 ///
 /// Lists running containers, paginated.
@@ -164,8 +524,11 @@ pub async fn recreate_app(
         (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
     )
 ))]
-pub async fn list_apps(Query(pagination): Query<Pagination>) -> Result<impl IntoResponse, Error> {
-    let all_apps = match docker::get_running_containers() {
+pub async fn list_apps(
+    State(state): State<AppState>,
+    Query(pagination): Query<Pagination>,
+) -> Result<impl IntoResponse, Error> {
+    let all_apps = match state.running_apps().await {
         Ok(apps) => apps,
         Err(Error::DockerCommandFailed) => Vec::new(),
         Err(e) => return Err(e),
@@ -225,11 +588,10 @@ pub async fn list_apps(Query(pagination): Query<Pagination>) -> Result<impl Into
     )
 ))]
 pub async fn get_app(
-    State(docker): State<Arc<dyn DockerClient>>,
+    State(state): State<AppState>,
     Path(name): Path<String>,
 ) -> Result<impl IntoResponse, Error> {
-    let app =
-        docker::get_container_by_name(docker.as_ref(), &name)?.ok_or(Error::ContainerNotFound)?;
+    let app = state.app(&name).await?.ok_or(Error::ContainerNotFound)?;
     Ok((StatusCode::OK, Json(app)))
 }
 
@@ -242,6 +604,7 @@ pub async fn get_app(
 /// - `200 OK` with the logs as plain text.
 /// - `404 Not Found` if the container does not exist.
 /// - `500 Internal Server Error` if fetching logs fails.
+/// - `504 Gateway Timeout` if Docker did not answer in time.
 #[cfg_attr(feature = "openapi", utoipa::path(
     get,
     path = "/apps/{name}/logs",
@@ -250,20 +613,91 @@ pub async fn get_app(
     responses(
         (status = 200, description = "Container logs", content_type = "text/plain", body = String),
         (status = 404, description = "App not found", body = crate::api::error::ApiError),
-        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError),
+        (status = 504, description = "Docker operation timed out", body = crate::api::error::ApiError)
     )
 ))]
-pub async fn get_app_logs(Path(name): Path<String>) -> Result<Response, Error> {
-    let logs = docker::get_container_logs(&name)?;
+pub async fn get_app_logs(
+    State(docker): State<Arc<dyn DockerClient>>,
+    Path(name): Path<String>,
+) -> Result<Response, Error> {
+    let logs = docker.logs(&name).await?;
     Ok((StatusCode::OK, [(header::CONTENT_TYPE, "text/plain")], logs).into_response())
 }
 
+/// Handles GET /apps/:name/stats
+///
+/// Returns a resource usage snapshot of a container. With `?stream=true`,
+/// samples are pushed as `stats` server-sent events every `interval` seconds
+/// until the client disconnects or sampling fails (reported as an `error` event).
+///
+/// # Returns
+/// - `200 OK` with JSON stats, or a `text/event-stream` when streaming
+/// - `404 Not Found` if the container doesn't exist
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/apps/{name}/stats",
+    tag = "Apps",
+    params(("name", Path, description = "Container name"), StatsQuery),
+    responses(
+        (status = 200, description = "Resource usage snapshot, or a stream of `stats` events", body = crate::docker::models::ContainerStats),
+        (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn get_app_stats(
+    State(docker): State<Arc<dyn DockerClient>>,
+    Path(name): Path<String>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Response, Error> {
+    // Sample once up front so that a missing container is a plain 404.
+    let first = docker.stats(&name).await?;
+    if !query.stream.unwrap_or(false) {
+        return Ok((StatusCode::OK, Json(first)).into_response());
+    }
+
+    let period = Duration::from_secs(query.interval.unwrap_or(1).clamp(1, 60));
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let events = stream::unfold(
+        (docker, name, ticker, Some(first), false),
+        |(docker, name, mut ticker, pending, failed)| async move {
+            if failed {
+                return None;
+            }
+            let sample = match pending {
+                Some(stats) => Ok(stats),
+                None => {
+                    ticker.tick().await;
+                    docker.stats(&name).await
+                }
+            };
+            let event = match &sample {
+                Ok(stats) => Event::default().event("stats").json_data(stats).ok()?,
+                Err(e) => Event::default().event("error").data(e.to_string()),
+            };
+            let failed = sample.is_err();
+            Some((
+                Ok::<_, Infallible>(event),
+                (docker, name, ticker, None, failed),
+            ))
+        },
+    );
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
 /// Handles GET /apps/:name/status
 ///
-/// Returns the status of a container (`running`, `exited`, etc.)
+/// Returns the status of a container (`running`, `exited`, etc.), its health
+/// (`starting`, `healthy`, `unhealthy`) when it has a health check, and the
+/// last time the health supervisor restarted it, with why.
 ///
 /// # Returns
-/// - `200 OK` with JSON { status }
+/// - `200 OK` with JSON { status, health, last_health_restart }
 /// - `404 Not Found` if the container doesn't exist
 /// - `500 Internal Server Error` on error
 #[cfg_attr(feature = "openapi", utoipa::path(
@@ -278,11 +712,18 @@ pub async fn get_app_logs(Path(name): Path<String>) -> Result<Response, Error> {
     )
 ))]
 pub async fn get_app_status(
-    State(docker): State<Arc<dyn DockerClient>>,
+    State(state): State<AppState>,
     Path(name): Path<String>,
 ) -> Result<impl IntoResponse, Error> {
-    let state = docker::get_container_status(docker.as_ref(), &name)?;
-    Ok((StatusCode::OK, Json(StatusResponse { status: state })))
+    let container = state.container_state(&name).await?;
+    Ok((
+        StatusCode::OK,
+        Json(StatusResponse {
+            health: HealthStatus::from_state(&container),
+            status: container.status,
+            last_health_restart: state.health_restarts.last(&name),
+        }),
+    ))
 }
 
 /// Deletes an application/container by its name.
@@ -292,7 +733,9 @@ pub async fn get_app_status(
 ///
 /// # Returns
 /// - `204 No Content` if deleted successfully
+/// - `403 Forbidden` if the API key lacks the `write` permission
 /// - `404 Not Found` if container doesn't exist
+/// - `409 Conflict` if the app is still busy with another operation after the wait limit
 /// - `500 Internal Server Error` if something went wrong
 #[cfg_attr(feature = "openapi", utoipa::path(
     delete,
@@ -301,11 +744,22 @@ pub async fn get_app_status(
     params(("name", Path, description = "Container name")),
     responses(
         (status = 204, description = "App deleted"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
         (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 409, description = "App busy with another operation", body = crate::api::error::ApiError),
         (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
     )
 ))]
-pub async fn delete_app(Path(name): Path<String>) -> Result<impl IntoResponse, Error> {
-    docker::remove_container(&name)?;
+pub async fn delete_app(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("delete apps")?;
+    }
+    let _lock = state.lock(&name, "delete").await?;
+    state.docker.remove(&name).await?;
+    state.refresh(&name).await;
     Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Extension, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+
+use crate::{
+    api::state::AppState, errors::Error, models::namespace::Namespace,
+    services::docker::DockerClient,
+};
+
+use super::ArchiveQuery;
+
+fn container_path(query: &ArchiveQuery) -> Result<&str, Error> {
+    match query.path.trim() {
+        "" => Err(Error::BadRequest("path must not be empty".into())),
+        path => Ok(path),
+    }
+}
+
+/// Handles GET /apps/:name/archive
+///
+/// Streams a tar archive of a file or directory of the app, relative to its
+/// parent: `path=/etc/nginx` yields entries under `nginx/`. Reading any file
+/// of the app, secrets included, is as powerful as a shell in it, so this
+/// needs the `exec` permission rather than `read`.
+///
+/// # Returns
+/// - `200 OK` with the tar archive
+/// - `400 Bad Request` if `path` is empty
+/// - `403 Forbidden` if the API key lacks the `exec` permission
+/// - `404 Not Found` if the container or the path doesn't exist
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/apps/{name}/archive",
+    tag = "Apps",
+    params(("name", Path, description = "Container name"), ArchiveQuery),
+    responses(
+        (status = 200, description = "Tar archive of the path", content_type = "application/x-tar", body = Vec<u8>),
+        (status = 400, description = "Invalid path", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing exec permission", body = crate::api::error::ApiError),
+        (status = 404, description = "App or path not found", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn get_app_archive(
+    State(docker): State<Arc<dyn DockerClient>>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+    Query(query): Query<ArchiveQuery>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_exec("copy files out of apps")?;
+    }
+    let archive = docker.get_archive(&name, container_path(&query)?).await?;
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/x-tar")],
+        Body::from_stream(archive),
+    ))
+}
+
+/// Handles PUT /apps/:name/archive
+///
+/// Extracts the uploaded tar archive into a directory of the app, which
+/// doesn't need to be running. The app is locked meanwhile, so that files are
+/// never written into a container that a recreate is about to replace.
+///
+/// # Returns
+/// - `200 OK` once extracted
+/// - `400 Bad Request` if `path` is empty, not a directory or the archive is invalid
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the container or the directory doesn't exist
+/// - `409 Conflict` if the directory is on a read-only filesystem, or the app
+///   is still busy with another operation after the wait limit
+/// - `413 Payload Too Large` if the archive exceeds `ARCHIVE_UPLOAD_LIMIT_MB`
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    put,
+    path = "/apps/{name}/archive",
+    tag = "Apps",
+    params(("name", Path, description = "Container name"), ArchiveQuery),
+    request_body(content = Vec<u8>, content_type = "application/x-tar", description = "Tar archive to extract"),
+    responses(
+        (status = 200, description = "Archive extracted"),
+        (status = 400, description = "Invalid path or archive", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "App or directory not found", body = crate::api::error::ApiError),
+        (status = 409, description = "Read-only destination, or app busy", body = crate::api::error::ApiError),
+        (status = 413, description = "Archive too large"),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn put_app_archive(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+    Query(query): Query<ArchiveQuery>,
+    body: Bytes,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("copy files into apps")?;
+    }
+    let path = container_path(&query)?;
+    let _lock = state.lock(&name, "copy files").await?;
+    state.docker.put_archive(&name, path, body).await?;
+    Ok(StatusCode::OK)
+}
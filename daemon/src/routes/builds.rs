@@ -0,0 +1,287 @@
+use std::convert::Infallible;
+
+use axum::{
+    body::Bytes,
+    extract::{Extension, Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use futures_util::stream;
+use tokio::sync::mpsc;
+
+use crate::{
+    api::state::AppState, docker::BuildOptions, errors::Error, models::namespace::Namespace,
+};
+
+use super::{BuildQuery, BuildRequest};
+
+/// Tag of builds started without one.
+fn default_tag() -> String {
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    format!("lightshuttle-build:{}", &id[..12])
+}
+
+/// Build options of a tar context upload, from the query string.
+fn options_from_query(query: BuildQuery) -> Result<BuildOptions, Error> {
+    let build_args = match query.build_args.as_deref() {
+        Some(json) => serde_json::from_str(json)
+            .map_err(|e| Error::BadRequest(format!("Invalid build_args: {e}")))?,
+        None => Default::default(),
+    };
+    Ok(BuildOptions {
+        tag: query.tag.unwrap_or_else(default_tag),
+        dockerfile: query.dockerfile.unwrap_or_else(|| "Dockerfile".into()),
+        target: query.target,
+        build_args,
+    })
+}
+
+/// Tar context of an inline Dockerfile: the uploaded files, if any, plus the
+/// Dockerfile, which replaces one the archive may already hold.
+fn inline_context(payload: BuildRequest) -> Result<(Bytes, BuildOptions), Error> {
+    if payload.dockerfile.trim().is_empty() {
+        return Err(Error::BadRequest("dockerfile must not be empty".into()));
+    }
+    let invalid = |e: std::io::Error| Error::BadRequest(format!("Invalid build context: {e}"));
+
+    let mut archive = tar::Builder::new(Vec::new());
+    if let Some(context) = payload.context {
+        let files = BASE64
+            .decode(context.trim())
+            .map_err(|e| Error::BadRequest(format!("Invalid build context: {e}")))?;
+        for entry in tar::Archive::new(files.as_slice())
+            .entries()
+            .map_err(invalid)?
+        {
+            let mut entry = entry.map_err(invalid)?;
+            let path = entry.path().map_err(invalid)?.into_owned();
+            if path.strip_prefix(".").unwrap_or(&path) == std::path::Path::new("Dockerfile") {
+                continue;
+            }
+            let mut header = entry.header().clone();
+            archive
+                .append_data(&mut header, &path, &mut entry)
+                .map_err(invalid)?;
+        }
+    }
+    let mut header = tar::Header::new_gnu();
+    header.set_size(payload.dockerfile.len() as u64);
+    header.set_mode(0o644);
+    archive
+        .append_data(&mut header, "Dockerfile", payload.dockerfile.as_bytes())
+        .map_err(invalid)?;
+    let context = archive.into_inner().map_err(invalid)?;
+
+    Ok((
+        context.into(),
+        BuildOptions {
+            tag: payload.tag.unwrap_or_else(default_tag),
+            dockerfile: "Dockerfile".into(),
+            target: payload.target,
+            build_args: payload.build_args.unwrap_or_default(),
+        },
+    ))
+}
+
+/// Handles POST /builds
+///
+/// Builds an image on the daemon and streams the build output as server-sent
+/// events. The body is either:
+/// - a tar build context (`Content-Type: application/x-tar`), with the tag,
+///   Dockerfile path, target and build args in the query string;
+/// - a JSON [`BuildRequest`] carrying an inline Dockerfile and, optionally,
+///   a base64 tar of the files it copies.
+///
+/// Base images are pulled with the namespace's registry credentials. The
+/// stream starts with a `build` event carrying the build record, then
+/// `output` events carry the build output line by line, and a final `done`
+/// event carries the finished record, or an `error` event the failure. The
+/// build completes, and is recorded, even if the client goes away.
+///
+/// # Returns
+/// - `200 OK` with the event stream
+/// - `400 Bad Request` if the options are invalid or the Dockerfile is missing
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `413 Payload Too Large` if the context exceeds `BUILD_CONTEXT_LIMIT_MB`
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/builds",
+    tag = "Builds",
+    params(BuildQuery),
+    request_body(
+        content(
+            (Vec<u8> = "application/x-tar"),
+            (BuildRequest = "application/json")
+        ),
+        description = "Tar build context, or an inline Dockerfile"
+    ),
+    responses(
+        (status = 200, description = "Stream of `build` and `output` events ended by `done` or `error`", body = crate::services::builds::BuildRecord),
+        (status = 400, description = "Invalid build", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 413, description = "Build context too large"),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn create_build(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Query(query): Query<BuildQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("build images")?;
+    }
+    let scope = Namespace::scope(namespace.as_deref()).to_string();
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let (context, opts) = match content_type.split(';').next().unwrap_or_default().trim() {
+        "application/x-tar" | "application/tar" => (body, options_from_query(query)?),
+        "application/json" => {
+            let payload: BuildRequest = serde_json::from_slice(&body)
+                .map_err(|e| Error::BadRequest(format!("Invalid build request: {e}")))?;
+            inline_context(payload)?
+        }
+        other => {
+            return Err(Error::BadRequest(format!(
+                "Unsupported content type '{other}': expected application/x-tar or application/json"
+            )))
+        }
+    };
+    opts.validate()?;
+
+    let auths = state.credentials.auths(&scope);
+    let record = state.builds.start(&scope, &opts)?;
+    tracing::info!(namespace = %scope, build = %record.id, tag = %opts.tag, "Build started");
+
+    let (tx, mut output) = mpsc::channel::<String>(256);
+    let build = {
+        let (docker, builds, id) = (
+            state.docker.clone(),
+            state.builds.clone(),
+            record.id.clone(),
+        );
+        tokio::spawn(async move {
+            let result = docker.build_image(context, &opts, &auths, tx).await;
+            let finished = builds.finish(&scope, &id, &result);
+            match &result {
+                Ok(image) => {
+                    tracing::info!(namespace = %scope, build = %id, %image, "Build succeeded")
+                }
+                Err(e) => {
+                    tracing::info!(namespace = %scope, build = %id, error = %e, "Build failed")
+                }
+            }
+            result.and(finished)
+        })
+    };
+
+    // Wait for the first line so that failures before any output are plain HTTP errors.
+    let first = output.recv().await;
+    let (build, finished) = match first {
+        Some(_) => (Some(build), None),
+        None => (
+            None,
+            Some(
+                build
+                    .await
+                    .map_err(|e| Error::Unexpected(e.to_string()))??,
+            ),
+        ),
+    };
+
+    let (events_tx, events) = mpsc::channel::<Event>(256);
+    tokio::spawn(async move {
+        if let Ok(event) = Event::default().event("build").json_data(&record) {
+            let _ = events_tx.send(event).await;
+        }
+        let mut next = first;
+        while let Some(line) = next {
+            // The client went away; the build still completes.
+            let _ = events_tx
+                .send(Event::default().event("output").data(line))
+                .await;
+            next = output.recv().await;
+        }
+
+        let result = match (build, finished) {
+            (Some(build), _) => build
+                .await
+                .unwrap_or_else(|e| Err(Error::Unexpected(e.to_string()))),
+            (None, finished) => finished.ok_or(Error::DockerCommandFailed),
+        };
+        let event = match result {
+            Ok(record) => Event::default()
+                .event("done")
+                .json_data(&record)
+                .unwrap_or_else(|e| Event::default().event("error").data(e.to_string())),
+            Err(e) => Event::default().event("error").data(e.to_string()),
+        };
+        let _ = events_tx.send(event).await;
+    });
+
+    let events = stream::unfold(events, |mut events| async move {
+        let event = events.recv().await?;
+        Some((Ok::<_, Infallible>(event), events))
+    });
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+/// Handles GET /builds
+///
+/// Lists the builds of the caller's namespace, newest first.
+///
+/// # Returns
+/// - `200 OK` with the list of builds
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/builds",
+    tag = "Builds",
+    responses(
+        (status = 200, description = "Builds of the namespace", body = [crate::services::builds::BuildRecord])
+    )
+))]
+pub async fn list_builds(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+) -> impl IntoResponse {
+    let scope = Namespace::scope(namespace.as_deref());
+    (StatusCode::OK, Json(state.builds.list(scope)))
+}
+
+/// Handles GET /builds/:id
+///
+/// Returns a build of the caller's namespace.
+///
+/// # Returns
+/// - `200 OK` with the build record
+/// - `404 Not Found` if the namespace has no such build
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/builds/{id}",
+    tag = "Builds",
+    params(("id", Path, description = "Build id")),
+    responses(
+        (status = 200, description = "Build record", body = crate::services::builds::BuildRecord),
+        (status = 404, description = "Build not found", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn get_build(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    let scope = Namespace::scope(namespace.as_deref());
+    Ok((StatusCode::OK, Json(state.builds.get(scope, &id)?)))
+}
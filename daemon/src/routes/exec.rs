@@ -0,0 +1,200 @@
+use axum::{
+    extract::{
+        ws::{rejection::WebSocketUpgradeRejection, Message, WebSocket, WebSocketUpgrade},
+        Extension, Json, Path, Query, State,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    api::state::AppState,
+    docker::ExecConfig,
+    errors::Error,
+    models::namespace::Namespace,
+    services::docker::{ExecInput, ExecSession},
+};
+
+use super::{ExecRequest, ExecSessionQuery};
+
+/// Control message sent by the client as a WebSocket text frame.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ExecControl {
+    Resize { cols: u16, rows: u16 },
+    Stdin { data: String },
+}
+
+/// Handles POST /apps/:name/exec
+///
+/// Runs a command inside a running app and waits for it to finish. Like
+/// interactive sessions, the command waits for an operation in flight on the
+/// app before it starts, but does not hold the lock while it runs: commands
+/// can last long, and removing or recreating the container ends them.
+///
+/// # Returns
+/// - `200 OK` with `{ stdout, stderr, exit_code }`
+/// - `400 Bad Request` if `cmd` is empty
+/// - `403 Forbidden` if the API key lacks the `exec` permission
+/// - `404 Not Found` if the container doesn't exist
+/// - `409 Conflict` if the container is not running, or the app is still busy
+///   with another operation after the wait limit
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/apps/{name}/exec",
+    tag = "Apps",
+    params(("name", Path, description = "Container name")),
+    request_body = ExecRequest,
+    responses(
+        (status = 200, description = "Command output", body = crate::docker::models::ExecOutput),
+        (status = 400, description = "Invalid command", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing exec permission", body = crate::api::error::ApiError),
+        (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 409, description = "App not running, or busy", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn exec_app(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+    Json(payload): Json<ExecRequest>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_exec("exec into apps")?;
+    }
+    let cfg = ExecConfig {
+        cmd: payload.cmd,
+        env: payload.env.unwrap_or_default(),
+        working_dir: payload.working_dir,
+        user: payload.user,
+        tty: false,
+    };
+    drop(state.lock(&name, "exec").await?);
+    let output = state.docker.exec(&name, &cfg).await?;
+    Ok((StatusCode::OK, Json(output)))
+}
+
+/// Handles GET /apps/:name/exec/ws
+///
+/// Starts an interactive command and upgrades to a WebSocket carrying the session:
+/// - binary frames from the client are written to stdin;
+/// - text frames are JSON control messages, `{"type":"resize","cols":120,"rows":40}`
+///   or `{"type":"stdin","data":"ls\n"}`;
+/// - binary frames from the server carry the terminal output;
+/// - once the command exits, `{"type":"exit","exit_code":0}` is sent and the socket closed.
+///
+/// Closing the socket closes the command's stdin. The app is locked only while
+/// the session starts: sessions can last hours, and removing or recreating the
+/// container ends them.
+///
+/// # Returns
+/// - `101 Switching Protocols` once the session is started
+/// - `400 Bad Request` if `cmd` is not a JSON array of strings, or is empty
+/// - `403 Forbidden` if the API key lacks the `exec` permission
+/// - `404 Not Found` if the container doesn't exist
+/// - `409 Conflict` if the container is not running, or the app is still busy
+///   with another operation after the wait limit
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/apps/{name}/exec/ws",
+    tag = "Apps",
+    params(("name", Path, description = "Container name"), ExecSessionQuery),
+    responses(
+        (status = 101, description = "WebSocket session started"),
+        (status = 400, description = "Invalid command", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing exec permission", body = crate::api::error::ApiError),
+        (status = 404, description = "App not found", body = crate::api::error::ApiError),
+        (status = 409, description = "App not running, or busy", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn exec_app_ws(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+    Query(query): Query<ExecSessionQuery>,
+    ws: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Result<Response, Error> {
+    // Permissions are checked before the handshake is validated.
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_exec("exec into apps")?;
+    }
+    let cmd = match query.cmd.as_deref() {
+        Some(argv) => serde_json::from_str(argv)
+            .map_err(|_| Error::BadRequest("cmd must be a JSON array of strings".into()))?,
+        None => vec!["/bin/sh".to_string()],
+    };
+    let ws = match ws {
+        Ok(ws) => ws,
+        Err(rejection) => return Ok(rejection.into_response()),
+    };
+    let cfg = ExecConfig {
+        cmd,
+        env: Default::default(),
+        working_dir: query.working_dir,
+        user: query.user,
+        tty: query.tty.unwrap_or(true),
+    };
+
+    // Started before upgrading so that failures are plain HTTP errors.
+    let session = {
+        let _lock = state.lock(&name, "exec").await?;
+        state.docker.exec_session(&name, &cfg).await?
+    };
+    if let (Some(cols), Some(rows)) = (query.cols, query.rows) {
+        let _ = session.input.send(ExecInput::Resize { cols, rows }).await;
+    }
+    Ok(ws.on_upgrade(move |socket| relay(socket, session)))
+}
+
+/// Shuttles frames between the WebSocket and the exec session until either side ends.
+async fn relay(mut socket: WebSocket, session: ExecSession) {
+    let ExecSession {
+        input,
+        mut output,
+        exit,
+    } = session;
+
+    loop {
+        tokio::select! {
+            chunk = output.recv() => match chunk {
+                Some(data) => {
+                    if socket.send(Message::Binary(data.to_vec())).await.is_err() {
+                        return;
+                    }
+                }
+                None => break,
+            },
+            message = socket.recv() => {
+                let forwarded = match message {
+                    Some(Ok(Message::Binary(data))) => ExecInput::Data(data.into()),
+                    Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                        Ok(ExecControl::Resize { cols, rows }) => ExecInput::Resize { cols, rows },
+                        Ok(ExecControl::Stdin { data }) => ExecInput::Data(data.into()),
+                        Err(e) => {
+                            let error = json!({ "type": "error", "message": e.to_string() });
+                            let _ = socket.send(Message::Text(error.to_string())).await;
+                            continue;
+                        }
+                    },
+                    Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                    // Dropping `input` closes the command's stdin.
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                };
+                // The command may exit before reading everything; its output ends the loop.
+                let _ = input.send(forwarded).await;
+            }
+        }
+    }
+
+    let status = match exit.await {
+        Ok(Ok(code)) => json!({ "type": "exit", "exit_code": code }),
+        Ok(Err(e)) => json!({ "type": "error", "message": e.to_string() }),
+        Err(_) => json!({ "type": "error", "message": "Exec session ended unexpectedly" }),
+    };
+    let _ = socket.send(Message::Text(status.to_string())).await;
+    let _ = socket.send(Message::Close(None)).await;
+}
@@ -0,0 +1,301 @@
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{
+    extract::{Extension, Json, Path, Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use futures_util::stream;
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use crate::{
+    api::state::AppState,
+    docker::{ImageSummary, PullProgress},
+    errors::Error,
+    models::namespace::Namespace,
+    services::docker::DockerClient,
+};
+
+use super::{ImageDetailsResponse, ImageResponse, PruneQuery, PullImageRequest};
+
+/// Names of the containers, in any state, created from `image`.
+async fn apps_using(docker: &dyn DockerClient, image: &ImageSummary) -> Result<Vec<String>, Error> {
+    Ok(docker
+        .list(true)
+        .await?
+        .into_iter()
+        .filter(|c| image.matches(&c.image))
+        .map(|c| c.name)
+        .collect())
+}
+
+/// Handles GET /images
+///
+/// Lists the images stored by the engine with the apps using each of them.
+///
+/// # Returns
+/// - `200 OK` with the list of images
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/images",
+    tag = "Images",
+    responses(
+        (status = 200, description = "List of images", body = [ImageResponse]),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn list_images(
+    State(docker): State<Arc<dyn DockerClient>>,
+) -> Result<impl IntoResponse, Error> {
+    let containers = docker.list(true).await?;
+    let images: Vec<ImageResponse> = docker
+        .list_images()
+        .await?
+        .into_iter()
+        .map(|image| ImageResponse {
+            apps: containers
+                .iter()
+                .filter(|c| image.matches(&c.image))
+                .map(|c| c.name.clone())
+                .collect(),
+            id: image.id,
+            tags: image.tags,
+            size: image.size,
+            created: image.created,
+        })
+        .collect();
+    Ok((StatusCode::OK, Json(images)))
+}
+
+/// Handles GET /images/:name
+///
+/// Returns the details of an image, by id or reference. Slashes in the
+/// reference must be URL-encoded (`ghcr.io%2Forg%2Fapp:1.0`).
+///
+/// # Returns
+/// - `200 OK` with the image details
+/// - `404 Not Found` if the image doesn't exist
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/images/{name}",
+    tag = "Images",
+    params(("name", Path, description = "Image id or reference, URL-encoded")),
+    responses(
+        (status = 200, description = "Image details", body = ImageDetailsResponse),
+        (status = 404, description = "Image not found", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn get_image(
+    State(docker): State<Arc<dyn DockerClient>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    let inspect = docker.inspect_image(&name).await?;
+    let summary = inspect.summary();
+    let apps = apps_using(docker.as_ref(), &summary).await?;
+    let config = inspect.config.unwrap_or_default();
+
+    let mut exposed_ports: Vec<String> = config
+        .exposed_ports
+        .unwrap_or_default()
+        .into_keys()
+        .collect();
+    exposed_ports.sort();
+    Ok((
+        StatusCode::OK,
+        Json(ImageDetailsResponse {
+            id: summary.id,
+            tags: summary.tags,
+            digests: inspect.repo_digests.unwrap_or_default(),
+            size: summary.size,
+            created: summary.created,
+            architecture: inspect.architecture,
+            os: inspect.os,
+            env: config.env.unwrap_or_default(),
+            cmd: config.cmd.unwrap_or_default(),
+            entrypoint: config.entrypoint.unwrap_or_default(),
+            working_dir: config.working_dir,
+            user: config.user,
+            exposed_ports,
+            labels: config.labels.unwrap_or_default(),
+            apps,
+        }),
+    ))
+}
+
+/// Handles DELETE /images/:name
+///
+/// Removes an image, or only the given tag when the image carries several.
+/// Images used by an app, even a stopped one, are kept.
+///
+/// # Returns
+/// - `204 No Content` if the image was removed
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the image doesn't exist
+/// - `409 Conflict` if apps use the image
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    delete,
+    path = "/images/{name}",
+    tag = "Images",
+    params(("name", Path, description = "Image id or reference, URL-encoded")),
+    responses(
+        (status = 204, description = "Image removed"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "Image not found", body = crate::api::error::ApiError),
+        (status = 409, description = "Image in use", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn delete_image(
+    State(docker): State<Arc<dyn DockerClient>>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("remove images")?;
+    }
+    let image = docker.inspect_image(&name).await?.summary();
+    let apps = apps_using(docker.as_ref(), &image).await?;
+    if !apps.is_empty() {
+        return Err(Error::Conflict(format!(
+            "Image {name} is used by apps: {}",
+            apps.join(", ")
+        )));
+    }
+    docker.remove_image(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Handles POST /images/prune
+///
+/// Removes dangling images, or every image unused by a container with `all=true`.
+///
+/// # Returns
+/// - `200 OK` with the removed images and the reclaimed space
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/images/prune",
+    tag = "Images",
+    params(PruneQuery),
+    responses(
+        (status = 200, description = "Prune report", body = crate::docker::models::PruneReport),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn prune_images(
+    State(docker): State<Arc<dyn DockerClient>>,
+    namespace: Option<Extension<Namespace>>,
+    Query(query): Query<PruneQuery>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("prune images")?;
+    }
+    let report = docker.prune_images(query.all.unwrap_or(false)).await?;
+    Ok((StatusCode::OK, Json(report)))
+}
+
+/// Handles POST /images/pull
+///
+/// Pulls an image with the namespace's credentials for its registry, if any,
+/// and streams the progress as server-sent events:
+/// - `progress` events carry `{ layer, status, current, total }`;
+/// - a final `done` event carries `{ image, id }`, or an `error` event the failure.
+///
+/// # Returns
+/// - `200 OK` with the event stream
+/// - `400 Bad Request` if the image reference is empty
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the registry doesn't know the image
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/images/pull",
+    tag = "Images",
+    request_body = PullImageRequest,
+    responses(
+        (status = 200, description = "Stream of `progress` events ended by `done` or `error`", body = crate::docker::models::PullProgress),
+        (status = 400, description = "Invalid image reference", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "Image not found", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn pull_image(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Json(payload): Json<PullImageRequest>,
+) -> Result<Response, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("pull images")?;
+    }
+    let reference = payload.image.trim().to_string();
+    if reference.is_empty() {
+        return Err(Error::BadRequest("image must not be empty".into()));
+    }
+    let auth = state.registry_auth(Namespace::scope(namespace.as_deref()), &reference);
+    let docker = state.docker;
+
+    let (tx, mut progress) = mpsc::channel::<PullProgress>(64);
+    let pull = {
+        let (docker, reference) = (docker.clone(), reference.clone());
+        tokio::spawn(async move { docker.pull_image(&reference, auth.as_ref(), tx).await })
+    };
+
+    // Wait for the first message so that failures before any progress are plain HTTP errors.
+    let first = progress.recv().await;
+    let pull = match first {
+        Some(_) => Some(pull),
+        None => {
+            pull.await.map_err(|e| Error::Unexpected(e.to_string()))??;
+            None
+        }
+    };
+
+    let (events_tx, events) = mpsc::channel::<Event>(64);
+    tokio::spawn(async move {
+        let mut next = first;
+        while let Some(step) = next {
+            if let Ok(event) = Event::default().event("progress").json_data(&step) {
+                // The client went away; the pull still completes.
+                let _ = events_tx.send(event).await;
+            }
+            next = progress.recv().await;
+        }
+
+        let result = match pull {
+            Some(pull) => pull
+                .await
+                .unwrap_or_else(|e| Err(Error::Unexpected(e.to_string()))),
+            None => Ok(()),
+        };
+        let id = match result {
+            Ok(()) => docker.inspect_image(&reference).await.map(|i| i.id),
+            Err(e) => Err(e),
+        };
+        let event = match id {
+            Ok(id) => Event::default()
+                .event("done")
+                .data(json!({ "image": reference, "id": id }).to_string()),
+            Err(e) => Event::default().event("error").data(e.to_string()),
+        };
+        let _ = events_tx.send(event).await;
+    });
+
+    let events = stream::unfold(events, |mut events| async move {
+        let event = events.recv().await?;
+        Some((Ok::<_, Infallible>(event), events))
+    });
+    Ok(Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
@@ -1,10 +1,19 @@
 pub mod apps;
+pub mod archive;
+pub mod builds;
+pub mod exec;
 pub mod health;
+pub mod images;
 pub mod metrics;
 pub mod models;
+pub mod networks;
+pub mod registries;
+pub mod system;
 pub mod version;
+pub mod volumes;
 
 pub use health::health;
 pub use metrics::metrics;
 pub use models::*;
+pub use system::system_info;
 pub use version::version;
@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::docker::models::AppInstance;
+use crate::docker::models::{AppInstance, WaitCondition};
 use serde::{Deserialize, Serialize};
 
 /// Request payload for creating a new application/container.
@@ -8,13 +8,100 @@ use serde::{Deserialize, Serialize};
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CreateAppRequest {
     pub name: String,
+    /// Image reference; leave empty when creating the app from `build`.
+    #[serde(default)]
     pub image: String,
-    pub ports: Vec<u16>,
-    pub container_port: u16,
+    /// Id of a succeeded build whose image the app runs, instead of `image`.
+    pub build: Option<String>,
+    /// `{host, container, protocol, host_ip}` mappings, or bare host ports
+    /// forwarded to `container_port` over TCP.
+    #[serde(default)]
+    pub ports: Vec<crate::docker::PortSpec>,
+    /// Container port of the bare host ports in `ports`.
+    pub container_port: Option<u16>,
     pub labels: Option<HashMap<String, String>>,
     pub env: Option<HashMap<String, String>>,
-    pub volumes: Option<Vec<String>>,
+    /// `source:target[:ro]` strings, or named volumes with a read-only flag.
+    pub volumes: Option<Vec<crate::docker::VolumeMount>>,
     pub restart_policy: Option<String>,
+    /// Networks to attach the app to, instead of the default one.
+    pub networks: Option<Vec<crate::docker::NetworkAttachment>>,
+    /// CPU, memory and process limits, unlimited when unset.
+    #[serde(flatten)]
+    pub limits: crate::docker::ResourceLimits,
+    /// Health check run inside the app; the daemon restarts the app once it keeps failing.
+    pub healthcheck: Option<crate::docker::HealthCheck>,
+    /// Command, entrypoint, working directory, user and hostname, the image defaults when unset.
+    #[serde(flatten)]
+    pub process: crate::docker::ProcessOverrides,
+    /// Capabilities, read-only root filesystem, tmpfs mounts, no-new-privileges and AppArmor profile.
+    #[serde(flatten)]
+    pub security: crate::docker::SecurityOptions,
+}
+
+/// Request payload for running a command inside an application.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ExecRequest {
+    /// Program and arguments, run without a shell.
+    pub cmd: Vec<String>,
+    pub env: Option<HashMap<String, String>>,
+    pub working_dir: Option<String>,
+    pub user: Option<String>,
+}
+
+/// Query parameters for an interactive exec session.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams, utoipa::ToSchema))]
+pub struct ExecSessionQuery {
+    /// Program and arguments as a JSON array of strings, like
+    /// `["sh","-c","ls /srv"]` (defaults to `["/bin/sh"]`).
+    pub cmd: Option<String>,
+    pub working_dir: Option<String>,
+    pub user: Option<String>,
+    /// Allocate a pseudo-terminal (defaults to `true`).
+    pub tty: Option<bool>,
+    /// Initial terminal width.
+    pub cols: Option<u16>,
+    /// Initial terminal height.
+    pub rows: Option<u16>,
+}
+
+/// Request payload for creating a network.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateNetworkRequest {
+    pub name: String,
+    /// Network driver (`bridge` by default).
+    pub driver: Option<String>,
+    /// Subnet in CIDR notation, allocated by the engine when unset.
+    pub subnet: Option<String>,
+    /// Cut the network off from the outside world.
+    #[serde(default)]
+    pub internal: bool,
+}
+
+/// Request payload for creating a named volume.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateVolumeRequest {
+    pub name: String,
+    /// Volume driver (`local` by default).
+    pub driver: Option<String>,
+    /// Options passed to the driver.
+    #[serde(default)]
+    pub driver_opts: BTreeMap<String, String>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// Query parameters for copying files to and from an application.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams, utoipa::ToSchema))]
+pub struct ArchiveQuery {
+    /// Path inside the container: the file or directory to archive, or the
+    /// directory to extract into.
+    pub path: String,
 }
 
 /// Pagination parameters for listing applications.
@@ -26,6 +113,50 @@ pub struct Pagination {
     pub search: Option<String>,
 }
 
+/// Query parameters for container resource statistics.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams, utoipa::ToSchema))]
+pub struct StatsQuery {
+    /// Emit samples as server-sent events instead of a single snapshot.
+    pub stream: Option<bool>,
+    /// Seconds between two streamed samples (1 to 60, defaults to 1).
+    pub interval: Option<u64>,
+}
+
+/// Query parameters for restarting an app.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams, utoipa::ToSchema))]
+pub struct RestartQuery {
+    /// Seconds to wait for the app to stop before killing it; the engine's default when unset.
+    pub timeout: Option<u64>,
+}
+
+/// Query parameters for sending a signal to an app.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams, utoipa::ToSchema))]
+pub struct KillQuery {
+    /// Signal name or number, such as `SIGHUP` or `9` (defaults to `SIGKILL`).
+    pub signal: Option<String>,
+}
+
+/// Query parameters for waiting for an app to exit.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams, utoipa::ToSchema))]
+pub struct WaitQuery {
+    /// State to wait for (defaults to `not-running`).
+    pub condition: Option<WaitCondition>,
+    /// Seconds to wait before giving up with `504`; waits as long as it takes when unset.
+    pub timeout: Option<u64>,
+}
+
+/// Payload for renaming an app.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RenameAppRequest {
+    /// New name of the app.
+    pub name: String,
+}
+
 /// Standard response format for paginated lists.
 #[derive(Serialize)]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
@@ -56,4 +187,143 @@ pub struct ContainerIdResponse {
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct StatusResponse {
     pub status: String,
+    /// Set when the app has a health check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<crate::docker::HealthStatus>,
+    /// Last restart of the app by the health supervisor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_health_restart: Option<crate::services::health::HealthRestart>,
+}
+
+/// Image stored by the engine, with the apps using it.
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ImageResponse {
+    pub id: String,
+    /// `repository:tag` references; empty for dangling images.
+    pub tags: Vec<String>,
+    /// Size in bytes.
+    pub size: u64,
+    /// Creation time, RFC 3339.
+    pub created: String,
+    /// Names of the apps created from this image.
+    pub apps: Vec<String>,
+}
+
+/// Network of the engine, with the apps attached to it.
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NetworkResponse {
+    pub id: String,
+    pub name: String,
+    pub driver: String,
+    /// Subnet in CIDR notation.
+    pub subnet: Option<String>,
+    /// Whether the network is cut off from the outside world.
+    pub internal: bool,
+    /// Creation time, RFC 3339.
+    pub created: String,
+    /// Names of the apps attached to this network.
+    pub apps: Vec<String>,
+}
+
+/// Named volume, with its size and the apps mounting it.
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VolumeResponse {
+    pub name: String,
+    pub driver: String,
+    /// Path of the volume data on the host.
+    pub mountpoint: String,
+    /// Creation time, RFC 3339.
+    pub created: String,
+    pub labels: BTreeMap<String, String>,
+    /// Driver options.
+    pub options: BTreeMap<String, String>,
+    /// Size on disk in bytes, when the engine computed it.
+    pub size: Option<u64>,
+    /// Names of the apps mounting this volume.
+    pub apps: Vec<String>,
+}
+
+/// Detailed view of an image.
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ImageDetailsResponse {
+    pub id: String,
+    pub tags: Vec<String>,
+    /// `repository@sha256:...` references of the pulled manifests.
+    pub digests: Vec<String>,
+    pub size: u64,
+    pub created: String,
+    pub architecture: String,
+    pub os: String,
+    /// Default environment, as `KEY=value`.
+    pub env: Vec<String>,
+    pub cmd: Vec<String>,
+    pub entrypoint: Vec<String>,
+    pub working_dir: String,
+    pub user: String,
+    /// Exposed ports, as `port/protocol`.
+    pub exposed_ports: Vec<String>,
+    pub labels: HashMap<String, String>,
+    /// Names of the apps created from this image.
+    pub apps: Vec<String>,
+}
+
+/// Request payload for pulling an image.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PullImageRequest {
+    /// Image reference, `nginx`, `nginx:1.27` or `ghcr.io/org/app@sha256:...`.
+    pub image: String,
+}
+
+/// Query parameters for pruning images.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams, utoipa::ToSchema))]
+pub struct PruneQuery {
+    /// Remove every image unused by a container, not only dangling ones.
+    pub all: Option<bool>,
+}
+
+/// Request payload for storing registry credentials.
+///
+/// The password is write-only: no response ever includes it.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RegistryCredentialRequest {
+    pub username: String,
+    /// Password or access token.
+    pub password: String,
+}
+
+/// Query parameters of a build whose body is a tar context.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams, utoipa::ToSchema))]
+pub struct BuildQuery {
+    /// Reference to tag the image with (defaults to `lightshuttle-build:<random>`).
+    pub tag: Option<String>,
+    /// Path of the Dockerfile inside the context (defaults to `Dockerfile`).
+    pub dockerfile: Option<String>,
+    /// Stage of a multi-stage Dockerfile to build.
+    pub target: Option<String>,
+    /// `ARG` values as a JSON object, `{"VERSION":"1.2"}`.
+    pub build_args: Option<String>,
+}
+
+/// Request payload of a build from an inline Dockerfile.
+#[derive(Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BuildRequest {
+    /// Dockerfile contents.
+    pub dockerfile: String,
+    /// Base64 of a tar archive holding the files the Dockerfile copies.
+    pub context: Option<String>,
+    /// Reference to tag the image with (defaults to `lightshuttle-build:<random>`).
+    pub tag: Option<String>,
+    /// Stage of a multi-stage Dockerfile to build.
+    pub target: Option<String>,
+    /// `ARG` values, by name.
+    pub build_args: Option<BTreeMap<String, String>>,
 }
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    docker::{ContainerSummary, NetworkConfig, NetworkInfo},
+    errors::Error,
+    models::namespace::Namespace,
+    services::docker::DockerClient,
+};
+
+use super::{CreateNetworkRequest, NetworkResponse};
+
+/// View of a network with the names of the `containers`, in any state, attached to it.
+fn network_response(network: NetworkInfo, containers: &[ContainerSummary]) -> NetworkResponse {
+    NetworkResponse {
+        apps: containers
+            .iter()
+            .filter(|c| c.networks.contains(&network.name))
+            .map(|c| c.name.clone())
+            .collect(),
+        id: network.id,
+        name: network.name,
+        driver: network.driver,
+        subnet: network.subnet,
+        internal: network.internal,
+        created: network.created,
+    }
+}
+
+/// Handles GET /networks
+///
+/// Lists the networks of the engine, built-in ones included, with the apps
+/// attached to each of them.
+///
+/// # Returns
+/// - `200 OK` with the list of networks
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/networks",
+    tag = "Networks",
+    responses(
+        (status = 200, description = "List of networks", body = [NetworkResponse]),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn list_networks(
+    State(docker): State<Arc<dyn DockerClient>>,
+) -> Result<impl IntoResponse, Error> {
+    let containers = docker.list(true).await?;
+    let networks: Vec<NetworkResponse> = docker
+        .list_networks()
+        .await?
+        .into_iter()
+        .map(|network| network_response(network, &containers))
+        .collect();
+    Ok((StatusCode::OK, Json(networks)))
+}
+
+/// Handles POST /networks
+///
+/// Creates a user-defined network. Apps attached to the same network reach
+/// each other by name, without publishing ports on the host.
+///
+/// # Returns
+/// - `201 Created` with the network
+/// - `400 Bad Request` if the name, driver or subnet is invalid
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `409 Conflict` if a network with that name exists
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/networks",
+    tag = "Networks",
+    request_body = CreateNetworkRequest,
+    responses(
+        (status = 201, description = "Network created", body = NetworkResponse),
+        (status = 400, description = "Invalid network", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 409, description = "Network exists", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn create_network(
+    State(docker): State<Arc<dyn DockerClient>>,
+    namespace: Option<Extension<Namespace>>,
+    Json(payload): Json<CreateNetworkRequest>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("create networks")?;
+    }
+    let cfg = NetworkConfig {
+        name: payload.name,
+        driver: payload.driver,
+        subnet: payload.subnet,
+        internal: payload.internal,
+    };
+    cfg.validate()?;
+    let id = docker.create_network(&cfg).await?;
+    let network = docker.inspect_network(&id).await?;
+    Ok((StatusCode::CREATED, Json(network_response(network, &[]))))
+}
+
+/// Handles GET /networks/:name
+///
+/// Returns a network, by name or id, with the apps attached to it.
+///
+/// # Returns
+/// - `200 OK` with the network
+/// - `404 Not Found` if the network doesn't exist
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/networks/{name}",
+    tag = "Networks",
+    params(("name", Path, description = "Network name or id")),
+    responses(
+        (status = 200, description = "Network details", body = NetworkResponse),
+        (status = 404, description = "Network not found", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn get_network(
+    State(docker): State<Arc<dyn DockerClient>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    let network = docker.inspect_network(&name).await?;
+    let containers = docker.list(true).await?;
+    Ok((StatusCode::OK, Json(network_response(network, &containers))))
+}
+
+/// Handles DELETE /networks/:name
+///
+/// Removes a network. Networks an app is attached to, even a stopped one,
+/// and the built-in networks are kept.
+///
+/// # Returns
+/// - `204 No Content` if the network was removed
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the network doesn't exist
+/// - `409 Conflict` if apps are attached or the network is built in
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    delete,
+    path = "/networks/{name}",
+    tag = "Networks",
+    params(("name", Path, description = "Network name or id")),
+    responses(
+        (status = 204, description = "Network removed"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "Network not found", body = crate::api::error::ApiError),
+        (status = 409, description = "Network in use or built in", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn delete_network(
+    State(docker): State<Arc<dyn DockerClient>>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("remove networks")?;
+    }
+    let network = docker.inspect_network(&name).await?;
+    let containers = docker.list(true).await?;
+    let network = network_response(network, &containers);
+    if !network.apps.is_empty() {
+        return Err(Error::Conflict(format!(
+            "Network {name} is used by apps: {}",
+            network.apps.join(", ")
+        )));
+    }
+    docker.remove_network(&network.id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
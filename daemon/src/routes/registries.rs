@@ -0,0 +1,114 @@
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{api::state::AppState, errors::Error, models::namespace::Namespace};
+
+use super::RegistryCredentialRequest;
+
+/// Handles GET /registries
+///
+/// Lists the registries the caller's namespace holds credentials for.
+/// Passwords are never returned.
+///
+/// # Returns
+/// - `200 OK` with the list of credentials, without passwords
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/registries",
+    tag = "Registries",
+    responses(
+        (status = 200, description = "Registry credentials of the namespace", body = [crate::services::credentials::RegistryCredential])
+    )
+))]
+pub async fn list_registries(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+) -> impl IntoResponse {
+    let scope = Namespace::scope(namespace.as_deref());
+    (StatusCode::OK, Json(state.credentials.list(scope)))
+}
+
+/// Handles PUT /registries/:registry
+///
+/// Stores the credentials used when pulling images from `registry` for apps
+/// of the caller's namespace, replacing existing ones.
+///
+/// # Returns
+/// - `201 Created` for a new registry, `200 OK` when replaced, without the password
+/// - `400 Bad Request` if the registry, username or password is invalid
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `500 Internal Server Error` if the store cannot be written
+#[cfg_attr(feature = "openapi", utoipa::path(
+    put,
+    path = "/registries/{registry}",
+    tag = "Registries",
+    params(("registry", Path, description = "Registry host, `ghcr.io` or `registry.example.com:5000`")),
+    request_body = RegistryCredentialRequest,
+    responses(
+        (status = 200, description = "Credentials replaced", body = crate::services::credentials::RegistryCredential),
+        (status = 201, description = "Credentials stored", body = crate::services::credentials::RegistryCredential),
+        (status = 400, description = "Invalid credentials", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn put_registry(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(registry): Path<String>,
+    Json(payload): Json<RegistryCredentialRequest>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("change registry credentials")?;
+    }
+    let scope = Namespace::scope(namespace.as_deref());
+    let (credential, created) =
+        state
+            .credentials
+            .put(scope, &registry, &payload.username, &payload.password)?;
+    tracing::info!(namespace = scope, registry = %credential.registry, "Registry credentials stored");
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(credential)))
+}
+
+/// Handles DELETE /registries/:registry
+///
+/// Removes the caller's namespace credentials for `registry`.
+///
+/// # Returns
+/// - `204 No Content` if the credentials were removed
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the namespace holds no credentials for the registry
+/// - `500 Internal Server Error` if the store cannot be written
+#[cfg_attr(feature = "openapi", utoipa::path(
+    delete,
+    path = "/registries/{registry}",
+    tag = "Registries",
+    params(("registry", Path, description = "Registry host")),
+    responses(
+        (status = 204, description = "Credentials removed"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "No credentials for the registry", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn delete_registry(
+    State(state): State<AppState>,
+    namespace: Option<Extension<Namespace>>,
+    Path(registry): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("change registry credentials")?;
+    }
+    let scope = Namespace::scope(namespace.as_deref());
+    state.credentials.remove(scope, &registry)?;
+    tracing::info!(namespace = scope, %registry, "Registry credentials removed");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use serde::Serialize;
+
+use crate::{
+    docker::{EngineInfo, MIN_API_VERSION},
+    services::docker::{BackendInfo, DockerClient},
+};
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SystemInfoResponse {
+    version: &'static str,
+    /// Cargo features the daemon was built with.
+    features: Vec<&'static str>,
+    docker: BackendInfo,
+    /// Version and capabilities of the engine; `null` when it cannot be reached.
+    engine: Option<EngineInfo>,
+    /// Oldest Engine API version the daemon supports.
+    min_api_version: &'static str,
+}
+
+/// Cargo features enabled at build time.
+fn features() -> Vec<&'static str> {
+    [
+        ("openapi", cfg!(feature = "openapi")),
+        ("testing", cfg!(feature = "testing")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+/// Handles GET /system/info
+///
+/// Reports the daemon version and features, the Docker backend and endpoint
+/// in use, and the version and capabilities of the engine behind them.
+#[cfg_attr(
+    feature = "openapi",
+    utoipa::path(
+        get,
+        path = "/system/info",
+        tag = "System",
+        responses((status = 200, description = "Daemon, Docker backend and engine information", body = SystemInfoResponse))
+    )
+)]
+pub async fn system_info(State(docker): State<Arc<dyn DockerClient>>) -> Json<SystemInfoResponse> {
+    let engine = docker
+        .engine_info()
+        .await
+        .inspect_err(|e| tracing::warn!(error = %e, "Failed to query the Docker engine"))
+        .ok();
+    Json(SystemInfoResponse {
+        version: env!("CARGO_PKG_VERSION"),
+        features: features(),
+        docker: docker.describe(),
+        engine,
+        min_api_version: MIN_API_VERSION,
+    })
+}
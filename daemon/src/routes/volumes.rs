@@ -0,0 +1,194 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Extension, Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    docker::{VolumeConfig, VolumeInfo, VolumeUsage},
+    errors::Error,
+    models::namespace::Namespace,
+    services::docker::DockerClient,
+};
+
+use super::{CreateVolumeRequest, VolumeResponse};
+
+/// View of a volume with its size and the apps, in any state, mounting it.
+fn volume_response(volume: VolumeInfo, usage: &HashMap<String, VolumeUsage>) -> VolumeResponse {
+    let usage = usage.get(&volume.name).cloned().unwrap_or_default();
+    let mut apps = usage.apps;
+    apps.sort();
+    apps.dedup();
+    VolumeResponse {
+        name: volume.name,
+        driver: volume.driver,
+        mountpoint: volume.mountpoint,
+        created: volume.created,
+        labels: volume.labels.unwrap_or_default(),
+        options: volume.options.unwrap_or_default(),
+        size: usage.size,
+        apps,
+    }
+}
+
+/// Handles GET /volumes
+///
+/// Lists the named volumes of the engine with their size on disk and the
+/// apps mounting each of them.
+///
+/// # Returns
+/// - `200 OK` with the list of volumes
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/volumes",
+    tag = "Volumes",
+    responses(
+        (status = 200, description = "List of volumes", body = [VolumeResponse]),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn list_volumes(
+    State(docker): State<Arc<dyn DockerClient>>,
+) -> Result<impl IntoResponse, Error> {
+    let usage = docker.volume_usage().await?;
+    let volumes: Vec<VolumeResponse> = docker
+        .list_volumes()
+        .await?
+        .into_iter()
+        .map(|volume| volume_response(volume, &usage))
+        .collect();
+    Ok((StatusCode::OK, Json(volumes)))
+}
+
+/// Handles POST /volumes
+///
+/// Creates a named volume. Apps mount it with
+/// `{"volume": "<name>", "target": "/path", "read_only": false}`.
+///
+/// # Returns
+/// - `201 Created` with the volume
+/// - `400 Bad Request` if the name, driver, options or labels are invalid
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `409 Conflict` if a volume with that name exists
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/volumes",
+    tag = "Volumes",
+    request_body = CreateVolumeRequest,
+    responses(
+        (status = 201, description = "Volume created", body = VolumeResponse),
+        (status = 400, description = "Invalid volume", body = crate::api::error::ApiError),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 409, description = "Volume exists", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn create_volume(
+    State(docker): State<Arc<dyn DockerClient>>,
+    namespace: Option<Extension<Namespace>>,
+    Json(payload): Json<CreateVolumeRequest>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("create volumes")?;
+    }
+    let cfg = VolumeConfig {
+        name: payload.name,
+        driver: payload.driver,
+        driver_opts: payload.driver_opts,
+        labels: payload.labels,
+    };
+    cfg.validate()?;
+    // Engines return an existing volume unchanged instead of failing.
+    match docker.inspect_volume(&cfg.name).await {
+        Ok(_) => {
+            return Err(Error::Conflict(format!(
+                "Volume {} already exists",
+                cfg.name
+            )))
+        }
+        Err(Error::NotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+    let volume = docker.create_volume(&cfg).await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(volume_response(volume, &HashMap::new())),
+    ))
+}
+
+/// Handles GET /volumes/:name
+///
+/// Returns a named volume with its size on disk and the apps mounting it.
+///
+/// # Returns
+/// - `200 OK` with the volume
+/// - `404 Not Found` if the volume doesn't exist
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/volumes/{name}",
+    tag = "Volumes",
+    params(("name", Path, description = "Volume name")),
+    responses(
+        (status = 200, description = "Volume details", body = VolumeResponse),
+        (status = 404, description = "Volume not found", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn get_volume(
+    State(docker): State<Arc<dyn DockerClient>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    let volume = docker.inspect_volume(&name).await?;
+    let usage = docker.volume_usage().await?;
+    Ok((StatusCode::OK, Json(volume_response(volume, &usage))))
+}
+
+/// Handles DELETE /volumes/:name
+///
+/// Removes a named volume and its data. Volumes an app mounts, even a
+/// stopped one, are kept.
+///
+/// # Returns
+/// - `204 No Content` if the volume was removed
+/// - `403 Forbidden` if the API key lacks the `write` permission
+/// - `404 Not Found` if the volume doesn't exist
+/// - `409 Conflict` if apps mount the volume
+/// - `500 Internal Server Error` on error
+#[cfg_attr(feature = "openapi", utoipa::path(
+    delete,
+    path = "/volumes/{name}",
+    tag = "Volumes",
+    params(("name", Path, description = "Volume name")),
+    responses(
+        (status = 204, description = "Volume removed"),
+        (status = 403, description = "Missing write permission", body = crate::api::error::ApiError),
+        (status = 404, description = "Volume not found", body = crate::api::error::ApiError),
+        (status = 409, description = "Volume in use", body = crate::api::error::ApiError),
+        (status = 500, description = "Internal server error", body = crate::api::error::ApiError)
+    )
+))]
+pub async fn delete_volume(
+    State(docker): State<Arc<dyn DockerClient>>,
+    namespace: Option<Extension<Namespace>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    if let Some(Extension(ns)) = &namespace {
+        ns.require_write("remove volumes")?;
+    }
+    let volume = docker.inspect_volume(&name).await?;
+    let usage = docker.volume_usage().await?;
+    let volume = volume_response(volume, &usage);
+    if !volume.apps.is_empty() {
+        return Err(Error::Conflict(format!(
+            "Volume {name} is mounted by apps: {}",
+            volume.apps.join(", ")
+        )));
+    }
+    docker.remove_volume(&name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,200 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    docker::BuildOptions,
+    errors::Error,
+    services::storage::{self, now},
+};
+
+/// Builds kept per namespace; the oldest finished ones are dropped first.
+pub const MAX_BUILDS: usize = 100;
+
+/// Progress of a build.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum BuildStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Build started through the API, referenced by id when creating an app.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BuildRecord {
+    pub id: String,
+    pub status: BuildStatus,
+    /// Reference the image is tagged with.
+    pub tag: String,
+    /// Id of the built image, once succeeded.
+    pub image_id: Option<String>,
+    /// Path of the Dockerfile inside the context.
+    pub dockerfile: String,
+    pub target: Option<String>,
+    pub build_args: BTreeMap<String, String>,
+    /// Failure reason, once failed.
+    pub error: Option<String>,
+    /// Start time, RFC 3339.
+    pub started: String,
+    /// End time, RFC 3339.
+    pub finished: Option<String>,
+}
+
+/// Builds of every namespace, keyed by namespace, oldest first.
+type Entries = BTreeMap<String, VecDeque<BuildRecord>>;
+
+/// History of image builds, scoped per namespace.
+///
+/// Records are written to disk as plain JSON when the store has a path,
+/// so that apps can be created from a build after a daemon restart.
+pub struct BuildStore {
+    path: Option<PathBuf>,
+    entries: RwLock<Entries>,
+}
+
+impl BuildStore {
+    /// Store kept in memory only.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            entries: RwLock::default(),
+        }
+    }
+
+    /// Opens the store at `path`; a missing file is an empty store.
+    ///
+    /// Builds still running when the daemon stopped are marked as failed.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let mut entries: Entries = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data).map_err(|e| store_error(&path, e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Entries::new(),
+            Err(e) => return Err(store_error(&path, e)),
+        };
+        for record in entries.values_mut().flatten() {
+            if record.status == BuildStatus::Running {
+                record.status = BuildStatus::Failed;
+                record.error = Some("Interrupted by a daemon restart".into());
+            }
+        }
+        Ok(Self {
+            path: Some(path),
+            entries: RwLock::new(entries),
+        })
+    }
+
+    /// Opens the store at `BUILDS_FILE`, `builds.json` in the data directory by default.
+    pub fn from_env() -> Result<Self, Error> {
+        let path = std::env::var("BUILDS_FILE")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| storage::data_dir().join("builds.json"));
+        Self::open(path)
+    }
+
+    /// Builds of a namespace, newest first.
+    pub fn list(&self, namespace: &str) -> Vec<BuildRecord> {
+        let entries = self.entries.read().unwrap_or_else(|p| p.into_inner());
+        entries
+            .get(namespace)
+            .into_iter()
+            .flatten()
+            .rev()
+            .cloned()
+            .collect()
+    }
+
+    /// A build of the namespace, by id.
+    pub fn get(&self, namespace: &str, id: &str) -> Result<BuildRecord, Error> {
+        let entries = self.entries.read().unwrap_or_else(|p| p.into_inner());
+        entries
+            .get(namespace)
+            .and_then(|builds| builds.iter().find(|b| b.id == id))
+            .cloned()
+            .ok_or_else(|| Error::NotFound(format!("No build {id}")))
+    }
+
+    /// Records a new running build.
+    pub fn start(&self, namespace: &str, opts: &BuildOptions) -> Result<BuildRecord, Error> {
+        let record = BuildRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            status: BuildStatus::Running,
+            tag: opts.tag.clone(),
+            image_id: None,
+            dockerfile: opts.dockerfile.clone(),
+            target: opts.target.clone(),
+            build_args: opts.build_args.clone(),
+            error: None,
+            started: now(),
+            finished: None,
+        };
+        self.update(|entries| {
+            let builds = entries.entry(namespace.to_string()).or_default();
+            builds.push_back(record.clone());
+            while builds.len() > MAX_BUILDS {
+                let Some(oldest) = builds.iter().position(|b| b.status != BuildStatus::Running)
+                else {
+                    break;
+                };
+                builds.remove(oldest);
+            }
+        })?;
+        Ok(record)
+    }
+
+    /// Records the outcome of a build: the image id, or the failure.
+    pub fn finish(
+        &self,
+        namespace: &str,
+        id: &str,
+        result: &Result<String, Error>,
+    ) -> Result<BuildRecord, Error> {
+        let mut finished = None;
+        self.update(|entries| {
+            let record = entries
+                .get_mut(namespace)
+                .and_then(|builds| builds.iter_mut().find(|b| b.id == id));
+            if let Some(record) = record {
+                match result {
+                    Ok(image_id) => {
+                        record.status = BuildStatus::Succeeded;
+                        record.image_id = Some(image_id.clone());
+                    }
+                    Err(e) => {
+                        record.status = BuildStatus::Failed;
+                        record.error = Some(e.to_string());
+                    }
+                }
+                record.finished = Some(now());
+                finished = Some(record.clone());
+            }
+        })?;
+        finished.ok_or_else(|| Error::NotFound(format!("No build {id}")))
+    }
+
+    /// Applies `change` and persists the result; memory is only updated once saved.
+    fn update(&self, change: impl FnOnce(&mut Entries)) -> Result<(), Error> {
+        let mut entries = self.entries.write().unwrap_or_else(|p| p.into_inner());
+        let mut updated = entries.clone();
+        change(&mut updated);
+        if let Some(path) = &self.path {
+            let data = serde_json::to_vec_pretty(&updated)
+                .map_err(|e| Error::Unexpected(e.to_string()))?;
+            storage::write_private(path, &data)?;
+        }
+        *entries = updated;
+        Ok(())
+    }
+}
+
+fn store_error(path: &Path, reason: impl std::fmt::Display) -> Error {
+    Error::Unexpected(format!("Build store {}: {reason}", path.display()))
+}
@@ -0,0 +1,262 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    time::Duration,
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    docker::{
+        app_from_inspect, AppInstance, AppStatus, ContainerEvent, ContainerInspect, ContainerState,
+    },
+    errors::Error,
+    services::docker::DockerClient,
+};
+
+/// Default interval between two full resyncs.
+pub const DEFAULT_RESYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Delay before reconnecting to a closed events stream.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Actions that can change what the index holds for a container.
+const TRACKED_ACTIONS: &[&str] = &[
+    "create", "start", "restart", "stop", "die", "kill", "pause", "unpause", "oom", "rename",
+    "update",
+];
+
+/// In-memory index of containers, kept up to date by the engine events stream.
+///
+/// Listing and lookups are served from the index without any Docker call.
+/// Each event triggers at most one `inspect` of the affected container, and
+/// a periodic resync compares the index with `list` to correct drift.
+pub struct ContainerCache {
+    client: Arc<dyn DockerClient>,
+    containers: RwLock<HashMap<String, ContainerInspect>>,
+    ready: AtomicBool,
+}
+
+impl ContainerCache {
+    /// Creates an empty cache; call [`ContainerCache::spawn`] or
+    /// [`ContainerCache::resync`] to populate it.
+    pub fn new(client: Arc<dyn DockerClient>) -> Self {
+        Self {
+            client,
+            containers: RwLock::default(),
+            ready: AtomicBool::new(false),
+        }
+    }
+
+    /// Creates a cache and starts its background tasks: the events
+    /// subscription (reconnected when it drops) and the periodic resync.
+    pub fn spawn(client: Arc<dyn DockerClient>, resync_interval: Duration) -> Arc<Self> {
+        let cache = Arc::new(Self::new(client));
+
+        let watcher = cache.clone();
+        tokio::spawn(async move { watcher.watch().await });
+
+        let resync = cache.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(resync_interval);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = resync.resync().await {
+                    tracing::warn!(error = %e, "Container cache resync failed");
+                }
+            }
+        });
+
+        cache
+    }
+
+    /// Whether the index has been populated at least once.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<String, ContainerInspect>> {
+        self.containers
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<String, ContainerInspect>> {
+        self.containers
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn watch(&self) {
+        loop {
+            let (tx, mut rx) = mpsc::channel(256);
+            let client = self.client.clone();
+            let stream = tokio::spawn(async move { client.events(tx).await });
+
+            // Resync once subscribed so that nothing between the two is missed.
+            if let Err(e) = self.resync().await {
+                tracing::warn!(error = %e, "Container cache resync failed");
+            }
+            while let Some(event) = rx.recv().await {
+                self.apply(&event).await;
+            }
+
+            match stream.await {
+                Ok(Err(e)) => tracing::warn!(error = %e, "Docker events stream closed"),
+                Err(e) => tracing::warn!(error = %e, "Docker events task failed"),
+                Ok(Ok(())) => {}
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    /// Updates the index for a single event.
+    pub async fn apply(&self, event: &ContainerEvent) {
+        if let Some(old_name) = &event.old_name {
+            self.write().remove(old_name);
+        }
+        if event.action == "destroy" {
+            self.write()
+                .retain(|name, c| *name != event.name && c.id != event.id);
+            return;
+        }
+
+        // Health checks report as `health_status: healthy`.
+        let tracked = TRACKED_ACTIONS.contains(&event.action.as_str())
+            || event.action.starts_with("health_status");
+        if !tracked {
+            return;
+        }
+        let target = if event.name.is_empty() {
+            &event.id
+        } else {
+            &event.name
+        };
+        if let Err(e) = self.refresh(target).await {
+            tracing::debug!(error = %e, container = %target, "Failed to refresh cached container");
+        }
+    }
+
+    /// Re-inspects one container, dropping it from the index if it no longer exists.
+    pub async fn refresh(&self, name: &str) -> Result<(), Error> {
+        match self.client.inspect(name).await {
+            Ok(container) => {
+                let key = container.name.trim_start_matches('/').to_string();
+                let mut containers = self.write();
+                containers.retain(|n, c| c.id != container.id || *n == key);
+                containers.insert(key, container);
+                Ok(())
+            }
+            Err(Error::ContainerNotFound) => {
+                self.write().retain(|n, c| n != name && c.id != name);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Compares the index with a full `list` and re-inspects what changed.
+    ///
+    /// Costs one `list` plus one `inspect` per new or changed container.
+    pub async fn resync(&self) -> Result<(), Error> {
+        let summaries = self.client.list(true).await?;
+
+        let stale: Vec<String> = {
+            let containers = self.read();
+            summaries
+                .iter()
+                .filter(|s| {
+                    !matches!(
+                        containers.get(&s.name),
+                        Some(c) if c.id == s.id && c.state.status == s.state
+                    )
+                })
+                .map(|s| s.name.clone())
+                .collect()
+        };
+
+        let mut fresh = Vec::with_capacity(stale.len());
+        for name in &stale {
+            match self.client.inspect(name).await {
+                Ok(container) => fresh.push((name.clone(), container)),
+                Err(Error::ContainerNotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let listed: HashSet<&str> = summaries.iter().map(|s| s.name.as_str()).collect();
+        let mut containers = self.write();
+        containers.retain(|name, _| listed.contains(name.as_str()));
+        containers.extend(fresh);
+        drop(containers);
+
+        self.ready.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Running containers, newest first, as returned by `GET /apps`.
+    pub fn running(&self) -> Vec<AppInstance> {
+        let containers = self.read();
+        let mut running: Vec<&ContainerInspect> = containers
+            .values()
+            .filter(|c| matches!(AppStatus::from_state(&c.state.status), AppStatus::Running))
+            .collect();
+        running.sort_by(|a, b| b.created.cmp(&a.created).then(a.name.cmp(&b.name)));
+
+        running
+            .into_iter()
+            .enumerate()
+            .map(|(idx, c)| AppInstance {
+                id: idx as u32 + 1,
+                ports: host_ports(c),
+                ..app_from_inspect(c)
+            })
+            .collect()
+    }
+
+    /// A cached container, as returned by `GET /apps/{name}`.
+    pub fn get(&self, name: &str) -> Option<AppInstance> {
+        self.read().get(name).map(app_from_inspect)
+    }
+
+    /// State of a cached container (`running`, `exited`, ...).
+    pub fn status(&self, name: &str) -> Option<String> {
+        self.read().get(name).map(|c| c.state.status.clone())
+    }
+
+    /// Runtime state of a cached container, health included.
+    pub fn state(&self, name: &str) -> Option<ContainerState> {
+        self.read().get(name).map(|c| c.state.clone())
+    }
+
+    /// Removes a container from the index.
+    pub fn forget(&self, name: &str) {
+        self.write().remove(name);
+    }
+}
+
+/// Published host ports of an inspected container.
+///
+/// IPv4 and IPv6 bindings of the same port are reported once.
+fn host_ports(container: &ContainerInspect) -> Vec<u16> {
+    let mut ports: Vec<u16> = Vec::new();
+    let bindings = container
+        .network_settings
+        .ports
+        .iter()
+        .flat_map(|ports| ports.values())
+        .flatten()
+        .flatten();
+    for binding in bindings {
+        if let Ok(port) = binding.host_port.parse() {
+            if !ports.contains(&port) {
+                ports.push(port);
+            }
+        }
+    }
+    ports
+}
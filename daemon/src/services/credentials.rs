@@ -0,0 +1,338 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    docker::{registry_of, RegistryAuth},
+    errors::Error,
+    services::storage::{self, now},
+};
+
+/// Namespace used for requests that carry none, i.e. when authentication is disabled.
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Associated data binding the ciphertext to this file format.
+const AAD: &[u8] = b"lightshuttle-registries-v1";
+
+/// Registry credential as exposed by the API; the password is never included.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RegistryCredential {
+    /// Registry host, `ghcr.io` or `registry.example.com:5000`.
+    pub registry: String,
+    pub username: String,
+    /// Creation time, RFC 3339.
+    pub created: String,
+    /// Last update time, RFC 3339.
+    pub updated: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Stored {
+    username: String,
+    password: String,
+    created: String,
+    updated: String,
+}
+
+/// Credentials of every namespace, keyed by namespace then registry.
+type Entries = BTreeMap<String, BTreeMap<String, Stored>>;
+
+/// On-disk envelope: the serialized entries sealed with AES-256-GCM.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Private registry credentials, scoped per namespace and encrypted at rest.
+///
+/// The whole store is sealed with AES-256-GCM under a fresh nonce on every
+/// write; passwords only leave it as [`RegistryAuth`] values handed to the
+/// Docker backend.
+pub struct CredentialStore {
+    path: Option<PathBuf>,
+    key: LessSafeKey,
+    entries: RwLock<Entries>,
+}
+
+impl CredentialStore {
+    /// Store kept in memory only, under a random key.
+    pub fn in_memory() -> Self {
+        let mut key = [0u8; 32];
+        // The system RNG only fails on platforms without a usable entropy source.
+        SystemRandom::new()
+            .fill(&mut key)
+            .expect("system random generator unavailable");
+        Self {
+            path: None,
+            key: sealing_key(&key).expect("32-byte key"),
+            entries: RwLock::default(),
+        }
+    }
+
+    /// Opens the store at `path`, decrypting it with the 32-byte `key`.
+    ///
+    /// A missing file is an empty store; it is created on the first write.
+    pub fn open(path: impl Into<PathBuf>, key: &[u8]) -> Result<Self, Error> {
+        let path = path.into();
+        let key = sealing_key(key)?;
+        let entries = match std::fs::read(&path) {
+            Ok(data) => unseal(&key, &data).map_err(|reason| store_error(&path, reason))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Entries::new(),
+            Err(e) => return Err(store_error(&path, e)),
+        };
+        Ok(Self {
+            path: Some(path),
+            key,
+            entries: RwLock::new(entries),
+        })
+    }
+
+    /// Opens the store configured by the environment.
+    ///
+    /// - `REGISTRY_CREDENTIALS_FILE`: store location, `registries.json` in the
+    ///   data directory by default;
+    /// - `REGISTRY_CREDENTIALS_KEY`: base64 of the 32-byte key. When unset, a
+    ///   key is generated next to the store (`registries.key`, mode 0600).
+    pub fn from_env() -> Result<Self, Error> {
+        let path = std::env::var("REGISTRY_CREDENTIALS_FILE")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| storage::data_dir().join("registries.json"));
+        let key = match std::env::var("REGISTRY_CREDENTIALS_KEY") {
+            Ok(key) if !key.is_empty() => BASE64.decode(key.trim()).map_err(|e| {
+                Error::InvalidRequest(format!("Invalid REGISTRY_CREDENTIALS_KEY: {e}"))
+            })?,
+            _ => key_file(&path.with_extension("key"))?,
+        };
+        Self::open(path, &key)
+    }
+
+    /// Credentials of a namespace, sorted by registry.
+    pub fn list(&self, namespace: &str) -> Vec<RegistryCredential> {
+        let entries = self.entries.read().unwrap_or_else(|p| p.into_inner());
+        entries
+            .get(namespace)
+            .into_iter()
+            .flatten()
+            .map(|(registry, stored)| view(registry, stored))
+            .collect()
+    }
+
+    /// Stores the credentials of `registry`, replacing existing ones.
+    ///
+    /// Returns the stored credential and whether it is new.
+    pub fn put(
+        &self,
+        namespace: &str,
+        registry: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<(RegistryCredential, bool), Error> {
+        let registry = normalize_registry(registry)?;
+        if username.is_empty() || password.is_empty() {
+            return Err(Error::BadRequest(
+                "username and password must not be empty".into(),
+            ));
+        }
+
+        let mut entries = self.entries.write().unwrap_or_else(|p| p.into_inner());
+        let mut updated = entries.clone();
+        let now = now();
+        let scoped = updated.entry(namespace.to_string()).or_default();
+        let created = scoped
+            .get(&registry)
+            .map_or_else(|| now.clone(), |s| s.created.clone());
+        let is_new = !scoped.contains_key(&registry);
+        let stored = Stored {
+            username: username.to_string(),
+            password: password.to_string(),
+            created,
+            updated: now,
+        };
+        let credential = view(&registry, &stored);
+        scoped.insert(registry, stored);
+
+        self.save(&updated)?;
+        *entries = updated;
+        Ok((credential, is_new))
+    }
+
+    /// Removes the credentials of `registry`.
+    pub fn remove(&self, namespace: &str, registry: &str) -> Result<(), Error> {
+        let registry = normalize_registry(registry)?;
+        let mut entries = self.entries.write().unwrap_or_else(|p| p.into_inner());
+        let mut updated = entries.clone();
+        let scoped = updated.entry(namespace.to_string()).or_default();
+        if scoped.remove(&registry).is_none() {
+            return Err(Error::NotFound(format!(
+                "No credentials for registry {registry}"
+            )));
+        }
+        if scoped.is_empty() {
+            updated.remove(namespace);
+        }
+
+        self.save(&updated)?;
+        *entries = updated;
+        Ok(())
+    }
+
+    /// Credentials a namespace presents when pulling `image`, if any.
+    pub fn auth_for(&self, namespace: &str, image: &str) -> Option<RegistryAuth> {
+        let registry = registry_of(image);
+        let entries = self.entries.read().unwrap_or_else(|p| p.into_inner());
+        let stored = entries.get(namespace)?.get(&registry)?;
+        Some(RegistryAuth {
+            username: stored.username.clone(),
+            password: stored.password.clone(),
+            server_address: registry,
+        })
+    }
+
+    /// Every login of a namespace, as presented to the registries of a build.
+    pub fn auths(&self, namespace: &str) -> Vec<RegistryAuth> {
+        let entries = self.entries.read().unwrap_or_else(|p| p.into_inner());
+        entries
+            .get(namespace)
+            .into_iter()
+            .flatten()
+            .map(|(registry, stored)| RegistryAuth {
+                username: stored.username.clone(),
+                password: stored.password.clone(),
+                server_address: registry.clone(),
+            })
+            .collect()
+    }
+
+    fn save(&self, entries: &Entries) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let plaintext =
+            serde_json::to_vec(entries).map_err(|e| Error::Unexpected(e.to_string()))?;
+        let envelope = seal(&self.key, plaintext)?;
+        let data =
+            serde_json::to_vec_pretty(&envelope).map_err(|e| Error::Unexpected(e.to_string()))?;
+        storage::write_private(path, &data)
+    }
+}
+
+/// Canonical registry host: scheme, path and Docker Hub aliases are folded.
+///
+/// # Examples
+/// ```
+/// use lightshuttle_core::services::credentials::normalize_registry;
+///
+/// assert_eq!(normalize_registry("https://index.docker.io/v1/").unwrap(), "docker.io");
+/// assert_eq!(normalize_registry("GHCR.io").unwrap(), "ghcr.io");
+/// ```
+pub fn normalize_registry(registry: &str) -> Result<String, Error> {
+    let host = registry.trim();
+    let host = host
+        .strip_prefix("https://")
+        .or_else(|| host.strip_prefix("http://"))
+        .unwrap_or(host);
+    let host = host.split('/').next().unwrap_or_default().to_lowercase();
+    if host.is_empty() || host.contains(|c: char| c.is_whitespace() || c == '@') {
+        return Err(Error::BadRequest(format!("Invalid registry '{registry}'")));
+    }
+    Ok(match host.as_str() {
+        "index.docker.io" | "registry-1.docker.io" | "registry.hub.docker.com" => {
+            "docker.io".to_string()
+        }
+        _ => host,
+    })
+}
+
+fn view(registry: &str, stored: &Stored) -> RegistryCredential {
+    RegistryCredential {
+        registry: registry.to_string(),
+        username: stored.username.clone(),
+        created: stored.created.clone(),
+        updated: stored.updated.clone(),
+    }
+}
+
+fn store_error(path: &Path, reason: impl std::fmt::Display) -> Error {
+    Error::Unexpected(format!(
+        "Registry credentials store {}: {reason}",
+        path.display()
+    ))
+}
+
+fn sealing_key(key: &[u8]) -> Result<LessSafeKey, Error> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| Error::InvalidRequest("Registry credentials key must be 32 bytes".into()))
+}
+
+/// Reads the key stored at `path`, generating it on first use.
+fn key_file(path: &Path) -> Result<Vec<u8>, Error> {
+    match std::fs::read_to_string(path) {
+        Ok(key) => BASE64.decode(key.trim()).map_err(|e| store_error(path, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let mut key = vec![0u8; 32];
+            SystemRandom::new()
+                .fill(&mut key)
+                .map_err(|_| Error::Unexpected("System random generator unavailable".into()))?;
+            storage::write_private(path, BASE64.encode(&key).as_bytes())?;
+            tracing::info!(path = %path.display(), "Generated registry credentials key");
+            Ok(key)
+        }
+        Err(e) => Err(store_error(path, e)),
+    }
+}
+
+fn seal(key: &LessSafeKey, mut data: Vec<u8>) -> Result<Envelope, Error> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| Error::Unexpected("System random generator unavailable".into()))?;
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(AAD),
+        &mut data,
+    )
+    .map_err(|_| Error::Unexpected("Failed to encrypt registry credentials".into()))?;
+    Ok(Envelope {
+        version: 1,
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(data),
+    })
+}
+
+fn unseal(key: &LessSafeKey, data: &[u8]) -> Result<Entries, String> {
+    let envelope: Envelope = serde_json::from_slice(data).map_err(|e| e.to_string())?;
+    if envelope.version != 1 {
+        return Err(format!("unsupported version {}", envelope.version));
+    }
+    let nonce: [u8; NONCE_LEN] = BASE64
+        .decode(&envelope.nonce)
+        .map_err(|e| e.to_string())?
+        .try_into()
+        .map_err(|_| "invalid nonce".to_string())?;
+    let mut ciphertext = BASE64
+        .decode(&envelope.ciphertext)
+        .map_err(|e| e.to_string())?;
+    let plaintext = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(AAD),
+            &mut ciphertext,
+        )
+        .map_err(|_| "decryption failed, wrong key or corrupted file".to_string())?;
+    serde_json::from_slice(plaintext).map_err(|e| e.to_string())
+}
@@ -1,33 +1,500 @@
-use std::{collections::HashMap, process::Command};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use crate::{docker::ContainerConfig, errors::Error};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::{stream, Stream, StreamExt};
+use serde::Serialize;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader, Lines},
+    process::{Child, ChildStdout, Command},
+    sync::{mpsc, oneshot},
+};
+
+use crate::{
+    docker::{
+        volume_name, BuildOptions, ContainerConfig, ContainerEvent, ContainerInspect,
+        ContainerStats, ContainerSummary, EngineInfo, ExecConfig, ExecOutput, HealthCheck,
+        ImageInspect, ImageSummary, NetworkAttachment, NetworkConfig, NetworkInfo,
+        ProcessOverrides, PruneReport, PullProgress, RegistryAuth, ResourceLimits, SecurityOptions,
+        VolumeConfig, VolumeInfo, VolumeUsage, WaitCondition, MIN_API_VERSION,
+    },
+    errors::Error,
+    services::{
+        endpoint::{DockerEndpoint, EndpointInfo},
+        engine::{network_from_json, EngineDockerClient},
+        http::Endpoint,
+        podman::PodmanClient,
+        seccomp::SeccompProfile,
+        storage::write_private,
+        timeout::{TimeoutDockerClient, Timeouts},
+    },
+};
+
+/// Backend and endpoint a Docker client talks to.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BackendInfo {
+    /// `cli`, `engine`, `podman`, or `memory` and `replay` in tests.
+    pub backend: String,
+    /// Endpoint in use; `null` when the backend has no engine endpoint.
+    pub endpoint: Option<EndpointInfo>,
+}
+
+/// Input sent to an interactive exec session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExecInput {
+    /// Bytes written to the process stdin.
+    Data(Bytes),
+    /// New terminal size, in characters.
+    Resize { cols: u16, rows: u16 },
+}
+
+/// Interactive process started by [`DockerClient::exec_session`].
+///
+/// Dropping `input` closes the process stdin. `output` ends once the process
+/// has exited, after which `exit` resolves with its exit code.
+pub struct ExecSession {
+    pub input: mpsc::Sender<ExecInput>,
+    pub output: mpsc::Receiver<Bytes>,
+    pub exit: oneshot::Receiver<Result<i64, Error>>,
+}
+
+/// Tar archive read from a container by [`DockerClient::get_archive`], chunk by chunk.
+///
+/// An error item means the archive was cut short.
+pub type ArchiveStream = Pin<Box<dyn Stream<Item = Result<Bytes, Error>> + Send>>;
+
+/// Reads a whole [`ArchiveStream`] into memory.
+pub async fn collect_archive(mut archive: ArchiveStream) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = archive.next().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+    Ok(bytes)
+}
 
 /// Abstraction over Docker interactions.
 ///
 /// This trait exposes a minimal set of operations required by the
 /// application and allows for future alternative implementations
 /// (e.g. talking to a daemon over sockets or HTTP).
+///
+/// All operations are async so that slow Docker calls never block a
+/// runtime worker; the trait is object safe and used as `Arc<dyn DockerClient>`.
+#[async_trait]
 pub trait DockerClient: Send + Sync + 'static {
+    /// Describe the backend and the endpoint in use.
+    fn describe(&self) -> BackendInfo;
+    /// Version and capabilities of the engine behind the backend.
+    async fn engine_info(&self) -> Result<EngineInfo, Error>;
     /// Run a new container.
-    fn run(&self, cfg: ContainerConfig) -> Result<String, Error>;
+    async fn run(&self, cfg: ContainerConfig<'_>) -> Result<String, Error>;
     /// Start an existing container.
-    fn start(&self, name: &str) -> Result<(), Error>;
+    async fn start(&self, name: &str) -> Result<(), Error>;
     /// Stop a running container.
-    fn stop(&self, name: &str) -> Result<(), Error>;
-    /// Inspect a container and return the raw JSON output.
-    fn inspect(&self, name: &str) -> Result<String, Error>;
+    async fn stop(&self, name: &str) -> Result<(), Error>;
+    /// Freeze every process of a running container.
+    async fn pause(&self, name: &str) -> Result<(), Error>;
+    /// Resume a paused container.
+    async fn unpause(&self, name: &str) -> Result<(), Error>;
+    /// Stop then start a container, killing it after `timeout` seconds,
+    /// or the engine's default grace period, if it does not stop.
+    async fn restart(&self, name: &str, timeout: Option<u64>) -> Result<(), Error>;
+    /// Send a signal, such as `SIGHUP` or `9`, to the main process of a running container.
+    async fn kill(&self, name: &str, signal: &str) -> Result<(), Error>;
+    /// Give a container a new name.
+    async fn rename(&self, name: &str, new_name: &str) -> Result<(), Error>;
+    /// Block until a container meets `condition` and return its exit code.
+    async fn wait(&self, name: &str, condition: WaitCondition) -> Result<i64, Error>;
+    /// Inspect a container.
+    async fn inspect(&self, name: &str) -> Result<ContainerInspect, Error>;
+    /// List containers; stopped ones are included when `all` is set.
+    async fn list(&self, all: bool) -> Result<Vec<ContainerSummary>, Error>;
+    /// Remove a container, killing it first if it is running.
+    async fn remove(&self, name: &str) -> Result<(), Error>;
+    /// Fetch the logs of a container.
+    async fn logs(&self, name: &str) -> Result<String, Error>;
+    /// Take a resource usage snapshot of a container.
+    async fn stats(&self, name: &str) -> Result<ContainerStats, Error>;
+    /// Run a command in a running container and collect its output.
+    async fn exec(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error>;
+    /// Start an interactive command in a running container.
+    async fn exec_session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error>;
+    /// Tar archive of a file or directory of a container.
+    ///
+    /// A missing container or path fails the call; the archive itself is
+    /// streamed rather than held in memory.
+    async fn get_archive(&self, name: &str, path: &str) -> Result<ArchiveStream, Error>;
+    /// Extract a tar archive into a directory of a container.
+    async fn put_archive(&self, name: &str, path: &str, archive: Bytes) -> Result<(), Error>;
+    /// List the networks of the engine, built-in ones included.
+    async fn list_networks(&self) -> Result<Vec<NetworkInfo>, Error>;
+    /// Inspect a network by name or id.
+    async fn inspect_network(&self, name: &str) -> Result<NetworkInfo, Error>;
+    /// Create a network and return its id.
+    async fn create_network(&self, cfg: &NetworkConfig) -> Result<String, Error>;
+    /// Remove a network; the engine refuses while containers are attached.
+    async fn remove_network(&self, name: &str) -> Result<(), Error>;
+    /// List the named volumes of the engine.
+    async fn list_volumes(&self) -> Result<Vec<VolumeInfo>, Error>;
+    /// Inspect a named volume.
+    async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo, Error>;
+    /// Create a named volume.
+    async fn create_volume(&self, cfg: &VolumeConfig) -> Result<VolumeInfo, Error>;
+    /// Remove a named volume; the engine refuses while a container mounts it.
+    async fn remove_volume(&self, name: &str) -> Result<(), Error>;
+    /// Disk usage of every named volume and the containers mounting it, by volume name.
+    async fn volume_usage(&self) -> Result<HashMap<String, VolumeUsage>, Error>;
+    /// List the images stored by the engine.
+    async fn list_images(&self) -> Result<Vec<ImageSummary>, Error>;
+    /// Inspect an image by reference or id.
+    async fn inspect_image(&self, name: &str) -> Result<ImageInspect, Error>;
+    /// Remove an image; the engine refuses while a container uses it.
+    async fn remove_image(&self, name: &str) -> Result<(), Error>;
+    /// Remove dangling images, or every unused image with `all`.
+    async fn prune_images(&self, all: bool) -> Result<PruneReport, Error>;
+    /// Pull an image, reporting progress on `tx`.
+    ///
+    /// `auth` is presented to the image's registry when set. Progress is
+    /// best effort: the pull completes even once `tx` is closed.
+    async fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        tx: mpsc::Sender<PullProgress>,
+    ) -> Result<(), Error>;
+    /// Build an image from a tar `context`, forwarding the build output on `tx`.
+    ///
+    /// `auths` are presented to the registries base images are pulled from.
+    /// Returns the id of the built image, tagged `opts.tag`. Like pull
+    /// progress, the output is best effort.
+    async fn build_image(
+        &self,
+        context: Bytes,
+        opts: &BuildOptions,
+        auths: &[RegistryAuth],
+        tx: mpsc::Sender<String>,
+    ) -> Result<String, Error>;
+    /// Stream container events into `tx` until the stream ends or the receiver is dropped.
+    async fn events(&self, tx: mpsc::Sender<ContainerEvent>) -> Result<(), Error>;
+}
+
+/// Builds the Docker client selected by the `DOCKER_BACKEND` environment variable.
+///
+/// - `cli` (default): shell out to the `docker` binary.
+/// - `engine`: talk to the Engine API directly.
+/// - `podman`: talk to the libpod API at `CONTAINER_HOST` or the user's Podman socket.
+///
+/// `cli` and `engine` talk to the endpoint resolved by [`DockerEndpoint::resolve`],
+/// or to `/var/run/docker.sock` when none is configured. An unknown backend and
+/// invalid endpoint or TLS settings are reported as an error rather than
+/// silently ignored: the Docker image ships without the `docker` binary.
+///
+/// Every backend enforces the deadlines of [`Timeouts::from_env`] and applies
+/// the seccomp profile of [`SeccompProfile::from_env`] to the containers it creates.
+pub fn client_from_env() -> Result<Arc<dyn DockerClient>, Error> {
+    let backend = std::env::var("DOCKER_BACKEND").unwrap_or_default();
+    let backend = match backend.trim() {
+        "" => "cli",
+        backend @ ("cli" | "engine" | "podman") => backend,
+        other => {
+            return Err(Error::InvalidRequest(format!(
+                "Unknown DOCKER_BACKEND '{other}': expected cli, engine or podman"
+            )))
+        }
+    };
+    let timeouts = Timeouts::from_env();
+    let seccomp = SeccompProfile::from_env()?;
+    if backend == "podman" {
+        let client = PodmanClient::from_env()?.with_seccomp(seccomp);
+        tracing::info!(host = %client.endpoint().host, "Using Podman libpod backend");
+        return Ok(Arc::new(TimeoutDockerClient::new(
+            Arc::new(client),
+            timeouts,
+        )));
+    }
+
+    let endpoint = DockerEndpoint::resolve()?.unwrap_or_else(DockerEndpoint::default_socket);
+    let client: Arc<dyn DockerClient> = if backend == "engine" {
+        Arc::new(EngineDockerClient::connect(endpoint)?.with_seccomp(seccomp))
+    } else {
+        Arc::new(ShellDockerClient::with_endpoint(endpoint).with_seccomp(seccomp))
+    };
+
+    let info = client.describe();
+    if let Some(endpoint) = &info.endpoint {
+        tracing::info!(
+            backend = %info.backend,
+            endpoint = %endpoint.name,
+            host = %endpoint.host,
+            tls = endpoint.tls,
+            "Docker client ready"
+        );
+    }
+    Ok(Arc::new(TimeoutDockerClient::new(client, timeouts)))
+}
+
+/// Checks that the engine speaks [`MIN_API_VERSION`] before serving.
+///
+/// An older engine is refused when `strict`, and only logged otherwise. An
+/// engine that cannot be reached is logged too: the daemon may start first.
+pub async fn check_engine(client: &dyn DockerClient, strict: bool) -> Result<(), Error> {
+    let info = match client.engine_info().await {
+        Ok(info) => info,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to query the Docker engine version");
+            return Ok(());
+        }
+    };
+    if info.is_supported() {
+        tracing::info!(
+            version = %info.version,
+            api_version = %info.api_version,
+            "Docker engine supported"
+        );
+        return Ok(());
+    }
+    let message = format!(
+        "Docker engine API {} is older than the minimum supported {MIN_API_VERSION}",
+        info.api_version
+    );
+    if strict {
+        return Err(Error::Unexpected(message));
+    }
+    tracing::warn!("{message}");
+    Ok(())
 }
 
 /// Docker client backed by shelling out to the `docker` CLI.
-pub struct ShellDockerClient;
+///
+/// Without an endpoint the CLI picks its own (`DOCKER_HOST`, current context);
+/// with one, `--host` and the TLS flags are passed explicitly.
+#[derive(Default)]
+pub struct ShellDockerClient {
+    endpoint: Option<DockerEndpoint>,
+    seccomp: SeccompProfile,
+}
+
+impl ShellDockerClient {
+    /// Creates a client targeting `endpoint`.
+    pub fn with_endpoint(endpoint: DockerEndpoint) -> Self {
+        Self {
+            endpoint: Some(endpoint),
+            ..Default::default()
+        }
+    }
+
+    /// Applies `profile` to every container created.
+    pub fn with_seccomp(mut self, profile: SeccompProfile) -> Self {
+        self.seccomp = profile;
+        self
+    }
+
+    /// `docker` command carrying the global endpoint flags, killed if the
+    /// call is dropped, such as past its deadline.
+    fn command(&self) -> Command {
+        let mut cmd = Command::new("docker");
+        cmd.kill_on_drop(true);
+        if let Some(endpoint) = &self.endpoint {
+            let host = match &endpoint.address {
+                Endpoint::Unix(_) => endpoint.host.clone(),
+                Endpoint::Tcp(addr) => format!("tcp://{addr}"),
+            };
+            cmd.arg("--host").arg(host);
+            if let Some(tls) = &endpoint.tls {
+                cmd.arg("--tlsverify").arg("--tlscacert").arg(&tls.ca);
+                if let Some((cert, key)) = &tls.client {
+                    cmd.arg("--tlscert").arg(cert).arg("--tlskey").arg(key);
+                }
+            }
+        }
+        cmd
+    }
 
+    async fn network_inspect(&self, names: &[&str]) -> Result<Vec<NetworkInfo>, Error> {
+        let output = self
+            .command()
+            .args(["network", "inspect"])
+            .args(names)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if !output.status.success() {
+            return Err(network_error(&String::from_utf8_lossy(&output.stderr)));
+        }
+        let networks: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(networks.iter().map(network_from_json).collect())
+    }
+
+    async fn volume_inspect(&self, names: &[&str]) -> Result<Vec<VolumeInfo>, Error> {
+        let output = self
+            .command()
+            .args(["volume", "inspect"])
+            .args(names)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if output.status.success() {
+            serde_json::from_slice(&output.stdout)
+                .map_err(|e| Error::DockerOutputParse(e.to_string()))
+        } else {
+            Err(volume_error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    /// Starts `docker events` for containers, replaying events from `since`
+    /// (a Unix timestamp) when set. The process lives as long as the child.
+    fn subscribe_events(
+        &self,
+        since: Option<&str>,
+    ) -> Result<(Child, Lines<BufReader<ChildStdout>>), Error> {
+        let mut cmd = self.command();
+        cmd.args([
+            "events",
+            "--filter",
+            "type=container",
+            "--format",
+            "{{json .}}",
+        ]);
+        if let Some(since) = since {
+            cmd.args(["--since", since]);
+        }
+        let mut child = cmd
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|_| Error::DockerCommandFailed)?;
+        let stdout = child.stdout.take().ok_or(Error::DockerCommandFailed)?;
+        Ok((child, BufReader::new(stdout).lines()))
+    }
+
+    /// Runs a lifecycle command such as `docker pause` on a container.
+    async fn container_command(&self, args: &[&str]) -> Result<(), Error> {
+        let output = self
+            .command()
+            .args(args)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(container_error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    /// JSON printed by `docker <args> --format '{{json .}}'`-style commands.
+    async fn json_output(&self, args: &[&str]) -> Result<serde_json::Value, Error> {
+        let output = self
+            .command()
+            .args(args)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Unexpected(stderr.trim().to_string()));
+        }
+        serde_json::from_slice(&output.stdout).map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    /// Attaches a created container to one more network.
+    async fn connect_network(&self, name: &str, network: &NetworkAttachment) -> Result<(), Error> {
+        let mut args = vec!["network", "connect"];
+        for alias in &network.aliases {
+            args.extend(["--alias", alias]);
+        }
+        args.extend([network.name.as_str(), name]);
+        let output = self
+            .command()
+            .args(&args)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(network_error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    async fn inspect_images(&self, names: &[String]) -> Result<Vec<ImageInspect>, Error> {
+        let output = self
+            .command()
+            .args(["image", "inspect"])
+            .args(names)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+
+        if output.status.success() {
+            serde_json::from_slice(&output.stdout)
+                .map_err(|e| Error::DockerOutputParse(e.to_string()))
+        } else {
+            Err(image_error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    /// `docker exec` command line for `cfg`, without the interactive flags.
+    fn exec_command(&self, name: &str, cfg: &ExecConfig) -> Command {
+        let mut cmd = self.command();
+        cmd.arg("exec");
+        for pair in cfg.env_pairs() {
+            cmd.arg("--env").arg(pair);
+        }
+        if let Some(dir) = &cfg.working_dir {
+            cmd.arg("--workdir").arg(dir);
+        }
+        if let Some(user) = &cfg.user {
+            cmd.arg("--user").arg(user);
+        }
+        cmd.arg(name).args(&cfg.cmd);
+        cmd
+    }
+}
+
+#[async_trait]
 impl DockerClient for ShellDockerClient {
-    fn run(&self, cfg: ContainerConfig) -> Result<String, Error> {
-        let port = cfg.container_port;
+    fn describe(&self) -> BackendInfo {
+        BackendInfo {
+            backend: "cli".to_string(),
+            endpoint: self.endpoint.as_ref().map(DockerEndpoint::info),
+        }
+    }
+
+    async fn engine_info(&self) -> Result<EngineInfo, Error> {
+        let version = self
+            .json_output(&["version", "--format", "{{json .Server}}"])
+            .await?;
+        let info = self
+            .json_output(&["info", "--format", "{{json .}}"])
+            .await?;
+        Ok(EngineInfo::from_json(&version, &info))
+    }
+
+    async fn run(&self, cfg: ContainerConfig<'_>) -> Result<String, Error> {
+        cfg.validate()?;
+
         let port_args: Vec<String> = cfg
-            .host_ports
+            .ports
             .iter()
-            .flat_map(|host| vec!["-p".to_string(), format!("{host}:{port}")])
+            .flat_map(|port| {
+                let host_ip = match port.host_ip.as_deref() {
+                    Some(ip) if ip.contains(':') => format!("[{ip}]:"),
+                    Some(ip) => format!("{ip}:"),
+                    None => String::new(),
+                };
+                let publish = format!("{host_ip}{}:{}", port.host, port.key());
+                vec!["-p".to_string(), publish]
+            })
             .collect();
 
         let label_args: Vec<String> = cfg
@@ -51,54 +518,94 @@ impl DockerClient for ShellDockerClient {
             .flat_map(|mount| vec!["-v".to_string(), mount.to_string()])
             .collect();
 
-        if let Some(vols) = cfg.volumes {
-            for v in vols {
-                if !v.contains(':') || v.starts_with(':') || v.ends_with(':') {
-                    return Err(Error::BadRequest(format!("Invalid volume format: '{v}'")));
-                }
-            }
-        }
-
-        if let Some(policy) = cfg.restart_policy {
-            let valid = ["no", "always", "on-failure", "unless-stopped"];
-            if !valid.contains(&policy) {
-                return Err(Error::InvalidRequest(format!(
-                    "Invalid restart policy: '{policy}'"
-                )));
-            }
-        }
+        // `docker run` attaches a single network: the others are connected
+        // between `create` and `start`.
+        let (first_network, other_networks) = match cfg.networks.split_first() {
+            Some((first, others)) => (Some(first), others),
+            None => (None, &[][..]),
+        };
+        let network_args: Vec<String> = first_network
+            .into_iter()
+            .flat_map(|network| {
+                let aliases = network
+                    .aliases
+                    .iter()
+                    .flat_map(|alias| ["--network-alias".to_string(), alias.clone()]);
+                ["--network".to_string(), network.name.clone()]
+                    .into_iter()
+                    .chain(aliases)
+            })
+            .collect();
 
-        let mut args = vec!["run", "-d", "--rm", "--name", cfg.name];
+        let mut args = if other_networks.is_empty() {
+            vec!["run", "-d"]
+        } else {
+            vec!["create"]
+        };
+        args.extend(["--rm", "--name", cfg.name]);
         args.extend(port_args.iter().map(String::as_str));
         args.extend(label_args.iter().map(String::as_str));
         args.extend(env_args.iter().map(String::as_str));
         args.extend(volume_args.iter().map(String::as_str));
+        args.extend(network_args.iter().map(String::as_str));
 
         if let Some(policy) = cfg.restart_policy {
             args.push("--restart");
             args.push(policy);
         }
 
+        let limit_args = cfg.limits.map(limit_args).unwrap_or_default();
+        args.extend(limit_args.iter().map(String::as_str));
+        let health_args = cfg.healthcheck.map(health_args).unwrap_or_default();
+        args.extend(health_args.iter().map(String::as_str));
+        let (process_args, command_args) = cfg.process.map(process_args).unwrap_or_default();
+        args.extend(process_args.iter().map(String::as_str));
+        let security_args = security_args(cfg.security, &self.seccomp);
+        args.extend(security_args.iter().map(String::as_str));
+
         args.push(cfg.image);
+        args.extend(command_args.iter().map(String::as_str));
 
-        let output = Command::new("docker")
+        let output = self
+            .command()
             .args(&args)
             .output()
+            .await
             .map_err(|_| Error::DockerCommandFailed)?;
 
-        if output.status.success() {
-            let container_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            Ok(container_id)
-        } else {
+        if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            Err(Error::Unexpected(stderr.trim().to_string()))
+            return Err(Error::Unexpected(stderr.trim().to_string()));
+        }
+        let container_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if other_networks.is_empty() {
+            return Ok(container_id);
+        }
+
+        let mut started = Ok(());
+        for network in other_networks {
+            started = self.connect_network(cfg.name, network).await;
+            if started.is_err() {
+                break;
+            }
+        }
+        if started.is_ok() {
+            started = self.start(cfg.name).await;
+        }
+        if let Err(e) = started {
+            // Leave no half-attached container behind.
+            let _ = self.remove(cfg.name).await;
+            return Err(e);
         }
+        Ok(container_id)
     }
 
-    fn start(&self, name: &str) -> Result<(), Error> {
-        let output = Command::new("docker")
+    async fn start(&self, name: &str) -> Result<(), Error> {
+        let output = self
+            .command()
             .args(["start", name])
             .output()
+            .await
             .map_err(|_| Error::DockerCommandFailed)?;
 
         if output.status.success() {
@@ -113,10 +620,12 @@ impl DockerClient for ShellDockerClient {
         }
     }
 
-    fn stop(&self, name: &str) -> Result<(), Error> {
-        let output = Command::new("docker")
+    async fn stop(&self, name: &str) -> Result<(), Error> {
+        let output = self
+            .command()
             .args(["stop", name])
             .output()
+            .await
             .map_err(|_| Error::DockerCommandFailed)?;
 
         if output.status.success() {
@@ -131,14 +640,103 @@ impl DockerClient for ShellDockerClient {
         }
     }
 
-    fn inspect(&self, name: &str) -> Result<String, Error> {
-        let output = Command::new("docker")
-            .args(["inspect", name])
+    async fn pause(&self, name: &str) -> Result<(), Error> {
+        self.container_command(&["pause", name]).await
+    }
+
+    async fn unpause(&self, name: &str) -> Result<(), Error> {
+        self.container_command(&["unpause", name]).await
+    }
+
+    async fn restart(&self, name: &str, timeout: Option<u64>) -> Result<(), Error> {
+        let timeout = timeout.map(|secs| secs.to_string());
+        let mut args = vec!["restart"];
+        if let Some(secs) = &timeout {
+            args.extend(["--time", secs]);
+        }
+        args.push(name);
+        self.container_command(&args).await
+    }
+
+    async fn kill(&self, name: &str, signal: &str) -> Result<(), Error> {
+        self.container_command(&["kill", "--signal", signal, name])
+            .await
+    }
+
+    async fn rename(&self, name: &str, new_name: &str) -> Result<(), Error> {
+        self.container_command(&["rename", name, new_name]).await
+    }
+
+    /// `docker wait` only waits for `not-running`; the other conditions
+    /// follow the `die` and `destroy` events of the container.
+    async fn wait(&self, name: &str, condition: WaitCondition) -> Result<i64, Error> {
+        if condition == WaitCondition::NotRunning {
+            let output = self
+                .command()
+                .args(["wait", name])
+                .output()
+                .await
+                .map_err(|_| Error::DockerCommandFailed)?;
+            if !output.status.success() {
+                return Err(container_error(&String::from_utf8_lossy(&output.stderr)));
+            }
+            return String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .parse()
+                .map_err(|_| Error::DockerOutputParse("Invalid exit code".into()));
+        }
+
+        // Subscribed before inspecting, and replayed from when the wait
+        // started, so that an exit or removal in between is not missed.
+        let since = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| format!("{}.{:09}", d.as_secs(), d.subsec_nanos()))
+            .unwrap_or_default();
+        let (_child, mut lines) = self.subscribe_events(Some(&since))?;
+        let container = self.inspect(name).await?;
+        let mut exit_code = container.state.exit_code;
+        loop {
+            let Some(line) = lines
+                .next_line()
+                .await
+                .map_err(|_| Error::DockerCommandFailed)?
+            else {
+                return Err(Error::Unexpected("Docker events stream ended".into()));
+            };
+            let Some(event) = ContainerEvent::from_message(&line) else {
+                continue;
+            };
+            if event.id != container.id {
+                continue;
+            }
+            match event.action.as_str() {
+                "die" => {
+                    exit_code = event.exit_code.unwrap_or(exit_code);
+                    if condition == WaitCondition::NextExit {
+                        return Ok(exit_code);
+                    }
+                }
+                "destroy" => return Ok(exit_code),
+                _ => {}
+            }
+        }
+    }
+
+    async fn inspect(&self, name: &str) -> Result<ContainerInspect, Error> {
+        let output = self
+            .command()
+            .args(["inspect", "--type", "container", name])
             .output()
+            .await
             .map_err(|_| Error::DockerCommandFailed)?;
 
         if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            let containers: Vec<ContainerInspect> = serde_json::from_slice(&output.stdout)
+                .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+            containers
+                .into_iter()
+                .next()
+                .ok_or(Error::ContainerNotFound)
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
             if stderr.contains("no such container") {
@@ -148,4 +746,1011 @@ impl DockerClient for ShellDockerClient {
             }
         }
     }
+
+    async fn list(&self, all: bool) -> Result<Vec<ContainerSummary>, Error> {
+        let mut args = vec!["ps", "--no-trunc", "--format", "{{json .}}"];
+        if all {
+            args.push("--all");
+        }
+
+        let output = self
+            .command()
+            .args(&args)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+
+        if !output.status.success() {
+            return Err(Error::DockerCommandFailed);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let entry: serde_json::Value = serde_json::from_str(line)
+                    .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+                let field = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+                Ok(ContainerSummary {
+                    id: field("ID"),
+                    name: field("Names"),
+                    image: field("Image"),
+                    state: field("State"),
+                    status: field("Status"),
+                    ports: parse_ports(&field("Ports")),
+                    labels: parse_labels(&field("Labels")),
+                    networks: field("Networks")
+                        .split(',')
+                        .filter(|n| !n.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    async fn remove(&self, name: &str) -> Result<(), Error> {
+        let output = self
+            .command()
+            .args(["rm", "-f", name])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if stderr.contains("No such container") || stderr.contains("Error: No such container") {
+            return Err(Error::ContainerNotFound);
+        }
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(Error::Unexpected(stderr.trim().to_string()))
+        }
+    }
+
+    async fn logs(&self, name: &str) -> Result<String, Error> {
+        let output = self
+            .command()
+            .args(["logs", name])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+
+        if output.status.success() {
+            let logs = String::from_utf8_lossy(&output.stdout).to_string();
+            Ok(logs)
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+            if stderr.contains("no such container") {
+                Err(Error::ContainerNotFound)
+            } else {
+                Err(Error::Unexpected(stderr.trim().to_string()))
+            }
+        }
+    }
+
+    async fn stats(&self, name: &str) -> Result<ContainerStats, Error> {
+        let output = self
+            .command()
+            .args(["stats", "--no-stream", "--format", "{{json .}}", name])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+            return if stderr.contains("no such container") {
+                Err(Error::ContainerNotFound)
+            } else {
+                Err(Error::Unexpected(stderr.trim().to_string()))
+            };
+        }
+
+        let entry: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        let field = |key: &str| entry[key].as_str().unwrap_or_default();
+        let pair = |key: &str| {
+            let (a, b) = field(key).split_once('/').unwrap_or((field(key), ""));
+            (parse_size(a), parse_size(b))
+        };
+
+        let (memory_usage, memory_limit) = pair("MemUsage");
+        let (network_rx, network_tx) = pair("NetIO");
+        let (block_read, block_write) = pair("BlockIO");
+        Ok(ContainerStats {
+            cpu_percent: field("CPUPerc")
+                .trim_end_matches('%')
+                .parse()
+                .unwrap_or(0.0),
+            memory_usage,
+            memory_limit,
+            network_rx,
+            network_tx,
+            block_read,
+            block_write,
+            pids: field("PIDs").parse().unwrap_or(0),
+        })
+    }
+
+    async fn exec(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error> {
+        cfg.validate()?;
+        let output = self
+            .exec_command(name, cfg)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        // Failures of `docker exec` itself cannot be told apart by exit code.
+        if !output.status.success() && stderr.starts_with("Error response from daemon") {
+            return Err(exec_error(&stderr));
+        }
+        Ok(ExecOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr,
+            exit_code: output.status.code().map_or(-1, i64::from),
+        })
+    }
+
+    async fn exec_session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error> {
+        cfg.validate()?;
+        if cfg.tty {
+            // The CLI refuses `-t` without a terminal on its own stdin.
+            return Err(Error::BadRequest(
+                "TTY sessions need the engine or podman backend".into(),
+            ));
+        }
+        let container = self.inspect(name).await?;
+        if container.state.status != "running" {
+            return Err(Error::Conflict(format!("Container {name} is not running")));
+        }
+
+        let mut child = self
+            .exec_command(name, cfg)
+            .arg("--interactive")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|_| Error::DockerCommandFailed)?;
+        let (Some(mut stdin), Some(stdout), Some(stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(Error::DockerCommandFailed);
+        };
+
+        let (input, mut input_rx) = mpsc::channel(64);
+        let (output_tx, output) = mpsc::channel(64);
+        let (exit_tx, exit) = oneshot::channel();
+
+        tokio::spawn(async move {
+            while let Some(input) = input_rx.recv().await {
+                // Resizing needs a TTY; ignored here.
+                if let ExecInput::Data(data) = input {
+                    if stdin.write_all(&data).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        tokio::spawn(pump_output(stdout, output_tx.clone()));
+        tokio::spawn(pump_output(stderr, output_tx));
+        tokio::spawn(async move {
+            let status = child.wait().await.map_err(|_| Error::DockerCommandFailed);
+            let _ = exit_tx.send(status.map(|s| s.code().map_or(-1, i64::from)));
+        });
+
+        Ok(ExecSession {
+            input,
+            output,
+            exit,
+        })
+    }
+
+    async fn get_archive(&self, name: &str, path: &str) -> Result<ArchiveStream, Error> {
+        let mut child = self
+            .command()
+            .args(["cp", &format!("{name}:{path}"), "-"])
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|_| Error::DockerCommandFailed)?;
+        let mut stdout = child.stdout.take().ok_or(Error::DockerCommandFailed)?;
+        let mut stderr = child.stderr.take().ok_or(Error::DockerCommandFailed)?;
+        let stderr = tokio::spawn(async move {
+            let mut message = String::new();
+            let _ = stderr.read_to_string(&mut message).await;
+            message
+        });
+
+        // A missing container or path fails before any output is written.
+        let mut buf = vec![0; 8192];
+        let n = stdout
+            .read(&mut buf)
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if n == 0 {
+            let status = child.wait().await.map_err(|_| Error::DockerCommandFailed)?;
+            if !status.success() {
+                return Err(archive_error(&stderr.await.unwrap_or_default()));
+            }
+        }
+
+        let (tx, mut rx) = mpsc::channel(4);
+        let _ = tx.send(Ok(Bytes::copy_from_slice(&buf[..n]))).await;
+        // Dropping the stream closes `rx`, which ends the task and kills the CLI.
+        tokio::spawn(async move {
+            loop {
+                match stdout.read(&mut buf).await {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        if tx
+                            .send(Ok(Bytes::copy_from_slice(&buf[..n])))
+                            .await
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                    Err(_) => {
+                        let _ = tx.send(Err(Error::DockerCommandFailed)).await;
+                        return;
+                    }
+                }
+            }
+            if !child.wait().await.is_ok_and(|s| s.success()) {
+                let message = stderr.await.unwrap_or_default();
+                let _ = tx.send(Err(archive_error(&message))).await;
+            }
+        });
+        Ok(Box::pin(stream::poll_fn(move |cx| rx.poll_recv(cx))))
+    }
+
+    async fn put_archive(&self, name: &str, path: &str, archive: Bytes) -> Result<(), Error> {
+        let mut child = self
+            .command()
+            .args(["cp", "-", &format!("{name}:{path}")])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|_| Error::DockerCommandFailed)?;
+        let mut stdin = child.stdin.take().ok_or(Error::DockerCommandFailed)?;
+        // The CLI may fail before reading the whole archive; its stderr tells why.
+        let _ = stdin.write_all(&archive).await;
+        drop(stdin);
+
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(archive_error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    async fn list_networks(&self) -> Result<Vec<NetworkInfo>, Error> {
+        let output = self
+            .command()
+            .args(["network", "ls", "--quiet", "--no-trunc"])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if !output.status.success() {
+            return Err(network_error(&String::from_utf8_lossy(&output.stderr)));
+        }
+        let ids = String::from_utf8_lossy(&output.stdout);
+        let ids: Vec<&str> = ids.split_whitespace().collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.network_inspect(&ids).await
+    }
+
+    async fn inspect_network(&self, name: &str) -> Result<NetworkInfo, Error> {
+        self.network_inspect(&[name])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::NotFound(format!("network {name} not found")))
+    }
+
+    async fn create_network(&self, cfg: &NetworkConfig) -> Result<String, Error> {
+        cfg.validate()?;
+        let mut args = vec!["network", "create"];
+        if let Some(driver) = &cfg.driver {
+            args.extend(["--driver", driver]);
+        }
+        if let Some(subnet) = &cfg.subnet {
+            args.extend(["--subnet", subnet]);
+        }
+        if cfg.internal {
+            args.push("--internal");
+        }
+        args.push(&cfg.name);
+
+        let output = self
+            .command()
+            .args(&args)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(network_error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        let output = self
+            .command()
+            .args(["network", "rm", name])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(network_error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    async fn list_volumes(&self) -> Result<Vec<VolumeInfo>, Error> {
+        let output = self
+            .command()
+            .args(["volume", "ls", "--quiet"])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if !output.status.success() {
+            return Err(volume_error(&String::from_utf8_lossy(&output.stderr)));
+        }
+        let names = String::from_utf8_lossy(&output.stdout);
+        let names: Vec<&str> = names.split_whitespace().collect();
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.volume_inspect(&names).await
+    }
+
+    async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo, Error> {
+        self.volume_inspect(&[name])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::NotFound(format!("no such volume: {name}")))
+    }
+
+    async fn create_volume(&self, cfg: &VolumeConfig) -> Result<VolumeInfo, Error> {
+        cfg.validate()?;
+        let mut args = vec!["volume".to_string(), "create".to_string()];
+        if let Some(driver) = &cfg.driver {
+            args.extend(["--driver".to_string(), driver.clone()]);
+        }
+        for (key, value) in &cfg.driver_opts {
+            args.extend(["--opt".to_string(), format!("{key}={value}")]);
+        }
+        for (key, value) in &cfg.labels {
+            args.extend(["--label".to_string(), format!("{key}={value}")]);
+        }
+        args.push(cfg.name.clone());
+
+        let output = self
+            .command()
+            .args(&args)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if !output.status.success() {
+            return Err(volume_error(&String::from_utf8_lossy(&output.stderr)));
+        }
+        self.inspect_volume(&cfg.name).await
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        let output = self
+            .command()
+            .args(["volume", "rm", name])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(volume_error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    async fn volume_usage(&self) -> Result<HashMap<String, VolumeUsage>, Error> {
+        let output = self
+            .command()
+            .args(["system", "df", "--verbose", "--format", "{{json .}}"])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Unexpected(stderr.trim().to_string()));
+        }
+        let df: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        let mut usage: HashMap<String, VolumeUsage> = df["Volumes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|volume| {
+                let name = volume["Name"].as_str()?.to_string();
+                // `N/A` when the size could not be computed.
+                let size = volume["Size"]
+                    .as_str()
+                    .filter(|size| size.starts_with(|c: char| c.is_ascii_digit()))
+                    .map(parse_size);
+                Some((
+                    name,
+                    VolumeUsage {
+                        size,
+                        apps: Vec::new(),
+                    },
+                ))
+            })
+            .collect();
+
+        let output = self
+            .command()
+            .args(["ps", "--all", "--no-trunc", "--format", "{{json .}}"])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Unexpected(stderr.trim().to_string()));
+        }
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let entry: serde_json::Value =
+                serde_json::from_str(line).map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+            let name = entry["Names"].as_str().unwrap_or_default();
+            // Bind mounts are listed by host path, named volumes by name.
+            for volume in entry["Mounts"]
+                .as_str()
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(volume_name)
+            {
+                usage
+                    .entry(volume.to_string())
+                    .or_default()
+                    .apps
+                    .push(name.to_string());
+            }
+        }
+        Ok(usage)
+    }
+
+    async fn list_images(&self) -> Result<Vec<ImageSummary>, Error> {
+        let output = self
+            .command()
+            .args(["image", "ls", "--quiet", "--no-trunc"])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if !output.status.success() {
+            return Err(Error::DockerCommandFailed);
+        }
+
+        // Listed once per tag; inspect each image once for exact sizes and dates.
+        let mut ids: Vec<String> = Vec::new();
+        for id in String::from_utf8_lossy(&output.stdout).split_whitespace() {
+            if !ids.iter().any(|known| known == id) {
+                ids.push(id.to_string());
+            }
+        }
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .inspect_images(&ids)
+            .await?
+            .iter()
+            .map(ImageInspect::summary)
+            .collect())
+    }
+
+    async fn inspect_image(&self, name: &str) -> Result<ImageInspect, Error> {
+        self.inspect_images(&[name.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(Error::ImageNotFound)
+    }
+
+    async fn remove_image(&self, name: &str) -> Result<(), Error> {
+        let output = self
+            .command()
+            .args(["image", "rm", name])
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(image_error(&String::from_utf8_lossy(&output.stderr)))
+        }
+    }
+
+    async fn prune_images(&self, all: bool) -> Result<PruneReport, Error> {
+        let mut args = vec!["image", "prune", "--force"];
+        if all {
+            args.push("--all");
+        }
+        let output = self
+            .command()
+            .args(&args)
+            .output()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Unexpected(stderr.trim().to_string()));
+        }
+
+        // "untagged: nginx:latest", "deleted: sha256:...", "Total reclaimed space: 187.4MB"
+        let mut report = PruneReport::default();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if let Some(space) = line.strip_prefix("Total reclaimed space:") {
+                report.space_reclaimed = parse_size(space);
+            } else if let Some((kind, target)) = line.split_once(": ") {
+                if kind.eq_ignore_ascii_case("deleted") || kind.eq_ignore_ascii_case("untagged") {
+                    report.deleted.push(target.trim().to_string());
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    async fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        tx: mpsc::Sender<PullProgress>,
+    ) -> Result<(), Error> {
+        // Credentials go through a throwaway CLI config, never the daemon user's own.
+        let config = auth
+            .map(|auth| CliConfig::write(std::slice::from_ref(auth)))
+            .transpose()?;
+        let mut command = self.command();
+        if let Some(config) = &config {
+            command.arg("--config").arg(&config.0);
+        }
+        let mut child = command
+            .args(["pull", reference])
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|_| Error::DockerCommandFailed)?;
+        let stdout = child.stdout.take().ok_or(Error::DockerCommandFailed)?;
+        let mut stderr = child.stderr.take().ok_or(Error::DockerCommandFailed)?;
+
+        // Without a terminal the CLI prints one `<layer>: <status>` line per step.
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?
+        {
+            let progress = match line.split_once(": ") {
+                Some((layer, status))
+                    if layer.len() == 12 && layer.chars().all(|c| c.is_ascii_hexdigit()) =>
+                {
+                    PullProgress {
+                        layer: Some(layer.to_string()),
+                        status: status.to_string(),
+                        ..Default::default()
+                    }
+                }
+                _ => PullProgress {
+                    status: line,
+                    ..Default::default()
+                },
+            };
+            let _ = tx.send(progress).await;
+        }
+
+        let mut errors = String::new();
+        let _ = stderr.read_to_string(&mut errors).await;
+        let status = child.wait().await.map_err(|_| Error::DockerCommandFailed)?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(image_error(&errors))
+        }
+    }
+
+    async fn build_image(
+        &self,
+        context: Bytes,
+        opts: &BuildOptions,
+        auths: &[RegistryAuth],
+        tx: mpsc::Sender<String>,
+    ) -> Result<String, Error> {
+        opts.validate()?;
+        let config = (!auths.is_empty())
+            .then(|| CliConfig::write(auths))
+            .transpose()?;
+        let iidfile =
+            std::env::temp_dir().join(format!("lightshuttle-iid-{}", uuid::Uuid::new_v4()));
+        let mut command = self.command();
+        if let Some(config) = &config {
+            command.arg("--config").arg(&config.0);
+        }
+        command
+            .args(["build", "--file", &opts.dockerfile, "--tag", &opts.tag])
+            .arg("--iidfile")
+            .arg(&iidfile);
+        if let Some(target) = &opts.target {
+            command.arg("--target").arg(target);
+        }
+        for (name, value) in &opts.build_args {
+            command.arg("--build-arg").arg(format!("{name}={value}"));
+        }
+        let mut child = command
+            .arg("-")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|_| Error::DockerCommandFailed)?;
+        let (Some(mut stdin), Some(stdout), Some(stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(Error::DockerCommandFailed);
+        };
+
+        // The context is written while the output is read, so neither pipe fills up.
+        tokio::spawn(async move {
+            let _ = stdin.write_all(&context).await;
+        });
+        let stdout_tx = tx.clone();
+        let forward_stdout = tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let _ = stdout_tx.send(line).await;
+            }
+        });
+        // BuildKit reports its progress and errors on stderr.
+        let mut last_error = String::new();
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if !line.trim().is_empty() {
+                last_error = line.trim().to_string();
+            }
+            let _ = tx.send(line).await;
+        }
+        let _ = forward_stdout.await;
+
+        let status = child.wait().await.map_err(|_| Error::DockerCommandFailed)?;
+        let id = std::fs::read_to_string(&iidfile).map(|id| id.trim().to_string());
+        let _ = std::fs::remove_file(&iidfile);
+        match id {
+            Ok(id) if status.success() && !id.is_empty() => Ok(id),
+            _ => Err(Error::Unexpected(last_error)),
+        }
+    }
+
+    async fn events(&self, tx: mpsc::Sender<ContainerEvent>) -> Result<(), Error> {
+        let (_child, mut lines) = self.subscribe_events(None)?;
+
+        loop {
+            let line = tokio::select! {
+                _ = tx.closed() => return Ok(()),
+                line = lines.next_line() => line.map_err(|_| Error::DockerCommandFailed)?,
+            };
+            let Some(line) = line else {
+                // `docker events` only exits when the daemon goes away.
+                return Err(Error::DockerCommandFailed);
+            };
+            if let Some(event) = ContainerEvent::from_message(&line) {
+                if tx.send(event).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Maps the daemon error printed by a failed `docker exec`.
+fn exec_error(stderr: &str) -> Error {
+    let lower = stderr.to_lowercase();
+    if lower.contains("no such container") {
+        Error::ContainerNotFound
+    } else if lower.contains("is not running") || lower.contains("is paused") {
+        Error::Conflict(stderr.trim().to_string())
+    } else {
+        Error::Unexpected(stderr.trim().to_string())
+    }
+}
+
+/// Maps the daemon error printed by a failed `docker network` command.
+fn network_error(stderr: &str) -> Error {
+    let lower = stderr.to_lowercase();
+    let message = stderr.trim().to_string();
+    if lower.contains("not found") || lower.contains("no such network") {
+        Error::NotFound(message)
+    } else if lower.contains("already exists")
+        || lower.contains("active endpoints")
+        || lower.contains("pre-defined")
+    {
+        Error::Conflict(message)
+    } else if lower.contains("no such container") {
+        Error::ContainerNotFound
+    } else {
+        Error::Unexpected(message)
+    }
+}
+
+/// Maps the daemon error printed by a failed container lifecycle command.
+fn container_error(stderr: &str) -> Error {
+    let lower = stderr.to_lowercase();
+    let message = stderr.trim().to_string();
+    if lower.contains("no such container") {
+        Error::ContainerNotFound
+    } else if lower.contains("is not running")
+        || lower.contains("is already paused")
+        || lower.contains("is not paused")
+        || lower.contains("already in use")
+    {
+        Error::Conflict(message)
+    } else if lower.contains("invalid signal") || lower.contains("invalid container name") {
+        Error::BadRequest(message)
+    } else {
+        Error::Unexpected(message)
+    }
+}
+
+/// Maps the daemon error printed by a failed `docker volume` command.
+fn volume_error(stderr: &str) -> Error {
+    let lower = stderr.to_lowercase();
+    let message = stderr.trim().to_string();
+    if lower.contains("no such volume") || lower.contains("not found") {
+        Error::NotFound(message)
+    } else if lower.contains("in use") {
+        Error::Conflict(message)
+    } else {
+        Error::Unexpected(message)
+    }
+}
+
+/// Maps the daemon error printed by a failed `docker cp`.
+fn archive_error(stderr: &str) -> Error {
+    let lower = stderr.to_lowercase();
+    // Missing paths read "No such container:path: web:/etc/x" on recent CLIs.
+    if lower.contains("no such container:path") || lower.contains("could not find the file") {
+        Error::NotFound(stderr.trim().to_string())
+    } else if lower.contains("no such container") {
+        Error::ContainerNotFound
+    } else if lower.contains("read-only") || lower.contains("not a directory") {
+        Error::Conflict(stderr.trim().to_string())
+    } else {
+        Error::Unexpected(stderr.trim().to_string())
+    }
+}
+
+/// Docker CLI config directory holding registry logins, removed on drop.
+struct CliConfig(PathBuf);
+
+impl CliConfig {
+    fn write(auths: &[RegistryAuth]) -> Result<Self, Error> {
+        let dir = std::env::temp_dir().join(format!("lightshuttle-cli-{}", uuid::Uuid::new_v4()));
+        let logins: serde_json::Map<String, serde_json::Value> = auths
+            .iter()
+            .map(|auth| {
+                let login = serde_json::json!({ "auth": auth.basic() });
+                (auth.config_key().to_string(), login)
+            })
+            .collect();
+        let config = serde_json::json!({ "auths": logins });
+        write_private(&dir.join("config.json"), config.to_string().as_bytes())?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for CliConfig {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Maps the daemon error printed by a failed image command.
+fn image_error(stderr: &str) -> Error {
+    let lower = stderr.to_lowercase();
+    if lower.contains("no such image")
+        || lower.contains("manifest unknown")
+        || lower.contains("not found")
+        || lower.contains("pull access denied")
+    {
+        Error::ImageNotFound
+    } else if lower.contains("conflict") || lower.contains("is being used") {
+        Error::Conflict(stderr.trim().to_string())
+    } else {
+        Error::Unexpected(stderr.trim().to_string())
+    }
+}
+
+/// Forwards everything read from `reader` to an exec session output.
+pub(crate) async fn pump_output<R>(mut reader: R, tx: mpsc::Sender<Bytes>)
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = vec![0; 8192];
+    loop {
+        match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                if tx.send(Bytes::copy_from_slice(&buf[..n])).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// `docker run` flags of resource limits; sizes are passed in bytes.
+fn limit_args(limits: &ResourceLimits) -> Vec<String> {
+    let flags = [
+        ("--cpus", limits.cpus.map(|cpus| cpus.to_string())),
+        ("--cpu-shares", limits.cpu_shares.map(|v| v.to_string())),
+        ("--memory", limits.memory.map(|v| v.to_string())),
+        (
+            "--memory-reservation",
+            limits.memory_reservation.map(|v| v.to_string()),
+        ),
+        ("--memory-swap", limits.memory_swap.map(|v| v.to_string())),
+        ("--pids-limit", limits.pids_limit.map(|v| v.to_string())),
+    ];
+    flags
+        .into_iter()
+        .filter_map(|(flag, value)| Some([flag.to_string(), value?]))
+        .flatten()
+        .collect()
+}
+
+/// `docker run` flags of security options and the daemon's seccomp profile.
+fn security_args(security: Option<&SecurityOptions>, seccomp: &SeccompProfile) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut security_opt = |option: String| args.extend(["--security-opt".to_string(), option]);
+    if let Some(option) = seccomp.cli_option() {
+        security_opt(option);
+    }
+    let Some(security) = security else {
+        return args;
+    };
+    if security.no_new_privileges {
+        security_opt("no-new-privileges".to_string());
+    }
+    if let Some(profile) = &security.apparmor {
+        security_opt(format!("apparmor={profile}"));
+    }
+    for cap in &security.cap_add {
+        args.extend(["--cap-add".to_string(), cap.clone()]);
+    }
+    for cap in &security.cap_drop {
+        args.extend(["--cap-drop".to_string(), cap.clone()]);
+    }
+    if security.read_only {
+        args.push("--read-only".to_string());
+    }
+    for (path, options) in &security.tmpfs {
+        let mount = if options.is_empty() {
+            path.clone()
+        } else {
+            format!("{path}:{options}")
+        };
+        args.extend(["--tmpfs".to_string(), mount]);
+    }
+    args
+}
+
+/// `docker run` flags of process overrides, and the arguments following the image.
+///
+/// `--entrypoint` only takes the executable: the rest of the entrypoint
+/// goes before the command, as the engine would run it anyway.
+fn process_args(process: &ProcessOverrides) -> (Vec<String>, Vec<String>) {
+    let mut entrypoint = process.entrypoint.iter().flatten();
+    let flags = [
+        ("--entrypoint", entrypoint.next().cloned()),
+        ("--workdir", process.working_dir.clone()),
+        ("--user", process.user.clone()),
+        ("--hostname", process.hostname.clone()),
+    ];
+    let flags = flags
+        .into_iter()
+        .filter_map(|(flag, value)| Some([flag.to_string(), value?]))
+        .flatten()
+        .collect();
+    let command = entrypoint
+        .chain(process.command.iter().flatten())
+        .cloned()
+        .collect();
+    (flags, command)
+}
+
+/// `docker run` flags of a health check, run with the container's shell.
+fn health_args(healthcheck: &HealthCheck) -> Vec<String> {
+    let secs = |secs: Option<u64>| secs.map(|secs| format!("{secs}s"));
+    let flags = [
+        ("--health-cmd", Some(healthcheck.command.clone())),
+        ("--health-interval", secs(healthcheck.interval)),
+        ("--health-timeout", secs(healthcheck.timeout)),
+        (
+            "--health-retries",
+            healthcheck.retries.map(|v| v.to_string()),
+        ),
+        ("--health-start-period", secs(healthcheck.start_period)),
+    ];
+    flags
+        .into_iter()
+        .filter_map(|(flag, value)| Some([flag.to_string(), value?]))
+        .flatten()
+        .collect()
+}
+
+/// Parses the ports string from `docker ps` into a list of `u16` host ports.
+fn parse_ports(ports_info: &str) -> Vec<u16> {
+    ports_info
+        .split(',')
+        .filter_map(|entry| {
+            let parts: Vec<&str> = entry.trim().split("->").collect();
+            // Unpublished ports ("80/tcp") have no host side.
+            if parts.len() == 2 {
+                if let Some(port_part) = parts[0].split(':').next_back() {
+                    return port_part.parse::<u16>().ok();
+                }
+            }
+            None
+        })
+        .collect()
+}
+
+/// Parses a human-readable size from `docker stats` (`1.5MiB`, `12kB`, `0B`).
+fn parse_size(value: &str) -> u64 {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: f64 = match unit.trim() {
+        "" | "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return 0,
+    };
+    (number.parse::<f64>().unwrap_or(0.0) * multiplier) as u64
+}
+
+/// Parses the `key=value,key=value` labels string from `docker ps`.
+fn parse_labels(labels: &str) -> HashMap<String, String> {
+    labels
+        .split(',')
+        .filter_map(|pair| {
+            let (k, v) = pair.split_once('=')?;
+            Some((k.to_string(), v.to_string()))
+        })
+        .collect()
 }
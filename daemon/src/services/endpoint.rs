@@ -0,0 +1,309 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    errors::Error,
+    services::http::{Endpoint, DEFAULT_DOCKER_SOCKET},
+};
+
+/// Default port of a plain TCP Docker endpoint.
+const DEFAULT_HTTP_PORT: u16 = 2375;
+/// Default port of a TLS Docker endpoint.
+const DEFAULT_TLS_PORT: u16 = 2376;
+
+/// Certificate files used to reach a TLS-protected Docker endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TlsFiles {
+    /// CA used to verify the engine certificate.
+    pub ca: PathBuf,
+    /// Client certificate and key; `None` when the engine does not require client auth.
+    pub client: Option<(PathBuf, PathBuf)>,
+}
+
+impl TlsFiles {
+    /// Uses `ca.pem`, `cert.pem` and `key.pem` from `dir`, like the Docker CLI.
+    pub fn from_dir(dir: &Path) -> Result<Self, Error> {
+        let ca = dir.join("ca.pem");
+        if !ca.is_file() {
+            return Err(Error::InvalidRequest(format!(
+                "TLS CA certificate not found: {}",
+                ca.display()
+            )));
+        }
+
+        let cert = dir.join("cert.pem");
+        let key = dir.join("key.pem");
+        let client = match (cert.is_file(), key.is_file()) {
+            (true, true) => Some((cert, key)),
+            (false, false) => None,
+            _ => {
+                return Err(Error::InvalidRequest(format!(
+                    "TLS client certificate and key must both exist in {}",
+                    dir.display()
+                )))
+            }
+        };
+
+        Ok(Self { ca, client })
+    }
+}
+
+/// Entry of the `DOCKER_ENDPOINTS_FILE` daemon configuration.
+///
+/// ```json
+/// {
+///   "local": { "host": "unix:///var/run/docker.sock" },
+///   "prod": { "host": "tcp://10.0.0.5:2376", "tls_verify": true, "cert_path": "/etc/lightshuttle/prod" }
+/// }
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct EndpointConfig {
+    /// `unix://`, `tcp://`, `http://` or `https://` address.
+    pub host: String,
+    /// Use TLS and verify the engine certificate.
+    #[serde(default)]
+    pub tls_verify: bool,
+    /// Directory holding `ca.pem`, `cert.pem` and `key.pem`.
+    #[serde(default)]
+    pub cert_path: Option<PathBuf>,
+}
+
+/// Docker endpoint resolved from the daemon configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DockerEndpoint {
+    /// Configuration name, context name, or `env` when taken from `DOCKER_HOST`.
+    pub name: String,
+    /// Normalized address (`unix:///...` or `tcp://host:port`).
+    pub host: String,
+    /// Transport address.
+    pub address: Endpoint,
+    /// TLS material, when TLS is enabled.
+    pub tls: Option<TlsFiles>,
+}
+
+/// Public description of the endpoint in use, reported by `GET /system/info`.
+#[derive(Clone, Debug, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EndpointInfo {
+    pub name: String,
+    pub host: String,
+    pub tls: bool,
+}
+
+impl DockerEndpoint {
+    /// Parses a `DOCKER_HOST`-style address.
+    ///
+    /// `tcp://` endpoints use TLS when `tls` is provided; `https://` requires it
+    /// and `http://` forbids it. Ports default to 2375, or 2376 with TLS.
+    ///
+    /// # Examples
+    /// ```
+    /// use lightshuttle_core::services::{endpoint::DockerEndpoint, http::Endpoint};
+    ///
+    /// let ep = DockerEndpoint::parse("env", "tcp://docker-proxy", None).unwrap();
+    /// assert_eq!(ep.address, Endpoint::Tcp("docker-proxy:2375".into()));
+    /// assert!(DockerEndpoint::parse("env", "ssh://host", None).is_err());
+    /// ```
+    pub fn parse(name: &str, host: &str, tls: Option<TlsFiles>) -> Result<Self, Error> {
+        let invalid = |reason: &str| {
+            Error::InvalidRequest(format!("Invalid Docker endpoint '{host}': {reason}"))
+        };
+
+        let (scheme, rest) = host
+            .split_once("://")
+            .ok_or_else(|| invalid("missing scheme"))?;
+
+        let (address, normalized) = match scheme {
+            "unix" => {
+                if rest.is_empty() {
+                    return Err(invalid("missing socket path"));
+                }
+                if tls.is_some() {
+                    return Err(invalid("TLS is not supported on Unix sockets"));
+                }
+                (Endpoint::Unix(PathBuf::from(rest)), host.to_string())
+            }
+            "tcp" | "http" | "https" => {
+                if scheme == "https" && tls.is_none() {
+                    return Err(invalid("https requires DOCKER_TLS_VERIFY or tls_verify"));
+                }
+                if scheme == "http" && tls.is_some() {
+                    return Err(invalid("TLS cannot be used with http://"));
+                }
+
+                let authority = rest.trim_end_matches('/');
+                if authority.is_empty() || authority.contains('/') {
+                    return Err(invalid("expected host[:port]"));
+                }
+                let addr = if has_port(authority) {
+                    authority.to_string()
+                } else {
+                    let port = if tls.is_some() {
+                        DEFAULT_TLS_PORT
+                    } else {
+                        DEFAULT_HTTP_PORT
+                    };
+                    format!("{authority}:{port}")
+                };
+                (Endpoint::Tcp(addr.clone()), format!("tcp://{addr}"))
+            }
+            other => return Err(invalid(&format!("unsupported scheme '{other}'"))),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            host: normalized,
+            address,
+            tls,
+        })
+    }
+
+    /// Builds an endpoint from a `DOCKER_ENDPOINTS_FILE` entry.
+    pub fn from_config(name: &str, cfg: &EndpointConfig) -> Result<Self, Error> {
+        let tls = if cfg.tls_verify || cfg.host.starts_with("https://") {
+            let dir = cfg.cert_path.clone().unwrap_or_else(docker_config_dir);
+            Some(TlsFiles::from_dir(&dir)?)
+        } else {
+            None
+        };
+        Self::parse(name, &cfg.host, tls)
+    }
+
+    /// Plain endpoint for an address, without TLS.
+    pub fn plain(name: &str, address: Endpoint) -> Self {
+        let host = match &address {
+            Endpoint::Unix(path) => format!("unix://{}", path.display()),
+            Endpoint::Tcp(addr) => format!("tcp://{addr}"),
+        };
+        Self {
+            name: name.to_string(),
+            host,
+            address,
+            tls: None,
+        }
+    }
+
+    /// The local Docker socket.
+    pub fn default_socket() -> Self {
+        Self::plain(
+            "default",
+            Endpoint::Unix(PathBuf::from(DEFAULT_DOCKER_SOCKET)),
+        )
+    }
+
+    /// Resolves the endpoint from the environment.
+    ///
+    /// Precedence, highest first:
+    /// 1. `DOCKER_ENDPOINT`, naming an entry of `DOCKER_ENDPOINTS_FILE`
+    /// 2. `DOCKER_HOST`, with `DOCKER_TLS_VERIFY` and `DOCKER_CERT_PATH`
+    /// 3. `DOCKER_CONTEXT`, or the `currentContext` of the Docker CLI config
+    ///
+    /// Returns `Ok(None)` when nothing is configured.
+    pub fn resolve() -> Result<Option<Self>, Error> {
+        if let Some(name) = non_empty_var("DOCKER_ENDPOINT") {
+            let endpoints = load_endpoints_file()?;
+            let cfg = endpoints.get(&name).ok_or_else(|| {
+                Error::InvalidRequest(format!("Docker endpoint '{name}' is not configured"))
+            })?;
+            return Self::from_config(&name, cfg).map(Some);
+        }
+
+        if let Some(host) = non_empty_var("DOCKER_HOST") {
+            let cfg = EndpointConfig {
+                host,
+                tls_verify: non_empty_var("DOCKER_TLS_VERIFY").is_some(),
+                cert_path: non_empty_var("DOCKER_CERT_PATH").map(PathBuf::from),
+            };
+            return Self::from_config("env", &cfg).map(Some);
+        }
+
+        match non_empty_var("DOCKER_CONTEXT").or_else(current_context) {
+            Some(context) if context != "default" => Self::from_context(&context).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Loads a Docker CLI context from `~/.docker/contexts`.
+    pub fn from_context(context: &str) -> Result<Self, Error> {
+        let digest = format!("{:x}", Sha256::digest(context.as_bytes()));
+        let root = docker_config_dir().join("contexts");
+
+        let meta_path = root.join("meta").join(&digest).join("meta.json");
+        let meta: serde_json::Value = std::fs::read_to_string(&meta_path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .ok_or_else(|| {
+                Error::InvalidRequest(format!("Docker context '{context}' not found"))
+            })?;
+
+        let docker = &meta["Endpoints"]["docker"];
+        let host = docker["Host"].as_str().ok_or_else(|| {
+            Error::InvalidRequest(format!("Docker context '{context}' has no docker host"))
+        })?;
+
+        let tls_dir = root.join("tls").join(&digest).join("docker");
+        let tls = if tls_dir.join("ca.pem").is_file()
+            && !docker["SkipTLSVerify"].as_bool().unwrap_or(false)
+        {
+            Some(TlsFiles::from_dir(&tls_dir)?)
+        } else {
+            None
+        };
+
+        Self::parse(context, host, tls)
+    }
+
+    /// Public description of this endpoint.
+    pub fn info(&self) -> EndpointInfo {
+        EndpointInfo {
+            name: self.name.clone(),
+            host: self.host.clone(),
+            tls: self.tls.is_some(),
+        }
+    }
+}
+
+/// Reads the named endpoints declared in `DOCKER_ENDPOINTS_FILE`.
+pub fn load_endpoints_file() -> Result<HashMap<String, EndpointConfig>, Error> {
+    let path = non_empty_var("DOCKER_ENDPOINTS_FILE").ok_or_else(|| {
+        Error::InvalidRequest("DOCKER_ENDPOINT is set but DOCKER_ENDPOINTS_FILE is not".into())
+    })?;
+    let data = std::fs::read_to_string(&path)
+        .map_err(|e| Error::InvalidRequest(format!("Failed to read {path}: {e}")))?;
+    serde_json::from_str(&data)
+        .map_err(|e| Error::InvalidRequest(format!("Failed to parse {path}: {e}")))
+}
+
+fn non_empty_var(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.is_empty())
+}
+
+/// `DOCKER_CONFIG`, or `~/.docker`.
+fn docker_config_dir() -> PathBuf {
+    non_empty_var("DOCKER_CONFIG")
+        .map(PathBuf::from)
+        .or_else(|| non_empty_var("HOME").map(|home| PathBuf::from(home).join(".docker")))
+        .unwrap_or_else(|| PathBuf::from(".docker"))
+}
+
+/// `currentContext` from the Docker CLI `config.json`.
+fn current_context() -> Option<String> {
+    let data = std::fs::read_to_string(docker_config_dir().join("config.json")).ok()?;
+    let config: serde_json::Value = serde_json::from_str(&data).ok()?;
+    config["currentContext"].as_str().map(str::to_string)
+}
+
+fn has_port(authority: &str) -> bool {
+    match authority.rsplit_once(':') {
+        // IPv6 literals (`[::1]`) only carry a port after the closing bracket.
+        Some((host, port)) => {
+            (!host.starts_with('[') || host.ends_with(']')) && port.parse::<u16>().is_ok()
+        }
+        None => false,
+    }
+}
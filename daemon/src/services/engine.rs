@@ -0,0 +1,1348 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    sync::{mpsc, oneshot},
+};
+
+use crate::{
+    docker::{
+        BuildMessage, BuildOptions, ContainerConfig, ContainerEvent, ContainerInspect,
+        ContainerStats, ContainerSummary, EngineInfo, ExecConfig, ExecOutput, ImageInspect,
+        ImageSummary, NetworkConfig, NetworkInfo, PruneReport, PullProgress, RegistryAuth,
+        VolumeConfig, VolumeInfo, VolumeUsage, WaitCondition,
+    },
+    errors::Error,
+    services::{
+        docker::{pump_output, ArchiveStream, BackendInfo, DockerClient, ExecInput, ExecSession},
+        endpoint::DockerEndpoint,
+        http::{self, encode, BodyStream, Endpoint, Response, Transport},
+        seccomp::SeccompProfile,
+    },
+};
+
+/// Engine API version used for every request.
+pub const API_VERSION: &str = "v1.41";
+
+/// Docker client talking HTTP directly to the Engine API.
+///
+/// No `docker` binary is required: requests go to the Unix socket
+/// or TCP address (optionally over TLS) the endpoint points at.
+pub struct EngineDockerClient {
+    endpoint: DockerEndpoint,
+    transport: Transport,
+    seccomp: SeccompProfile,
+}
+
+impl EngineDockerClient {
+    /// Creates a client for the given address, without TLS.
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self {
+            transport: Transport::new(endpoint.clone()),
+            endpoint: DockerEndpoint::plain("default", endpoint),
+            seccomp: SeccompProfile::default(),
+        }
+    }
+
+    /// Creates a client for a resolved endpoint, loading its TLS certificates.
+    pub fn connect(endpoint: DockerEndpoint) -> Result<Self, Error> {
+        Ok(Self {
+            transport: Transport::connect_to(&endpoint)?,
+            endpoint,
+            seccomp: SeccompProfile::default(),
+        })
+    }
+
+    /// Applies `profile` to every container created.
+    pub fn with_seccomp(mut self, profile: SeccompProfile) -> Self {
+        self.seccomp = profile;
+        self
+    }
+
+    /// Returns the endpoint this client talks to.
+    pub fn endpoint(&self) -> &DockerEndpoint {
+        &self.endpoint
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Response, Error> {
+        let payload = body.map(|b| b.to_string().into_bytes());
+        http::request(
+            &self.transport,
+            method,
+            &format!("/{API_VERSION}{path}"),
+            payload,
+        )
+        .await
+    }
+
+    async fn create(&self, cfg: &ContainerConfig<'_>) -> Result<Response, Error> {
+        let body = create_body(cfg, &self.seccomp);
+        self.call(
+            "POST",
+            &format!("/containers/create?name={}", encode(cfg.name)),
+            Some(&body),
+        )
+        .await
+    }
+
+    fn exec_api(&self) -> ExecApi {
+        ExecApi {
+            transport: self.transport.clone(),
+            prefix: format!("/{API_VERSION}"),
+            map_error,
+        }
+    }
+
+    async fn pull(&self, image: &str) -> Result<(), Error> {
+        let (tx, _) = mpsc::channel(1);
+        self.pull_image(image, None, tx).await
+    }
+
+    /// `POST /containers/{name}/{action}`, answering no content.
+    async fn post_action(&self, name: &str, action: &str) -> Result<(), Error> {
+        let res = self
+            .call(
+                "POST",
+                &format!("/containers/{}/{action}", encode(name)),
+                None,
+            )
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_error(&res))
+        }
+    }
+}
+
+#[async_trait]
+impl DockerClient for EngineDockerClient {
+    fn describe(&self) -> BackendInfo {
+        BackendInfo {
+            backend: "engine".to_string(),
+            endpoint: Some(self.endpoint.info()),
+        }
+    }
+
+    async fn engine_info(&self) -> Result<EngineInfo, Error> {
+        engine_info(&self.transport).await
+    }
+
+    async fn run(&self, cfg: ContainerConfig<'_>) -> Result<String, Error> {
+        cfg.validate()?;
+
+        let mut res = self.create(&cfg).await?;
+        if res.status == 404 && !is_missing_network(&res) {
+            self.pull(cfg.image).await?;
+            res = self.create(&cfg).await?;
+        }
+        if !res.is_success() {
+            return Err(map_network_error(&res));
+        }
+
+        let created: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        let id = created["Id"]
+            .as_str()
+            .ok_or_else(|| Error::DockerOutputParse("Missing container id".into()))?
+            .to_string();
+
+        // Containers are created on a single network: the others are connected before start.
+        for network in cfg.networks.iter().skip(1) {
+            let body = json!({
+                "Container": id,
+                "EndpointConfig": { "Aliases": network.aliases },
+            });
+            let res = self
+                .call(
+                    "POST",
+                    &format!("/networks/{}/connect", encode(&network.name)),
+                    Some(&body),
+                )
+                .await?;
+            if !res.is_success() {
+                // Leave no half-attached container behind.
+                let _ = self
+                    .call("DELETE", &format!("/containers/{id}?force=true"), None)
+                    .await;
+                return Err(map_network_error(&res));
+            }
+        }
+
+        let res = self
+            .call("POST", &format!("/containers/{id}/start"), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+
+        Ok(id)
+    }
+
+    async fn start(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call("POST", &format!("/containers/{}/start", encode(name)), None)
+            .await?;
+        // 304: already started
+        if res.is_success() || res.status == 304 {
+            Ok(())
+        } else {
+            Err(map_error(&res))
+        }
+    }
+
+    async fn stop(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call("POST", &format!("/containers/{}/stop", encode(name)), None)
+            .await?;
+        // 304: already stopped
+        if res.is_success() || res.status == 304 {
+            Ok(())
+        } else {
+            Err(map_error(&res))
+        }
+    }
+
+    async fn pause(&self, name: &str) -> Result<(), Error> {
+        self.post_action(name, "pause").await
+    }
+
+    async fn unpause(&self, name: &str) -> Result<(), Error> {
+        self.post_action(name, "unpause").await
+    }
+
+    async fn restart(&self, name: &str, timeout: Option<u64>) -> Result<(), Error> {
+        let action = match timeout {
+            Some(secs) => format!("restart?t={secs}"),
+            None => "restart".to_string(),
+        };
+        self.post_action(name, &action).await
+    }
+
+    async fn kill(&self, name: &str, signal: &str) -> Result<(), Error> {
+        self.post_action(name, &format!("kill?signal={}", encode(signal)))
+            .await
+    }
+
+    async fn rename(&self, name: &str, new_name: &str) -> Result<(), Error> {
+        self.post_action(name, &format!("rename?name={}", encode(new_name)))
+            .await
+    }
+
+    async fn wait(&self, name: &str, condition: WaitCondition) -> Result<i64, Error> {
+        wait_for(&self.transport, &format!("/{API_VERSION}"), name, condition).await
+    }
+
+    async fn inspect(&self, name: &str) -> Result<ContainerInspect, Error> {
+        let res = self
+            .call("GET", &format!("/containers/{}/json", encode(name)), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn list(&self, all: bool) -> Result<Vec<ContainerSummary>, Error> {
+        let res = self
+            .call("GET", &format!("/containers/json?all={all}"), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+
+        let entries: Vec<Value> = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(entries.iter().map(summary_from_json).collect())
+    }
+
+    async fn remove(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call(
+                "DELETE",
+                &format!("/containers/{}?force=true", encode(name)),
+                None,
+            )
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_error(&res))
+        }
+    }
+
+    async fn logs(&self, name: &str) -> Result<String, Error> {
+        let res = self
+            .call(
+                "GET",
+                &format!("/containers/{}/logs?stdout=true&stderr=true", encode(name)),
+                None,
+            )
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        Ok(String::from_utf8_lossy(&demux(&res.body)).to_string())
+    }
+
+    async fn stats(&self, name: &str) -> Result<ContainerStats, Error> {
+        // `stream=false` waits for a second sample so that CPU usage can be computed.
+        let res = self
+            .call(
+                "GET",
+                &format!("/containers/{}/stats?stream=false", encode(name)),
+                None,
+            )
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        let raw: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(stats_from_json(&raw))
+    }
+
+    async fn exec(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error> {
+        self.exec_api().run(name, cfg).await
+    }
+
+    async fn exec_session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error> {
+        self.exec_api().session(name, cfg).await
+    }
+
+    async fn get_archive(&self, name: &str, path: &str) -> Result<ArchiveStream, Error> {
+        let res = http::stream(
+            &self.transport,
+            "GET",
+            &format!(
+                "/{API_VERSION}/containers/{}/archive?path={}",
+                encode(name),
+                encode(path)
+            ),
+            None,
+        )
+        .await?;
+        if !res.is_success() {
+            return Err(map_archive_error(&res.collect().await?, map_error));
+        }
+        Ok(Box::pin(res.into_chunks()))
+    }
+
+    async fn put_archive(&self, name: &str, path: &str, archive: Bytes) -> Result<(), Error> {
+        let res = http::stream_with(
+            &self.transport,
+            "PUT",
+            &format!(
+                "/{API_VERSION}/containers/{}/archive?path={}",
+                encode(name),
+                encode(path)
+            ),
+            &[("Content-Type", "application/x-tar".to_string())],
+            Some(archive.to_vec()),
+        )
+        .await?
+        .collect()
+        .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_archive_error(&res, map_error))
+        }
+    }
+
+    async fn list_networks(&self) -> Result<Vec<NetworkInfo>, Error> {
+        let res = self.call("GET", "/networks", None).await?;
+        if !res.is_success() {
+            return Err(map_network_error(&res));
+        }
+        let networks: Vec<Value> = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(networks.iter().map(network_from_json).collect())
+    }
+
+    async fn inspect_network(&self, name: &str) -> Result<NetworkInfo, Error> {
+        let res = self
+            .call("GET", &format!("/networks/{}", encode(name)), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_network_error(&res));
+        }
+        let network: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(network_from_json(&network))
+    }
+
+    async fn create_network(&self, cfg: &NetworkConfig) -> Result<String, Error> {
+        cfg.validate()?;
+        let mut body = json!({
+            "Name": cfg.name,
+            "Driver": cfg.driver.as_deref().unwrap_or("bridge"),
+            "Internal": cfg.internal,
+            "CheckDuplicate": true,
+        });
+        if let Some(subnet) = &cfg.subnet {
+            body["IPAM"] = json!({ "Config": [{ "Subnet": subnet }] });
+        }
+        let res = self.call("POST", "/networks/create", Some(&body)).await?;
+        if !res.is_success() {
+            return Err(map_network_error(&res));
+        }
+        let created: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        created["Id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| Error::DockerOutputParse("Missing network id".into()))
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call("DELETE", &format!("/networks/{}", encode(name)), None)
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_network_error(&res))
+        }
+    }
+
+    async fn list_volumes(&self) -> Result<Vec<VolumeInfo>, Error> {
+        let res = self.call("GET", "/volumes", None).await?;
+        if !res.is_success() {
+            return Err(map_volume_error(&res));
+        }
+        let list: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        serde_json::from_value::<Option<Vec<VolumeInfo>>>(list["Volumes"].clone())
+            .map(Option::unwrap_or_default)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo, Error> {
+        let res = self
+            .call("GET", &format!("/volumes/{}", encode(name)), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_volume_error(&res));
+        }
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn create_volume(&self, cfg: &VolumeConfig) -> Result<VolumeInfo, Error> {
+        cfg.validate()?;
+        let body = json!({
+            "Name": cfg.name,
+            "Driver": cfg.driver.as_deref().unwrap_or("local"),
+            "DriverOpts": cfg.driver_opts,
+            "Labels": cfg.labels,
+        });
+        let res = self.call("POST", "/volumes/create", Some(&body)).await?;
+        if !res.is_success() {
+            return Err(map_volume_error(&res));
+        }
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call("DELETE", &format!("/volumes/{}", encode(name)), None)
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_volume_error(&res))
+        }
+    }
+
+    async fn volume_usage(&self) -> Result<HashMap<String, VolumeUsage>, Error> {
+        let res = self.call("GET", "/system/df", None).await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        let df: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        let mut usage: HashMap<String, VolumeUsage> = df["Volumes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|volume| {
+                let usage = VolumeUsage {
+                    // -1 while the engine has not computed the size.
+                    size: volume["UsageData"]["Size"].as_u64(),
+                    apps: Vec::new(),
+                };
+                Some((volume["Name"].as_str()?.to_string(), usage))
+            })
+            .collect();
+
+        let res = self.call("GET", "/containers/json?all=1", None).await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        add_volume_users(&res, &mut usage)?;
+        Ok(usage)
+    }
+
+    async fn list_images(&self) -> Result<Vec<ImageSummary>, Error> {
+        let res = self.call("GET", "/images/json", None).await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        let entries: Vec<Value> = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(entries.iter().map(image_from_json).collect())
+    }
+
+    async fn inspect_image(&self, name: &str) -> Result<ImageInspect, Error> {
+        let res = self
+            .call("GET", &format!("/images/{}/json", encode(name)), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_image_error(&res));
+        }
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn remove_image(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call("DELETE", &format!("/images/{}", encode(name)), None)
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_image_error(&res))
+        }
+    }
+
+    async fn prune_images(&self, all: bool) -> Result<PruneReport, Error> {
+        // Only dangling images unless `dangling=false` widens it to every unused one.
+        let filters = encode(&format!(r#"{{"dangling":["{}"]}}"#, !all));
+        let res = self
+            .call("POST", &format!("/images/prune?filters={filters}"), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        let report: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(PruneReport {
+            deleted: report["ImagesDeleted"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry["Deleted"].as_str().or(entry["Untagged"].as_str()))
+                .map(str::to_string)
+                .collect(),
+            space_reclaimed: report["SpaceReclaimed"].as_u64().unwrap_or(0),
+        })
+    }
+
+    async fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        tx: mpsc::Sender<PullProgress>,
+    ) -> Result<(), Error> {
+        // Without a tag the engine would pull every tag of the repository.
+        let tagged = reference.contains('@')
+            || reference
+                .rsplit_once(':')
+                .is_some_and(|(_, tag)| !tag.contains('/'));
+        let mut path = format!(
+            "/{API_VERSION}/images/create?fromImage={}",
+            encode(reference)
+        );
+        if !tagged {
+            path.push_str("&tag=latest");
+        }
+        let headers: Vec<_> = auth
+            .map(|auth| ("X-Registry-Auth", auth.header()))
+            .into_iter()
+            .collect();
+        let mut stream = http::stream_with(&self.transport, "POST", &path, &headers, None).await?;
+        if !stream.is_success() {
+            return Err(map_image_error(&stream.collect().await?));
+        }
+
+        // Pull failures are reported inside the progress stream with a 200 status.
+        while let Some(line) = stream.next_line().await? {
+            match PullProgress::from_message(&line) {
+                Some(Ok(progress)) => {
+                    let _ = tx.send(progress).await;
+                }
+                Some(Err(message)) => return Err(Error::Unexpected(message)),
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn build_image(
+        &self,
+        context: Bytes,
+        opts: &BuildOptions,
+        auths: &[RegistryAuth],
+        tx: mpsc::Sender<String>,
+    ) -> Result<String, Error> {
+        opts.validate()?;
+        let path = format!("/{API_VERSION}/build?{}", build_query(opts));
+        let stream = http::stream_with(
+            &self.transport,
+            "POST",
+            &path,
+            &build_headers(auths),
+            Some(context.to_vec()),
+        )
+        .await?;
+        if !stream.is_success() {
+            return Err(map_image_error(&stream.collect().await?));
+        }
+        match forward_build(stream, &tx).await? {
+            Some(id) => Ok(id),
+            None => Ok(self.inspect_image(&opts.tag).await?.id),
+        }
+    }
+
+    async fn events(&self, tx: mpsc::Sender<ContainerEvent>) -> Result<(), Error> {
+        let filters = encode(r#"{"type":["container"]}"#);
+        let stream = http::stream(
+            &self.transport,
+            "GET",
+            &format!("/{API_VERSION}/events?filters={filters}"),
+            None,
+        )
+        .await?;
+        if !stream.is_success() {
+            return Err(map_error(&stream.collect().await?));
+        }
+        forward_events(stream, tx).await
+    }
+}
+
+/// Exec endpoints of the Engine API, which libpod mirrors under its own prefix.
+#[derive(Clone)]
+pub(crate) struct ExecApi {
+    pub transport: Transport,
+    /// Prepended to every path (`/v1.41`, `/v4.0.0/libpod`).
+    pub prefix: String,
+    pub map_error: fn(&Response) -> Error,
+}
+
+impl ExecApi {
+    async fn call(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Response, Error> {
+        let payload = body.map(|b| b.to_string().into_bytes());
+        http::request(
+            &self.transport,
+            method,
+            &format!("{}{path}", self.prefix),
+            payload,
+        )
+        .await
+    }
+
+    async fn create(&self, name: &str, cfg: &ExecConfig, stdin: bool) -> Result<String, Error> {
+        let mut body = json!({
+            "AttachStdin": stdin,
+            "AttachStdout": true,
+            "AttachStderr": true,
+            "Tty": cfg.tty,
+            "Cmd": cfg.cmd,
+            "Env": cfg.env_pairs(),
+        });
+        if let Some(dir) = &cfg.working_dir {
+            body["WorkingDir"] = json!(dir);
+        }
+        if let Some(user) = &cfg.user {
+            body["User"] = json!(user);
+        }
+
+        let res = self
+            .call(
+                "POST",
+                &format!("/containers/{}/exec", encode(name)),
+                Some(&body),
+            )
+            .await?;
+        if !res.is_success() {
+            return Err((self.map_error)(&res));
+        }
+        let created: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        created["Id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| Error::DockerOutputParse("Missing exec id".into()))
+    }
+
+    /// Runs `cfg` to completion, collecting stdout and stderr.
+    pub async fn run(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error> {
+        cfg.validate()?;
+        let id = self.create(name, cfg, false).await?;
+        let start = json!({ "Detach": false, "Tty": cfg.tty });
+        let res = self
+            .call("POST", &format!("/exec/{id}/start"), Some(&start))
+            .await?;
+        if !res.is_success() {
+            return Err((self.map_error)(&res));
+        }
+
+        let (stdout, stderr) = if cfg.tty {
+            (res.body, Vec::new())
+        } else {
+            demux_split(&res.body)
+        };
+        Ok(ExecOutput {
+            stdout: String::from_utf8_lossy(&stdout).to_string(),
+            stderr: String::from_utf8_lossy(&stderr).to_string(),
+            exit_code: self.exit_code(&id).await?,
+        })
+    }
+
+    /// Starts `cfg` on a hijacked connection carrying stdin and the output.
+    pub async fn session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error> {
+        cfg.validate()?;
+        let id = self.create(name, cfg, true).await?;
+        let start = json!({ "Detach": false, "Tty": cfg.tty });
+        let stream = match http::upgrade(
+            &self.transport,
+            "POST",
+            &format!("{}/exec/{id}/start", self.prefix),
+            Some(start.to_string().into_bytes()),
+        )
+        .await?
+        {
+            Ok(stream) => stream,
+            Err(res) => return Err((self.map_error)(&res)),
+        };
+        let (reader, mut writer) = tokio::io::split(stream);
+
+        let (input, mut input_rx) = mpsc::channel(64);
+        let (output_tx, output) = mpsc::channel(64);
+        let (exit_tx, exit) = oneshot::channel();
+
+        let api = self.clone();
+        let exec_id = id.clone();
+        tokio::spawn(async move {
+            while let Some(input) = input_rx.recv().await {
+                match input {
+                    ExecInput::Data(data) => {
+                        if writer.write_all(&data).await.is_err() {
+                            break;
+                        }
+                    }
+                    ExecInput::Resize { cols, rows } => {
+                        if let Err(e) = api.resize(&exec_id, cols, rows).await {
+                            tracing::debug!(error = %e, "Failed to resize exec session");
+                        }
+                    }
+                }
+            }
+            let _ = writer.shutdown().await;
+        });
+
+        let api = self.clone();
+        let tty = cfg.tty;
+        tokio::spawn(async move {
+            if tty {
+                pump_output(reader, output_tx).await;
+            } else {
+                pump_frames(reader, output_tx).await;
+            }
+            let _ = exit_tx.send(api.exit_code(&id).await);
+        });
+
+        Ok(ExecSession {
+            input,
+            output,
+            exit,
+        })
+    }
+
+    async fn resize(&self, id: &str, cols: u16, rows: u16) -> Result<(), Error> {
+        let res = self
+            .call(
+                "POST",
+                &format!("/exec/{id}/resize?h={rows}&w={cols}"),
+                None,
+            )
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err((self.map_error)(&res))
+        }
+    }
+
+    /// Exit code of a finished exec; the engine may take a moment to record it.
+    async fn exit_code(&self, id: &str) -> Result<i64, Error> {
+        for _ in 0..20 {
+            let res = self.call("GET", &format!("/exec/{id}/json"), None).await?;
+            if !res.is_success() {
+                return Err((self.map_error)(&res));
+            }
+            let state: Value = serde_json::from_slice(&res.body)
+                .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+            if state["Running"] != json!(true) {
+                if let Some(code) = state["ExitCode"].as_i64() {
+                    return Ok(code);
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        Err(Error::Unexpected(format!(
+            "Exec {id} did not report an exit code"
+        )))
+    }
+}
+
+/// Forwards the payloads of a multiplexed stream as they arrive.
+async fn pump_frames<R>(mut reader: R, tx: mpsc::Sender<Bytes>)
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = Vec::new();
+    let mut chunk = vec![0; 8192];
+    loop {
+        match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+        while buf.len() >= 8 {
+            let size = u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]) as usize;
+            if buf.len() < 8 + size {
+                break;
+            }
+            let frame: Vec<u8> = buf.drain(..8 + size).skip(8).collect();
+            if tx.send(Bytes::from(frame)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Forwards an events stream of JSON messages to `tx`.
+///
+/// Returns once the receiver is dropped; the end of the stream is an error
+/// since the engine only closes it when going away.
+pub(crate) async fn forward_events(
+    mut stream: BodyStream,
+    tx: mpsc::Sender<ContainerEvent>,
+) -> Result<(), Error> {
+    loop {
+        let line = tokio::select! {
+            _ = tx.closed() => return Ok(()),
+            line = stream.next_line() => line?,
+        };
+        let Some(line) = line else {
+            return Err(Error::DockerCommandFailed);
+        };
+        if let Some(event) = ContainerEvent::from_message(&line) {
+            if tx.send(event).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Query string of a build request, shared by the Engine API and libpod.
+pub(crate) fn build_query(opts: &BuildOptions) -> String {
+    let mut query = format!(
+        "t={}&dockerfile={}&buildargs={}",
+        encode(&opts.tag),
+        encode(&opts.dockerfile),
+        encode(&opts.build_args_json())
+    );
+    if let Some(target) = &opts.target {
+        query.push_str(&format!("&target={}", encode(target)));
+    }
+    query
+}
+
+/// Headers of a build request: the tar context type and the registry logins.
+pub(crate) fn build_headers(auths: &[RegistryAuth]) -> Vec<(&'static str, String)> {
+    let mut headers = vec![("Content-Type", "application/x-tar".to_string())];
+    if !auths.is_empty() {
+        headers.push(("X-Registry-Config", RegistryAuth::config_header(auths)));
+    }
+    headers
+}
+
+/// Forwards the output of a build stream to `tx`, line by line.
+///
+/// Returns the id of the built image when the stream reports it. Build
+/// failures are reported inside the stream with a 200 status.
+pub(crate) async fn forward_build(
+    mut stream: BodyStream,
+    tx: &mpsc::Sender<String>,
+) -> Result<Option<String>, Error> {
+    let mut id = None;
+    while let Some(line) = stream.next_line().await? {
+        match BuildMessage::from_message(&line) {
+            Some(Ok(BuildMessage::Output(output))) => {
+                for line in BuildMessage::lines(&output) {
+                    let _ = tx.send(line.to_string()).await;
+                }
+            }
+            Some(Ok(BuildMessage::ImageId(image))) => id = Some(image),
+            Some(Err(message)) => return Err(Error::Unexpected(message)),
+            None => {}
+        }
+    }
+    Ok(id)
+}
+
+/// Converts a `GET /images/json` entry into an `ImageSummary`.
+pub(crate) fn image_from_json(entry: &Value) -> ImageSummary {
+    ImageSummary {
+        id: entry["Id"].as_str().unwrap_or_default().to_string(),
+        tags: entry["RepoTags"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter(|tag| !tag.contains("<none>"))
+            .map(str::to_string)
+            .collect(),
+        size: entry["Size"].as_u64().unwrap_or(0),
+        created: rfc3339(entry["Created"].as_i64().unwrap_or(0)),
+    }
+}
+
+/// Converts an Engine API network into a `NetworkInfo`.
+pub(crate) fn network_from_json(entry: &Value) -> NetworkInfo {
+    let text = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+    NetworkInfo {
+        id: text("Id"),
+        name: text("Name"),
+        driver: text("Driver"),
+        subnet: entry["IPAM"]["Config"][0]["Subnet"]
+            .as_str()
+            .map(str::to_string),
+        internal: entry["Internal"].as_bool().unwrap_or(false),
+        created: text("Created"),
+    }
+}
+
+/// Adds the containers of a `GET /containers/json` response to the usage of
+/// the named volumes they mount.
+pub(crate) fn add_volume_users(
+    res: &Response,
+    usage: &mut HashMap<String, VolumeUsage>,
+) -> Result<(), Error> {
+    let containers: Vec<Value> =
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+    for entry in &containers {
+        let name = entry["Names"][0]
+            .as_str()
+            .unwrap_or_default()
+            .trim_start_matches('/');
+        let volumes = entry["Mounts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|mount| mount["Type"] == "volume")
+            .filter_map(|mount| mount["Name"].as_str());
+        for volume in volumes {
+            usage
+                .entry(volume.to_string())
+                .or_default()
+                .apps
+                .push(name.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Engine info from the unversioned `/version` and `/info` endpoints, which
+/// answer even when the engine is older than [`API_VERSION`].
+pub(crate) async fn engine_info(transport: &Transport) -> Result<EngineInfo, Error> {
+    let mut bodies = Vec::with_capacity(2);
+    for path in ["/version", "/info"] {
+        let res = http::request(transport, "GET", path, None).await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        let body: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        bodies.push(body);
+    }
+    Ok(EngineInfo::from_json(&bodies[0], &bodies[1]))
+}
+
+/// Waits on `POST {prefix}/containers/{name}/wait` of a Docker-compatible API.
+pub(crate) async fn wait_for(
+    transport: &Transport,
+    prefix: &str,
+    name: &str,
+    condition: WaitCondition,
+) -> Result<i64, Error> {
+    let path = format!(
+        "{prefix}/containers/{}/wait?condition={}",
+        encode(name),
+        condition.as_str()
+    );
+    let res = http::request(transport, "POST", &path, None).await?;
+    if !res.is_success() {
+        return Err(map_error(&res));
+    }
+    let body: Value =
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+    if let Some(message) = body["Error"]["Message"].as_str().filter(|m| !m.is_empty()) {
+        return Err(Error::Unexpected(message.to_string()));
+    }
+    body["StatusCode"]
+        .as_i64()
+        .ok_or_else(|| Error::DockerOutputParse("Missing wait status code".into()))
+}
+
+/// `true` when a 404 on container creation is about a network rather than the image.
+pub(crate) fn is_missing_network(res: &Response) -> bool {
+    let message = res.error_message().to_lowercase();
+    message.contains("network") && !message.contains("image")
+}
+
+/// Formats a Unix timestamp as an RFC 3339 UTC date.
+pub fn rfc3339(secs: i64) -> String {
+    // Days to civil date, from Howard Hinnant's `civil_from_days`.
+    let (days, time) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+/// Converts a `GET /containers/json` entry into a `ContainerSummary`.
+fn summary_from_json(entry: &Value) -> ContainerSummary {
+    let text = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+    ContainerSummary {
+        id: text("Id"),
+        name: entry["Names"][0]
+            .as_str()
+            .unwrap_or_default()
+            .trim_start_matches('/')
+            .to_string(),
+        image: text("Image"),
+        state: text("State"),
+        status: text("Status"),
+        ports: entry["Ports"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p["PublicPort"].as_u64())
+            .filter_map(|p| u16::try_from(p).ok())
+            .collect(),
+        labels: entry["Labels"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(k, v)| (k.clone(), v.as_str().unwrap_or_default().to_string()))
+            .collect(),
+        networks: entry["NetworkSettings"]["Networks"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(name, _)| name.clone())
+            .collect(),
+    }
+}
+
+/// Converts a `GET /containers/{id}/stats` sample, computing usage like `docker stats`.
+pub fn stats_from_json(raw: &Value) -> ContainerStats {
+    let num = |v: &Value| v.as_u64().unwrap_or(0);
+
+    let cpu = &raw["cpu_stats"];
+    let precpu = &raw["precpu_stats"];
+    let cpu_delta = num(&cpu["cpu_usage"]["total_usage"])
+        .saturating_sub(num(&precpu["cpu_usage"]["total_usage"]));
+    let system_delta =
+        num(&cpu["system_cpu_usage"]).saturating_sub(num(&precpu["system_cpu_usage"]));
+    let online_cpus = match num(&cpu["online_cpus"]) {
+        0 => cpu["cpu_usage"]["percpu_usage"]
+            .as_array()
+            .map_or(1, |cpus| cpus.len().max(1) as u64),
+        n => n,
+    };
+    let cpu_percent = if cpu_delta > 0 && system_delta > 0 {
+        cpu_delta as f64 / system_delta as f64 * online_cpus as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    // Page cache is not counted: `cache` on cgroup v1, `inactive_file` on v2.
+    let memory = &raw["memory_stats"];
+    let cache = match num(&memory["stats"]["total_inactive_file"]) {
+        0 => num(&memory["stats"]["inactive_file"]).max(num(&memory["stats"]["cache"])),
+        n => n,
+    };
+
+    let (network_rx, network_tx) = raw["networks"]
+        .as_object()
+        .into_iter()
+        .flat_map(|networks| networks.values())
+        .fold((0, 0), |(rx, tx), net| {
+            (rx + num(&net["rx_bytes"]), tx + num(&net["tx_bytes"]))
+        });
+
+    let (block_read, block_write) = raw["blkio_stats"]["io_service_bytes_recursive"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .fold((0, 0), |(read, write), entry| {
+            match entry["op"].as_str().map(str::to_lowercase).as_deref() {
+                Some("read") => (read + num(&entry["value"]), write),
+                Some("write") => (read, write + num(&entry["value"])),
+                _ => (read, write),
+            }
+        });
+
+    ContainerStats {
+        cpu_percent,
+        memory_usage: num(&memory["usage"]).saturating_sub(cache),
+        memory_limit: num(&memory["limit"]),
+        network_rx,
+        network_tx,
+        block_read,
+        block_write,
+        pids: num(&raw["pids_stats"]["current"]),
+    }
+}
+
+/// Strips the 8-byte frame headers of a multiplexed stdout/stderr stream.
+///
+/// Containers started with a TTY send raw output, which is returned unchanged.
+pub fn demux(raw: &[u8]) -> Vec<u8> {
+    let is_framed = |data: &[u8]| data.len() >= 8 && data[0] <= 2 && data[1..4] == [0, 0, 0];
+    if !is_framed(raw) {
+        return raw.to_vec();
+    }
+
+    let mut out = Vec::with_capacity(raw.len());
+    let mut rest = raw;
+    while rest.len() >= 8 {
+        let size = u32::from_be_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let end = (8 + size).min(rest.len());
+        out.extend_from_slice(&rest[8..end]);
+        rest = &rest[end..];
+    }
+    out
+}
+
+/// Splits a multiplexed stream into stdout and stderr.
+///
+/// Unframed input (TTY sessions) is returned as stdout.
+pub fn demux_split(raw: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let is_framed = |data: &[u8]| data.len() >= 8 && data[0] <= 2 && data[1..4] == [0, 0, 0];
+    if !is_framed(raw) {
+        return (raw.to_vec(), Vec::new());
+    }
+
+    let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+    let mut rest = raw;
+    while rest.len() >= 8 {
+        let size = u32::from_be_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let end = (8 + size).min(rest.len());
+        let target = if rest[0] == 2 {
+            &mut stderr
+        } else {
+            &mut stdout
+        };
+        target.extend_from_slice(&rest[8..end]);
+        rest = &rest[end..];
+    }
+    (stdout, stderr)
+}
+
+/// Builds the `POST /containers/create` payload for a container config.
+fn create_body(cfg: &ContainerConfig, seccomp: &SeccompProfile) -> Value {
+    let mut bindings: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for port in cfg.ports {
+        let mut binding = json!({ "HostPort": port.host.to_string() });
+        if let Some(ip) = &port.host_ip {
+            binding["HostIp"] = json!(ip);
+        }
+        bindings.entry(port.key()).or_default().push(binding);
+    }
+
+    let env: Vec<String> = cfg
+        .env
+        .unwrap_or(&HashMap::new())
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect();
+
+    let mut host_config = json!({
+        "Binds": cfg.volumes.cloned().unwrap_or_default(),
+        // Mirrors `docker run --rm`, which the engine refuses alongside a restart policy.
+        "AutoRemove": matches!(cfg.restart_policy, None | Some("no")),
+    });
+    if !bindings.is_empty() {
+        host_config["PortBindings"] = json!(bindings);
+    }
+    if let Some(policy) = cfg.restart_policy {
+        host_config["RestartPolicy"] = json!({ "Name": policy });
+    }
+
+    if let Some(network) = cfg.networks.first() {
+        host_config["NetworkMode"] = json!(network.name);
+    }
+    if let Some(limits) = cfg.limits {
+        let fields = [
+            ("NanoCpus", limits.nano_cpus()),
+            ("CpuShares", limits.cpu_shares.map(|v| v as i64)),
+            ("Memory", limits.memory.map(|v| v as i64)),
+            (
+                "MemoryReservation",
+                limits.memory_reservation.map(|v| v as i64),
+            ),
+            ("MemorySwap", limits.memory_swap),
+            ("PidsLimit", limits.pids_limit.map(|v| v as i64)),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value {
+                host_config[field] = json!(value);
+            }
+        }
+    }
+
+    let mut security_opt: Vec<String> = seccomp.engine_option().into_iter().collect();
+    if let Some(security) = cfg.security {
+        if security.no_new_privileges {
+            security_opt.push("no-new-privileges".to_string());
+        }
+        if let Some(profile) = &security.apparmor {
+            security_opt.push(format!("apparmor={profile}"));
+        }
+        if !security.cap_add.is_empty() {
+            host_config["CapAdd"] = json!(security.cap_add);
+        }
+        if !security.cap_drop.is_empty() {
+            host_config["CapDrop"] = json!(security.cap_drop);
+        }
+        if security.read_only {
+            host_config["ReadonlyRootfs"] = json!(true);
+        }
+        if !security.tmpfs.is_empty() {
+            host_config["Tmpfs"] = json!(security.tmpfs);
+        }
+    }
+    if !security_opt.is_empty() {
+        host_config["SecurityOpt"] = json!(security_opt);
+    }
+
+    let mut body = json!({
+        "Image": cfg.image,
+        "Env": env,
+        "Labels": cfg.labels.cloned().unwrap_or_default(),
+        "HostConfig": host_config,
+    });
+    if let Some(network) = cfg.networks.first() {
+        body["NetworkingConfig"] = json!({
+            "EndpointsConfig": { network.name.clone(): { "Aliases": network.aliases } },
+        });
+    }
+    if !bindings.is_empty() {
+        let exposed: BTreeMap<&String, Value> =
+            bindings.keys().map(|key| (key, json!({}))).collect();
+        body["ExposedPorts"] = json!(exposed);
+    }
+    if let Some(healthcheck) = cfg.healthcheck {
+        body["Healthcheck"] = json!(healthcheck.to_config());
+    }
+    if let Some(process) = cfg.process {
+        let fields = [
+            ("Cmd", process.command.as_ref().map(|cmd| json!(cmd))),
+            ("Entrypoint", process.entrypoint.as_ref().map(|e| json!(e))),
+            (
+                "WorkingDir",
+                process.working_dir.as_ref().map(|dir| json!(dir)),
+            ),
+            ("User", process.user.as_ref().map(|user| json!(user))),
+            (
+                "Hostname",
+                process.hostname.as_ref().map(|host| json!(host)),
+            ),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value {
+                body[field] = value;
+            }
+        }
+    }
+    body
+}
+
+/// Maps an archive error: a 404 is either the container or the path,
+/// and a 403 a read-only destination.
+pub(crate) fn map_archive_error(res: &Response, map_error: fn(&Response) -> Error) -> Error {
+    let message = res.error_message();
+    match res.status {
+        404 if !message.to_lowercase().contains("no such container") => Error::NotFound(message),
+        403 => Error::Conflict(message),
+        _ => map_error(res),
+    }
+}
+
+/// Maps a network error: a 404 is the network unless the message names a
+/// container, and a 403 a network in use or built in.
+fn map_network_error(res: &Response) -> Error {
+    let message = res.error_message();
+    match res.status {
+        404 if !message.to_lowercase().contains("no such container") => Error::NotFound(message),
+        403 => Error::Conflict(message),
+        _ => map_error(res),
+    }
+}
+
+/// Maps a volume error: a 404 is the volume, and a 409 a volume in use.
+fn map_volume_error(res: &Response) -> Error {
+    match res.status {
+        404 => Error::NotFound(res.error_message()),
+        _ => map_error(res),
+    }
+}
+
+/// Like [`map_error`], but a 404 is [`Error::ImageNotFound`].
+fn map_image_error(res: &Response) -> Error {
+    match res.status {
+        404 => Error::ImageNotFound,
+        _ => map_error(res),
+    }
+}
+
+/// Maps an engine error response onto the daemon error type.
+fn map_error(res: &Response) -> Error {
+    let message = res.error_message();
+    match res.status {
+        404 => Error::ContainerNotFound,
+        400 => Error::BadRequest(message),
+        409 => Error::Conflict(message),
+        _ => Error::Unexpected(message),
+    }
+}
@@ -0,0 +1,189 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use serde::Serialize;
+
+use crate::{
+    docker::{ContainerInspect, HealthStatus, HEALTH_RESTART_LABEL},
+    errors::Error,
+    services::{docker::DockerClient, locks::AppLocks, storage::now},
+};
+
+/// Default interval between two supervisor passes.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Restart of an unhealthy app by the supervisor, and why it happened.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HealthRestart {
+    /// Restart time, RFC 3339.
+    pub at: String,
+    /// Failed checks in a row that triggered the restart.
+    pub failing_streak: u32,
+    /// Exit code of the last failed check.
+    pub exit_code: Option<i64>,
+    /// Output of the last failed check.
+    pub output: Option<String>,
+    /// Restarts of the app by the supervisor since the daemon started.
+    pub count: u32,
+}
+
+/// Last health restart of each app, by name, kept in memory.
+#[derive(Default)]
+pub struct HealthRestarts {
+    restarts: Mutex<HashMap<String, HealthRestart>>,
+}
+
+impl HealthRestarts {
+    /// Last restart of `app` by the supervisor, if any.
+    pub fn last(&self, app: &str) -> Option<HealthRestart> {
+        self.restarts().get(app).cloned()
+    }
+
+    fn restarts(&self) -> MutexGuard<'_, HashMap<String, HealthRestart>> {
+        self.restarts.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    fn record(&self, app: &str, container: &ContainerInspect) -> HealthRestart {
+        let health = container.state.health.clone().unwrap_or_default();
+        let probe = health.log.iter().flatten().next_back();
+        let mut restarts = self.restarts();
+        let count = restarts.get(app).map_or(0, |last| last.count) + 1;
+        let restart = HealthRestart {
+            at: now(),
+            failing_streak: health.failing_streak,
+            exit_code: probe.map(|probe| probe.exit_code),
+            output: probe.map(|probe| probe.output.trim().to_string()),
+            count,
+        };
+        restarts.insert(app.to_string(), restart.clone());
+        restart
+    }
+}
+
+/// Restarts apps that stay unhealthy, which the engine never does itself.
+///
+/// Only apps carrying the [`HEALTH_RESTART_LABEL`] set at creation are
+/// supervised: an app is restarted once unhealthy with at least that many
+/// failed checks in a row. The restart takes the app lock, so it never races
+/// a user operation, and is recorded in [`HealthRestarts`].
+pub struct HealthSupervisor {
+    docker: Arc<dyn DockerClient>,
+    locks: Arc<AppLocks>,
+    restarts: Arc<HealthRestarts>,
+}
+
+impl HealthSupervisor {
+    pub fn new(
+        docker: Arc<dyn DockerClient>,
+        locks: Arc<AppLocks>,
+        restarts: Arc<HealthRestarts>,
+    ) -> Self {
+        Self {
+            docker,
+            locks,
+            restarts,
+        }
+    }
+
+    /// Reads the interval between two passes from `HEALTH_SUPERVISOR_INTERVAL_SECS`.
+    ///
+    /// Unset or invalid values keep the default.
+    pub fn interval_from_env() -> Duration {
+        match std::env::var("HEALTH_SUPERVISOR_INTERVAL_SECS") {
+            Ok(secs) => match secs.trim().parse::<u64>() {
+                Ok(secs) if secs > 0 => Duration::from_secs(secs),
+                _ => {
+                    tracing::warn!(
+                        "Invalid HEALTH_SUPERVISOR_INTERVAL_SECS: {secs}; using the default"
+                    );
+                    DEFAULT_INTERVAL
+                }
+            },
+            Err(_) => DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Runs a pass every `interval` in the background.
+    pub fn spawn(self, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.check().await {
+                    tracing::warn!(error = %e, "Health supervisor pass failed");
+                }
+            }
+        });
+    }
+
+    /// Restarts the supervised apps that are unhealthy past their threshold.
+    ///
+    /// # Returns
+    /// - `Ok(names)` of the apps restarted during this pass
+    /// - `Err(Error)` if the running containers could not be listed
+    pub async fn check(&self) -> Result<Vec<String>, Error> {
+        let mut restarted = Vec::new();
+        for container in self.docker.list(false).await? {
+            let Some(restart_after) = container
+                .labels
+                .get(HEALTH_RESTART_LABEL)
+                .and_then(|value| value.parse::<u32>().ok())
+                .filter(|checks| *checks > 0)
+            else {
+                continue;
+            };
+            match self.check_app(&container.name, restart_after).await {
+                Ok(true) => restarted.push(container.name),
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!(app = %container.name, error = %e, "Health restart failed")
+                }
+            }
+        }
+        Ok(restarted)
+    }
+
+    /// Restarts `name` if it failed `restart_after` checks in a row and is unhealthy.
+    async fn check_app(&self, name: &str, restart_after: u32) -> Result<bool, Error> {
+        if !due(&self.docker.inspect(name).await?, restart_after) {
+            return Ok(false);
+        }
+        let _lock = match self.locks.acquire(name, "restart").await {
+            Ok(lock) => lock,
+            // Busy with a user operation: checked again on the next pass.
+            Err(Error::Conflict(_)) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        // The app may have been restarted or recreated while waiting for the lock.
+        let container = self.docker.inspect(name).await?;
+        if !due(&container, restart_after) {
+            return Ok(false);
+        }
+
+        self.docker.restart(name, None).await?;
+        let restart = self.restarts.record(name, &container);
+        tracing::warn!(
+            app = name,
+            failing_streak = restart.failing_streak,
+            exit_code = ?restart.exit_code,
+            output = restart.output.as_deref().unwrap_or_default(),
+            "Restarted unhealthy app"
+        );
+        Ok(true)
+    }
+}
+
+/// `true` when a running container is unhealthy with `restart_after` failed checks in a row.
+fn due(container: &ContainerInspect, restart_after: u32) -> bool {
+    container.state.running
+        && HealthStatus::from_state(&container.state) == Some(HealthStatus::Unhealthy)
+        && container
+            .state
+            .health
+            .as_ref()
+            .is_some_and(|health| health.failing_streak >= restart_after)
+}
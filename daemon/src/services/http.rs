@@ -0,0 +1,426 @@
+use std::{fs::File, io::BufReader, path::PathBuf, sync::Arc};
+
+use bytes::Bytes;
+use futures_util::{stream, Stream};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Incoming, header, upgrade::Upgraded, Request, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    rustls::{self, pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+
+#[cfg(unix)]
+use tokio::net::UnixStream;
+
+use crate::{
+    errors::Error,
+    services::endpoint::{DockerEndpoint, TlsFiles},
+};
+
+/// Default location of the Docker engine socket.
+pub const DEFAULT_DOCKER_SOCKET: &str = "/var/run/docker.sock";
+
+/// Address of a container engine API.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    /// Unix domain socket path (`unix:///var/run/docker.sock`).
+    Unix(PathBuf),
+    /// TCP `host:port` (`tcp://`, `http://` or `https://`).
+    Tcp(String),
+}
+
+impl Endpoint {
+    /// Parses a `DOCKER_HOST`-style address.
+    ///
+    /// See [`DockerEndpoint::parse`] for the accepted schemes.
+    ///
+    /// # Examples
+    /// ```
+    /// use lightshuttle_core::services::http::Endpoint;
+    ///
+    /// let ep = Endpoint::parse("tcp://docker-proxy:2375").unwrap();
+    /// assert_eq!(ep, Endpoint::Tcp("docker-proxy:2375".into()));
+    /// ```
+    pub fn parse(host: &str) -> Result<Self, Error> {
+        DockerEndpoint::parse("env", host, None).map(|ep| ep.address)
+    }
+}
+
+/// Address of an engine plus the TLS settings used to reach it.
+#[derive(Clone)]
+pub struct Transport {
+    endpoint: Endpoint,
+    tls: Option<Tls>,
+}
+
+#[derive(Clone)]
+struct Tls {
+    connector: TlsConnector,
+    server_name: ServerName<'static>,
+}
+
+impl Transport {
+    /// Plain transport, without TLS.
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self {
+            endpoint,
+            tls: None,
+        }
+    }
+
+    /// Builds the transport for a resolved endpoint, loading its certificates.
+    pub fn connect_to(endpoint: &DockerEndpoint) -> Result<Self, Error> {
+        let tls = match (&endpoint.tls, &endpoint.address) {
+            (Some(files), Endpoint::Tcp(addr)) => Some(Tls {
+                connector: TlsConnector::from(Arc::new(client_config(files)?)),
+                server_name: server_name(addr)?,
+            }),
+            _ => None,
+        };
+        Ok(Self {
+            endpoint: endpoint.address.clone(),
+            tls,
+        })
+    }
+
+    /// Returns the address this transport connects to.
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+}
+
+fn tls_error(path: &std::path::Path, reason: impl std::fmt::Display) -> Error {
+    Error::InvalidRequest(format!("Invalid TLS file {}: {reason}", path.display()))
+}
+
+/// Builds a rustls client config trusting `ca.pem` and presenting the client certificate.
+fn client_config(files: &TlsFiles) -> Result<ClientConfig, Error> {
+    let mut roots = RootCertStore::empty();
+    for cert in read_certs(&files.ca)? {
+        roots.add(cert).map_err(|e| tls_error(&files.ca, e))?;
+    }
+
+    let builder =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| Error::Unexpected(e.to_string()))?
+            .with_root_certificates(roots);
+
+    match &files.client {
+        Some((cert, key)) => {
+            let chain = read_certs(cert)?;
+            let mut reader = BufReader::new(File::open(key).map_err(|e| tls_error(key, e))?);
+            let key_der = rustls_pemfile::private_key(&mut reader)
+                .map_err(|e| tls_error(key, e))?
+                .ok_or_else(|| tls_error(key, "no private key found"))?;
+            builder
+                .with_client_auth_cert(chain, key_der)
+                .map_err(|e| tls_error(cert, e))
+        }
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+fn read_certs(
+    path: &std::path::Path,
+) -> Result<Vec<rustls::pki_types::CertificateDer<'static>>, Error> {
+    let mut reader = BufReader::new(File::open(path).map_err(|e| tls_error(path, e))?);
+    let certs = rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| tls_error(path, e))?;
+    if certs.is_empty() {
+        return Err(tls_error(path, "no certificate found"));
+    }
+    Ok(certs)
+}
+
+/// Host part of a `host:port` address, used for certificate verification.
+fn server_name(addr: &str) -> Result<ServerName<'static>, Error> {
+    let host = addr
+        .rsplit_once(':')
+        .map_or(addr, |(host, _)| host)
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    ServerName::try_from(host.to_string())
+        .map_err(|e| Error::InvalidRequest(format!("Invalid TLS server name '{host}': {e}")))
+}
+
+/// Response returned by the engine.
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Returns `true` for 2xx status codes.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Extracts the `message` field of an engine error body, falling back to the raw text.
+    pub fn error_message(&self) -> String {
+        serde_json::from_slice::<serde_json::Value>(&self.body)
+            .ok()
+            .and_then(|v| v["message"].as_str().map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(&self.body).trim().to_string())
+    }
+}
+
+/// Sends a single HTTP/1.1 request and collects the whole response.
+///
+/// Each call opens a fresh connection; the connection task ends once the
+/// response body has been read.
+pub async fn request(
+    transport: &Transport,
+    method: &str,
+    path: &str,
+    body: Option<Vec<u8>>,
+) -> Result<Response, Error> {
+    stream(transport, method, path, body).await?.collect().await
+}
+
+/// Streaming response body, read chunk by chunk or line by line.
+pub struct BodyStream {
+    pub status: u16,
+    body: Incoming,
+    buf: Vec<u8>,
+}
+
+impl BodyStream {
+    /// Returns `true` for 2xx status codes.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Next chunk of data, or `None` once the body is complete.
+    pub async fn next_chunk(&mut self) -> Result<Option<Bytes>, Error> {
+        if !self.buf.is_empty() {
+            return Ok(Some(Bytes::from(std::mem::take(&mut self.buf))));
+        }
+        loop {
+            match self.body.frame().await {
+                None => return Ok(None),
+                Some(Err(_)) => return Err(Error::DockerCommandFailed),
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        return Ok(Some(data));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Next newline-delimited line (without the newline), or `None` at the end.
+    pub async fn next_line(&mut self) -> Result<Option<String>, Error> {
+        loop {
+            if let Some(pos) = self.buf.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buf.drain(..=pos).collect();
+                return Ok(Some(String::from_utf8_lossy(&line).trim_end().to_string()));
+            }
+            match self.body.frame().await {
+                None if self.buf.is_empty() => return Ok(None),
+                None => {
+                    let line = std::mem::take(&mut self.buf);
+                    return Ok(Some(String::from_utf8_lossy(&line).trim_end().to_string()));
+                }
+                Some(Err(_)) => return Err(Error::DockerCommandFailed),
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        self.buf.extend_from_slice(&data);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remaining chunks of the body as a [`Stream`], for forwarding without buffering.
+    pub fn into_chunks(self) -> impl Stream<Item = Result<Bytes, Error>> + Send {
+        stream::unfold(self, |mut body| async move {
+            body.next_chunk()
+                .await
+                .transpose()
+                .map(|chunk| (chunk, body))
+        })
+    }
+
+    /// Reads the rest of the body.
+    pub async fn collect(mut self) -> Result<Response, Error> {
+        let mut body = std::mem::take(&mut self.buf);
+        let rest = self
+            .body
+            .collect()
+            .await
+            .map_err(|_| Error::DockerCommandFailed)?
+            .to_bytes();
+        body.extend_from_slice(&rest);
+        Ok(Response {
+            status: self.status,
+            body,
+        })
+    }
+}
+
+/// Sends a single HTTP/1.1 request and returns as soon as the headers arrive.
+///
+/// Used for long-lived responses such as the events stream; dropping the
+/// stream closes the connection.
+pub async fn stream(
+    transport: &Transport,
+    method: &str,
+    path: &str,
+    body: Option<Vec<u8>>,
+) -> Result<BodyStream, Error> {
+    stream_with(transport, method, path, &[], body).await
+}
+
+/// Like [`stream`], with extra request headers such as `X-Registry-Auth`.
+///
+/// A `Content-Type` given here replaces the default `application/json`.
+pub async fn stream_with(
+    transport: &Transport,
+    method: &str,
+    path: &str,
+    headers: &[(&str, String)],
+    body: Option<Vec<u8>>,
+) -> Result<BodyStream, Error> {
+    let req = build_request(method, path, headers, body, false)?;
+    let res = dispatch(transport, req).await?;
+
+    Ok(BodyStream {
+        status: res.status().as_u16(),
+        body: res.into_body(),
+        buf: Vec::new(),
+    })
+}
+
+/// Raw bidirectional stream of a connection hijacked by the engine.
+pub type RawStream = TokioIo<Upgraded>;
+
+/// Sends a request asking the engine to hijack the connection (`Upgrade: tcp`),
+/// as exec and attach do for interactive sessions.
+///
+/// Returns the raw stream on `101 Switching Protocols`, or the engine's
+/// response when it refused the upgrade.
+pub async fn upgrade(
+    transport: &Transport,
+    method: &str,
+    path: &str,
+    body: Option<Vec<u8>>,
+) -> Result<Result<RawStream, Response>, Error> {
+    let req = build_request(method, path, &[], body, true)?;
+    let res = dispatch(transport, req).await?;
+    if res.status() != StatusCode::SWITCHING_PROTOCOLS {
+        let refused = BodyStream {
+            status: res.status().as_u16(),
+            body: res.into_body(),
+            buf: Vec::new(),
+        };
+        return Ok(Err(refused.collect().await?));
+    }
+
+    let upgraded = hyper::upgrade::on(res)
+        .await
+        .map_err(|_| Error::DockerCommandFailed)?;
+    Ok(Ok(TokioIo::new(upgraded)))
+}
+
+fn build_request(
+    method: &str,
+    path: &str,
+    headers: &[(&str, String)],
+    body: Option<Vec<u8>>,
+    upgrade: bool,
+) -> Result<Request<Full<Bytes>>, Error> {
+    let mut req = Request::builder()
+        .method(method)
+        .uri(path)
+        .header(header::HOST, "docker");
+    if !headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+    {
+        req = req.header(header::CONTENT_TYPE, "application/json");
+    }
+    for (name, value) in headers {
+        req = req.header(*name, value);
+    }
+    if upgrade {
+        req = req
+            .header(header::CONNECTION, "Upgrade")
+            .header(header::UPGRADE, "tcp");
+    }
+    req.body(Full::new(Bytes::from(body.unwrap_or_default())))
+        .map_err(|e| Error::Unexpected(e.to_string()))
+}
+
+async fn dispatch(
+    transport: &Transport,
+    req: Request<Full<Bytes>>,
+) -> Result<hyper::Response<Incoming>, Error> {
+    match &transport.endpoint {
+        #[cfg(unix)]
+        Endpoint::Unix(path) => {
+            let stream = UnixStream::connect(path)
+                .await
+                .map_err(|_| Error::DockerCommandFailed)?;
+            send(stream, req).await
+        }
+        #[cfg(not(unix))]
+        Endpoint::Unix(_) => Err(Error::DockerCommandFailed),
+        Endpoint::Tcp(addr) => {
+            let stream = TcpStream::connect(addr)
+                .await
+                .map_err(|_| Error::DockerCommandFailed)?;
+            match &transport.tls {
+                Some(tls) => {
+                    let stream = tls
+                        .connector
+                        .connect(tls.server_name.clone(), stream)
+                        .await
+                        .map_err(|e| {
+                            tracing::warn!(error = %e, "TLS handshake with Docker endpoint failed");
+                            Error::DockerCommandFailed
+                        })?;
+                    send(stream, req).await
+                }
+                None => send(stream, req).await,
+            }
+        }
+    }
+}
+
+async fn send<S>(stream: S, req: Request<Full<Bytes>>) -> Result<hyper::Response<Incoming>, Error>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .map_err(|_| Error::DockerCommandFailed)?;
+    tokio::spawn(async move {
+        if let Err(e) = conn.with_upgrades().await {
+            tracing::debug!(error = %e, "Docker API connection closed with error");
+        }
+    });
+    sender
+        .send_request(req)
+        .await
+        .map_err(|_| Error::DockerCommandFailed)
+}
+
+/// Percent-encodes a value for use in a query string.
+pub fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
@@ -0,0 +1,143 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+use crate::{errors::Error, services::storage::now};
+
+/// Time a mutation waits for the app by default before giving up.
+pub const DEFAULT_WAIT: Duration = Duration::from_secs(30);
+
+/// Operation holding the lock of an app.
+#[derive(Clone, Debug)]
+struct InFlight {
+    operation: &'static str,
+    /// Start time, RFC 3339.
+    since: String,
+}
+
+#[derive(Default)]
+struct Slot {
+    lock: Arc<AsyncMutex<()>>,
+    current: Mutex<Option<InFlight>>,
+}
+
+/// Locks serializing the mutations of each app, by name.
+///
+/// Concurrent operations on the same app queue up in arrival order, so that
+/// a recreate never races another one on the container name. An operation
+/// still queued after the wait limit fails with [`Error::Conflict`]
+/// describing the operation in flight. Reads never take a lock.
+pub struct AppLocks {
+    wait: Duration,
+    slots: Mutex<HashMap<String, Arc<Slot>>>,
+}
+
+impl Default for AppLocks {
+    fn default() -> Self {
+        Self::new(DEFAULT_WAIT)
+    }
+}
+
+impl AppLocks {
+    /// Locks giving up after `wait`; a zero wait fails at once on a busy app.
+    pub fn new(wait: Duration) -> Self {
+        Self {
+            wait,
+            slots: Mutex::default(),
+        }
+    }
+
+    /// Reads the wait limit from `APP_LOCK_WAIT_SECS`, in seconds.
+    ///
+    /// Unset or invalid values keep the default.
+    pub fn from_env() -> Self {
+        let wait = match std::env::var("APP_LOCK_WAIT_SECS") {
+            Ok(secs) => secs
+                .trim()
+                .parse::<u64>()
+                .map(Duration::from_secs)
+                .unwrap_or_else(|_| {
+                    tracing::warn!("Invalid APP_LOCK_WAIT_SECS: {secs}; using the default");
+                    DEFAULT_WAIT
+                }),
+            Err(_) => DEFAULT_WAIT,
+        };
+        Self::new(wait)
+    }
+
+    /// Waits for the lock of `app` to run `operation`, released with the guard.
+    pub async fn acquire(&self, app: &str, operation: &'static str) -> Result<AppLock<'_>, Error> {
+        let slot = self.slots().entry(app.to_string()).or_default().clone();
+        let lock = slot.lock.clone().lock_owned();
+
+        let guard = match tokio::time::timeout(self.wait, lock).await {
+            Ok(guard) => guard,
+            Err(_) => {
+                let current = slot
+                    .current
+                    .lock()
+                    .unwrap_or_else(|p| p.into_inner())
+                    .clone();
+                let busy = match current {
+                    Some(InFlight {
+                        operation: running,
+                        since,
+                    }) => {
+                        format!("App {app} is busy: {running} in progress since {since}")
+                    }
+                    None => format!("App {app} is busy"),
+                };
+                drop(slot);
+                self.release(app);
+                tracing::info!(app, operation, "Gave up waiting for the app lock");
+                return Err(Error::Conflict(busy));
+            }
+        };
+        *slot.current.lock().unwrap_or_else(|p| p.into_inner()) = Some(InFlight {
+            operation,
+            since: now(),
+        });
+        Ok(AppLock {
+            locks: self,
+            app: app.to_string(),
+            slot: Some(slot),
+            _guard: guard,
+        })
+    }
+
+    fn slots(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<Slot>>> {
+        self.slots.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Forgets the slot of `app` once nobody holds or waits for it.
+    fn release(&self, app: &str) {
+        let mut slots = self.slots();
+        if slots
+            .get(app)
+            .is_some_and(|slot| Arc::strong_count(slot) == 1)
+        {
+            slots.remove(app);
+        }
+    }
+}
+
+/// Lock of an app, held for the duration of a mutation.
+pub struct AppLock<'a> {
+    locks: &'a AppLocks,
+    app: String,
+    slot: Option<Arc<Slot>>,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl Drop for AppLock<'_> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot.take() {
+            *slot.current.lock().unwrap_or_else(|p| p.into_inner()) = None;
+        }
+        self.locks.release(&self.app);
+    }
+}
@@ -0,0 +1,1513 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::stream;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use crate::{
+    docker::{
+        canonical_reference, validate_container_name, validate_signal, volume_name, BuildOptions,
+        ContainerConfig, ContainerEvent, ContainerHealth, ContainerInspect, ContainerInspectConfig,
+        ContainerState, ContainerStats, ContainerSummary, EndpointSettings, EngineInfo, ExecConfig,
+        ExecOutput, HealthCheck, HealthProbe, HostConfig, ImageInspect, ImageInspectConfig,
+        ImageSummary, NetworkAttachment, NetworkConfig, NetworkInfo, NetworkSettings, PortBinding,
+        PortMapping, ProcessOverrides, PruneReport, PullProgress, RegistryAuth, ResourceLimits,
+        RestartPolicy, SecurityOptions, VolumeConfig, VolumeInfo, VolumeUsage, WaitCondition,
+        DEFAULT_HEALTH_RETRIES, DEFAULT_NETWORKS, MIN_API_VERSION,
+    },
+    errors::Error,
+    services::{
+        docker::{ArchiveStream, BackendInfo, DockerClient, ExecInput, ExecSession},
+        engine::rfc3339,
+        seccomp::SeccompProfile,
+        storage::now,
+    },
+};
+
+/// Container tracked by the in-memory backend.
+#[derive(Clone, Debug)]
+struct FakeContainer {
+    id: String,
+    image: String,
+    ports: Vec<PortMapping>,
+    labels: HashMap<String, String>,
+    env: HashMap<String, String>,
+    volumes: Vec<String>,
+    restart_policy: Option<String>,
+    /// `docker run --rm` semantics: removed once stopped.
+    auto_remove: bool,
+    state: String,
+    exit_code: i64,
+    oom_killed: bool,
+    /// Last exit time, RFC 3339.
+    finished_at: Option<String>,
+    logs: Vec<String>,
+    stats: ContainerStats,
+    execs: Vec<ExecConfig>,
+    resizes: Vec<(u16, u16)>,
+    /// Regular files of the container filesystem, by absolute path.
+    files: BTreeMap<String, Vec<u8>>,
+    /// User-defined networks, by name; the default bridge when empty.
+    networks: Vec<NetworkAttachment>,
+    /// Signals sent with `kill`, oldest first.
+    signals: Vec<String>,
+    limits: ResourceLimits,
+    healthcheck: Option<HealthCheck>,
+    health: Option<ContainerHealth>,
+    process: ProcessOverrides,
+    /// `CMD` of the image when the container was created.
+    image_command: Option<Vec<String>>,
+    security: SecurityOptions,
+    /// Security options as the engine reports them, seccomp profile included.
+    security_opt: Vec<String>,
+}
+
+impl FakeContainer {
+    /// Back to `starting` after a (re)start, as with a real engine.
+    fn reset_health(&mut self) {
+        if self.healthcheck.is_some() {
+            self.health = Some(ContainerHealth {
+                status: "starting".to_string(),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// Docker client that simulates the container lifecycle in memory.
+///
+/// Containers keep their names, states, ports, labels, env, volumes, logs,
+/// restart policy, resource limits and health, images are tracked with the containers using them,
+/// and every container change is published on the events stream,
+/// so the whole router can be exercised without a Docker host. Only
+/// available with the `testing` feature.
+///
+/// # Examples
+/// ```
+/// use lightshuttle_core::services::memory::InMemoryDockerClient;
+///
+/// let docker = InMemoryDockerClient::new();
+/// docker.insert("web", "nginx:latest", "running");
+/// docker.exit("web", 1);
+/// assert_eq!(docker.state("web").as_deref(), Some("exited"));
+/// ```
+pub struct InMemoryDockerClient {
+    containers: Arc<Mutex<HashMap<String, FakeContainer>>>,
+    images: Mutex<Vec<ImageSummary>>,
+    /// Default `CMD` of images, by image id.
+    image_commands: Mutex<HashMap<String, Vec<String>>>,
+    /// References whose pull fails as if the registry did not know them.
+    missing: Mutex<Vec<String>>,
+    pulls: Mutex<Vec<(String, Option<RegistryAuth>)>>,
+    builds: Mutex<Vec<(BuildOptions, Vec<RegistryAuth>)>>,
+    networks: Mutex<Vec<NetworkInfo>>,
+    /// Named volumes with their size on disk, by name.
+    volumes: Mutex<BTreeMap<String, (VolumeInfo, u64)>>,
+    engine: Mutex<EngineInfo>,
+    next_id: Mutex<u64>,
+    latency: Duration,
+    seccomp: SeccompProfile,
+    events: broadcast::Sender<ContainerEvent>,
+}
+
+impl Default for InMemoryDockerClient {
+    fn default() -> Self {
+        Self {
+            containers: Arc::default(),
+            images: Mutex::default(),
+            image_commands: Mutex::default(),
+            missing: Mutex::default(),
+            pulls: Mutex::default(),
+            builds: Mutex::default(),
+            networks: Mutex::new(vec![NetworkInfo {
+                id: format!("{:064x}", 0),
+                name: "bridge".to_string(),
+                driver: "bridge".to_string(),
+                subnet: Some("172.17.0.0/16".to_string()),
+                internal: false,
+                created: rfc3339(0),
+            }]),
+            volumes: Mutex::default(),
+            engine: Mutex::new(EngineInfo {
+                version: "memory".to_string(),
+                api_version: MIN_API_VERSION.to_string(),
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+                storage_driver: "memory".to_string(),
+                cgroup_version: Some("2".to_string()),
+                rootless: false,
+                security_options: vec!["seccomp".to_string()],
+            }),
+            next_id: Mutex::default(),
+            latency: Duration::ZERO,
+            seccomp: SeccompProfile::default(),
+            events: broadcast::channel(1024).0,
+        }
+    }
+}
+
+impl InMemoryDockerClient {
+    /// Creates an empty backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delays every Docker operation by `latency`, simulating a slow engine.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Applies `profile` to every container created, reported as the engine does.
+    pub fn with_seccomp(mut self, profile: SeccompProfile) -> Self {
+        self.seccomp = profile;
+        self
+    }
+
+    async fn delay(&self) {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+    }
+
+    fn containers(&self) -> MutexGuard<'_, HashMap<String, FakeContainer>> {
+        self.containers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn images(&self) -> MutexGuard<'_, Vec<ImageSummary>> {
+        self.images.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    fn emit(&self, id: &str, name: &str, action: &str) {
+        // No subscriber is not an error.
+        let _ = self.events.send(ContainerEvent {
+            id: id.to_string(),
+            name: name.to_string(),
+            action: action.to_string(),
+            ..Default::default()
+        });
+    }
+
+    /// Records the exit of the main process and emits `die`.
+    fn die(&self, c: &mut FakeContainer, name: &str) {
+        c.finished_at = Some(now());
+        let _ = self.events.send(ContainerEvent {
+            id: c.id.clone(),
+            name: name.to_string(),
+            action: "die".to_string(),
+            exit_code: Some(c.exit_code),
+            ..Default::default()
+        });
+    }
+
+    fn next_id(&self) -> String {
+        let mut id = self.next_id.lock().unwrap_or_else(|p| p.into_inner());
+        *id += 1;
+        format!("{:064x}", *id)
+    }
+
+    /// Registers an image under the given tags and returns its id.
+    ///
+    /// An image without tags is dangling. A tag already carried by another
+    /// image moves to the new one, as it would after a rebuild.
+    pub fn add_image(&self, tags: &[&str], size: u64) -> String {
+        let id = format!("sha256:{}", self.next_id());
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let mut images = self.images();
+        for image in images.iter_mut() {
+            image.tags.retain(|tag| {
+                !tags
+                    .iter()
+                    .any(|t| canonical_reference(t) == canonical_reference(tag))
+            });
+        }
+        images.push(ImageSummary {
+            id: id.clone(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            size,
+            created: rfc3339(now),
+        });
+        id
+    }
+
+    /// Sets the default `CMD` of the image `reference`, used by containers created from now on.
+    pub fn set_image_command(&self, reference: &str, cmd: &[&str]) {
+        let Some(id) = self
+            .images()
+            .iter()
+            .find(|image| image.matches(reference))
+            .map(|image| image.id.clone())
+        else {
+            return;
+        };
+        self.image_commands
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(id, cmd.iter().map(|arg| arg.to_string()).collect());
+    }
+
+    fn image_command(&self, reference: &str) -> Option<Vec<String>> {
+        let id = self
+            .images()
+            .iter()
+            .find(|image| image.matches(reference))
+            .map(|image| image.id.clone())?;
+        self.image_commands
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&id)
+            .cloned()
+    }
+
+    /// Makes pulls of `reference` fail as if the registry did not know it.
+    pub fn mark_missing(&self, reference: &str) {
+        self.missing
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .push(canonical_reference(reference));
+    }
+
+    /// References pulled so far, oldest first, with the credentials presented.
+    pub fn pulls(&self) -> Vec<(String, Option<RegistryAuth>)> {
+        self.pulls.lock().unwrap_or_else(|p| p.into_inner()).clone()
+    }
+
+    /// Builds run so far, oldest first, with the credentials presented.
+    pub fn builds(&self) -> Vec<(BuildOptions, Vec<RegistryAuth>)> {
+        self.builds
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    fn networks(&self) -> MutexGuard<'_, Vec<NetworkInfo>> {
+        self.networks.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Network by name or id, failing like Docker when it doesn't exist.
+    fn network(&self, name: &str) -> Result<NetworkInfo, Error> {
+        self.networks()
+            .iter()
+            .find(|n| n.name == name || n.id == name)
+            .cloned()
+            .ok_or_else(|| Error::NotFound(format!("network {name} not found")))
+    }
+
+    fn volumes(&self) -> MutexGuard<'_, BTreeMap<String, (VolumeInfo, u64)>> {
+        self.volumes.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Sets the size on disk reported for a named volume.
+    pub fn set_volume_size(&self, name: &str, size: u64) {
+        if let Some((_, current)) = self.volumes().get_mut(name) {
+            *current = size;
+        }
+    }
+
+    /// Creates the volume unless it exists, as Docker does on create and on first mount.
+    fn ensure_volume(&self, cfg: &VolumeConfig) -> VolumeInfo {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let info = VolumeInfo {
+            name: cfg.name.clone(),
+            driver: cfg.driver.clone().unwrap_or_else(|| "local".to_string()),
+            mountpoint: format!("/var/lib/docker/volumes/{}/_data", cfg.name),
+            created: rfc3339(now),
+            labels: Some(cfg.labels.clone()),
+            options: Some(cfg.driver_opts.clone()),
+        };
+        self.volumes()
+            .entry(cfg.name.clone())
+            .or_insert((info, 0))
+            .0
+            .clone()
+    }
+
+    /// Registers `reference` unless an image already matches it, like an implicit pull.
+    fn ensure_image(&self, reference: &str) {
+        if !self.images().iter().any(|image| image.matches(reference)) {
+            self.add_image(&[reference], 0);
+        }
+    }
+
+    /// Registers an existing container in the given state (`created`, `running`, `exited`, ...).
+    ///
+    /// Unlike containers started through `run`, it is not auto-removed when it stops.
+    pub fn insert(&self, name: &str, image: &str, state: &str) {
+        self.ensure_image(image);
+        let id = self.next_id();
+        self.containers().insert(
+            name.to_string(),
+            FakeContainer {
+                id: id.clone(),
+                image: image.to_string(),
+                ports: vec![],
+                labels: HashMap::new(),
+                env: HashMap::new(),
+                volumes: vec![],
+                restart_policy: None,
+                auto_remove: false,
+                state: state.to_string(),
+                exit_code: 0,
+                oom_killed: false,
+                finished_at: None,
+                logs: vec![],
+                stats: ContainerStats::default(),
+                execs: vec![],
+                resizes: vec![],
+                files: BTreeMap::new(),
+                networks: vec![],
+                signals: vec![],
+                limits: ResourceLimits::default(),
+                healthcheck: None,
+                health: None,
+                process: ProcessOverrides::default(),
+                image_command: None,
+                security: SecurityOptions::default(),
+                security_opt: vec![],
+            },
+        );
+        self.emit(&id, name, "create");
+    }
+
+    /// Returns the current state of a container, if it exists.
+    pub fn state(&self, name: &str) -> Option<String> {
+        self.containers().get(name).map(|c| c.state.clone())
+    }
+
+    /// Returns the names of all known containers, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.containers().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Appends a line to the logs of a container.
+    pub fn push_log(&self, name: &str, line: &str) {
+        if let Some(c) = self.containers().get_mut(name) {
+            c.logs.push(line.to_string());
+        }
+    }
+
+    /// Sets the engine version and capabilities the backend reports.
+    pub fn set_engine_info(&self, info: EngineInfo) {
+        *self.engine.lock().unwrap_or_else(|p| p.into_inner()) = info;
+    }
+
+    /// Sets the resource usage reported for a container.
+    pub fn set_stats(&self, name: &str, stats: ContainerStats) {
+        if let Some(c) = self.containers().get_mut(name) {
+            c.stats = stats;
+        }
+    }
+
+    /// Signals sent to a container, oldest first.
+    pub fn signals(&self, name: &str) -> Vec<String> {
+        self.containers()
+            .get(name)
+            .map(|c| c.signals.clone())
+            .unwrap_or_default()
+    }
+
+    /// Commands executed in a container, oldest first.
+    pub fn execs(&self, name: &str) -> Vec<ExecConfig> {
+        self.containers()
+            .get(name)
+            .map(|c| c.execs.clone())
+            .unwrap_or_default()
+    }
+
+    /// Terminal sizes (`cols`, `rows`) requested by exec sessions of a container.
+    pub fn resizes(&self, name: &str) -> Vec<(u16, u16)> {
+        self.containers()
+            .get(name)
+            .map(|c| c.resizes.clone())
+            .unwrap_or_default()
+    }
+
+    /// Writes a file into the filesystem of a container.
+    pub fn put_file(&self, name: &str, path: &str, contents: &[u8]) {
+        if let Some(c) = self.containers().get_mut(name) {
+            c.files.insert(normalize_path(path), contents.to_vec());
+        }
+    }
+
+    /// Contents of a file of a container, if it exists.
+    pub fn file(&self, name: &str, path: &str) -> Option<Vec<u8>> {
+        self.containers()
+            .get(name)
+            .and_then(|c| c.files.get(&normalize_path(path)).cloned())
+    }
+
+    /// Records an exec, failing like Docker when the container is not running.
+    fn record_exec(&self, name: &str, cfg: &ExecConfig) -> Result<(), Error> {
+        cfg.validate()?;
+        let mut containers = self.containers();
+        let c = containers.get_mut(name).ok_or(Error::ContainerNotFound)?;
+        if c.state != "running" {
+            return Err(Error::Conflict(format!("Container {name} is not running")));
+        }
+        c.execs.push(cfg.clone());
+        Ok(())
+    }
+
+    /// Simulates a run of the health check of a running container.
+    ///
+    /// A zero `exit_code` makes it healthy; `retries` failures in a row make
+    /// it unhealthy. Status changes are published as `health_status: ...` events.
+    pub fn probe(&self, name: &str, exit_code: i64, output: &str) {
+        let mut containers = self.containers();
+        let Some(c) = containers.get_mut(name) else {
+            return;
+        };
+        let Some(retries) = c
+            .healthcheck
+            .as_ref()
+            .map(|check| check.retries.unwrap_or(DEFAULT_HEALTH_RETRIES))
+        else {
+            return;
+        };
+        let health = c.health.get_or_insert_with(ContainerHealth::default);
+        let log = health.log.get_or_insert_with(Vec::new);
+        let at = now();
+        log.push(HealthProbe {
+            start: at.clone(),
+            end: at,
+            exit_code,
+            output: output.to_string(),
+        });
+        // The engine keeps the last five results.
+        if log.len() > 5 {
+            log.remove(0);
+        }
+
+        let previous = std::mem::take(&mut health.status);
+        if exit_code == 0 {
+            health.failing_streak = 0;
+            health.status = "healthy".to_string();
+        } else {
+            health.failing_streak += 1;
+            health.status = if health.failing_streak >= retries {
+                "unhealthy".to_string()
+            } else if previous.is_empty() {
+                "starting".to_string()
+            } else {
+                previous.clone()
+            };
+        }
+        if health.status != previous {
+            let action = format!("health_status: {}", health.status);
+            self.emit(&c.id, name, &action);
+        }
+    }
+
+    /// Simulates the kernel killing a container that ran out of memory.
+    pub fn oom_kill(&self, name: &str) {
+        if let Some(c) = self.containers().get_mut(name) {
+            c.oom_killed = true;
+        }
+        self.exit(name, 137);
+    }
+
+    /// Simulates the main process of a container exiting with `code`.
+    ///
+    /// The restart policy decides whether the container comes back up,
+    /// stays exited or is removed.
+    pub fn exit(&self, name: &str, code: i64) {
+        let mut containers = self.containers();
+        let Some(c) = containers.get_mut(name) else {
+            return;
+        };
+        c.exit_code = code;
+        let id = c.id.clone();
+        let restart = match c.restart_policy.as_deref() {
+            Some("always") | Some("unless-stopped") => true,
+            Some("on-failure") => code != 0,
+            _ => false,
+        };
+        self.die(c, name);
+        if restart {
+            c.state = "running".to_string();
+            c.reset_health();
+            self.emit(&id, name, "start");
+        } else if c.auto_remove {
+            containers.remove(name);
+            self.emit(&id, name, "destroy");
+        } else {
+            c.state = "exited".to_string();
+        }
+    }
+}
+
+#[async_trait]
+impl DockerClient for InMemoryDockerClient {
+    fn describe(&self) -> BackendInfo {
+        BackendInfo {
+            backend: "memory".to_string(),
+            endpoint: None,
+        }
+    }
+
+    async fn engine_info(&self) -> Result<EngineInfo, Error> {
+        self.delay().await;
+        Ok(self
+            .engine
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone())
+    }
+
+    async fn run(&self, cfg: ContainerConfig<'_>) -> Result<String, Error> {
+        cfg.validate()?;
+        self.delay().await;
+
+        if self.containers().contains_key(cfg.name) {
+            return Err(Error::Conflict(format!(
+                "The container name \"/{}\" is already in use",
+                cfg.name
+            )));
+        }
+        // Attachments are recorded by network name, as the engine reports them.
+        let networks = cfg
+            .networks
+            .iter()
+            .map(|attachment| {
+                let network = self.network(&attachment.name)?;
+                Ok(NetworkAttachment {
+                    name: network.name,
+                    aliases: attachment.aliases.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.ensure_image(cfg.image);
+        for mount in cfg.volumes.into_iter().flatten() {
+            if let Some(volume) = volume_name(mount) {
+                self.ensure_volume(&VolumeConfig {
+                    name: volume.to_string(),
+                    ..Default::default()
+                });
+            }
+        }
+        let id = self.next_id();
+        let security = cfg.security.cloned().unwrap_or_default();
+        let image_command = self.image_command(cfg.image);
+        let mut containers = self.containers();
+        if containers.contains_key(cfg.name) {
+            return Err(Error::Conflict(format!(
+                "The container name \"/{}\" is already in use",
+                cfg.name
+            )));
+        }
+
+        containers.insert(
+            cfg.name.to_string(),
+            FakeContainer {
+                id: id.clone(),
+                image: cfg.image.to_string(),
+                ports: cfg.ports.to_vec(),
+                labels: cfg.labels.cloned().unwrap_or_default(),
+                env: cfg.env.cloned().unwrap_or_default(),
+                volumes: cfg.volumes.cloned().unwrap_or_default(),
+                restart_policy: cfg.restart_policy.map(str::to_string),
+                auto_remove: matches!(cfg.restart_policy, None | Some("no")),
+                state: "running".to_string(),
+                exit_code: 0,
+                oom_killed: false,
+                finished_at: None,
+                logs: vec![],
+                stats: ContainerStats::default(),
+                execs: vec![],
+                resizes: vec![],
+                files: BTreeMap::new(),
+                networks,
+                signals: vec![],
+                limits: cfg.limits.cloned().unwrap_or_default(),
+                healthcheck: cfg.healthcheck.cloned(),
+                health: None,
+                process: cfg.process.cloned().unwrap_or_default(),
+                image_command,
+                security_opt: self
+                    .seccomp
+                    .engine_option()
+                    .into_iter()
+                    .chain(
+                        security
+                            .no_new_privileges
+                            .then(|| "no-new-privileges".to_string()),
+                    )
+                    .chain(security.apparmor.iter().map(|p| format!("apparmor={p}")))
+                    .collect(),
+                security,
+            },
+        );
+        if let Some(c) = containers.get_mut(cfg.name) {
+            c.reset_health();
+        }
+        self.emit(&id, cfg.name, "create");
+        self.emit(&id, cfg.name, "start");
+        Ok(id)
+    }
+
+    async fn start(&self, name: &str) -> Result<(), Error> {
+        self.delay().await;
+        let mut containers = self.containers();
+        let c = containers.get_mut(name).ok_or(Error::ContainerNotFound)?;
+        c.state = "running".to_string();
+        c.reset_health();
+        self.emit(&c.id, name, "start");
+        Ok(())
+    }
+
+    async fn stop(&self, name: &str) -> Result<(), Error> {
+        self.delay().await;
+        let mut containers = self.containers();
+        let c = containers.get_mut(name).ok_or(Error::ContainerNotFound)?;
+        let id = c.id.clone();
+        self.die(c, name);
+        self.emit(&id, name, "stop");
+        if c.auto_remove {
+            containers.remove(name);
+            self.emit(&id, name, "destroy");
+        } else {
+            c.state = "exited".to_string();
+        }
+        Ok(())
+    }
+
+    async fn pause(&self, name: &str) -> Result<(), Error> {
+        self.delay().await;
+        let mut containers = self.containers();
+        let c = containers.get_mut(name).ok_or(Error::ContainerNotFound)?;
+        if c.state != "running" {
+            return Err(Error::Conflict(format!("Container {name} is not running")));
+        }
+        c.state = "paused".to_string();
+        self.emit(&c.id, name, "pause");
+        Ok(())
+    }
+
+    async fn unpause(&self, name: &str) -> Result<(), Error> {
+        self.delay().await;
+        let mut containers = self.containers();
+        let c = containers.get_mut(name).ok_or(Error::ContainerNotFound)?;
+        if c.state != "paused" {
+            return Err(Error::Conflict(format!("Container {name} is not paused")));
+        }
+        c.state = "running".to_string();
+        self.emit(&c.id, name, "unpause");
+        Ok(())
+    }
+
+    async fn restart(&self, name: &str, _timeout: Option<u64>) -> Result<(), Error> {
+        self.delay().await;
+        let mut containers = self.containers();
+        let c = containers.get_mut(name).ok_or(Error::ContainerNotFound)?;
+        if c.state == "running" {
+            self.die(c, name);
+        }
+        c.state = "running".to_string();
+        c.reset_health();
+        self.emit(&c.id, name, "restart");
+        Ok(())
+    }
+
+    /// `KILL`, `TERM` and `INT` end the main process; other signals are only recorded.
+    async fn kill(&self, name: &str, signal: &str) -> Result<(), Error> {
+        validate_signal(signal)?;
+        self.delay().await;
+        let exit_code = {
+            let mut containers = self.containers();
+            let c = containers.get_mut(name).ok_or(Error::ContainerNotFound)?;
+            if c.state != "running" {
+                return Err(Error::Conflict(format!("Container {name} is not running")));
+            }
+            c.signals.push(signal.to_string());
+            self.emit(&c.id, name, "kill");
+            match signal.trim_start_matches("SIG") {
+                "KILL" | "9" => Some(137),
+                "TERM" | "15" => Some(143),
+                "INT" | "2" => Some(130),
+                _ => None,
+            }
+        };
+        if let Some(code) = exit_code {
+            self.exit(name, code);
+        }
+        Ok(())
+    }
+
+    /// Waiters are woken by the events the simulated lifecycle emits.
+    async fn wait(&self, name: &str, condition: WaitCondition) -> Result<i64, Error> {
+        self.delay().await;
+        let mut events = self.events.subscribe();
+        let mut exit_code = {
+            let containers = self.containers();
+            let c = containers.get(name).ok_or(Error::ContainerNotFound)?;
+            let running = matches!(c.state.as_str(), "running" | "paused" | "restarting");
+            if condition == WaitCondition::NotRunning && !running {
+                return Ok(c.exit_code);
+            }
+            c.exit_code
+        };
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Err(Error::DockerCommandFailed),
+            };
+            if event.name != name {
+                continue;
+            }
+            match event.action.as_str() {
+                "die" => {
+                    exit_code = event.exit_code.unwrap_or(exit_code);
+                    if condition != WaitCondition::Removed {
+                        return Ok(exit_code);
+                    }
+                }
+                "destroy" => return Ok(exit_code),
+                _ => {}
+            }
+        }
+    }
+
+    async fn rename(&self, name: &str, new_name: &str) -> Result<(), Error> {
+        validate_container_name(new_name)?;
+        self.delay().await;
+        let mut containers = self.containers();
+        if containers.contains_key(new_name) {
+            return Err(Error::Conflict(format!(
+                "The container name \"/{new_name}\" is already in use"
+            )));
+        }
+        let c = containers.remove(name).ok_or(Error::ContainerNotFound)?;
+        let _ = self.events.send(ContainerEvent {
+            id: c.id.clone(),
+            name: new_name.to_string(),
+            action: "rename".to_string(),
+            old_name: Some(name.to_string()),
+            ..Default::default()
+        });
+        containers.insert(new_name.to_string(), c);
+        Ok(())
+    }
+
+    async fn inspect(&self, name: &str) -> Result<ContainerInspect, Error> {
+        self.delay().await;
+        let containers = self.containers();
+        let c = containers.get(name).ok_or(Error::ContainerNotFound)?;
+
+        // Requested bindings keep an empty address for every interface,
+        // which the network settings report as `0.0.0.0`.
+        let mut requested: HashMap<String, Option<Vec<PortBinding>>> = HashMap::new();
+        let mut published: HashMap<String, Option<Vec<PortBinding>>> = HashMap::new();
+        for port in &c.ports {
+            let binding = |host_ip: &str| PortBinding {
+                host_ip: host_ip.to_string(),
+                host_port: port.host.to_string(),
+            };
+            let host_ip = port.host_ip.as_deref();
+            requested
+                .entry(port.key())
+                .or_default()
+                .get_or_insert_with(Vec::new)
+                .push(binding(host_ip.unwrap_or_default()));
+            published
+                .entry(port.key())
+                .or_default()
+                .get_or_insert_with(Vec::new)
+                .push(binding(host_ip.unwrap_or("0.0.0.0")));
+        }
+
+        Ok(ContainerInspect {
+            id: c.id.clone(),
+            name: format!("/{name}"),
+            created: String::new(),
+            state: ContainerState {
+                status: c.state.clone(),
+                running: c.state == "running",
+                exit_code: c.exit_code,
+                oom_killed: c.oom_killed,
+                finished_at: c
+                    .finished_at
+                    .clone()
+                    .unwrap_or_else(|| "0001-01-01T00:00:00Z".to_string()),
+                health: c.health.clone(),
+            },
+            config: ContainerInspectConfig {
+                image: c.image.clone(),
+                env: Some(c.env.iter().map(|(k, v)| format!("{k}={v}")).collect()),
+                labels: Some(c.labels.clone()),
+                healthcheck: c.healthcheck.as_ref().map(HealthCheck::to_config),
+                // Like engines, the image default unless overridden.
+                cmd: c
+                    .process
+                    .command
+                    .clone()
+                    .or_else(|| c.image_command.clone()),
+                entrypoint: c.process.entrypoint.clone(),
+                working_dir: c.process.working_dir.clone().unwrap_or_default(),
+                user: c.process.user.clone().unwrap_or_default(),
+                hostname: c
+                    .process
+                    .hostname
+                    .clone()
+                    .unwrap_or_else(|| c.id.chars().take(12).collect()),
+            },
+            host_config: HostConfig {
+                binds: Some(c.volumes.clone()),
+                port_bindings: Some(requested),
+                restart_policy: RestartPolicy {
+                    name: c.restart_policy.clone().unwrap_or_default(),
+                },
+                nano_cpus: c.limits.nano_cpus().unwrap_or_default(),
+                cpu_shares: c.limits.cpu_shares.unwrap_or_default() as i64,
+                memory: c.limits.memory.unwrap_or_default() as i64,
+                memory_reservation: c.limits.memory_reservation.unwrap_or_default() as i64,
+                memory_swap: c.limits.memory_swap.unwrap_or_default(),
+                pids_limit: c.limits.pids_limit.map(|pids| pids as i64),
+                cap_add: Some(c.security.cap_add.clone()),
+                cap_drop: Some(c.security.cap_drop.clone()),
+                readonly_rootfs: c.security.read_only,
+                tmpfs: Some(c.security.tmpfs.clone().into_iter().collect()),
+                security_opt: Some(c.security_opt.clone()),
+                ..Default::default()
+            },
+            network_settings: NetworkSettings {
+                ports: Some(published),
+                networks: Some(if c.networks.is_empty() {
+                    HashMap::from([("bridge".to_string(), EndpointSettings::default())])
+                } else {
+                    // Like engines before API 1.44, the short id is listed among the aliases.
+                    c.networks
+                        .iter()
+                        .map(|network| {
+                            let mut aliases = network.aliases.clone();
+                            aliases.push(c.id[..12].to_string());
+                            let endpoint = EndpointSettings {
+                                aliases: Some(aliases),
+                            };
+                            (network.name.clone(), endpoint)
+                        })
+                        .collect()
+                }),
+            },
+        })
+    }
+
+    async fn list(&self, all: bool) -> Result<Vec<ContainerSummary>, Error> {
+        self.delay().await;
+        let containers = self.containers();
+        let mut names: Vec<&String> = containers.keys().collect();
+        names.sort();
+
+        Ok(names
+            .into_iter()
+            .map(|name| (name, &containers[name]))
+            .filter(|(_, c)| all || c.state == "running")
+            .map(|(name, c)| ContainerSummary {
+                id: c.id.clone(),
+                name: name.clone(),
+                image: c.image.clone(),
+                state: c.state.clone(),
+                status: c.state.clone(),
+                ports: c.ports.iter().map(|port| port.host).collect(),
+                labels: c.labels.clone(),
+                networks: if c.networks.is_empty() {
+                    vec!["bridge".to_string()]
+                } else {
+                    c.networks.iter().map(|n| n.name.clone()).collect()
+                },
+            })
+            .collect())
+    }
+
+    async fn remove(&self, name: &str) -> Result<(), Error> {
+        self.delay().await;
+        let removed = self
+            .containers()
+            .remove(name)
+            .ok_or(Error::ContainerNotFound)?;
+        self.emit(&removed.id, name, "destroy");
+        Ok(())
+    }
+
+    async fn logs(&self, name: &str) -> Result<String, Error> {
+        self.delay().await;
+        let containers = self.containers();
+        let c = containers.get(name).ok_or(Error::ContainerNotFound)?;
+        Ok(c.logs.iter().map(|line| format!("{line}\n")).collect())
+    }
+
+    async fn stats(&self, name: &str) -> Result<ContainerStats, Error> {
+        self.delay().await;
+        let containers = self.containers();
+        let c = containers.get(name).ok_or(Error::ContainerNotFound)?;
+        // Like Docker, stopped containers report zero usage.
+        if c.state == "running" {
+            Ok(c.stats.clone())
+        } else {
+            Ok(ContainerStats::default())
+        }
+    }
+
+    /// Commands are not run: stdout echoes the command line and the exit code is 0.
+    async fn exec(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error> {
+        self.delay().await;
+        self.record_exec(name, cfg)?;
+        Ok(ExecOutput {
+            stdout: format!("{}\n", cfg.cmd.join(" ")),
+            stderr: String::new(),
+            exit_code: 0,
+        })
+    }
+
+    /// Behaves like a terminal echoing its input; Ctrl-D (`0x04`) or closing
+    /// the input ends the session with exit code 0.
+    async fn exec_session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error> {
+        self.delay().await;
+        self.record_exec(name, cfg)?;
+
+        let (input, mut input_rx) = mpsc::channel(64);
+        let (output_tx, output) = mpsc::channel(64);
+        let (exit_tx, exit) = oneshot::channel();
+        let containers = self.containers.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            while let Some(input) = input_rx.recv().await {
+                match input {
+                    ExecInput::Data(data) => {
+                        let end = data.iter().position(|b| *b == 0x04);
+                        let echo = Bytes::copy_from_slice(&data[..end.unwrap_or(data.len())]);
+                        if !echo.is_empty() && output_tx.send(echo).await.is_err() {
+                            break;
+                        }
+                        if end.is_some() {
+                            break;
+                        }
+                    }
+                    ExecInput::Resize { cols, rows } => {
+                        let mut containers = containers.lock().unwrap_or_else(|p| p.into_inner());
+                        if let Some(c) = containers.get_mut(&name) {
+                            c.resizes.push((cols, rows));
+                        }
+                    }
+                }
+            }
+            drop(output_tx);
+            let _ = exit_tx.send(Ok(0));
+        });
+
+        Ok(ExecSession {
+            input,
+            output,
+            exit,
+        })
+    }
+
+    async fn get_archive(&self, name: &str, path: &str) -> Result<ArchiveStream, Error> {
+        self.delay().await;
+        let containers = self.containers();
+        let c = containers.get(name).ok_or(Error::ContainerNotFound)?;
+        let path = normalize_path(path);
+        let not_found = || {
+            Error::NotFound(format!(
+                "Could not find the file {path} in container {name}"
+            ))
+        };
+        let invalid = |e: std::io::Error| Error::Unexpected(e.to_string());
+
+        let mut archive = tar::Builder::new(Vec::new());
+        let mut append = |entry: &str, contents: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            archive.append_data(&mut header, entry, contents)
+        };
+        // Like Docker, entries are relative to the parent of the requested path.
+        let base = path.rsplit('/').next().filter(|b| !b.is_empty());
+        if let Some(contents) = c.files.get(&path) {
+            append(base.unwrap_or_default(), contents).map_err(invalid)?;
+        } else {
+            let prefix = if path == "/" {
+                "/".to_string()
+            } else {
+                format!("{path}/")
+            };
+            let mut found = false;
+            for (file, contents) in c.files.range(prefix.clone()..) {
+                let Some(rest) = file.strip_prefix(&prefix) else {
+                    break;
+                };
+                let entry = match base {
+                    Some(base) => format!("{base}/{rest}"),
+                    None => rest.to_string(),
+                };
+                append(&entry, contents).map_err(invalid)?;
+                found = true;
+            }
+            if !found && path != "/" {
+                return Err(not_found());
+            }
+        }
+        let archive = Bytes::from(archive.into_inner().map_err(invalid)?);
+        Ok(Box::pin(stream::iter([Ok(archive)])))
+    }
+
+    /// Extracts the regular files of the archive; directories are created as needed.
+    async fn put_archive(&self, name: &str, path: &str, archive: Bytes) -> Result<(), Error> {
+        self.delay().await;
+        let mut containers = self.containers();
+        let c = containers.get_mut(name).ok_or(Error::ContainerNotFound)?;
+        let path = normalize_path(path);
+        if c.files.contains_key(&path) {
+            return Err(Error::BadRequest(format!(
+                "extraction point is not a directory: {path}"
+            )));
+        }
+        let invalid = |e: std::io::Error| Error::BadRequest(format!("Invalid archive: {e}"));
+
+        let mut files = Vec::new();
+        for entry in tar::Archive::new(archive.as_ref())
+            .entries()
+            .map_err(invalid)?
+        {
+            let mut entry = entry.map_err(invalid)?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let entry_path = entry
+                .path()
+                .map_err(invalid)?
+                .to_string_lossy()
+                .into_owned();
+            let mut contents = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut contents).map_err(invalid)?;
+            files.push((normalize_path(&format!("{path}/{entry_path}")), contents));
+        }
+        c.files.extend(files);
+        Ok(())
+    }
+
+    async fn list_networks(&self) -> Result<Vec<NetworkInfo>, Error> {
+        self.delay().await;
+        Ok(self.networks().clone())
+    }
+
+    async fn inspect_network(&self, name: &str) -> Result<NetworkInfo, Error> {
+        self.delay().await;
+        self.network(name)
+    }
+
+    /// Networks without a subnet get the next free `172.x.0.0/16`, as Docker allocates them.
+    async fn create_network(&self, cfg: &NetworkConfig) -> Result<String, Error> {
+        cfg.validate()?;
+        self.delay().await;
+        let id = self.next_id();
+        let mut networks = self.networks();
+        if networks.iter().any(|n| n.name == cfg.name) {
+            return Err(Error::Conflict(format!(
+                "network with name {} already exists",
+                cfg.name
+            )));
+        }
+        let subnet = cfg
+            .subnet
+            .clone()
+            .unwrap_or_else(|| format!("172.{}.0.0/16", 17 + networks.len()));
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        networks.push(NetworkInfo {
+            id: id.clone(),
+            name: cfg.name.clone(),
+            driver: cfg.driver.clone().unwrap_or_else(|| "bridge".to_string()),
+            subnet: Some(subnet),
+            internal: cfg.internal,
+            created: rfc3339(now),
+        });
+        Ok(id)
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        self.delay().await;
+        let network = self.network(name)?;
+        if DEFAULT_NETWORKS.contains(&network.name.as_str()) {
+            return Err(Error::Conflict(format!(
+                "{} is a pre-defined network and cannot be removed",
+                network.name
+            )));
+        }
+        let in_use = self
+            .containers()
+            .values()
+            .any(|c| c.networks.iter().any(|n| n.name == network.name));
+        if in_use {
+            return Err(Error::Conflict(format!(
+                "error while removing network: network {} id {} has active endpoints",
+                network.name, network.id
+            )));
+        }
+        self.networks().retain(|n| n.id != network.id);
+        Ok(())
+    }
+
+    async fn list_volumes(&self) -> Result<Vec<VolumeInfo>, Error> {
+        self.delay().await;
+        Ok(self
+            .volumes()
+            .values()
+            .map(|(info, _)| info.clone())
+            .collect())
+    }
+
+    async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo, Error> {
+        self.delay().await;
+        self.volumes()
+            .get(name)
+            .map(|(info, _)| info.clone())
+            .ok_or_else(|| Error::NotFound(format!("get {name}: no such volume")))
+    }
+
+    /// Creating an existing volume returns it unchanged, as Docker does.
+    async fn create_volume(&self, cfg: &VolumeConfig) -> Result<VolumeInfo, Error> {
+        cfg.validate()?;
+        self.delay().await;
+        Ok(self.ensure_volume(cfg))
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        self.delay().await;
+        if !self.volumes().contains_key(name) {
+            return Err(Error::NotFound(format!("get {name}: no such volume")));
+        }
+        let users: Vec<String> = self
+            .containers()
+            .values()
+            .filter(|c| c.volumes.iter().any(|v| volume_name(v) == Some(name)))
+            .map(|c| c.id.clone())
+            .collect();
+        if !users.is_empty() {
+            return Err(Error::Conflict(format!(
+                "remove {name}: volume is in use - [{}]",
+                users.join(", ")
+            )));
+        }
+        self.volumes().remove(name);
+        Ok(())
+    }
+
+    async fn volume_usage(&self) -> Result<HashMap<String, VolumeUsage>, Error> {
+        self.delay().await;
+        let mut usage: HashMap<String, VolumeUsage> = self
+            .volumes()
+            .iter()
+            .map(|(name, (_, size))| {
+                let usage = VolumeUsage {
+                    size: Some(*size),
+                    apps: Vec::new(),
+                };
+                (name.clone(), usage)
+            })
+            .collect();
+        for (container, c) in self.containers().iter() {
+            for volume in c.volumes.iter().filter_map(|v| volume_name(v)) {
+                usage
+                    .entry(volume.to_string())
+                    .or_default()
+                    .apps
+                    .push(container.clone());
+            }
+        }
+        Ok(usage)
+    }
+
+    async fn list_images(&self) -> Result<Vec<ImageSummary>, Error> {
+        self.delay().await;
+        Ok(self.images().clone())
+    }
+
+    async fn inspect_image(&self, name: &str) -> Result<ImageInspect, Error> {
+        self.delay().await;
+        let images = self.images();
+        let image = images
+            .iter()
+            .find(|image| image.matches(name))
+            .ok_or(Error::ImageNotFound)?;
+        Ok(ImageInspect {
+            id: image.id.clone(),
+            repo_tags: Some(image.tags.clone()),
+            repo_digests: Some(vec![]),
+            created: image.created.clone(),
+            size: image.size,
+            architecture: "amd64".to_string(),
+            os: "linux".to_string(),
+            config: self
+                .image_commands
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .get(&image.id)
+                .map(|cmd| ImageInspectConfig {
+                    cmd: Some(cmd.clone()),
+                    ..Default::default()
+                }),
+        })
+    }
+
+    /// Removing a tag only untags the image while other tags remain.
+    async fn remove_image(&self, name: &str) -> Result<(), Error> {
+        self.delay().await;
+        let used = {
+            let images = self.images();
+            let image = images
+                .iter()
+                .find(|image| image.matches(name))
+                .ok_or(Error::ImageNotFound)?;
+            self.containers().values().any(|c| image.matches(&c.image))
+        };
+        if used {
+            return Err(Error::Conflict(format!(
+                "Unable to remove {name}: image is being used by a container"
+            )));
+        }
+
+        let mut images = self.images();
+        let index = images.iter().position(|image| image.matches(name)).unwrap();
+        let image = &mut images[index];
+        let tagged = image.tags.len();
+        image
+            .tags
+            .retain(|tag| canonical_reference(tag) != canonical_reference(name));
+        // Removing by id, or the last tag, deletes the image itself.
+        if image.tags.len() == tagged || image.tags.is_empty() {
+            images.remove(index);
+        }
+        Ok(())
+    }
+
+    async fn prune_images(&self, all: bool) -> Result<PruneReport, Error> {
+        self.delay().await;
+        let containers = self.containers();
+        let mut report = PruneReport::default();
+        self.images().retain(|image| {
+            let unused = !containers.values().any(|c| image.matches(&c.image));
+            if unused && (all || image.tags.is_empty()) {
+                report.deleted.push(image.id.clone());
+                report.space_reclaimed += image.size;
+                false
+            } else {
+                true
+            }
+        });
+        Ok(report)
+    }
+
+    /// Emits a short progress sequence for a single layer, then registers the image.
+    async fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        tx: mpsc::Sender<PullProgress>,
+    ) -> Result<(), Error> {
+        self.delay().await;
+        self.pulls
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .push((reference.to_string(), auth.cloned()));
+        let missing = self
+            .missing
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .contains(&canonical_reference(reference));
+        if missing {
+            return Err(Error::ImageNotFound);
+        }
+
+        let layer = Some("0123456789ab".to_string());
+        let steps = [
+            (None, format!("Pulling from {reference}"), None),
+            (layer.clone(), "Downloading".to_string(), Some(512)),
+            (layer.clone(), "Downloading".to_string(), Some(1024)),
+            (layer, "Pull complete".to_string(), None),
+            (
+                None,
+                format!("Downloaded newer image for {reference}"),
+                None,
+            ),
+        ];
+        for (layer, status, current) in steps {
+            let total = current.map(|_| 1024);
+            let _ = tx
+                .send(PullProgress {
+                    layer,
+                    status,
+                    current,
+                    total,
+                })
+                .await;
+        }
+        self.add_image(&[reference], 1024);
+        Ok(())
+    }
+
+    /// Walks the Dockerfile instructions without running them; `RUN false`
+    /// and `RUN exit <code>` fail the build as a failing command would.
+    async fn build_image(
+        &self,
+        context: Bytes,
+        opts: &BuildOptions,
+        auths: &[RegistryAuth],
+        tx: mpsc::Sender<String>,
+    ) -> Result<String, Error> {
+        opts.validate()?;
+        self.delay().await;
+        self.builds
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .push((opts.clone(), auths.to_vec()));
+
+        let dockerfile = read_context_file(&context, &opts.dockerfile)?.ok_or_else(|| {
+            Error::BadRequest(format!(
+                "Cannot locate specified Dockerfile: {}",
+                opts.dockerfile
+            ))
+        })?;
+        let instructions = stage_instructions(&dockerfile, opts.target.as_deref())?;
+
+        let total = instructions.len();
+        for (step, instruction) in instructions.iter().enumerate() {
+            let _ = tx
+                .send(format!("Step {}/{total} : {instruction}", step + 1))
+                .await;
+            let command = instruction
+                .split_once(char::is_whitespace)
+                .filter(|(keyword, _)| keyword.eq_ignore_ascii_case("RUN"))
+                .map(|(_, command)| command.trim());
+            let code = match command {
+                Some("false") => Some(1),
+                Some(command) => command
+                    .strip_prefix("exit ")
+                    .and_then(|code| code.trim().parse::<i64>().ok())
+                    .filter(|code| *code != 0),
+                None => None,
+            };
+            if let (Some(code), Some(command)) = (code, command) {
+                return Err(Error::Unexpected(format!(
+                    "The command '/bin/sh -c {command}' returned a non-zero code: {code}"
+                )));
+            }
+        }
+
+        let id = self.add_image(&[opts.tag.as_str()], context.len() as u64);
+        let _ = tx.send(format!("Successfully built {}", &id[7..19])).await;
+        let _ = tx.send(format!("Successfully tagged {}", opts.tag)).await;
+        Ok(id)
+    }
+
+    async fn events(&self, tx: mpsc::Sender<ContainerEvent>) -> Result<(), Error> {
+        let mut rx = self.events.subscribe();
+        loop {
+            let event = tokio::select! {
+                _ = tx.closed() => return Ok(()),
+                event = rx.recv() => event,
+            };
+            match event {
+                Ok(event) => {
+                    if tx.send(event).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Absolute form of a container path, without `.` components or a trailing slash.
+fn normalize_path(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    format!("/{}", parts.join("/"))
+}
+
+/// Reads the file at `path` from a tar build context.
+fn read_context_file(context: &[u8], path: &str) -> Result<Option<String>, Error> {
+    let invalid = |e: std::io::Error| Error::BadRequest(format!("Invalid build context: {e}"));
+    let wanted = std::path::Path::new(path.trim_start_matches("./"));
+    let mut archive = tar::Archive::new(context);
+    for entry in archive.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+        let entry_path = entry.path().map_err(invalid)?.into_owned();
+        if entry_path.strip_prefix(".").unwrap_or(&entry_path) == wanted {
+            let mut contents = String::new();
+            std::io::Read::read_to_string(&mut entry, &mut contents).map_err(invalid)?;
+            return Ok(Some(contents));
+        }
+    }
+    Ok(None)
+}
+
+/// Instructions of a Dockerfile up to the end of the `target` stage, or all of them.
+fn stage_instructions(dockerfile: &str, target: Option<&str>) -> Result<Vec<String>, Error> {
+    let instructions: Vec<String> = dockerfile
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    if instructions.is_empty() {
+        return Err(Error::Unexpected("the Dockerfile cannot be empty".into()));
+    }
+    let is_from = |line: &str| {
+        line.split_whitespace()
+            .next()
+            .is_some_and(|keyword| keyword.eq_ignore_ascii_case("FROM"))
+    };
+    let Some(target) = target else {
+        return Ok(instructions);
+    };
+
+    // `FROM <image> AS <name>` starts a named stage.
+    let stage_name = |line: &str| {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [_, _, keyword, name] if keyword.eq_ignore_ascii_case("AS") => Some(name.to_string()),
+            _ => None,
+        }
+    };
+    let start = instructions
+        .iter()
+        .position(|line| is_from(line) && stage_name(line).as_deref() == Some(target))
+        .ok_or_else(|| {
+            Error::Unexpected(format!(
+                "failed to reach build target {target} in Dockerfile"
+            ))
+        })?;
+    let end = instructions[start + 1..]
+        .iter()
+        .position(|line| is_from(line))
+        .map_or(instructions.len(), |next| start + 1 + next);
+    Ok(instructions[..end].to_vec())
+}
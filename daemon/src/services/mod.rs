@@ -1 +1,17 @@
+pub mod builds;
+pub mod cache;
+pub mod credentials;
 pub mod docker;
+pub mod endpoint;
+pub mod engine;
+pub mod health;
+pub mod http;
+pub mod locks;
+#[cfg(feature = "testing")]
+pub mod memory;
+pub mod podman;
+#[cfg(feature = "testing")]
+pub mod replay;
+pub mod seccomp;
+pub mod storage;
+pub mod timeout;
@@ -0,0 +1,868 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use crate::{
+    docker::{
+        BuildOptions, ContainerConfig, ContainerEvent, ContainerInspect, ContainerStats,
+        ContainerSummary, EngineInfo, ExecConfig, ExecOutput, ImageInspect, ImageSummary,
+        NetworkConfig, NetworkInfo, PruneReport, PullProgress, RegistryAuth, ResourceLimits,
+        VolumeConfig, VolumeInfo, VolumeUsage, WaitCondition,
+    },
+    errors::Error,
+    services::{
+        docker::{ArchiveStream, BackendInfo, DockerClient, ExecSession},
+        endpoint::DockerEndpoint,
+        engine::{
+            add_volume_users, build_headers, build_query, demux, engine_info, forward_build,
+            forward_events, image_from_json, is_missing_network, map_archive_error, wait_for,
+            ExecApi, API_VERSION,
+        },
+        http::{self, encode, Endpoint, Response, Transport},
+        seccomp::SeccompProfile,
+    },
+};
+
+/// libpod API version used for every request.
+pub const LIBPOD_API_VERSION: &str = "v4.0.0";
+
+/// CPU period CPU limits are expressed against, in microseconds.
+const CPU_PERIOD: u64 = 100_000;
+
+/// Docker client talking to Podman through the libpod REST API.
+///
+/// Targets the rootless user socket by default
+/// (`$XDG_RUNTIME_DIR/podman/podman.sock`), or `CONTAINER_HOST` when set.
+pub struct PodmanClient {
+    endpoint: DockerEndpoint,
+    transport: Transport,
+    seccomp: SeccompProfile,
+}
+
+impl PodmanClient {
+    /// Creates a client for the given address, without TLS.
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self {
+            transport: Transport::new(endpoint.clone()),
+            endpoint: DockerEndpoint::plain("podman", endpoint),
+            seccomp: SeccompProfile::default(),
+        }
+    }
+
+    /// Creates a client for `CONTAINER_HOST`, or the user's Podman socket.
+    pub fn from_env() -> Result<Self, Error> {
+        match std::env::var("CONTAINER_HOST") {
+            Ok(host) if !host.is_empty() => {
+                let endpoint = DockerEndpoint::parse("podman", &host, None)?;
+                Ok(Self {
+                    transport: Transport::connect_to(&endpoint)?,
+                    endpoint,
+                    seccomp: SeccompProfile::default(),
+                })
+            }
+            _ => Ok(Self::new(Endpoint::Unix(user_socket()))),
+        }
+    }
+
+    /// Applies `profile` to every container created; Podman reads the
+    /// file itself, so it must exist on the Podman host.
+    pub fn with_seccomp(mut self, profile: SeccompProfile) -> Self {
+        self.seccomp = profile;
+        self
+    }
+
+    /// Returns the endpoint this client talks to.
+    pub fn endpoint(&self) -> &DockerEndpoint {
+        &self.endpoint
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Response, Error> {
+        let payload = body.map(|b| b.to_string().into_bytes());
+        http::request(
+            &self.transport,
+            method,
+            &format!("/{LIBPOD_API_VERSION}/libpod{path}"),
+            payload,
+        )
+        .await
+    }
+
+    fn exec_api(&self) -> ExecApi {
+        ExecApi {
+            transport: self.transport.clone(),
+            prefix: format!("/{LIBPOD_API_VERSION}/libpod"),
+            map_error,
+        }
+    }
+
+    async fn pull(&self, image: &str) -> Result<(), Error> {
+        let (tx, _) = mpsc::channel(1);
+        self.pull_image(image, None, tx).await
+    }
+
+    /// Start and stop answer 304 when the container is already in the target state.
+    async fn post_action(&self, name: &str, action: &str) -> Result<(), Error> {
+        let res = self
+            .call(
+                "POST",
+                &format!("/containers/{}/{action}", encode(name)),
+                None,
+            )
+            .await?;
+        if res.is_success() || res.status == 304 {
+            Ok(())
+        } else {
+            Err(map_error(&res))
+        }
+    }
+}
+
+#[async_trait]
+impl DockerClient for PodmanClient {
+    fn describe(&self) -> BackendInfo {
+        BackendInfo {
+            backend: "podman".to_string(),
+            endpoint: Some(self.endpoint.info()),
+        }
+    }
+
+    /// Podman answers the Docker-compatible `/version` and `/info` too.
+    async fn engine_info(&self) -> Result<EngineInfo, Error> {
+        engine_info(&self.transport).await
+    }
+
+    async fn run(&self, cfg: ContainerConfig<'_>) -> Result<String, Error> {
+        cfg.validate()?;
+
+        let spec = create_spec(&cfg, &self.seccomp);
+        let mut res = self.call("POST", "/containers/create", Some(&spec)).await?;
+        if res.status == 404 && !is_missing_network(&res) {
+            self.pull(cfg.image).await?;
+            res = self.call("POST", "/containers/create", Some(&spec)).await?;
+        }
+        if !res.is_success() {
+            return Err(map_network_error(&res));
+        }
+
+        let created: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        let id = created["Id"]
+            .as_str()
+            .ok_or_else(|| Error::DockerOutputParse("Missing container id".into()))?
+            .to_string();
+
+        self.post_action(&id, "start").await?;
+        Ok(id)
+    }
+
+    async fn start(&self, name: &str) -> Result<(), Error> {
+        self.post_action(name, "start").await
+    }
+
+    async fn stop(&self, name: &str) -> Result<(), Error> {
+        self.post_action(name, "stop").await
+    }
+
+    async fn pause(&self, name: &str) -> Result<(), Error> {
+        self.post_action(name, "pause").await
+    }
+
+    async fn unpause(&self, name: &str) -> Result<(), Error> {
+        self.post_action(name, "unpause").await
+    }
+
+    async fn restart(&self, name: &str, timeout: Option<u64>) -> Result<(), Error> {
+        let action = match timeout {
+            Some(secs) => format!("restart?t={secs}"),
+            None => "restart".to_string(),
+        };
+        self.post_action(name, &action).await
+    }
+
+    async fn kill(&self, name: &str, signal: &str) -> Result<(), Error> {
+        self.post_action(name, &format!("kill?signal={}", encode(signal)))
+            .await
+    }
+
+    async fn rename(&self, name: &str, new_name: &str) -> Result<(), Error> {
+        self.post_action(name, &format!("rename?name={}", encode(new_name)))
+            .await
+    }
+
+    /// The libpod wait takes Podman states; the compatible one takes Docker conditions.
+    async fn wait(&self, name: &str, condition: WaitCondition) -> Result<i64, Error> {
+        wait_for(&self.transport, &format!("/{API_VERSION}"), name, condition).await
+    }
+
+    async fn inspect(&self, name: &str) -> Result<ContainerInspect, Error> {
+        let res = self
+            .call("GET", &format!("/containers/{}/json", encode(name)), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        // libpod inspect output is a superset of the Docker one.
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn list(&self, all: bool) -> Result<Vec<ContainerSummary>, Error> {
+        let res = self
+            .call("GET", &format!("/containers/json?all={all}"), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+
+        let entries: Vec<Value> = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(entries.iter().map(summary_from_json).collect())
+    }
+
+    async fn remove(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call(
+                "DELETE",
+                &format!("/containers/{}?force=true", encode(name)),
+                None,
+            )
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_error(&res))
+        }
+    }
+
+    async fn logs(&self, name: &str) -> Result<String, Error> {
+        let res = self
+            .call(
+                "GET",
+                &format!("/containers/{}/logs?stdout=true&stderr=true", encode(name)),
+                None,
+            )
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        Ok(String::from_utf8_lossy(&demux(&res.body)).to_string())
+    }
+
+    async fn stats(&self, name: &str) -> Result<ContainerStats, Error> {
+        let res = self
+            .call(
+                "GET",
+                &format!("/containers/stats?containers={}&stream=false", encode(name)),
+                None,
+            )
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+
+        let report: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        if let Some(err) = report["Error"].as_str().filter(|e| !e.is_empty()) {
+            return Err(if err.contains("no such container") {
+                Error::ContainerNotFound
+            } else {
+                Error::Unexpected(err.to_string())
+            });
+        }
+        let stats = &report["Stats"][0];
+        if stats.is_null() {
+            return Err(Error::ContainerNotFound);
+        }
+
+        let num = |key: &str| stats[key].as_u64().unwrap_or(0);
+        Ok(ContainerStats {
+            cpu_percent: stats["CPU"].as_f64().unwrap_or(0.0),
+            memory_usage: num("MemUsage"),
+            memory_limit: num("MemLimit"),
+            network_rx: num("NetInput"),
+            network_tx: num("NetOutput"),
+            block_read: num("BlockInput"),
+            block_write: num("BlockOutput"),
+            pids: num("PIDs"),
+        })
+    }
+
+    async fn exec(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error> {
+        self.exec_api().run(name, cfg).await
+    }
+
+    async fn exec_session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error> {
+        self.exec_api().session(name, cfg).await
+    }
+
+    async fn list_images(&self) -> Result<Vec<ImageSummary>, Error> {
+        let res = self.call("GET", "/images/json", None).await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        // Same shape as the Docker API listing.
+        let entries: Vec<Value> = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(entries.iter().map(image_from_json).collect())
+    }
+
+    async fn inspect_image(&self, name: &str) -> Result<ImageInspect, Error> {
+        let res = self
+            .call("GET", &format!("/images/{}/json", encode(name)), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_image_error(&res));
+        }
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn remove_image(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call("DELETE", &format!("/images/{}", encode(name)), None)
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_image_error(&res))
+        }
+    }
+
+    async fn prune_images(&self, all: bool) -> Result<PruneReport, Error> {
+        let res = self
+            .call("POST", &format!("/images/prune?all={all}"), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        let reports: Vec<Value> = serde_json::from_slice::<Option<Vec<Value>>>(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?
+            .unwrap_or_default();
+        Ok(PruneReport {
+            deleted: reports
+                .iter()
+                .filter_map(|report| report["Id"].as_str())
+                .map(str::to_string)
+                .collect(),
+            space_reclaimed: reports.iter().filter_map(|r| r["Size"].as_u64()).sum(),
+        })
+    }
+
+    async fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        tx: mpsc::Sender<PullProgress>,
+    ) -> Result<(), Error> {
+        let headers: Vec<_> = auth
+            .map(|auth| ("X-Registry-Auth", auth.header()))
+            .into_iter()
+            .collect();
+        let mut stream = http::stream_with(
+            &self.transport,
+            "POST",
+            &format!(
+                "/{LIBPOD_API_VERSION}/libpod/images/pull?reference={}",
+                encode(reference)
+            ),
+            &headers,
+            None,
+        )
+        .await?;
+        if !stream.is_success() {
+            return Err(map_image_error(&stream.collect().await?));
+        }
+
+        // libpod streams `{"stream": "..."}` lines instead of per-layer progress;
+        // like the Docker API, failures are reported inside the stream.
+        while let Some(line) = stream.next_line().await? {
+            let Ok(event) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            if let Some(err) = event["error"].as_str().filter(|e| !e.is_empty()) {
+                return Err(Error::Unexpected(err.to_string()));
+            }
+            if let Some(status) = event["stream"].as_str().map(str::trim) {
+                let progress = PullProgress {
+                    status: status.to_string(),
+                    ..Default::default()
+                };
+                let _ = tx.send(progress).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn build_image(
+        &self,
+        context: Bytes,
+        opts: &BuildOptions,
+        auths: &[RegistryAuth],
+        tx: mpsc::Sender<String>,
+    ) -> Result<String, Error> {
+        opts.validate()?;
+        let stream = http::stream_with(
+            &self.transport,
+            "POST",
+            &format!("/{LIBPOD_API_VERSION}/libpod/build?{}", build_query(opts)),
+            &build_headers(auths),
+            Some(context.to_vec()),
+        )
+        .await?;
+        if !stream.is_success() {
+            return Err(map_image_error(&stream.collect().await?));
+        }
+        // Older libpod releases end the stream without the `aux` id message.
+        match forward_build(stream, &tx).await? {
+            Some(id) => Ok(id),
+            None => Ok(self.inspect_image(&opts.tag).await?.id),
+        }
+    }
+
+    async fn list_networks(&self) -> Result<Vec<NetworkInfo>, Error> {
+        let res = self.call("GET", "/networks/json", None).await?;
+        if !res.is_success() {
+            return Err(map_network_error(&res));
+        }
+        let networks: Vec<Value> = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(networks.iter().map(network_from_json).collect())
+    }
+
+    async fn inspect_network(&self, name: &str) -> Result<NetworkInfo, Error> {
+        let res = self
+            .call("GET", &format!("/networks/{}/json", encode(name)), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_network_error(&res));
+        }
+        let network: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(network_from_json(&network))
+    }
+
+    async fn create_network(&self, cfg: &NetworkConfig) -> Result<String, Error> {
+        cfg.validate()?;
+        let mut body = json!({
+            "name": cfg.name,
+            "driver": cfg.driver.as_deref().unwrap_or("bridge"),
+            "internal": cfg.internal,
+        });
+        if let Some(subnet) = &cfg.subnet {
+            body["subnets"] = json!([{ "subnet": subnet }]);
+        }
+        let res = self.call("POST", "/networks/create", Some(&body)).await?;
+        if !res.is_success() {
+            return Err(map_network_error(&res));
+        }
+        let created: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        Ok(network_from_json(&created).id)
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call("DELETE", &format!("/networks/{}", encode(name)), None)
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_network_error(&res))
+        }
+    }
+
+    async fn list_volumes(&self) -> Result<Vec<VolumeInfo>, Error> {
+        let res = self.call("GET", "/volumes/json", None).await?;
+        if !res.is_success() {
+            return Err(map_volume_error(&res));
+        }
+        serde_json::from_slice::<Option<Vec<VolumeInfo>>>(&res.body)
+            .map(Option::unwrap_or_default)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo, Error> {
+        let res = self
+            .call("GET", &format!("/volumes/{}/json", encode(name)), None)
+            .await?;
+        if !res.is_success() {
+            return Err(map_volume_error(&res));
+        }
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn create_volume(&self, cfg: &VolumeConfig) -> Result<VolumeInfo, Error> {
+        cfg.validate()?;
+        let body = json!({
+            "Name": cfg.name,
+            "Driver": cfg.driver.as_deref().unwrap_or("local"),
+            "Options": cfg.driver_opts,
+            "Label": cfg.labels,
+        });
+        let res = self.call("POST", "/volumes/create", Some(&body)).await?;
+        if !res.is_success() {
+            return Err(map_volume_error(&res));
+        }
+        serde_json::from_slice(&res.body).map_err(|e| Error::DockerOutputParse(e.to_string()))
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        let res = self
+            .call("DELETE", &format!("/volumes/{}", encode(name)), None)
+            .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_volume_error(&res))
+        }
+    }
+
+    async fn volume_usage(&self) -> Result<HashMap<String, VolumeUsage>, Error> {
+        let res = self.call("GET", "/system/df", None).await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        let df: Value = serde_json::from_slice(&res.body)
+            .map_err(|e| Error::DockerOutputParse(e.to_string()))?;
+        let mut usage: HashMap<String, VolumeUsage> = df["Volumes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|volume| {
+                let usage = VolumeUsage {
+                    size: volume["Size"].as_u64(),
+                    apps: Vec::new(),
+                };
+                Some((volume["VolumeName"].as_str()?.to_string(), usage))
+            })
+            .collect();
+
+        // libpod lists mounts by destination; the compatible API names the volumes.
+        let res = http::request(
+            &self.transport,
+            "GET",
+            &format!("/{API_VERSION}/containers/json?all=1"),
+            None,
+        )
+        .await?;
+        if !res.is_success() {
+            return Err(map_error(&res));
+        }
+        add_volume_users(&res, &mut usage)?;
+        Ok(usage)
+    }
+
+    async fn get_archive(&self, name: &str, path: &str) -> Result<ArchiveStream, Error> {
+        let res = http::stream(
+            &self.transport,
+            "GET",
+            &format!(
+                "/{LIBPOD_API_VERSION}/libpod/containers/{}/archive?path={}",
+                encode(name),
+                encode(path)
+            ),
+            None,
+        )
+        .await?;
+        if !res.is_success() {
+            return Err(map_archive_error(&res.collect().await?, map_error));
+        }
+        Ok(Box::pin(res.into_chunks()))
+    }
+
+    async fn put_archive(&self, name: &str, path: &str, archive: Bytes) -> Result<(), Error> {
+        let res = http::stream_with(
+            &self.transport,
+            "PUT",
+            &format!(
+                "/{LIBPOD_API_VERSION}/libpod/containers/{}/archive?path={}",
+                encode(name),
+                encode(path)
+            ),
+            &[("Content-Type", "application/x-tar".to_string())],
+            Some(archive.to_vec()),
+        )
+        .await?
+        .collect()
+        .await?;
+        if res.is_success() {
+            Ok(())
+        } else {
+            Err(map_archive_error(&res, map_error))
+        }
+    }
+
+    async fn events(&self, tx: mpsc::Sender<ContainerEvent>) -> Result<(), Error> {
+        let filters = encode(r#"{"type":["container"]}"#);
+        let stream = http::stream(
+            &self.transport,
+            "GET",
+            &format!("/{LIBPOD_API_VERSION}/libpod/events?stream=true&filters={filters}"),
+            None,
+        )
+        .await?;
+        if !stream.is_success() {
+            return Err(map_error(&stream.collect().await?));
+        }
+        forward_events(stream, tx).await
+    }
+}
+
+/// Rootless Podman socket of the current user.
+fn user_socket() -> PathBuf {
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(default_runtime_dir);
+    runtime_dir.join("podman").join("podman.sock")
+}
+
+#[cfg(unix)]
+fn default_runtime_dir() -> PathBuf {
+    PathBuf::from(format!("/run/user/{}", users::get_current_uid()))
+}
+
+#[cfg(not(unix))]
+fn default_runtime_dir() -> PathBuf {
+    PathBuf::from("/run/podman")
+}
+
+/// Builds the libpod `SpecGenerator` payload for a container config.
+fn create_spec(cfg: &ContainerConfig, seccomp: &SeccompProfile) -> Value {
+    let portmappings: Vec<Value> = cfg
+        .ports
+        .iter()
+        .map(|port| {
+            let mut mapping = json!({
+                "host_port": port.host,
+                "container_port": port.container,
+                "protocol": port.protocol.as_str(),
+            });
+            if let Some(ip) = &port.host_ip {
+                mapping["host_ip"] = json!(ip);
+            }
+            mapping
+        })
+        .collect();
+
+    // Absolute sources are bind mounts, anything else is a named volume.
+    let mut mounts = Vec::new();
+    let mut volumes = Vec::new();
+    for volume in cfg.volumes.into_iter().flatten() {
+        let mut parts = volume.splitn(3, ':');
+        let source = parts.next().unwrap_or_default();
+        let destination = parts.next().unwrap_or_default();
+        let options: Vec<&str> = parts
+            .next()
+            .map(|opts| opts.split(',').collect())
+            .unwrap_or_default();
+        if source.starts_with('/') {
+            mounts.push(json!({
+                "type": "bind",
+                "source": source,
+                "destination": destination,
+                "options": options,
+            }));
+        } else {
+            volumes.push(json!({
+                "Name": source,
+                "Dest": destination,
+                "Options": options,
+            }));
+        }
+    }
+
+    let security = cfg.security.cloned().unwrap_or_default();
+    for (destination, options) in &security.tmpfs {
+        let options: Vec<&str> = options.split(',').filter(|o| !o.is_empty()).collect();
+        mounts.push(json!({
+            "type": "tmpfs",
+            "source": "tmpfs",
+            "destination": destination,
+            "options": options,
+        }));
+    }
+
+    let mut spec = json!({
+        "name": cfg.name,
+        "image": cfg.image,
+        "env": cfg.env.cloned().unwrap_or_default(),
+        "labels": cfg.labels.cloned().unwrap_or_default(),
+        "portmappings": portmappings,
+        "mounts": mounts,
+        "volumes": volumes,
+        // Mirrors `docker run --rm`; Podman also refuses it alongside a restart policy.
+        "remove": matches!(cfg.restart_policy, None | Some("no")),
+    });
+    if let Some(policy) = cfg.restart_policy {
+        spec["restart_policy"] = json!(policy);
+    }
+    if let Some(limits) = cfg.limits {
+        spec["resource_limits"] = resource_limits(limits);
+    }
+    if let Some(path) = seccomp.path() {
+        spec["seccomp_profile_path"] = json!(path);
+    }
+    if cfg.security.is_some() {
+        spec["cap_add"] = json!(security.cap_add);
+        spec["cap_drop"] = json!(security.cap_drop);
+        spec["read_only_filesystem"] = json!(security.read_only);
+        spec["no_new_privileges"] = json!(security.no_new_privileges);
+        if let Some(profile) = &security.apparmor {
+            spec["apparmor_profile"] = json!(profile);
+        }
+    }
+    if let Some(process) = cfg.process {
+        let fields = [
+            ("command", process.command.as_ref().map(|cmd| json!(cmd))),
+            ("entrypoint", process.entrypoint.as_ref().map(|e| json!(e))),
+            (
+                "work_dir",
+                process.working_dir.as_ref().map(|dir| json!(dir)),
+            ),
+            ("user", process.user.as_ref().map(|user| json!(user))),
+            (
+                "hostname",
+                process.hostname.as_ref().map(|host| json!(host)),
+            ),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value {
+                spec[field] = value;
+            }
+        }
+    }
+    if let Some(healthcheck) = cfg.healthcheck {
+        // Same shape and units as the Docker `Healthcheck`.
+        spec["healthconfig"] = json!(healthcheck.to_config());
+    }
+    if !cfg.networks.is_empty() {
+        // Rootless containers default to slirp4netns, which cannot join networks.
+        spec["netns"] = json!({ "nsmode": "bridge" });
+        spec["networks"] = cfg
+            .networks
+            .iter()
+            .map(|network| (network.name.clone(), json!({ "aliases": network.aliases })))
+            .collect();
+    }
+    spec
+}
+
+/// Linux resources of a libpod spec; CPUs become a quota over the default 100ms period.
+fn resource_limits(limits: &ResourceLimits) -> Value {
+    let mut resources = json!({});
+    if let Some(cpus) = limits.cpus {
+        resources["cpu"]["period"] = json!(CPU_PERIOD);
+        resources["cpu"]["quota"] = json!((cpus * CPU_PERIOD as f64).round() as i64);
+    }
+    if let Some(shares) = limits.cpu_shares {
+        resources["cpu"]["shares"] = json!(shares);
+    }
+    if let Some(memory) = limits.memory {
+        resources["memory"]["limit"] = json!(memory);
+    }
+    if let Some(reservation) = limits.memory_reservation {
+        resources["memory"]["reservation"] = json!(reservation);
+    }
+    if let Some(swap) = limits.memory_swap {
+        resources["memory"]["swap"] = json!(swap);
+    }
+    if let Some(pids) = limits.pids_limit {
+        resources["pids"]["limit"] = json!(pids);
+    }
+    resources
+}
+
+/// Converts a libpod network into a `NetworkInfo`.
+fn network_from_json(entry: &Value) -> NetworkInfo {
+    let text = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+    NetworkInfo {
+        id: text("id"),
+        name: text("name"),
+        driver: text("driver"),
+        subnet: entry["subnets"][0]["subnet"].as_str().map(str::to_string),
+        internal: entry["internal"].as_bool().unwrap_or(false),
+        created: text("created"),
+    }
+}
+
+/// Converts a libpod `GET /containers/json` entry into a `ContainerSummary`.
+fn summary_from_json(entry: &Value) -> ContainerSummary {
+    let text = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+    ContainerSummary {
+        id: text("Id"),
+        name: entry["Names"][0].as_str().unwrap_or_default().to_string(),
+        image: text("Image"),
+        state: text("State"),
+        status: text("Status"),
+        ports: entry["Ports"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p["host_port"].as_u64())
+            .filter_map(|p| u16::try_from(p).ok())
+            .filter(|p| *p != 0)
+            .collect(),
+        labels: entry["Labels"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(k, v)| (k.clone(), v.as_str().unwrap_or_default().to_string()))
+            .collect::<HashMap<_, _>>(),
+        networks: entry["Networks"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+    }
+}
+
+fn map_image_error(res: &Response) -> Error {
+    match res.status {
+        404 => Error::ImageNotFound,
+        _ => map_error(res),
+    }
+}
+
+/// Maps a network error; libpod reports networks in use or built in as 500.
+fn map_network_error(res: &Response) -> Error {
+    let message = res.error_message();
+    let lower = message.to_lowercase();
+    match res.status {
+        404 if !lower.contains("no such container") => Error::NotFound(message),
+        403 | 500 if lower.contains("being used") || lower.contains("cannot be removed") => {
+            Error::Conflict(message)
+        }
+        _ => map_error(res),
+    }
+}
+
+/// Maps a volume error: a 404 is the volume, and a 409 a volume in use.
+fn map_volume_error(res: &Response) -> Error {
+    match res.status {
+        404 => Error::NotFound(res.error_message()),
+        _ => map_error(res),
+    }
+}
+
+/// Maps a libpod error response (`{"cause", "message", "response"}`) onto the daemon error type.
+fn map_error(res: &Response) -> Error {
+    let message = res.error_message();
+    match res.status {
+        404 => Error::ContainerNotFound,
+        400 => Error::BadRequest(message),
+        409 => Error::Conflict(message),
+        // Name clashes surface as 500 on older Podman releases.
+        500 if message.contains("already in use") => Error::Conflict(message),
+        _ => Error::Unexpected(message),
+    }
+}
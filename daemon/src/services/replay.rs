@@ -0,0 +1,856 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use bytes::Bytes;
+use futures_util::stream;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{
+    docker::{
+        BuildOptions, ContainerConfig, ContainerEvent, ContainerInspect, ContainerStats,
+        ContainerSummary, EngineInfo, ExecConfig, ExecOutput, ImageInspect, ImageSummary,
+        NetworkConfig, NetworkInfo, PruneReport, PullProgress, RegistryAuth, VolumeConfig,
+        VolumeInfo, VolumeUsage, WaitCondition,
+    },
+    errors::Error,
+    services::docker::{collect_archive, ArchiveStream, BackendInfo, DockerClient, ExecSession},
+};
+
+/// Call made to a Docker client and its outcome, as stored in a fixture file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Interaction {
+    /// Trait method called: `run`, `inspect`, `pull_image`, ...
+    pub call: String,
+    /// Arguments of the call. Uploaded bodies are recorded by SHA-256 digest
+    /// and registry credentials by server address only.
+    pub args: Value,
+    /// Items sent to the caller's channel: pull progress, build output,
+    /// events, or base64 exec session output.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stream: Vec<Value>,
+    pub result: Outcome,
+}
+
+/// Result of a recorded call.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Ok(Value),
+    Err(RecordedError),
+}
+
+/// [`Error`] as stored in a fixture file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RecordedError {
+    /// Name of the `Error` variant.
+    pub kind: String,
+    pub detail: Option<String>,
+}
+
+impl From<&Error> for RecordedError {
+    fn from(error: &Error) -> Self {
+        let (kind, detail) = match error {
+            Error::DockerCommandFailed => ("DockerCommandFailed", None),
+            Error::ContainerNotFound => ("ContainerNotFound", None),
+            Error::ImageNotFound => ("ImageNotFound", None),
+            Error::NotFound(d) => ("NotFound", Some(d)),
+            Error::DockerOutputParse(d) => ("DockerOutputParse", Some(d)),
+            Error::Unexpected(d) => ("Unexpected", Some(d)),
+            Error::InvalidRequest(d) => ("InvalidRequest", Some(d)),
+            Error::BadRequest(d) => ("BadRequest", Some(d)),
+            Error::Conflict(d) => ("Conflict", Some(d)),
+            Error::Forbidden(d) => ("Forbidden", Some(d)),
+            Error::Timeout(d) => ("Timeout", Some(d)),
+        };
+        Self {
+            kind: kind.to_string(),
+            detail: detail.cloned(),
+        }
+    }
+}
+
+impl From<RecordedError> for Error {
+    fn from(error: RecordedError) -> Self {
+        let detail = error.detail.unwrap_or_default();
+        match error.kind.as_str() {
+            "DockerCommandFailed" => Error::DockerCommandFailed,
+            "ContainerNotFound" => Error::ContainerNotFound,
+            "ImageNotFound" => Error::ImageNotFound,
+            "NotFound" => Error::NotFound(detail),
+            "DockerOutputParse" => Error::DockerOutputParse(detail),
+            "InvalidRequest" => Error::InvalidRequest(detail),
+            "BadRequest" => Error::BadRequest(detail),
+            "Conflict" => Error::Conflict(detail),
+            "Forbidden" => Error::Forbidden(detail),
+            "Timeout" => Error::Timeout(detail),
+            _ => Error::Unexpected(detail),
+        }
+    }
+}
+
+impl Outcome {
+    fn of<T: Serialize>(result: &Result<T, Error>) -> Self {
+        match result {
+            Ok(value) => Outcome::Ok(serde_json::to_value(value).unwrap_or(Value::Null)),
+            Err(e) => Outcome::Err(e.into()),
+        }
+    }
+
+    fn into_result<T: DeserializeOwned>(self) -> Result<T, Error> {
+        match self {
+            Outcome::Ok(value) => serde_json::from_value(value)
+                .map_err(|e| Error::Unexpected(format!("Invalid fixture result: {e}"))),
+            Outcome::Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// `sha256:<hex>` digest standing in for an uploaded body.
+fn digest(body: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(body))
+}
+
+/// Registries credentials are presented to; the credentials themselves are never recorded.
+fn registries<'a>(auths: impl IntoIterator<Item = &'a RegistryAuth>) -> Vec<&'a str> {
+    auths
+        .into_iter()
+        .map(|auth| auth.server_address.as_str())
+        .collect()
+}
+
+/// Forwards `rx` to `tx` until either side closes, returning what was forwarded.
+async fn forward<T: Serialize>(mut rx: mpsc::Receiver<T>, tx: mpsc::Sender<T>) -> Vec<Value> {
+    let mut items = Vec::new();
+    while let Some(item) = rx.recv().await {
+        items.push(serde_json::to_value(&item).unwrap_or(Value::Null));
+        if tx.send(item).await.is_err() {
+            break;
+        }
+    }
+    items
+}
+
+/// Sends recorded stream items to `tx`; a closed receiver is not an error.
+async fn send_all<T: DeserializeOwned>(items: Vec<Value>, tx: &mpsc::Sender<T>) {
+    for item in items {
+        let Ok(item) = serde_json::from_value(item) else {
+            continue;
+        };
+        if tx.send(item).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Docker client that records every call made to another client, and its
+/// result, to a JSON fixture file that [`ReplayDockerClient`] serves back.
+///
+/// The file is rewritten after each call, in completion order, so that it is
+/// complete even if the process is killed. Streamed calls are recorded once
+/// the stream ends. Only available with the `testing` feature.
+///
+/// # Examples
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use lightshuttle_core::{
+///     api::routes::router_with_client,
+///     services::{docker::ShellDockerClient, replay::RecordingDockerClient},
+/// };
+///
+/// let docker = RecordingDockerClient::new(
+///     Arc::new(ShellDockerClient::default()),
+///     "tests/fixtures/docker/apps.json",
+/// );
+/// let app = router_with_client(Arc::new(docker));
+/// ```
+pub struct RecordingDockerClient {
+    inner: Arc<dyn DockerClient>,
+    path: PathBuf,
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+}
+
+impl RecordingDockerClient {
+    /// Records the calls made to `inner` into the file at `path`, replacing it.
+    pub fn new(inner: Arc<dyn DockerClient>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            interactions: Arc::default(),
+        }
+    }
+
+    /// Calls recorded so far, in completion order.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        lock(&self.interactions).clone()
+    }
+
+    fn recorder(&self) -> Recorder {
+        Recorder {
+            path: self.path.clone(),
+            interactions: self.interactions.clone(),
+        }
+    }
+
+    /// Runs `future` and records its result.
+    async fn record<T, F>(&self, call: &str, args: Value, future: F) -> Result<T, Error>
+    where
+        T: Serialize,
+        F: Future<Output = Result<T, Error>>,
+    {
+        let result = future.await;
+        self.recorder()
+            .push(call, args, Vec::new(), Outcome::of(&result));
+        result
+    }
+
+    /// Runs `call`, whose items are sent on `tx`, forwarding and recording them.
+    async fn record_stream<T, R, F>(
+        &self,
+        name: &str,
+        args: Value,
+        tx: mpsc::Sender<T>,
+        call: impl FnOnce(mpsc::Sender<T>) -> F,
+    ) -> Result<R, Error>
+    where
+        T: Serialize,
+        R: Serialize,
+        F: Future<Output = Result<R, Error>>,
+    {
+        let (inner_tx, inner_rx) = mpsc::channel(256);
+        let (result, stream) = tokio::join!(call(inner_tx), forward(inner_rx, tx));
+        self.recorder()
+            .push(name, args, stream, Outcome::of(&result));
+        result
+    }
+}
+
+/// Fixture file shared by a recording client and its exec sessions.
+struct Recorder {
+    path: PathBuf,
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+}
+
+impl Recorder {
+    /// Appends an interaction and rewrites the fixture file.
+    ///
+    /// Panics if the file cannot be written: a silently incomplete fixture
+    /// would only fail later, on replay.
+    fn push(&self, call: &str, args: Value, stream: Vec<Value>, result: Outcome) {
+        let mut interactions = lock(&self.interactions);
+        interactions.push(Interaction {
+            call: call.to_string(),
+            args,
+            stream,
+            result,
+        });
+        if let Err(e) = write_fixture(&self.path, &interactions) {
+            panic!("Cannot write fixture {}: {e}", self.path.display());
+        }
+    }
+}
+
+fn write_fixture(path: &Path, interactions: &[Interaction]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let data = serde_json::to_vec_pretty(interactions)?;
+    std::fs::write(path, data)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|p| p.into_inner())
+}
+
+#[async_trait]
+impl DockerClient for RecordingDockerClient {
+    fn describe(&self) -> BackendInfo {
+        self.inner.describe()
+    }
+
+    async fn engine_info(&self) -> Result<EngineInfo, Error> {
+        self.record("engine_info", Value::Null, self.inner.engine_info())
+            .await
+    }
+
+    async fn run(&self, cfg: ContainerConfig<'_>) -> Result<String, Error> {
+        let args = serde_json::to_value(&cfg).unwrap_or(Value::Null);
+        self.record("run", args, self.inner.run(cfg)).await
+    }
+
+    async fn start(&self, name: &str) -> Result<(), Error> {
+        let args = json!({ "name": name });
+        self.record("start", args, self.inner.start(name)).await
+    }
+
+    async fn stop(&self, name: &str) -> Result<(), Error> {
+        let args = json!({ "name": name });
+        self.record("stop", args, self.inner.stop(name)).await
+    }
+
+    async fn pause(&self, name: &str) -> Result<(), Error> {
+        let args = json!({ "name": name });
+        self.record("pause", args, self.inner.pause(name)).await
+    }
+
+    async fn unpause(&self, name: &str) -> Result<(), Error> {
+        let args = json!({ "name": name });
+        self.record("unpause", args, self.inner.unpause(name)).await
+    }
+
+    async fn restart(&self, name: &str, timeout: Option<u64>) -> Result<(), Error> {
+        let args = json!({ "name": name, "timeout": timeout });
+        self.record("restart", args, self.inner.restart(name, timeout))
+            .await
+    }
+
+    async fn kill(&self, name: &str, signal: &str) -> Result<(), Error> {
+        let args = json!({ "name": name, "signal": signal });
+        self.record("kill", args, self.inner.kill(name, signal))
+            .await
+    }
+
+    async fn rename(&self, name: &str, new_name: &str) -> Result<(), Error> {
+        let args = json!({ "name": name, "new_name": new_name });
+        self.record("rename", args, self.inner.rename(name, new_name))
+            .await
+    }
+
+    async fn wait(&self, name: &str, condition: WaitCondition) -> Result<i64, Error> {
+        let args = json!({ "name": name, "condition": condition });
+        self.record("wait", args, self.inner.wait(name, condition))
+            .await
+    }
+
+    async fn inspect(&self, name: &str) -> Result<ContainerInspect, Error> {
+        let args = json!({ "name": name });
+        self.record("inspect", args, self.inner.inspect(name)).await
+    }
+
+    async fn list(&self, all: bool) -> Result<Vec<ContainerSummary>, Error> {
+        let args = json!({ "all": all });
+        self.record("list", args, self.inner.list(all)).await
+    }
+
+    async fn remove(&self, name: &str) -> Result<(), Error> {
+        let args = json!({ "name": name });
+        self.record("remove", args, self.inner.remove(name)).await
+    }
+
+    async fn logs(&self, name: &str) -> Result<String, Error> {
+        let args = json!({ "name": name });
+        self.record("logs", args, self.inner.logs(name)).await
+    }
+
+    async fn stats(&self, name: &str) -> Result<ContainerStats, Error> {
+        let args = json!({ "name": name });
+        self.record("stats", args, self.inner.stats(name)).await
+    }
+
+    async fn exec(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error> {
+        let args = json!({ "name": name, "config": cfg });
+        self.record("exec", args, self.inner.exec(name, cfg)).await
+    }
+
+    /// The session output is recorded, as base64 chunks, once the process has
+    /// exited; the result is the start result wrapping the exit result.
+    async fn exec_session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error> {
+        let args = json!({ "name": name, "config": cfg });
+        let session = match self.inner.exec_session(name, cfg).await {
+            Ok(session) => session,
+            Err(e) => {
+                let outcome = Outcome::Err((&e).into());
+                self.recorder()
+                    .push("exec_session", args, Vec::new(), outcome);
+                return Err(e);
+            }
+        };
+
+        let (output_tx, output) = mpsc::channel(256);
+        let (exit_tx, exit) = oneshot::channel();
+        let recorder = self.recorder();
+        let (mut inner_output, inner_exit) = (session.output, session.exit);
+        tokio::spawn(async move {
+            let mut stream = Vec::new();
+            while let Some(chunk) = inner_output.recv().await {
+                stream.push(Value::String(BASE64.encode(&chunk)));
+                // The client went away; the process output is still recorded.
+                let _ = output_tx.send(chunk).await;
+            }
+            drop(output_tx);
+            let result = inner_exit.await.unwrap_or(Err(Error::DockerCommandFailed));
+            let exit = serde_json::to_value(Outcome::of(&result)).unwrap_or(Value::Null);
+            recorder.push("exec_session", args, stream, Outcome::Ok(exit));
+            let _ = exit_tx.send(result);
+        });
+        Ok(ExecSession {
+            input: session.input,
+            output,
+            exit,
+        })
+    }
+
+    /// The archive is read in full so that the fixture can hold it.
+    async fn get_archive(&self, name: &str, path: &str) -> Result<ArchiveStream, Error> {
+        let args = json!({ "name": name, "path": path });
+        let result = match self.inner.get_archive(name, path).await {
+            Ok(archive) => collect_archive(archive).await.map(Bytes::from),
+            Err(e) => Err(e),
+        };
+        let outcome = match &result {
+            Ok(archive) => Outcome::Ok(Value::String(BASE64.encode(archive))),
+            Err(e) => Outcome::Err(e.into()),
+        };
+        self.recorder()
+            .push("get_archive", args, Vec::new(), outcome);
+        let archive = result?;
+        Ok(Box::pin(stream::iter([Ok(archive)])))
+    }
+
+    async fn put_archive(&self, name: &str, path: &str, archive: Bytes) -> Result<(), Error> {
+        let args = json!({ "name": name, "path": path, "archive": digest(&archive) });
+        self.record(
+            "put_archive",
+            args,
+            self.inner.put_archive(name, path, archive),
+        )
+        .await
+    }
+
+    async fn list_networks(&self) -> Result<Vec<NetworkInfo>, Error> {
+        self.record("list_networks", Value::Null, self.inner.list_networks())
+            .await
+    }
+
+    async fn inspect_network(&self, name: &str) -> Result<NetworkInfo, Error> {
+        let args = json!({ "name": name });
+        self.record("inspect_network", args, self.inner.inspect_network(name))
+            .await
+    }
+
+    async fn create_network(&self, cfg: &NetworkConfig) -> Result<String, Error> {
+        let args = serde_json::to_value(cfg).unwrap_or(Value::Null);
+        self.record("create_network", args, self.inner.create_network(cfg))
+            .await
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        let args = json!({ "name": name });
+        self.record("remove_network", args, self.inner.remove_network(name))
+            .await
+    }
+
+    async fn list_volumes(&self) -> Result<Vec<VolumeInfo>, Error> {
+        self.record("list_volumes", Value::Null, self.inner.list_volumes())
+            .await
+    }
+
+    async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo, Error> {
+        let args = json!({ "name": name });
+        self.record("inspect_volume", args, self.inner.inspect_volume(name))
+            .await
+    }
+
+    async fn create_volume(&self, cfg: &VolumeConfig) -> Result<VolumeInfo, Error> {
+        let args = serde_json::to_value(cfg).unwrap_or(Value::Null);
+        self.record("create_volume", args, self.inner.create_volume(cfg))
+            .await
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        let args = json!({ "name": name });
+        self.record("remove_volume", args, self.inner.remove_volume(name))
+            .await
+    }
+
+    async fn volume_usage(&self) -> Result<HashMap<String, VolumeUsage>, Error> {
+        self.record("volume_usage", Value::Null, self.inner.volume_usage())
+            .await
+    }
+
+    async fn list_images(&self) -> Result<Vec<ImageSummary>, Error> {
+        self.record("list_images", Value::Null, self.inner.list_images())
+            .await
+    }
+
+    async fn inspect_image(&self, name: &str) -> Result<ImageInspect, Error> {
+        let args = json!({ "name": name });
+        self.record("inspect_image", args, self.inner.inspect_image(name))
+            .await
+    }
+
+    async fn remove_image(&self, name: &str) -> Result<(), Error> {
+        let args = json!({ "name": name });
+        self.record("remove_image", args, self.inner.remove_image(name))
+            .await
+    }
+
+    async fn prune_images(&self, all: bool) -> Result<PruneReport, Error> {
+        let args = json!({ "all": all });
+        self.record("prune_images", args, self.inner.prune_images(all))
+            .await
+    }
+
+    async fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        tx: mpsc::Sender<PullProgress>,
+    ) -> Result<(), Error> {
+        let args = json!({ "reference": reference, "registries": registries(auth) });
+        self.record_stream("pull_image", args, tx, |tx| {
+            self.inner.pull_image(reference, auth, tx)
+        })
+        .await
+    }
+
+    async fn build_image(
+        &self,
+        context: Bytes,
+        opts: &BuildOptions,
+        auths: &[RegistryAuth],
+        tx: mpsc::Sender<String>,
+    ) -> Result<String, Error> {
+        let args = json!({
+            "context": digest(&context),
+            "options": opts,
+            "registries": registries(auths),
+        });
+        self.record_stream("build_image", args, tx, |tx| {
+            self.inner.build_image(context, opts, auths, tx)
+        })
+        .await
+    }
+
+    async fn events(&self, tx: mpsc::Sender<ContainerEvent>) -> Result<(), Error> {
+        self.record_stream("events", Value::Null, tx, |tx| self.inner.events(tx))
+            .await
+    }
+}
+
+/// Docker client serving the calls recorded by [`RecordingDockerClient`].
+///
+/// Each call is answered by the first unused interaction with the same
+/// method and arguments, so that calls with different arguments may be made
+/// in any order while repeated calls get their results in recording order.
+/// A call no interaction matches panics with the call and the interactions
+/// left: replayed tests fail rather than run against made-up results. Only
+/// available with the `testing` feature.
+///
+/// # Examples
+/// ```
+/// use lightshuttle_core::services::replay::ReplayDockerClient;
+///
+/// let fixture = r#"[{"call": "stop", "args": {"name": "web"}, "result": {"ok": null}}]"#;
+/// let docker = ReplayDockerClient::new(serde_json::from_str(fixture).unwrap());
+/// assert_eq!(docker.remaining()[0].call, "stop");
+/// ```
+pub struct ReplayDockerClient {
+    pending: Mutex<Vec<Interaction>>,
+    unexpected: Mutex<Vec<String>>,
+}
+
+impl ReplayDockerClient {
+    /// Serves the given interactions.
+    pub fn new(interactions: Vec<Interaction>) -> Self {
+        Self {
+            pending: Mutex::new(interactions),
+            unexpected: Mutex::default(),
+        }
+    }
+
+    /// Serves the interactions of a fixture file written by [`RecordingDockerClient`].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| Error::Unexpected(format!("Fixture {}: {e}", path.display())))?;
+        let interactions = serde_json::from_slice(&data)
+            .map_err(|e| Error::Unexpected(format!("Fixture {}: {e}", path.display())))?;
+        Ok(Self::new(interactions))
+    }
+
+    /// Interactions not replayed yet.
+    pub fn remaining(&self) -> Vec<Interaction> {
+        lock(&self.pending).clone()
+    }
+
+    /// Panics if a call had no matching interaction, or if interactions were
+    /// left unused; call it at the end of a test.
+    ///
+    /// Catches unexpected calls made from spawned tasks, whose panics do not
+    /// reach the test.
+    pub fn assert_done(&self) {
+        let unexpected = lock(&self.unexpected);
+        assert!(
+            unexpected.is_empty(),
+            "Unexpected Docker calls: {}",
+            unexpected.join("; ")
+        );
+        let pending = lock(&self.pending);
+        assert!(
+            pending.is_empty(),
+            "Recorded Docker calls never made: {}",
+            pending
+                .iter()
+                .map(|i| format!("{}({})", i.call, i.args))
+                .collect::<Vec<_>>()
+                .join("; ")
+        );
+    }
+
+    /// Takes the interaction answering a call; panics if there is none.
+    fn take(&self, call: &str, args: Value) -> Interaction {
+        let mut pending = lock(&self.pending);
+        match pending
+            .iter()
+            .position(|i| i.call == call && i.args == args)
+        {
+            Some(index) => pending.remove(index),
+            None => {
+                let description = format!("{call}({args})");
+                lock(&self.unexpected).push(description.clone());
+                let left: Vec<String> = pending
+                    .iter()
+                    .map(|i| format!("{}({})", i.call, i.args))
+                    .collect();
+                drop(pending);
+                panic!(
+                    "Unexpected Docker call {description}; recorded calls left: [{}]",
+                    left.join(", ")
+                );
+            }
+        }
+    }
+
+    fn replay<T: DeserializeOwned>(&self, call: &str, args: Value) -> Result<T, Error> {
+        self.take(call, args).result.into_result()
+    }
+
+    async fn replay_stream<T, R>(
+        &self,
+        call: &str,
+        args: Value,
+        tx: &mpsc::Sender<T>,
+    ) -> Result<R, Error>
+    where
+        T: DeserializeOwned,
+        R: DeserializeOwned,
+    {
+        let interaction = self.take(call, args);
+        send_all(interaction.stream, tx).await;
+        interaction.result.into_result()
+    }
+}
+
+#[async_trait]
+impl DockerClient for ReplayDockerClient {
+    fn describe(&self) -> BackendInfo {
+        BackendInfo {
+            backend: "replay".to_string(),
+            endpoint: None,
+        }
+    }
+
+    async fn engine_info(&self) -> Result<EngineInfo, Error> {
+        self.replay("engine_info", Value::Null)
+    }
+
+    async fn run(&self, cfg: ContainerConfig<'_>) -> Result<String, Error> {
+        let args = serde_json::to_value(&cfg).unwrap_or(Value::Null);
+        self.replay("run", args)
+    }
+
+    async fn start(&self, name: &str) -> Result<(), Error> {
+        self.replay("start", json!({ "name": name }))
+    }
+
+    async fn stop(&self, name: &str) -> Result<(), Error> {
+        self.replay("stop", json!({ "name": name }))
+    }
+
+    async fn pause(&self, name: &str) -> Result<(), Error> {
+        self.replay("pause", json!({ "name": name }))
+    }
+
+    async fn unpause(&self, name: &str) -> Result<(), Error> {
+        self.replay("unpause", json!({ "name": name }))
+    }
+
+    async fn restart(&self, name: &str, timeout: Option<u64>) -> Result<(), Error> {
+        self.replay("restart", json!({ "name": name, "timeout": timeout }))
+    }
+
+    async fn kill(&self, name: &str, signal: &str) -> Result<(), Error> {
+        self.replay("kill", json!({ "name": name, "signal": signal }))
+    }
+
+    async fn rename(&self, name: &str, new_name: &str) -> Result<(), Error> {
+        self.replay("rename", json!({ "name": name, "new_name": new_name }))
+    }
+
+    async fn wait(&self, name: &str, condition: WaitCondition) -> Result<i64, Error> {
+        self.replay("wait", json!({ "name": name, "condition": condition }))
+    }
+
+    async fn inspect(&self, name: &str) -> Result<ContainerInspect, Error> {
+        self.replay("inspect", json!({ "name": name }))
+    }
+
+    async fn list(&self, all: bool) -> Result<Vec<ContainerSummary>, Error> {
+        self.replay("list", json!({ "all": all }))
+    }
+
+    async fn remove(&self, name: &str) -> Result<(), Error> {
+        self.replay("remove", json!({ "name": name }))
+    }
+
+    async fn logs(&self, name: &str) -> Result<String, Error> {
+        self.replay("logs", json!({ "name": name }))
+    }
+
+    async fn stats(&self, name: &str) -> Result<ContainerStats, Error> {
+        self.replay("stats", json!({ "name": name }))
+    }
+
+    async fn exec(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error> {
+        self.replay("exec", json!({ "name": name, "config": cfg }))
+    }
+
+    /// Input sent to the session is discarded; the recorded output is sent
+    /// as is.
+    async fn exec_session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error> {
+        let interaction = self.take("exec_session", json!({ "name": name, "config": cfg }));
+        let exit: Outcome = interaction.result.into_result()?;
+
+        let (input, mut input_rx) = mpsc::channel(16);
+        let (output_tx, output) = mpsc::channel(256);
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let chunks = interaction.stream;
+        tokio::spawn(async move {
+            for chunk in chunks {
+                let chunk = chunk.as_str().and_then(|c| BASE64.decode(c).ok());
+                if output_tx
+                    .send(Bytes::from(chunk.unwrap_or_default()))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            drop(output_tx);
+            let _ = exit_tx.send(exit.into_result());
+        });
+        tokio::spawn(async move { while input_rx.recv().await.is_some() {} });
+        Ok(ExecSession {
+            input,
+            output,
+            exit: exit_rx,
+        })
+    }
+
+    async fn get_archive(&self, name: &str, path: &str) -> Result<ArchiveStream, Error> {
+        let encoded: String = self.replay("get_archive", json!({ "name": name, "path": path }))?;
+        let archive = BASE64
+            .decode(encoded)
+            .map(Bytes::from)
+            .map_err(|e| Error::Unexpected(format!("Invalid fixture archive: {e}")))?;
+        Ok(Box::pin(stream::iter([Ok(archive)])))
+    }
+
+    async fn put_archive(&self, name: &str, path: &str, archive: Bytes) -> Result<(), Error> {
+        let args = json!({ "name": name, "path": path, "archive": digest(&archive) });
+        self.replay("put_archive", args)
+    }
+
+    async fn list_networks(&self) -> Result<Vec<NetworkInfo>, Error> {
+        self.replay("list_networks", Value::Null)
+    }
+
+    async fn inspect_network(&self, name: &str) -> Result<NetworkInfo, Error> {
+        self.replay("inspect_network", json!({ "name": name }))
+    }
+
+    async fn create_network(&self, cfg: &NetworkConfig) -> Result<String, Error> {
+        let args = serde_json::to_value(cfg).unwrap_or(Value::Null);
+        self.replay("create_network", args)
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        self.replay("remove_network", json!({ "name": name }))
+    }
+
+    async fn list_volumes(&self) -> Result<Vec<VolumeInfo>, Error> {
+        self.replay("list_volumes", Value::Null)
+    }
+
+    async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo, Error> {
+        self.replay("inspect_volume", json!({ "name": name }))
+    }
+
+    async fn create_volume(&self, cfg: &VolumeConfig) -> Result<VolumeInfo, Error> {
+        let args = serde_json::to_value(cfg).unwrap_or(Value::Null);
+        self.replay("create_volume", args)
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        self.replay("remove_volume", json!({ "name": name }))
+    }
+
+    async fn volume_usage(&self) -> Result<HashMap<String, VolumeUsage>, Error> {
+        self.replay("volume_usage", Value::Null)
+    }
+
+    async fn list_images(&self) -> Result<Vec<ImageSummary>, Error> {
+        self.replay("list_images", Value::Null)
+    }
+
+    async fn inspect_image(&self, name: &str) -> Result<ImageInspect, Error> {
+        self.replay("inspect_image", json!({ "name": name }))
+    }
+
+    async fn remove_image(&self, name: &str) -> Result<(), Error> {
+        self.replay("remove_image", json!({ "name": name }))
+    }
+
+    async fn prune_images(&self, all: bool) -> Result<PruneReport, Error> {
+        self.replay("prune_images", json!({ "all": all }))
+    }
+
+    async fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        tx: mpsc::Sender<PullProgress>,
+    ) -> Result<(), Error> {
+        let args = json!({ "reference": reference, "registries": registries(auth) });
+        self.replay_stream("pull_image", args, &tx).await
+    }
+
+    async fn build_image(
+        &self,
+        context: Bytes,
+        opts: &BuildOptions,
+        auths: &[RegistryAuth],
+        tx: mpsc::Sender<String>,
+    ) -> Result<String, Error> {
+        let args = json!({
+            "context": digest(&context),
+            "options": opts,
+            "registries": registries(auths),
+        });
+        self.replay_stream("build_image", args, &tx).await
+    }
+
+    async fn events(&self, tx: mpsc::Sender<ContainerEvent>) -> Result<(), Error> {
+        self.replay_stream("events", Value::Null, &tx).await
+    }
+}
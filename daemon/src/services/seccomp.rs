@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use crate::errors::Error;
+
+/// Profile bundled in the daemon image, used when `SECCOMP_PROFILE` is unset.
+pub const DEFAULT_PROFILE_PATH: &str = "/seccomp.json";
+
+/// Seccomp profile the daemon applies to every container it creates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SeccompProfile {
+    /// The engine's built-in profile.
+    #[default]
+    Engine,
+    /// No system call filtering.
+    Unconfined,
+    /// Profile read from a JSON file on the daemon's host.
+    File {
+        path: String,
+        /// Profile contents, sent inline to the Engine API.
+        json: String,
+    },
+}
+
+impl SeccompProfile {
+    /// Reads and checks the JSON profile at `path`.
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            Error::InvalidRequest(format!("Cannot read seccomp profile '{path}': {e}"))
+        })?;
+        serde_json::from_str::<serde_json::Value>(&json)
+            .map_err(|e| Error::InvalidRequest(format!("Invalid seccomp profile '{path}': {e}")))?;
+        Ok(Self::File {
+            path: path.to_string(),
+            json,
+        })
+    }
+
+    /// Reads `SECCOMP_PROFILE`: a JSON file, `unconfined`, or `default` for the engine's own.
+    ///
+    /// Unset, the bundled [`DEFAULT_PROFILE_PATH`] is used when it exists. A
+    /// profile that cannot be read is an error rather than silently dropped.
+    pub fn from_env() -> Result<Self, Error> {
+        let profile = match std::env::var("SECCOMP_PROFILE") {
+            Ok(value) => match value.trim() {
+                "" | "default" => Self::Engine,
+                "unconfined" => Self::Unconfined,
+                path => Self::load(path)?,
+            },
+            Err(_) if Path::new(DEFAULT_PROFILE_PATH).exists() => Self::load(DEFAULT_PROFILE_PATH)?,
+            Err(_) => Self::Engine,
+        };
+        match &profile {
+            Self::Engine => tracing::info!("Containers use the engine's seccomp profile"),
+            Self::Unconfined => tracing::warn!("Containers run without seccomp filtering"),
+            Self::File { path, .. } => tracing::info!(path, "Containers use the seccomp profile"),
+        }
+        Ok(profile)
+    }
+
+    /// `--security-opt` value for the Engine API, with the profile inline.
+    pub fn engine_option(&self) -> Option<String> {
+        match self {
+            Self::Engine => None,
+            Self::Unconfined => Some("seccomp=unconfined".to_string()),
+            Self::File { json, .. } => Some(format!("seccomp={json}")),
+        }
+    }
+
+    /// `--security-opt` value for the `docker` CLI, which reads the file itself.
+    pub fn cli_option(&self) -> Option<String> {
+        self.path().map(|path| format!("seccomp={path}"))
+    }
+
+    /// Profile path for libpod, or `unconfined`.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::Engine => None,
+            Self::Unconfined => Some("unconfined"),
+            Self::File { path, .. } => Some(path),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{errors::Error, services::engine::rfc3339};
+
+/// Directory holding the daemon's persistent state.
+///
+/// `LIGHTSHUTTLE_DATA_DIR` when set, else `$XDG_DATA_HOME/lightshuttle`,
+/// else `~/.local/share/lightshuttle`.
+pub fn data_dir() -> PathBuf {
+    let env_dir = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    };
+    if let Some(dir) = env_dir("LIGHTSHUTTLE_DATA_DIR") {
+        return dir;
+    }
+    env_dir("XDG_DATA_HOME")
+        .or_else(|| env_dir("HOME").map(|home| home.join(".local").join("share")))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("lightshuttle")
+}
+
+/// Atomically replaces `path` with `contents`, readable by the daemon user only.
+///
+/// Missing parent directories are created with the same restriction.
+pub fn write_private(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let io_error = |e: std::io::Error| Error::Unexpected(format!("{}: {e}", path.display()));
+    if let Some(parent) = path.parent() {
+        let mut builder = std::fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(parent).map_err(io_error)?;
+    }
+
+    let tmp = path.with_extension("tmp");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&tmp).map_err(io_error)?;
+    file.write_all(contents).map_err(io_error)?;
+    file.sync_all().map_err(io_error)?;
+    std::fs::rename(&tmp, path).map_err(io_error)
+}
+
+/// Current time, RFC 3339, as stamped on persisted records.
+pub fn now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    rfc3339(secs)
+}
@@ -0,0 +1,269 @@
+use std::{collections::HashMap, future::Future, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+use crate::{
+    docker::{
+        BuildOptions, ContainerConfig, ContainerEvent, ContainerInspect, ContainerStats,
+        ContainerSummary, EngineInfo, ExecConfig, ExecOutput, ImageInspect, ImageSummary,
+        NetworkConfig, NetworkInfo, PruneReport, PullProgress, RegistryAuth, VolumeConfig,
+        VolumeInfo, VolumeUsage, WaitCondition,
+    },
+    errors::Error,
+    services::docker::{ArchiveStream, BackendInfo, DockerClient, ExecSession},
+};
+
+/// Deadlines of the Docker operations that can hang on a wedged engine.
+///
+/// `None` waits forever.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Creating and starting a container, including the implicit pull.
+    pub run: Option<Duration>,
+    /// Stopping a container, grace period included.
+    pub stop: Option<Duration>,
+    /// Inspecting a container or the engine.
+    pub inspect: Option<Duration>,
+    pub logs: Option<Duration>,
+    pub pull: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            run: Some(Duration::from_secs(300)),
+            stop: Some(Duration::from_secs(60)),
+            inspect: Some(Duration::from_secs(15)),
+            logs: Some(Duration::from_secs(30)),
+            pull: Some(Duration::from_secs(600)),
+        }
+    }
+}
+
+impl Timeouts {
+    /// Reads `DOCKER_TIMEOUT_{RUN,STOP,INSPECT,LOGS,PULL}_SECS`, in seconds.
+    ///
+    /// `0` disables a deadline; unset or invalid values keep the default.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |operation: &str, default: Option<Duration>| {
+            let var = format!("DOCKER_TIMEOUT_{operation}_SECS");
+            match std::env::var(&var) {
+                Ok(secs) => match secs.trim().parse::<u64>() {
+                    Ok(0) => None,
+                    Ok(secs) => Some(Duration::from_secs(secs)),
+                    Err(_) => {
+                        tracing::warn!("Invalid {var}: {secs}; using the default");
+                        default
+                    }
+                },
+                Err(_) => default,
+            }
+        };
+        Self {
+            run: read("RUN", defaults.run),
+            stop: read("STOP", defaults.stop),
+            inspect: read("INSPECT", defaults.inspect),
+            logs: read("LOGS", defaults.logs),
+            pull: read("PULL", defaults.pull),
+        }
+    }
+}
+
+/// Docker client enforcing [`Timeouts`] on the operations of another client.
+///
+/// A call past its deadline is dropped, which kills the `docker` child
+/// process or closes the Engine API connection, and fails with
+/// [`Error::Timeout`] naming the operation.
+pub struct TimeoutDockerClient {
+    inner: Arc<dyn DockerClient>,
+    timeouts: Timeouts,
+}
+
+impl TimeoutDockerClient {
+    pub fn new(inner: Arc<dyn DockerClient>, timeouts: Timeouts) -> Self {
+        Self { inner, timeouts }
+    }
+}
+
+/// Runs `future` until `deadline`, cancelling it past that.
+async fn within<T>(
+    operation: &str,
+    deadline: Option<Duration>,
+    future: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+    let Some(deadline) = deadline else {
+        return future.await;
+    };
+    match tokio::time::timeout(deadline, future).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::warn!(operation, ?deadline, "Docker operation timed out");
+            Err(Error::Timeout(operation.to_string()))
+        }
+    }
+}
+
+#[async_trait]
+impl DockerClient for TimeoutDockerClient {
+    fn describe(&self) -> BackendInfo {
+        self.inner.describe()
+    }
+
+    async fn engine_info(&self) -> Result<EngineInfo, Error> {
+        within("inspect", self.timeouts.inspect, self.inner.engine_info()).await
+    }
+
+    async fn run(&self, cfg: ContainerConfig<'_>) -> Result<String, Error> {
+        within("run", self.timeouts.run, self.inner.run(cfg)).await
+    }
+
+    async fn start(&self, name: &str) -> Result<(), Error> {
+        self.inner.start(name).await
+    }
+
+    async fn stop(&self, name: &str) -> Result<(), Error> {
+        within("stop", self.timeouts.stop, self.inner.stop(name)).await
+    }
+
+    async fn pause(&self, name: &str) -> Result<(), Error> {
+        self.inner.pause(name).await
+    }
+
+    async fn unpause(&self, name: &str) -> Result<(), Error> {
+        self.inner.unpause(name).await
+    }
+
+    /// Restarting stops the container first, under the stop deadline.
+    async fn restart(&self, name: &str, timeout: Option<u64>) -> Result<(), Error> {
+        let restart = self.inner.restart(name, timeout);
+        within("stop", self.timeouts.stop, restart).await
+    }
+
+    async fn kill(&self, name: &str, signal: &str) -> Result<(), Error> {
+        self.inner.kill(name, signal).await
+    }
+
+    async fn rename(&self, name: &str, new_name: &str) -> Result<(), Error> {
+        self.inner.rename(name, new_name).await
+    }
+
+    /// Waiting is as long as the container runs: callers set their own deadline.
+    async fn wait(&self, name: &str, condition: WaitCondition) -> Result<i64, Error> {
+        self.inner.wait(name, condition).await
+    }
+
+    async fn inspect(&self, name: &str) -> Result<ContainerInspect, Error> {
+        within("inspect", self.timeouts.inspect, self.inner.inspect(name)).await
+    }
+
+    async fn list(&self, all: bool) -> Result<Vec<ContainerSummary>, Error> {
+        self.inner.list(all).await
+    }
+
+    async fn remove(&self, name: &str) -> Result<(), Error> {
+        self.inner.remove(name).await
+    }
+
+    async fn logs(&self, name: &str) -> Result<String, Error> {
+        within("logs", self.timeouts.logs, self.inner.logs(name)).await
+    }
+
+    async fn stats(&self, name: &str) -> Result<ContainerStats, Error> {
+        self.inner.stats(name).await
+    }
+
+    async fn exec(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error> {
+        self.inner.exec(name, cfg).await
+    }
+
+    async fn exec_session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error> {
+        self.inner.exec_session(name, cfg).await
+    }
+
+    async fn get_archive(&self, name: &str, path: &str) -> Result<ArchiveStream, Error> {
+        self.inner.get_archive(name, path).await
+    }
+
+    async fn put_archive(&self, name: &str, path: &str, archive: Bytes) -> Result<(), Error> {
+        self.inner.put_archive(name, path, archive).await
+    }
+
+    async fn list_networks(&self) -> Result<Vec<NetworkInfo>, Error> {
+        self.inner.list_networks().await
+    }
+
+    async fn inspect_network(&self, name: &str) -> Result<NetworkInfo, Error> {
+        self.inner.inspect_network(name).await
+    }
+
+    async fn create_network(&self, cfg: &NetworkConfig) -> Result<String, Error> {
+        self.inner.create_network(cfg).await
+    }
+
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        self.inner.remove_network(name).await
+    }
+
+    async fn list_volumes(&self) -> Result<Vec<VolumeInfo>, Error> {
+        self.inner.list_volumes().await
+    }
+
+    async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo, Error> {
+        self.inner.inspect_volume(name).await
+    }
+
+    async fn create_volume(&self, cfg: &VolumeConfig) -> Result<VolumeInfo, Error> {
+        self.inner.create_volume(cfg).await
+    }
+
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        self.inner.remove_volume(name).await
+    }
+
+    async fn volume_usage(&self) -> Result<HashMap<String, VolumeUsage>, Error> {
+        self.inner.volume_usage().await
+    }
+
+    async fn list_images(&self) -> Result<Vec<ImageSummary>, Error> {
+        self.inner.list_images().await
+    }
+
+    async fn inspect_image(&self, name: &str) -> Result<ImageInspect, Error> {
+        self.inner.inspect_image(name).await
+    }
+
+    async fn remove_image(&self, name: &str) -> Result<(), Error> {
+        self.inner.remove_image(name).await
+    }
+
+    async fn prune_images(&self, all: bool) -> Result<PruneReport, Error> {
+        self.inner.prune_images(all).await
+    }
+
+    async fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        tx: mpsc::Sender<PullProgress>,
+    ) -> Result<(), Error> {
+        let pull = self.inner.pull_image(reference, auth, tx);
+        within("pull", self.timeouts.pull, pull).await
+    }
+
+    async fn build_image(
+        &self,
+        context: Bytes,
+        opts: &BuildOptions,
+        auths: &[RegistryAuth],
+        tx: mpsc::Sender<String>,
+    ) -> Result<String, Error> {
+        self.inner.build_image(context, opts, auths, tx).await
+    }
+
+    async fn events(&self, tx: mpsc::Sender<ContainerEvent>) -> Result<(), Error> {
+        self.inner.events(tx).await
+    }
+}
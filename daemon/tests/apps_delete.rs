@@ -4,7 +4,7 @@ use axum::{
 };
 use lightshuttle_core::{
     api::routes::router,
-    docker::{remove_container, ContainerConfig},
+    docker::{ContainerConfig, PortMapping},
     services::docker::{DockerClient, ShellDockerClient},
 };
 use tower::ServiceExt;
@@ -17,21 +17,24 @@ async fn delete_existing_app_should_succeed() {
     }
 
     let container_name = "test-delete-lightshuttle";
-    let _ = remove_container(container_name);
+    let _ = ShellDockerClient::default().remove(container_name).await;
 
     let config = ContainerConfig {
         name: container_name,
         image: "nginx:latest",
-        host_ports: &[8088],
-        container_port: 80,
+        ports: &[PortMapping::tcp(8088, 80)],
         labels: None,
         env: None,
         volumes: None,
         restart_policy: None,
+        ..Default::default()
     };
 
-    let docker = ShellDockerClient;
-    docker.run(config).expect("Failed to launch container");
+    let docker = ShellDockerClient::default();
+    docker
+        .run(config)
+        .await
+        .expect("Failed to launch container");
 
     let app = router();
     let response = app
@@ -5,7 +5,7 @@ use axum::{
 use http_body_util::BodyExt;
 use lightshuttle_core::{
     api::routes::router,
-    docker::{remove_container, ContainerConfig},
+    docker::{ContainerConfig, PortMapping},
     services::docker::{DockerClient, ShellDockerClient},
 };
 use serde_json::Value;
@@ -58,20 +58,23 @@ async fn apps_search_filter_should_return_matching_container() {
     }
 
     let container_name = "lightshuttle-test-search-nginx";
-    let _ = remove_container(container_name);
+    let _ = ShellDockerClient::default().remove(container_name).await;
     let config = ContainerConfig {
         name: container_name,
         image: "nginx:latest",
-        host_ports: &[8089],
-        container_port: 80,
+        ports: &[PortMapping::tcp(8089, 80)],
         labels: None,
         env: None,
         volumes: None,
         restart_policy: None,
+        ..Default::default()
     };
 
-    let docker = ShellDockerClient;
-    docker.run(config).expect("Failed to create container");
+    let docker = ShellDockerClient::default();
+    docker
+        .run(config)
+        .await
+        .expect("Failed to create container");
 
     let app = router();
     let response = app
@@ -95,7 +98,10 @@ async fn apps_search_filter_should_return_matching_container() {
         "Container not found in filtered results"
     );
 
-    remove_container(container_name).expect("Failed to clean up container");
+    ShellDockerClient::default()
+        .remove(container_name)
+        .await
+        .expect("Failed to clean up container");
 }
 
 #[tokio::test]
@@ -127,20 +133,23 @@ async fn get_existing_app_should_succeed() {
     }
 
     let container_name = "test-nginx-lightshuttle";
-    let _ = remove_container(container_name);
+    let _ = ShellDockerClient::default().remove(container_name).await;
     let config = ContainerConfig {
         name: container_name,
         image: "nginx:latest",
-        host_ports: &[8089],
-        container_port: 80,
+        ports: &[PortMapping::tcp(8089, 80)],
         labels: None,
         env: None,
         volumes: None,
         restart_policy: None,
+        ..Default::default()
     };
 
-    let docker = ShellDockerClient;
-    docker.run(config).expect("Failed to create container");
+    let docker = ShellDockerClient::default();
+    docker
+        .run(config)
+        .await
+        .expect("Failed to create container");
     let app = router();
     let response = app
         .oneshot(
@@ -164,7 +173,10 @@ async fn get_existing_app_should_succeed() {
     assert_eq!(status, StatusCode::OK);
     assert_eq!(body["name"], container_name);
 
-    remove_container(container_name).expect("Failed to remove test container");
+    ShellDockerClient::default()
+        .remove(container_name)
+        .await
+        .expect("Failed to remove test container");
 }
 
 #[tokio::test]
@@ -203,16 +215,19 @@ async fn get_logs_should_succeed() {
     let config = ContainerConfig {
         name: container_name,
         image: "nginx:latest",
-        host_ports: &[8089],
-        container_port: 80,
+        ports: &[PortMapping::tcp(8089, 80)],
         labels: None,
         env: None,
         volumes: None,
         restart_policy: None,
+        ..Default::default()
     };
 
-    let docker = ShellDockerClient;
-    docker.run(config).expect("Failed to create container");
+    let docker = ShellDockerClient::default();
+    docker
+        .run(config)
+        .await
+        .expect("Failed to create container");
 
     let app = router();
     let response = app
@@ -235,7 +250,7 @@ async fn get_logs_should_succeed() {
     println!("Logs:\n{body_str}");
     assert!(body_str.contains("nginx"));
 
-    let _ = remove_container(container_name);
+    let _ = ShellDockerClient::default().remove(container_name).await;
 }
 
 #[tokio::test]
@@ -246,21 +261,24 @@ async fn get_app_status_should_return_running() {
     }
 
     let name = "test-status-nginx";
-    let _ = remove_container(name);
+    let _ = ShellDockerClient::default().remove(name).await;
 
     let config = ContainerConfig {
         name,
         image: "nginx:latest",
-        host_ports: &[8089],
-        container_port: 80,
+        ports: &[PortMapping::tcp(8089, 80)],
         labels: None,
         env: None,
         volumes: None,
         restart_policy: None,
+        ..Default::default()
     };
 
-    let docker = ShellDockerClient;
-    docker.run(config).expect("Failed to create container");
+    let docker = ShellDockerClient::default();
+    docker
+        .run(config)
+        .await
+        .expect("Failed to create container");
 
     let app = router();
 
@@ -281,5 +299,8 @@ async fn get_app_status_should_return_running() {
 
     assert_eq!(json["status"], "running");
 
-    remove_container(name).expect("cleanup failed");
+    ShellDockerClient::default()
+        .remove(name)
+        .await
+        .expect("cleanup failed");
 }
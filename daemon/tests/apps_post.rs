@@ -6,7 +6,7 @@ use axum::{
 use http_body_util::BodyExt;
 use lightshuttle_core::{
     api::routes::router,
-    docker::{remove_container, ContainerConfig},
+    docker::{ContainerConfig, PortMapping},
     services::docker::{DockerClient, ShellDockerClient},
 };
 use serde_json::{json, Value};
@@ -215,21 +215,24 @@ async fn post_apps_name_recreate_should_restart_container() {
     }
 
     let name = "test-recreate-nginx";
-    let _ = remove_container(name);
+    let _ = ShellDockerClient::default().remove(name).await;
 
     let config = ContainerConfig {
         name,
         image: "nginx:latest",
-        host_ports: &[8089],
-        container_port: 80,
+        ports: &[PortMapping::tcp(8089, 80)],
         labels: None,
         env: None,
         volumes: None,
         restart_policy: None,
+        ..Default::default()
     };
 
-    let docker = ShellDockerClient;
-    docker.run(config).expect("Failed to create container");
+    let docker = ShellDockerClient::default();
+    docker
+        .run(config)
+        .await
+        .expect("Failed to create container");
 
     let app = router();
     let response = app
@@ -250,7 +253,7 @@ async fn post_apps_name_recreate_should_restart_container() {
 
     assert!(json["container_id"].as_str().unwrap().is_empty());
 
-    let _ = remove_container(name);
+    let _ = ShellDockerClient::default().remove(name).await;
 }
 
 #[tokio::test]
@@ -261,7 +264,7 @@ async fn post_apps_should_support_labels() {
     }
 
     let container_name = "test-nginx-labels";
-    let _ = remove_container(container_name);
+    let _ = ShellDockerClient::default().remove(container_name).await;
 
     let payload = json!({
         "name": container_name,
@@ -303,7 +306,7 @@ async fn post_apps_should_support_labels() {
     assert_eq!(labels["app"], "lightshuttle");
     assert_eq!(labels["env"], "test");
 
-    let _ = remove_container(container_name);
+    let _ = ShellDockerClient::default().remove(container_name).await;
 }
 
 #[tokio::test]
@@ -314,7 +317,7 @@ async fn post_apps_should_set_environment_variables() {
     }
 
     let name = "test-env-nginx";
-    let _ = remove_container(name);
+    let _ = ShellDockerClient::default().remove(name).await;
 
     let payload = json!({
         "name": name,
@@ -345,7 +348,7 @@ async fn post_apps_should_set_environment_variables() {
 
     assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "bar");
 
-    let _ = remove_container(name);
+    let _ = ShellDockerClient::default().remove(name).await;
 }
 
 #[tokio::test]
@@ -392,7 +395,7 @@ async fn post_apps_should_mount_volume() {
         "Hello LightShuttle!"
     );
 
-    let _ = remove_container(name);
+    let _ = ShellDockerClient::default().remove(name).await;
     let _ = std::fs::remove_dir_all(host_path);
 }
 
@@ -404,7 +407,7 @@ async fn post_apps_should_apply_restart_policy() {
     }
 
     let name = "test-restart-policy";
-    let _ = remove_container(name);
+    let _ = ShellDockerClient::default().remove(name).await;
 
     let payload = json!({
         "name": name,
@@ -437,7 +440,7 @@ async fn post_apps_should_apply_restart_policy() {
     let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
     assert_eq!(value, "always");
 
-    let _ = remove_container(name);
+    let _ = ShellDockerClient::default().remove(name).await;
 }
 
 #[tokio::test]
@@ -506,7 +509,7 @@ async fn post_apps_name_recreate_should_preserve_volumes_and_env() {
         .unwrap();
     assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "1");
 
-    let _ = remove_container(name);
+    let _ = ShellDockerClient::default().remove(name).await;
     let _ = std::fs::remove_dir_all(host_path);
 }
 
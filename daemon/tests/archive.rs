@@ -0,0 +1,267 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use common::setup_with_keys;
+use http_body_util::BodyExt;
+use lightshuttle_core::services::memory::InMemoryDockerClient;
+use serde_json::Value;
+use tower::ServiceExt;
+
+/// `deployer` may write and exec, `maintainer` may only write, `auditor` may only read.
+const KEYS: &str = r#"{
+    "deployer": {"name": "ci", "read": true, "write": true, "exec": true},
+    "maintainer": {"name": "ci", "read": true, "write": true},
+    "auditor": {"name": "ci", "read": true, "write": false}
+}"#;
+
+/// Router with a running `web` app.
+fn with_web() -> (Arc<InMemoryDockerClient>, Router) {
+    let (docker, app) = setup_with_keys(KEYS);
+    docker.insert("web", "nginx:latest", "running");
+    (docker, app)
+}
+
+/// Sends a tar body and returns the status, `Content-Type` and raw body.
+async fn send_tar(
+    app: &Router,
+    key: &str,
+    method: &str,
+    uri: &str,
+    body: Vec<u8>,
+) -> (StatusCode, Option<String>, Vec<u8>) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("x-api-key", key)
+        .header("Content-Type", "application/x-tar")
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, content_type, bytes.to_vec())
+}
+
+fn tar_of(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut archive = tar::Builder::new(Vec::new());
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        archive
+            .append_data(&mut header, path, contents.as_bytes())
+            .unwrap();
+    }
+    archive.into_inner().unwrap()
+}
+
+fn entries(archive: &[u8]) -> Vec<(String, String)> {
+    tar::Archive::new(archive)
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            let mut contents = String::new();
+            std::io::Read::read_to_string(&mut entry, &mut contents).unwrap();
+            (path, contents)
+        })
+        .collect()
+}
+
+fn error_message(body: &[u8]) -> String {
+    let error: Value = serde_json::from_slice(body).unwrap();
+    error["message"].as_str().unwrap_or_default().to_string()
+}
+
+fn error_details(body: &[u8]) -> String {
+    let error: Value = serde_json::from_slice(body).unwrap();
+    error["details"].as_str().unwrap_or_default().to_string()
+}
+
+#[tokio::test]
+async fn archive_round_trips_a_directory() {
+    let (docker, app) = with_web();
+
+    let upload = tar_of(&[
+        ("nginx.conf", "worker_processes 1;"),
+        ("conf.d/site.conf", "server {}"),
+    ]);
+    let (status, _, _) = send_tar(
+        &app,
+        "deployer",
+        "PUT",
+        "/api/v1/apps/web/archive?path=/etc/nginx",
+        upload,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        docker.file("web", "/etc/nginx/conf.d/site.conf").unwrap(),
+        b"server {}"
+    );
+
+    let (status, content_type, body) = send_tar(
+        &app,
+        "deployer",
+        "GET",
+        "/api/v1/apps/web/archive?path=/etc/nginx",
+        vec![],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/x-tar"));
+    assert_eq!(
+        entries(&body),
+        [
+            (
+                "nginx/conf.d/site.conf".to_string(),
+                "server {}".to_string()
+            ),
+            (
+                "nginx/nginx.conf".to_string(),
+                "worker_processes 1;".to_string()
+            ),
+        ]
+    );
+
+    let (status, _, body) = send_tar(
+        &app,
+        "deployer",
+        "GET",
+        "/api/v1/apps/web/archive?path=/etc/nginx/nginx.conf",
+        vec![],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        entries(&body),
+        [("nginx.conf".to_string(), "worker_processes 1;".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn archive_reports_missing_containers_and_paths() {
+    let (docker, app) = with_web();
+    docker.put_file("web", "/etc/hostname", b"web");
+
+    let (status, _, body) = send_tar(
+        &app,
+        "deployer",
+        "GET",
+        "/api/v1/apps/ghost/archive?path=/etc",
+        vec![],
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error_message(&body), "Container not found");
+
+    let (status, _, body) = send_tar(
+        &app,
+        "deployer",
+        "PUT",
+        "/api/v1/apps/ghost/archive?path=/etc",
+        tar_of(&[("a", "b")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(error_message(&body), "Container not found");
+
+    let (status, _, body) = send_tar(
+        &app,
+        "deployer",
+        "GET",
+        "/api/v1/apps/web/archive?path=/etc/missing",
+        vec![],
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(error_details(&body).contains("/etc/missing"));
+}
+
+#[tokio::test]
+async fn archive_rejects_invalid_uploads() {
+    let (docker, app) = with_web();
+    docker.put_file("web", "/etc/hostname", b"web");
+
+    let (status, _, _) = send_tar(
+        &app,
+        "deployer",
+        "GET",
+        "/api/v1/apps/web/archive?path=",
+        vec![],
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _, _) = send_tar(
+        &app,
+        "deployer",
+        "PUT",
+        "/api/v1/apps/web/archive?path=/etc/hostname",
+        tar_of(&[("a", "b")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _, _) = send_tar(
+        &app,
+        "deployer",
+        "PUT",
+        "/api/v1/apps/web/archive?path=/srv",
+        b"not a tar archive".repeat(64),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn archive_upload_requires_write_permission() {
+    let (docker, app) = with_web();
+
+    let (status, _, body) = send_tar(
+        &app,
+        "auditor",
+        "PUT",
+        "/api/v1/apps/web/archive?path=/srv",
+        tar_of(&[("index.html", "hi")]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(error_details(&body).contains("copy files"));
+    assert!(docker.file("web", "/srv/index.html").is_none());
+}
+
+#[tokio::test]
+async fn archive_download_requires_exec_permission() {
+    let (docker, app) = with_web();
+    docker.put_file("web", "/run/secrets/db", b"hunter2");
+
+    for key in ["auditor", "maintainer"] {
+        let (status, content_type, body) = send_tar(
+            &app,
+            key,
+            "GET",
+            "/api/v1/apps/web/archive?path=/run/secrets",
+            vec![],
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{key}");
+        assert_eq!(content_type.as_deref(), Some("application/json"));
+        assert_eq!(
+            error_details(&body),
+            "Namespace ci is not allowed to copy files out of apps"
+        );
+    }
+}
@@ -0,0 +1,394 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use common::{api_keys, json};
+use http_body_util::BodyExt;
+use lightshuttle_core::{
+    api::{routes::router_with_state, state::AppState},
+    services::{
+        builds::{BuildStatus, BuildStore},
+        docker::DockerClient,
+        memory::InMemoryDockerClient,
+    },
+};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+/// `alpha` and `beta` are separate namespaces; `auditor` may only read.
+const KEYS: &str = r#"{
+    "alpha": {"name": "team-a", "read": true, "write": true},
+    "beta": {"name": "team-b", "read": true, "write": true},
+    "auditor": {"name": "team-a", "read": true, "write": false}
+}"#;
+
+const DOCKERFILE: &str = "FROM ghcr.io/acme/base:1 AS build\nRUN make\n\nFROM alpine:3.20 AS release\nCOPY --from=build /out /app\n";
+
+/// Router with the state it serves, so tests can reach the build store.
+fn setup() -> (Arc<InMemoryDockerClient>, AppState, Router) {
+    api_keys(KEYS);
+    let docker = Arc::new(InMemoryDockerClient::new());
+    let state = AppState::new(docker.clone());
+    (docker, state.clone(), router_with_state(state))
+}
+
+fn context(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut archive = tar::Builder::new(Vec::new());
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        archive
+            .append_data(&mut header, path, contents.as_bytes())
+            .unwrap();
+    }
+    archive.into_inner().unwrap()
+}
+
+async fn send(
+    app: &Router,
+    key: &str,
+    method: &str,
+    uri: &str,
+    content_type: &str,
+    body: Vec<u8>,
+) -> (StatusCode, String) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("x-api-key", key)
+        .header("Content-Type", content_type)
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+async fn send_json(
+    app: &Router,
+    key: &str,
+    method: &str,
+    uri: &str,
+    body: Value,
+) -> (StatusCode, String) {
+    send(
+        app,
+        key,
+        method,
+        uri,
+        "application/json",
+        body.to_string().into_bytes(),
+    )
+    .await
+}
+
+/// `(event, data)` pairs of a server-sent events body.
+fn events(body: &str) -> Vec<(String, String)> {
+    body.split("\n\n")
+        .filter_map(|event| {
+            let name = event.lines().find_map(|l| l.strip_prefix("event: "))?;
+            let data = event.lines().find_map(|l| l.strip_prefix("data: "))?;
+            Some((name.to_string(), data.to_string()))
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn tar_context_builds_stream_output_and_are_recorded() {
+    let (docker, _, app) = setup();
+    let tar = context(&[("Dockerfile", DOCKERFILE), ("src/main.c", "int main;")]);
+
+    let (status, body) = send(
+        &app,
+        "alpha",
+        "POST",
+        "/api/v1/builds?tag=acme/api:dev&build_args=%7B%22VERSION%22%3A%221.2%22%7D",
+        "application/x-tar",
+        tar,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let events = events(&body);
+    assert_eq!(events[0].0, "build");
+    let started = json(&events[0].1);
+    assert_eq!(started["status"], "running");
+    let output: Vec<&str> = events
+        .iter()
+        .filter(|(name, _)| name == "output")
+        .map(|(_, data)| data.as_str())
+        .collect();
+    assert_eq!(output[0], "Step 1/4 : FROM ghcr.io/acme/base:1 AS build");
+    assert_eq!(output.last().unwrap(), &"Successfully tagged acme/api:dev");
+
+    let (name, data) = events.last().unwrap();
+    assert_eq!(name, "done");
+    let done = json(data);
+    assert_eq!(done["id"], started["id"]);
+    assert_eq!(done["status"], "succeeded");
+    assert_eq!(done["tag"], "acme/api:dev");
+    assert_eq!(done["build_args"], json!({"VERSION": "1.2"}));
+    assert!(done["finished"].is_string());
+
+    let image = docker.inspect_image("acme/api:dev").await.unwrap();
+    assert_eq!(done["image_id"], image.id);
+
+    let uri = format!("/api/v1/builds/{}", done["id"].as_str().unwrap());
+    let (status, body) = send(&app, "alpha", "GET", &uri, "application/json", vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json(&body), done);
+}
+
+#[tokio::test]
+async fn inline_dockerfiles_replace_the_one_in_the_context() {
+    let (docker, _, app) = setup();
+    let files = BASE64.encode(context(&[
+        ("Dockerfile", "FROM scratch\n"),
+        ("app.py", "print('hi')"),
+    ]));
+
+    let (status, body) = send_json(
+        &app,
+        "alpha",
+        "POST",
+        "/api/v1/builds",
+        json!({
+            "dockerfile": DOCKERFILE,
+            "context": files,
+            "tag": "acme/api:build",
+            "target": "build",
+            "build_args": {"MODE": "debug"}
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let stream = events(&body);
+    let steps: Vec<&str> = stream
+        .iter()
+        .filter(|(name, data)| name == "output" && data.starts_with("Step"))
+        .map(|(_, data)| data.as_str())
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            "Step 1/2 : FROM ghcr.io/acme/base:1 AS build",
+            "Step 2/2 : RUN make"
+        ]
+    );
+    assert_eq!(stream.last().unwrap().0, "done");
+
+    let (opts, _) = docker.builds().pop().unwrap();
+    assert_eq!(opts.target.as_deref(), Some("build"));
+    assert_eq!(opts.build_args["MODE"], "debug");
+
+    let (status, body) = send_json(
+        &app,
+        "alpha",
+        "POST",
+        "/api/v1/builds",
+        json!({"dockerfile": DOCKERFILE, "target": "ghost"}),
+    )
+    .await;
+    // Failing before any output, the build is a plain HTTP error.
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(json(&body)["details"]
+        .as_str()
+        .unwrap()
+        .contains("failed to reach build target ghost"));
+}
+
+#[tokio::test]
+async fn apps_are_created_from_succeeded_builds_only() {
+    let (docker, _, app) = setup();
+    let build = |dockerfile: &'static str| {
+        let app = app.clone();
+        async move {
+            let (_, body) = send_json(
+                &app,
+                "alpha",
+                "POST",
+                "/api/v1/builds",
+                json!({"dockerfile": dockerfile, "tag": "acme/web:1"}),
+            )
+            .await;
+            json(&events(&body).first().unwrap().1)["id"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        }
+    };
+    let create = |payload: Value| {
+        let app = app.clone();
+        async move { send_json(&app, "alpha", "POST", "/api/v1/apps", payload).await }
+    };
+
+    let failed = build("FROM alpine\nRUN false\n").await;
+    let (status, body) = create(json!({
+        "name": "web", "build": failed, "ports": [8080], "container_port": 80
+    }))
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(json(&body)["details"]
+        .as_str()
+        .unwrap()
+        .contains("returned a non-zero code: 1"));
+
+    let built = build("FROM alpine\nRUN make\n").await;
+    let (status, _) = create(json!({
+        "name": "web", "build": built, "image": "nginx", "ports": [8080], "container_port": 80
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = create(json!({"name": "web", "ports": [8080], "container_port": 80})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = create(json!({
+        "name": "web", "build": "ghost", "ports": [8080], "container_port": 80
+    }))
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = create(json!({
+        "name": "web", "build": built, "ports": [8080], "container_port": 80
+    }))
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let container = docker.inspect("web").await.unwrap();
+    assert_eq!(container.config.image, "acme/web:1");
+}
+
+#[tokio::test]
+async fn invalid_builds_are_plain_http_errors() {
+    let (_, state, app) = setup();
+
+    let missing = context(&[("app.py", "print('hi')")]);
+    let (status, body) = send(
+        &app,
+        "alpha",
+        "POST",
+        "/api/v1/builds",
+        "application/x-tar",
+        missing,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json(&body)["details"]
+        .as_str()
+        .unwrap()
+        .contains("Cannot locate specified Dockerfile: Dockerfile"));
+    // The build is still recorded as failed.
+    assert_eq!(state.builds.list("team-a")[0].status, BuildStatus::Failed);
+
+    let tar = context(&[("Dockerfile", DOCKERFILE)]);
+    for uri in [
+        "/api/v1/builds?dockerfile=../Dockerfile",
+        "/api/v1/builds?tag=bad%20tag",
+        "/api/v1/builds?build_args=nope",
+    ] {
+        let (status, _) = send(&app, "alpha", "POST", uri, "application/x-tar", tar.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+    }
+    let (status, _) = send(&app, "alpha", "POST", "/api/v1/builds", "text/plain", tar).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn builds_are_scoped_per_namespace_and_use_its_credentials() {
+    let (docker, state, app) = setup();
+    state
+        .credentials
+        .put("team-a", "ghcr.io", "ci-bot", "hunter2-token")
+        .unwrap();
+
+    let (_, body) = send_json(
+        &app,
+        "alpha",
+        "POST",
+        "/api/v1/builds",
+        json!({"dockerfile": DOCKERFILE}),
+    )
+    .await;
+    let done = json(&events(&body).pop().unwrap().1);
+    assert!(done["tag"]
+        .as_str()
+        .unwrap()
+        .starts_with("lightshuttle-build:"));
+
+    let (_, auths) = docker.builds().pop().unwrap();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].server_address, "ghcr.io");
+    assert_eq!(auths[0].password, "hunter2-token");
+    assert!(!body.contains("hunter2-token"));
+
+    let uri = format!("/api/v1/builds/{}", done["id"].as_str().unwrap());
+    let (status, _) = send(&app, "beta", "GET", &uri, "application/json", vec![]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, body) = send(
+        &app,
+        "beta",
+        "GET",
+        "/api/v1/builds",
+        "application/json",
+        vec![],
+    )
+    .await;
+    assert_eq!(body, "[]");
+    let (status, body) = send(
+        &app,
+        "auditor",
+        "GET",
+        "/api/v1/builds",
+        "application/json",
+        vec![],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json(&body)[0]["id"], done["id"]);
+
+    let (status, _) = send_json(
+        &app,
+        "auditor",
+        "POST",
+        "/api/v1/builds",
+        json!({"dockerfile": DOCKERFILE}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[test]
+fn interrupted_builds_are_failed_on_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("builds.json");
+    let opts = lightshuttle_core::docker::BuildOptions {
+        tag: "api:dev".into(),
+        dockerfile: "Dockerfile".into(),
+        ..Default::default()
+    };
+
+    let store = BuildStore::open(&path).unwrap();
+    let done = store.start("team-a", &opts).unwrap();
+    store
+        .finish("team-a", &done.id, &Ok("sha256:1f2e".into()))
+        .unwrap();
+    let running = store.start("team-a", &opts).unwrap();
+
+    let reopened = BuildStore::open(&path).unwrap();
+    let done = reopened.get("team-a", &done.id).unwrap();
+    assert_eq!(done.status, BuildStatus::Succeeded);
+    assert_eq!(done.image_id.as_deref(), Some("sha256:1f2e"));
+    let running = reopened.get("team-a", &running.id).unwrap();
+    assert_eq!(running.status, BuildStatus::Failed);
+    assert_eq!(
+        running.error.as_deref(),
+        Some("Interrupted by a daemon restart")
+    );
+    assert!(reopened.get("team-b", &done.id).is_err());
+}
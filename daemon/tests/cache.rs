@@ -0,0 +1,346 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use lightshuttle_core::{
+    api::{routes::router_with_state, state::AppState},
+    docker::{
+        BuildOptions, ContainerConfig, ContainerEvent, ContainerInspect, ContainerStats,
+        ContainerSummary, EngineInfo, ExecConfig, ExecOutput, ImageInspect, ImageSummary,
+        NetworkConfig, NetworkInfo, PruneReport, PullProgress, RegistryAuth, VolumeConfig,
+        VolumeInfo, VolumeUsage, WaitCondition,
+    },
+    errors::Error,
+    services::{
+        cache::ContainerCache,
+        docker::{ArchiveStream, BackendInfo, DockerClient, ExecSession},
+        memory::InMemoryDockerClient,
+    },
+};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tower::ServiceExt;
+
+/// In-memory backend counting the read calls that reach it.
+#[derive(Default)]
+struct Counting {
+    inner: InMemoryDockerClient,
+    lists: AtomicUsize,
+    inspects: AtomicUsize,
+}
+
+impl Counting {
+    fn reads(&self) -> usize {
+        self.lists.load(Ordering::SeqCst) + self.inspects.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl DockerClient for Counting {
+    fn describe(&self) -> BackendInfo {
+        self.inner.describe()
+    }
+    async fn engine_info(&self) -> Result<EngineInfo, Error> {
+        self.inner.engine_info().await
+    }
+    async fn run(&self, cfg: ContainerConfig<'_>) -> Result<String, Error> {
+        self.inner.run(cfg).await
+    }
+    async fn start(&self, name: &str) -> Result<(), Error> {
+        self.inner.start(name).await
+    }
+    async fn stop(&self, name: &str) -> Result<(), Error> {
+        self.inner.stop(name).await
+    }
+    async fn pause(&self, name: &str) -> Result<(), Error> {
+        self.inner.pause(name).await
+    }
+    async fn unpause(&self, name: &str) -> Result<(), Error> {
+        self.inner.unpause(name).await
+    }
+    async fn restart(&self, name: &str, timeout: Option<u64>) -> Result<(), Error> {
+        self.inner.restart(name, timeout).await
+    }
+    async fn kill(&self, name: &str, signal: &str) -> Result<(), Error> {
+        self.inner.kill(name, signal).await
+    }
+    async fn rename(&self, name: &str, new_name: &str) -> Result<(), Error> {
+        self.inner.rename(name, new_name).await
+    }
+    async fn wait(&self, name: &str, condition: WaitCondition) -> Result<i64, Error> {
+        self.inner.wait(name, condition).await
+    }
+    async fn inspect(&self, name: &str) -> Result<ContainerInspect, Error> {
+        self.inspects.fetch_add(1, Ordering::SeqCst);
+        self.inner.inspect(name).await
+    }
+    async fn list(&self, all: bool) -> Result<Vec<ContainerSummary>, Error> {
+        self.lists.fetch_add(1, Ordering::SeqCst);
+        self.inner.list(all).await
+    }
+    async fn remove(&self, name: &str) -> Result<(), Error> {
+        self.inner.remove(name).await
+    }
+    async fn logs(&self, name: &str) -> Result<String, Error> {
+        self.inner.logs(name).await
+    }
+    async fn stats(&self, name: &str) -> Result<ContainerStats, Error> {
+        self.inner.stats(name).await
+    }
+    async fn exec(&self, name: &str, cfg: &ExecConfig) -> Result<ExecOutput, Error> {
+        self.inner.exec(name, cfg).await
+    }
+    async fn exec_session(&self, name: &str, cfg: &ExecConfig) -> Result<ExecSession, Error> {
+        self.inner.exec_session(name, cfg).await
+    }
+    async fn list_images(&self) -> Result<Vec<ImageSummary>, Error> {
+        self.inner.list_images().await
+    }
+    async fn inspect_image(&self, name: &str) -> Result<ImageInspect, Error> {
+        self.inner.inspect_image(name).await
+    }
+    async fn remove_image(&self, name: &str) -> Result<(), Error> {
+        self.inner.remove_image(name).await
+    }
+    async fn prune_images(&self, all: bool) -> Result<PruneReport, Error> {
+        self.inner.prune_images(all).await
+    }
+    async fn pull_image(
+        &self,
+        reference: &str,
+        auth: Option<&RegistryAuth>,
+        tx: mpsc::Sender<PullProgress>,
+    ) -> Result<(), Error> {
+        self.inner.pull_image(reference, auth, tx).await
+    }
+    async fn build_image(
+        &self,
+        context: Bytes,
+        opts: &BuildOptions,
+        auths: &[RegistryAuth],
+        tx: mpsc::Sender<String>,
+    ) -> Result<String, Error> {
+        self.inner.build_image(context, opts, auths, tx).await
+    }
+    async fn list_networks(&self) -> Result<Vec<NetworkInfo>, Error> {
+        self.inner.list_networks().await
+    }
+    async fn inspect_network(&self, name: &str) -> Result<NetworkInfo, Error> {
+        self.inner.inspect_network(name).await
+    }
+    async fn create_network(&self, cfg: &NetworkConfig) -> Result<String, Error> {
+        self.inner.create_network(cfg).await
+    }
+    async fn remove_network(&self, name: &str) -> Result<(), Error> {
+        self.inner.remove_network(name).await
+    }
+    async fn list_volumes(&self) -> Result<Vec<VolumeInfo>, Error> {
+        self.inner.list_volumes().await
+    }
+    async fn inspect_volume(&self, name: &str) -> Result<VolumeInfo, Error> {
+        self.inner.inspect_volume(name).await
+    }
+    async fn create_volume(&self, cfg: &VolumeConfig) -> Result<VolumeInfo, Error> {
+        self.inner.create_volume(cfg).await
+    }
+    async fn remove_volume(&self, name: &str) -> Result<(), Error> {
+        self.inner.remove_volume(name).await
+    }
+    async fn volume_usage(&self) -> Result<HashMap<String, VolumeUsage>, Error> {
+        self.inner.volume_usage().await
+    }
+    async fn get_archive(&self, name: &str, path: &str) -> Result<ArchiveStream, Error> {
+        self.inner.get_archive(name, path).await
+    }
+    async fn put_archive(&self, name: &str, path: &str, archive: Bytes) -> Result<(), Error> {
+        self.inner.put_archive(name, path, archive).await
+    }
+    async fn events(&self, tx: mpsc::Sender<ContainerEvent>) -> Result<(), Error> {
+        self.inner.events(tx).await
+    }
+}
+
+async fn get_json(app: &Router, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+/// Polls `check` until it holds, failing after one second.
+async fn within_a_second<F, Fut>(what: &str, mut check: F)
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while !check().await {
+        assert!(
+            Instant::now() < deadline,
+            "{what} not visible within a second"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+async fn spawn_cache(docker: Arc<Counting>) -> Arc<ContainerCache> {
+    let cache = ContainerCache::spawn(docker, Duration::from_secs(3600));
+    within_a_second("initial sync", || {
+        let ready = cache.is_ready();
+        async move { ready }
+    })
+    .await;
+    cache
+}
+
+#[tokio::test]
+async fn external_changes_are_visible_within_a_second() {
+    let docker = Arc::new(Counting::default());
+    let cache = spawn_cache(docker.clone()).await;
+    let app = router_with_state(AppState::with_cache(docker.clone(), cache));
+
+    docker.inner.insert("web", "nginx:latest", "running");
+    within_a_second("created container", || async {
+        get_json(&app, "/api/v1/apps").await.1["total"] == 1
+    })
+    .await;
+
+    docker.inner.exit("web", 137);
+    within_a_second("exit", || async {
+        get_json(&app, "/api/v1/apps/web/status").await.1["status"] == "exited"
+    })
+    .await;
+    assert_eq!(get_json(&app, "/api/v1/apps").await.1["total"], 0);
+
+    docker.inner.remove("web").await.unwrap();
+    within_a_second("removal", || async {
+        get_json(&app, "/api/v1/apps/web").await.0 == StatusCode::NOT_FOUND
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn listing_makes_no_docker_calls() {
+    let docker = Arc::new(Counting::default());
+    for i in 0..300 {
+        docker
+            .inner
+            .insert(&format!("app-{i:03}"), "nginx:latest", "running");
+    }
+    let cache = spawn_cache(docker.clone()).await;
+    let app = router_with_state(AppState::with_cache(docker.clone(), cache));
+
+    let before = docker.reads();
+    for _ in 0..10 {
+        let (status, list) = get_json(&app, "/api/v1/apps?limit=500").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(list["total"], 300);
+        let (status, _) = get_json(&app, "/api/v1/apps/app-042").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    assert_eq!(docker.reads(), before);
+}
+
+#[tokio::test]
+async fn mutations_through_the_api_are_read_back_immediately() {
+    let docker = Arc::new(Counting::default());
+    let cache = spawn_cache(docker.clone()).await;
+    let app = router_with_state(AppState::with_cache(docker.clone(), cache));
+
+    let payload = serde_json::json!({
+        "name": "web",
+        "image": "nginx:latest",
+        "ports": [8080],
+        "container_port": 80,
+        "restart_policy": "always"
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/apps")
+        .header("Content-Type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let (_, list) = get_json(&app, "/api/v1/apps").await;
+    assert_eq!(list["total"], 1);
+    assert_eq!(list["items"][0]["ports"], serde_json::json!([8080]));
+    let (_, app_view) = get_json(&app, "/api/v1/apps/web").await;
+    assert_eq!(app_view["ports"], serde_json::json!([80]));
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/apps/web/stop")
+        .body(Body::empty())
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap();
+    let (_, status) = get_json(&app, "/api/v1/apps/web/status").await;
+    assert_eq!(status["status"], "exited");
+}
+
+#[tokio::test]
+async fn resync_corrects_drift() {
+    let docker = Arc::new(InMemoryDockerClient::new());
+    // Not spawned: no events reach this cache.
+    let cache = ContainerCache::new(docker.clone());
+    assert!(!cache.is_ready());
+
+    docker.insert("api", "api:1", "running");
+    docker.insert("worker", "api:1", "running");
+    cache.resync().await.unwrap();
+    assert!(cache.is_ready());
+    assert_eq!(cache.running().len(), 2);
+
+    docker.exit("worker", 1);
+    docker.remove("api").await.unwrap();
+    docker.insert("cron", "api:1", "created");
+    assert_eq!(cache.running().len(), 2);
+
+    cache.resync().await.unwrap();
+    assert!(cache.get("api").is_none());
+    assert_eq!(cache.status("worker").as_deref(), Some("exited"));
+    assert_eq!(cache.status("cron").as_deref(), Some("created"));
+    assert!(cache.running().is_empty());
+}
+
+#[tokio::test]
+async fn rename_events_move_the_entry() {
+    let docker = Arc::new(InMemoryDockerClient::new());
+    let cache = ContainerCache::new(docker.clone());
+    docker.insert("old", "nginx:latest", "running");
+    cache.resync().await.unwrap();
+
+    // Simulate the engine renaming `old` to `new`.
+    docker.remove("old").await.unwrap();
+    docker.insert("new", "nginx:latest", "running");
+    cache
+        .apply(&ContainerEvent {
+            id: String::new(),
+            name: "new".into(),
+            action: "rename".into(),
+            old_name: Some("old".into()),
+            exit_code: None,
+        })
+        .await;
+
+    assert!(cache.get("old").is_none());
+    assert_eq!(cache.get("new").unwrap().name, "new");
+}
@@ -0,0 +1,57 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+
+use futures_util::StreamExt;
+use lightshuttle_core::{
+    errors::Error,
+    services::docker::{collect_archive, DockerClient, ShellDockerClient},
+};
+
+/// Stands in for `docker cp <name>:<path> -`.
+const FAKE_DOCKER: &str = r#"#!/bin/sh
+case "$2" in
+  web:/etc/nginx) printf 'tar-bytes' ;;
+  web:/var/log) printf 'partial'; echo "error: unexpected EOF" >&2; exit 1 ;;
+  web:*) echo "Error response from daemon: Could not find the file $2" >&2; exit 1 ;;
+  *) echo "Error response from daemon: No such container: ghost" >&2; exit 1 ;;
+esac
+"#;
+
+/// Puts the fake `docker` first on the `PATH` of this test process.
+fn fake_docker() {
+    let dir = std::env::temp_dir().join(format!("lightshuttle-cli-archive-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("docker");
+    std::fs::write(&script, FAKE_DOCKER).unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = std::env::var("PATH").unwrap_or_default();
+    std::env::set_var("PATH", format!("{}:{path}", dir.display()));
+}
+
+#[tokio::test]
+async fn cli_streams_archives_and_reports_failures() {
+    fake_docker();
+    let client = ShellDockerClient::default();
+
+    let archive = client.get_archive("web", "/etc/nginx").await.unwrap();
+    assert_eq!(collect_archive(archive).await.unwrap(), b"tar-bytes");
+
+    assert!(matches!(
+        client.get_archive("web", "/etc/missing").await,
+        Err(Error::NotFound(ref m)) if m.contains("/etc/missing")
+    ));
+    assert!(matches!(
+        client.get_archive("ghost", "/etc").await,
+        Err(Error::ContainerNotFound)
+    ));
+
+    // A CLI failing after some output cuts the stream short with an error.
+    let mut archive = client.get_archive("web", "/var/log").await.unwrap();
+    assert_eq!(archive.next().await.unwrap().unwrap().as_ref(), b"partial");
+    assert!(matches!(
+        archive.next().await,
+        Some(Err(Error::Unexpected(ref m))) if m.contains("unexpected EOF")
+    ));
+    assert!(archive.next().await.is_none());
+}
@@ -0,0 +1,50 @@
+#![cfg(unix)]
+
+use std::{os::unix::fs::PermissionsExt, time::Duration};
+
+use lightshuttle_core::{
+    docker::WaitCondition,
+    services::docker::{DockerClient, ShellDockerClient},
+};
+
+/// Stands in for `docker inspect` and `docker events` of a job that exited,
+/// then was removed, before `docker events` subscribed: only events replayed
+/// with `--since` show it.
+const FAKE_DOCKER: &str = r#"#!/bin/sh
+case "$1" in
+  inspect)
+    printf '%s' '[{"Id":"abc123","Name":"/job","State":{"Status":"exited","ExitCode":0},"Config":{"Image":"busybox"},"HostConfig":{},"NetworkSettings":{}}]' ;;
+  events)
+    case "$*" in
+      *--since*)
+        echo '{"Type":"container","Action":"die","Actor":{"ID":"abc123","Attributes":{"name":"job","exitCode":"3"}}}'
+        echo '{"Type":"container","Action":"destroy","Actor":{"ID":"abc123","Attributes":{"name":"job"}}}' ;;
+    esac
+    exec sleep 30 ;;
+esac
+"#;
+
+/// Puts the fake `docker` first on the `PATH` of this test process.
+fn fake_docker() {
+    let dir = std::env::temp_dir().join(format!("lightshuttle-cli-wait-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("docker");
+    std::fs::write(&script, FAKE_DOCKER).unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = std::env::var("PATH").unwrap_or_default();
+    std::env::set_var("PATH", format!("{}:{path}", dir.display()));
+}
+
+#[tokio::test]
+async fn cli_wait_sees_an_exit_before_the_subscription() {
+    fake_docker();
+    let client = ShellDockerClient::default();
+
+    for condition in [WaitCondition::NextExit, WaitCondition::Removed] {
+        let exit_code = tokio::time::timeout(Duration::from_secs(5), client.wait("job", condition))
+            .await
+            .unwrap_or_else(|_| panic!("{condition:?} wait hung"))
+            .unwrap();
+        assert_eq!(exit_code, 3, "{condition:?}");
+    }
+}
@@ -0,0 +1,202 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::sync::{Arc, OnceLock};
+#[cfg(unix)]
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::net::UnixListener,
+    path::PathBuf,
+    sync::Mutex,
+    thread,
+};
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use http_body_util::BodyExt;
+use lightshuttle_core::{api::routes::router_with_client, services::memory::InMemoryDockerClient};
+use serde_json::Value;
+#[cfg(unix)]
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+/// Router over a fresh in-memory backend.
+pub fn setup() -> (Arc<InMemoryDockerClient>, Router) {
+    let docker = Arc::new(InMemoryDockerClient::new());
+    let app = router_with_client(docker.clone());
+    (docker, app)
+}
+
+/// Like [`setup`], with API key authentication using `keys`.
+pub fn setup_with_keys(keys: &str) -> (Arc<InMemoryDockerClient>, Router) {
+    api_keys(keys);
+    setup()
+}
+
+/// Points `API_KEYS_FILE` at a file holding `keys`.
+///
+/// The key store is loaded once per process, so every test of a binary must
+/// use the same keys.
+pub fn api_keys(keys: &str) {
+    static KEYS: OnceLock<String> = OnceLock::new();
+    let loaded = KEYS.get_or_init(|| {
+        let path =
+            std::env::temp_dir().join(format!("lightshuttle-keys-{}.json", std::process::id()));
+        std::fs::write(&path, keys).unwrap();
+        std::env::set_var("API_KEYS_FILE", path);
+        keys.to_string()
+    });
+    assert_eq!(
+        loaded, keys,
+        "tests of one binary must share their API keys"
+    );
+}
+
+/// Parses a JSON response body or event payload.
+pub fn json(text: &str) -> Value {
+    serde_json::from_str(text).unwrap()
+}
+
+/// Sends a request with an optional JSON body and returns the raw response body.
+pub async fn send_text(
+    app: &Router,
+    key: Option<&str>,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, String) {
+    let mut builder = Request::builder().method(method).uri(uri);
+    if let Some(key) = key {
+        builder = builder.header("x-api-key", key);
+    }
+    let request = match body {
+        Some(json) => builder
+            .header("Content-Type", "application/json")
+            .body(Body::from(json.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+/// Sends a request without an API key; a body that is not JSON reads as `null`.
+pub async fn send(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let (status, text) = send_text(app, None, method, uri, body).await;
+    (status, serde_json::from_str(&text).unwrap_or(Value::Null))
+}
+
+/// Like [`send`], authenticated with `key`.
+pub async fn send_as(
+    app: &Router,
+    key: &str,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let (status, text) = send_text(app, Some(key), method, uri, body).await;
+    (status, serde_json::from_str(&text).unwrap_or(Value::Null))
+}
+
+/// Request captured by the stub engine.
+#[cfg(unix)]
+pub struct Captured {
+    pub method: String,
+    pub path: String,
+    /// Request headers, names lowercased.
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/// Starts a stub Engine API on a Unix socket.
+///
+/// `handler` maps `(method, path)` to `(status, body)`. A `101` status hijacks
+/// the connection like exec does: `body` is sent first, then everything the
+/// client writes is echoed back until it closes its side.
+#[cfg(unix)]
+pub fn stub_engine<F>(handler: F) -> (TempDir, PathBuf, Arc<Mutex<Vec<Captured>>>)
+where
+    F: Fn(&str, &str) -> (u16, String) + Send + 'static,
+{
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("docker.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+    let captured = Arc::new(Mutex::new(Vec::new()));
+    let log = captured.clone();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or_default().to_string();
+            let path = parts.next().unwrap_or_default().to_string();
+
+            let mut content_length = 0;
+            let mut headers = HashMap::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                    headers.insert(name.to_lowercase(), value.trim().to_string());
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let (status, response) = handler(&method, &path);
+            log.lock().unwrap().push(Captured {
+                method,
+                path,
+                headers,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            });
+
+            if status == 101 {
+                let reply = format!(
+                    "HTTP/1.1 101 UPGRADED\r\nConnection: Upgrade\r\nUpgrade: tcp\r\n\r\n{response}"
+                );
+                let _ = stream.write_all(reply.as_bytes());
+                thread::spawn(move || {
+                    let mut buf = [0; 1024];
+                    while let Ok(n) = reader.read(&mut buf) {
+                        if n == 0 || stream.write_all(&buf[..n]).is_err() {
+                            break;
+                        }
+                    }
+                });
+                continue;
+            }
+
+            let reply = format!(
+                "HTTP/1.1 {status} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            );
+            let _ = stream.write_all(reply.as_bytes());
+        }
+    });
+
+    (dir, socket, captured)
+}
@@ -0,0 +1,60 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use lightshuttle_core::{api::routes::router_with_client, services::memory::InMemoryDockerClient};
+use tower::ServiceExt;
+
+fn create_request() -> Request<Body> {
+    let payload = serde_json::json!({
+        "name": "slow",
+        "image": "nginx:latest",
+        "ports": [8080],
+        "container_port": 80,
+    });
+    Request::builder()
+        .method("POST")
+        .uri("/api/v1/apps")
+        .header("Content-Type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap()
+}
+
+async fn get_within(app: Router, uri: &str, limit: Duration) -> StatusCode {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    tokio::time::timeout(limit, app.oneshot(request))
+        .await
+        .unwrap_or_else(|_| panic!("{uri} did not answer within {limit:?}"))
+        .unwrap()
+        .status()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn health_and_metrics_stay_responsive_during_slow_docker_calls() {
+    // Every Docker call takes far longer than any timeout below.
+    let docker = InMemoryDockerClient::new().with_latency(Duration::from_secs(60));
+    let app = router_with_client(Arc::new(docker));
+
+    let pending: Vec<_> = (0..8)
+        .map(|_| tokio::spawn(app.clone().oneshot(create_request())))
+        .collect();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let limit = Duration::from_secs(1);
+    assert_eq!(
+        get_within(app.clone(), "/api/v1/health", limit).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get_within(app.clone(), "/api/v1/metrics", limit).await,
+        StatusCode::OK
+    );
+
+    for handle in pending {
+        assert!(!handle.is_finished());
+        handle.abort();
+    }
+}
@@ -1,7 +1,7 @@
 use std::env;
 
 use lightshuttle_core::{
-    docker::ContainerConfig,
+    docker::{ContainerConfig, PortMapping},
     services::docker::{DockerClient, ShellDockerClient},
 };
 
@@ -15,16 +15,16 @@ async fn test_launch_container_via_cli() {
     let config = ContainerConfig {
         name: "test-nginx-lightshuttle",
         image: "nginx:latest",
-        host_ports: &[8089],
-        container_port: 80,
+        ports: &[PortMapping::tcp(8089, 80)],
         labels: None,
         env: None,
         volumes: None,
         restart_policy: None,
+        ..Default::default()
     };
 
-    let docker = ShellDockerClient;
-    match docker.run(config) {
+    let docker = ShellDockerClient::default();
+    match docker.run(config).await {
         Ok(container_id) => {
             println!("✅ Launched container: {container_id}");
             assert!(!container_id.is_empty());
@@ -0,0 +1,348 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use lightshuttle_core::{
+    errors::Error,
+    services::{
+        docker::{client_from_env, DockerClient},
+        endpoint::{DockerEndpoint, TlsFiles},
+        engine::EngineDockerClient,
+        http::Endpoint,
+    },
+};
+use sha2::{Digest, Sha256};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+use tokio_rustls::{
+    rustls::{
+        self, pki_types::PrivateKeyDer, server::WebPkiClientVerifier, RootCertStore, ServerConfig,
+    },
+    TlsAcceptor,
+};
+
+/// Serializes tests that touch the process environment.
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+const ENV_VARS: [&str; 8] = [
+    "DOCKER_BACKEND",
+    "DOCKER_ENDPOINT",
+    "DOCKER_ENDPOINTS_FILE",
+    "DOCKER_HOST",
+    "DOCKER_TLS_VERIFY",
+    "DOCKER_CERT_PATH",
+    "DOCKER_CONTEXT",
+    "DOCKER_CONFIG",
+];
+
+/// Runs `f` with exactly the given Docker variables set.
+fn with_env<T>(vars: &[(&str, &str)], f: impl FnOnce() -> T) -> T {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|p| p.into_inner());
+    let saved: Vec<_> = ENV_VARS
+        .iter()
+        .map(|k| (k, std::env::var(k).ok()))
+        .collect();
+    for key in ENV_VARS {
+        std::env::remove_var(key);
+    }
+    for (key, value) in vars {
+        std::env::set_var(key, value);
+    }
+
+    let result = f();
+
+    for (key, value) in saved {
+        match value {
+            Some(v) => std::env::set_var(key, v),
+            None => std::env::remove_var(key),
+        }
+    }
+    result
+}
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls")
+}
+
+#[test]
+fn parses_supported_schemes() {
+    let unix = DockerEndpoint::parse("env", "unix:///run/user/1000/docker.sock", None).unwrap();
+    assert_eq!(
+        unix.address,
+        Endpoint::Unix("/run/user/1000/docker.sock".into())
+    );
+
+    let tcp = DockerEndpoint::parse("env", "tcp://10.0.0.5", None).unwrap();
+    assert_eq!(tcp.address, Endpoint::Tcp("10.0.0.5:2375".into()));
+    assert_eq!(tcp.host, "tcp://10.0.0.5:2375");
+
+    let http = DockerEndpoint::parse("env", "http://proxy:8080/", None).unwrap();
+    assert_eq!(http.address, Endpoint::Tcp("proxy:8080".into()));
+
+    let tls = TlsFiles::from_dir(&fixtures()).unwrap();
+    let secure = DockerEndpoint::parse("env", "tcp://10.0.0.5", Some(tls.clone())).unwrap();
+    assert_eq!(secure.address, Endpoint::Tcp("10.0.0.5:2376".into()));
+    let https = DockerEndpoint::parse("env", "https://[::1]:3376", Some(tls)).unwrap();
+    assert_eq!(https.address, Endpoint::Tcp("[::1]:3376".into()));
+}
+
+#[test]
+fn rejects_invalid_endpoints() {
+    let tls = TlsFiles::from_dir(&fixtures()).unwrap();
+    for (host, tls) in [
+        ("ssh://user@host", None),
+        ("/var/run/docker.sock", None),
+        ("unix://", None),
+        ("tcp://host/path", None),
+        ("https://host", None),
+        ("http://host", Some(tls.clone())),
+        ("unix:///var/run/docker.sock", Some(tls)),
+    ] {
+        let err = DockerEndpoint::parse("env", host, tls).unwrap_err();
+        assert!(matches!(err, Error::InvalidRequest(_)), "{host}");
+    }
+}
+
+#[test]
+fn resolves_nothing_without_configuration() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().to_str().unwrap();
+    let resolved = with_env(&[("DOCKER_CONFIG", config)], DockerEndpoint::resolve).unwrap();
+    assert_eq!(resolved, None);
+}
+
+#[test]
+fn unknown_backends_are_rejected() {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().to_str().unwrap();
+    let client = |backend| {
+        with_env(
+            &[("DOCKER_BACKEND", backend), ("DOCKER_CONFIG", config)],
+            client_from_env,
+        )
+    };
+
+    for backend in ["dokcer", "Engine"] {
+        let err = client(backend).err().unwrap();
+        assert!(
+            matches!(err, Error::InvalidRequest(ref m) if m.contains(&format!("'{backend}'"))),
+            "{backend}: {err}"
+        );
+    }
+    for (backend, expected) in [("", "cli"), ("cli", "cli"), ("engine", "engine")] {
+        assert_eq!(client(backend).unwrap().describe().backend, expected);
+    }
+}
+
+#[test]
+fn docker_host_honors_tls_variables() {
+    let certs = fixtures();
+    let resolved = with_env(
+        &[
+            ("DOCKER_HOST", "tcp://docker.example.com"),
+            ("DOCKER_TLS_VERIFY", "1"),
+            ("DOCKER_CERT_PATH", certs.to_str().unwrap()),
+        ],
+        DockerEndpoint::resolve,
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(resolved.name, "env");
+    assert_eq!(resolved.host, "tcp://docker.example.com:2376");
+    let tls = resolved.tls.unwrap();
+    assert_eq!(tls.ca, certs.join("ca.pem"));
+    assert_eq!(
+        tls.client,
+        Some((certs.join("cert.pem"), certs.join("key.pem")))
+    );
+}
+
+#[test]
+fn missing_certificates_are_reported() {
+    let empty = tempfile::tempdir().unwrap();
+    let err = with_env(
+        &[
+            ("DOCKER_HOST", "tcp://docker.example.com"),
+            ("DOCKER_TLS_VERIFY", "1"),
+            ("DOCKER_CERT_PATH", empty.path().to_str().unwrap()),
+        ],
+        DockerEndpoint::resolve,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("ca.pem"), "{err}");
+}
+
+#[test]
+fn named_endpoints_are_selected_by_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("endpoints.json");
+    let config = serde_json::json!({
+        "local": { "host": "unix:///var/run/docker.sock" },
+        "prod": { "host": "tcp://10.0.0.5", "tls_verify": true, "cert_path": fixtures() }
+    });
+    std::fs::write(&file, config.to_string()).unwrap();
+    let file = file.to_str().unwrap();
+
+    let prod = with_env(
+        &[
+            ("DOCKER_ENDPOINTS_FILE", file),
+            ("DOCKER_ENDPOINT", "prod"),
+            // A named endpoint wins over DOCKER_HOST.
+            ("DOCKER_HOST", "unix:///other.sock"),
+        ],
+        DockerEndpoint::resolve,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(prod.name, "prod");
+    assert_eq!(prod.host, "tcp://10.0.0.5:2376");
+    assert!(prod.tls.is_some());
+
+    let err = with_env(
+        &[
+            ("DOCKER_ENDPOINTS_FILE", file),
+            ("DOCKER_ENDPOINT", "staging"),
+        ],
+        DockerEndpoint::resolve,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("staging"), "{err}");
+}
+
+#[test]
+fn docker_contexts_are_resolved() {
+    let config = tempfile::tempdir().unwrap();
+    let digest = format!("{:x}", Sha256::digest(b"remote"));
+    let meta_dir = config.path().join("contexts/meta").join(&digest);
+    std::fs::create_dir_all(&meta_dir).unwrap();
+    std::fs::write(
+        meta_dir.join("meta.json"),
+        r#"{"Name":"remote","Endpoints":{"docker":{"Host":"tcp://build.internal:2376","SkipTLSVerify":false}}}"#,
+    )
+    .unwrap();
+    let tls_dir = config
+        .path()
+        .join("contexts/tls")
+        .join(&digest)
+        .join("docker");
+    std::fs::create_dir_all(&tls_dir).unwrap();
+    for file in ["ca.pem", "cert.pem", "key.pem"] {
+        std::fs::copy(fixtures().join(file), tls_dir.join(file)).unwrap();
+    }
+    std::fs::write(
+        config.path().join("config.json"),
+        r#"{"currentContext":"remote"}"#,
+    )
+    .unwrap();
+    let config_dir = config.path().to_str().unwrap();
+
+    let current = with_env(&[("DOCKER_CONFIG", config_dir)], DockerEndpoint::resolve)
+        .unwrap()
+        .unwrap();
+    assert_eq!(current.name, "remote");
+    assert_eq!(current.host, "tcp://build.internal:2376");
+    assert!(current.tls.is_some());
+
+    let err = with_env(
+        &[("DOCKER_CONFIG", config_dir), ("DOCKER_CONTEXT", "missing")],
+        DockerEndpoint::resolve,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("missing"), "{err}");
+}
+
+/// Starts a TLS stub engine requiring client certificates signed by the fixture CA.
+///
+/// Every request is answered with an empty container list.
+async fn tls_stub_engine() -> (String, Arc<Mutex<usize>>) {
+    let certs = fixtures();
+    let load = |name: &str| {
+        let pem = std::fs::read(certs.join(name)).unwrap();
+        rustls_pemfile::certs(&mut pem.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    };
+    let key_pem = std::fs::read(certs.join("server-key.pem")).unwrap();
+    let key: PrivateKeyDer = rustls_pemfile::private_key(&mut key_pem.as_slice())
+        .unwrap()
+        .unwrap();
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut roots = RootCertStore::empty();
+    for cert in load("ca.pem") {
+        roots.add(cert).unwrap();
+    }
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .unwrap();
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(load("server.pem"), key)
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let served = Arc::new(Mutex::new(0));
+    let count = served.clone();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let Ok(mut tls) = acceptor.accept(stream).await else {
+                continue;
+            };
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match tls.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            *count.lock().unwrap() += 1;
+            let reply = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]";
+            let _ = tls.write_all(reply.as_bytes()).await;
+            let _ = tls.shutdown().await;
+        }
+    });
+
+    (addr, served)
+}
+
+#[tokio::test]
+async fn engine_client_uses_mutual_tls() {
+    let (addr, served) = tls_stub_engine().await;
+    let tls = TlsFiles::from_dir(&fixtures()).unwrap();
+    let endpoint = DockerEndpoint::parse("secure", &format!("tcp://{addr}"), Some(tls)).unwrap();
+
+    let client = EngineDockerClient::connect(endpoint).unwrap();
+    assert!(client.list(false).await.unwrap().is_empty());
+    assert_eq!(*served.lock().unwrap(), 1);
+
+    let info = client.describe();
+    assert_eq!(info.backend, "engine");
+    let endpoint = info.endpoint.unwrap();
+    assert_eq!(endpoint.name, "secure");
+    assert!(endpoint.tls);
+}
+
+#[tokio::test]
+async fn engine_client_without_client_certificate_is_rejected() {
+    let (addr, served) = tls_stub_engine().await;
+    let tls = TlsFiles {
+        ca: fixtures().join("ca.pem"),
+        client: None,
+    };
+    let endpoint = DockerEndpoint::parse("secure", &format!("tcp://{addr}"), Some(tls)).unwrap();
+
+    let client = EngineDockerClient::connect(endpoint).unwrap();
+    let err = client.list(false).await.unwrap_err();
+    assert!(matches!(err, Error::DockerCommandFailed), "{err:?}");
+    assert_eq!(*served.lock().unwrap(), 0);
+}
@@ -0,0 +1,1295 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use common::stub_engine;
+use lightshuttle_core::{
+    docker::{
+        recreate_container, wait_container, BuildOptions, ContainerConfig, ExecConfig, HealthCheck,
+        HealthStatus, NetworkAttachment, NetworkConfig, PortMapping, RegistryAuth, SecurityOptions,
+        VolumeConfig, WaitCondition,
+    },
+    errors::Error,
+    services::{
+        docker::{collect_archive, DockerClient, ExecInput},
+        engine::{demux, demux_split, rfc3339, stats_from_json, EngineDockerClient},
+        http::Endpoint,
+        seccomp::SeccompProfile,
+        timeout::{TimeoutDockerClient, Timeouts},
+    },
+};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+const NGINX_PORTS: &[PortMapping] = &[PortMapping::tcp(8080, 80)];
+
+fn nginx_config<'a>() -> ContainerConfig<'a> {
+    ContainerConfig {
+        name: "web",
+        image: "nginx:latest",
+        ports: NGINX_PORTS,
+        labels: None,
+        env: None,
+        volumes: None,
+        restart_policy: Some("always"),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn run_creates_and_starts_container() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.contains("/containers/create") {
+            (201, r#"{"Id":"abc123","Warnings":[]}"#.into())
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let id = client.run(nginx_config()).await.unwrap();
+    assert_eq!(id, "abc123");
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].method, "POST");
+    assert_eq!(calls[0].path, "/v1.41/containers/create?name=web");
+    let body: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(body["Image"], "nginx:latest");
+    assert_eq!(
+        body["HostConfig"]["PortBindings"]["80/tcp"][0]["HostPort"],
+        "8080"
+    );
+    assert_eq!(body["HostConfig"]["RestartPolicy"]["Name"], "always");
+    assert_eq!(calls[1].path, "/v1.41/containers/abc123/start");
+}
+
+#[tokio::test]
+async fn run_pulls_missing_image_then_retries() {
+    let creates = Arc::new(Mutex::new(0));
+    let counter = creates.clone();
+    let (_dir, socket, captured) = stub_engine(move |_, path| {
+        if path.contains("/containers/create") {
+            let mut n = counter.lock().unwrap();
+            *n += 1;
+            if *n == 1 {
+                (404, r#"{"message":"No such image: nginx:latest"}"#.into())
+            } else {
+                (201, r#"{"Id":"def456"}"#.into())
+            }
+        } else if path.contains("/images/create") {
+            (200, r#"{"status":"Pulling from library/nginx"}"#.into())
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    assert_eq!(client.run(nginx_config()).await.unwrap(), "def456");
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(
+        calls[1].path,
+        "/v1.41/images/create?fromImage=nginx%3Alatest"
+    );
+    assert_eq!(calls.len(), 4);
+}
+
+#[tokio::test]
+async fn run_reports_name_conflict() {
+    let (_dir, socket, _) = stub_engine(|_, _| {
+        (
+            409,
+            r#"{"message":"Conflict. The container name \"/web\" is already in use"}"#.into(),
+        )
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    match client.run(nginx_config()).await {
+        Err(Error::Conflict(msg)) => assert!(msg.contains("already in use")),
+        other => panic!("expected conflict, got {:?}", other.err()),
+    }
+}
+
+#[tokio::test]
+async fn start_missing_container_is_not_found() {
+    let (_dir, socket, _) =
+        stub_engine(|_, _| (404, r#"{"message":"No such container: ghost"}"#.into()));
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    assert!(matches!(
+        client.start("ghost").await,
+        Err(Error::ContainerNotFound)
+    ));
+}
+
+#[tokio::test]
+async fn stop_already_stopped_container_succeeds() {
+    let (_dir, socket, captured) = stub_engine(|_, _| (304, String::new()));
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    client.stop("web").await.unwrap();
+    assert_eq!(
+        captured.lock().unwrap()[0].path,
+        "/v1.41/containers/web/stop"
+    );
+}
+
+#[tokio::test]
+async fn inspect_returns_typed_container() {
+    let (_dir, socket, _) = stub_engine(|_, _| {
+        (
+            200,
+            r#"{
+                "Id": "abc123",
+                "Name": "/web",
+                "Created": "2025-08-01T10:00:00Z",
+                "State": {"Status": "running", "Running": true, "ExitCode": 0},
+                "Config": {"Image": "nginx:latest", "Env": ["A=1"], "Labels": null},
+                "HostConfig": {"Binds": null, "RestartPolicy": {"Name": "always"}},
+                "NetworkSettings": {"Ports": {"80/tcp": [{"HostIp": "0.0.0.0", "HostPort": "8080"}]}}
+            }"#
+            .into(),
+        )
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let container = client.inspect("web").await.unwrap();
+    assert_eq!(container.name, "/web");
+    assert_eq!(container.state.status, "running");
+    assert_eq!(container.config.image, "nginx:latest");
+    assert_eq!(container.host_config.restart_policy.name, "always");
+    let ports = container.network_settings.ports.unwrap();
+    assert_eq!(ports["80/tcp"].as_ref().unwrap()[0].host_port, "8080");
+}
+
+#[tokio::test]
+async fn stats_are_computed_like_docker_stats() {
+    let (_dir, socket, captured) = stub_engine(|_, _| {
+        (
+            200,
+            r#"{
+                "cpu_stats": {"cpu_usage": {"total_usage": 400000000}, "system_cpu_usage": 20000000000, "online_cpus": 4},
+                "precpu_stats": {"cpu_usage": {"total_usage": 200000000}, "system_cpu_usage": 18000000000},
+                "memory_stats": {"usage": 54525952, "limit": 1073741824, "stats": {"inactive_file": 2097152}},
+                "networks": {"eth0": {"rx_bytes": 1000, "tx_bytes": 200}, "eth1": {"rx_bytes": 24, "tx_bytes": 56}},
+                "blkio_stats": {"io_service_bytes_recursive": [
+                    {"major": 8, "minor": 0, "op": "read", "value": 4096},
+                    {"major": 8, "minor": 0, "op": "write", "value": 8192},
+                    {"major": 8, "minor": 0, "op": "Total", "value": 12288}
+                ]},
+                "pids_stats": {"current": 7}
+            }"#
+            .into(),
+        )
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let stats = client.stats("web").await.unwrap();
+    assert!((stats.cpu_percent - 40.0).abs() < 1e-9);
+    assert_eq!(stats.memory_usage, 50 * 1024 * 1024);
+    assert_eq!(stats.memory_limit, 1024 * 1024 * 1024);
+    assert_eq!((stats.network_rx, stats.network_tx), (1024, 256));
+    assert_eq!((stats.block_read, stats.block_write), (4096, 8192));
+    assert_eq!(stats.pids, 7);
+    assert_eq!(
+        captured.lock().unwrap()[0].path,
+        "/v1.41/containers/web/stats?stream=false"
+    );
+}
+
+#[test]
+fn stats_of_a_stopped_container_are_zero() {
+    let raw: Value = serde_json::from_str(
+        r#"{"cpu_stats": {"cpu_usage": {"total_usage": 0}}, "precpu_stats": {"cpu_usage": {"total_usage": 0}},
+            "memory_stats": {}, "pids_stats": {}, "blkio_stats": {"io_service_bytes_recursive": null}}"#,
+    )
+    .unwrap();
+    assert_eq!(stats_from_json(&raw), Default::default());
+}
+
+#[tokio::test]
+async fn unreachable_socket_reports_command_failure() {
+    let client = EngineDockerClient::new(Endpoint::Unix("/nonexistent/docker.sock".into()));
+    assert!(matches!(
+        client.inspect("web").await,
+        Err(Error::DockerCommandFailed)
+    ));
+}
+
+#[tokio::test]
+async fn list_maps_engine_summaries() {
+    let (_dir, socket, captured) = stub_engine(|_, _| {
+        (
+            200,
+            r#"[{"Id":"abc","Names":["/web"],"Image":"nginx","State":"running","Status":"Up 1 minute",
+                "Ports":[{"PrivatePort":80,"PublicPort":8080,"Type":"tcp"},{"PrivatePort":443,"Type":"tcp"}],
+                "Labels":{"tier":"front"}}]"#
+                .into(),
+        )
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let containers = client.list(false).await.unwrap();
+    assert_eq!(containers.len(), 1);
+    assert_eq!(containers[0].name, "web");
+    assert_eq!(containers[0].ports, vec![8080]);
+    assert_eq!(containers[0].labels["tier"], "front");
+    assert_eq!(
+        captured.lock().unwrap()[0].path,
+        "/v1.41/containers/json?all=false"
+    );
+}
+
+#[tokio::test]
+async fn remove_missing_container_is_not_found() {
+    let (_dir, socket, captured) =
+        stub_engine(|_, _| (404, r#"{"message":"No such container: ghost"}"#.into()));
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    assert!(matches!(
+        client.remove("ghost").await,
+        Err(Error::ContainerNotFound)
+    ));
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].method, "DELETE");
+    assert_eq!(calls[0].path, "/v1.41/containers/ghost?force=true");
+}
+
+#[test]
+fn demux_strips_stream_headers() {
+    let mut framed = vec![1, 0, 0, 0, 0, 0, 0, 6];
+    framed.extend_from_slice(b"hello\n");
+    framed.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 5]);
+    framed.extend_from_slice(b"oops\n");
+
+    assert_eq!(demux(&framed), b"hello\noops\n");
+    assert_eq!(demux(b"raw tty output"), b"raw tty output");
+}
+
+#[tokio::test]
+async fn events_are_streamed_until_the_engine_closes() {
+    let (_dir, socket, captured) = stub_engine(|_, _| {
+        (
+            200,
+            concat!(
+                r#"{"Type":"container","Action":"start","Actor":{"ID":"abc","Attributes":{"name":"web"}}}"#,
+                "\n",
+                r#"{"Type":"network","Action":"connect","Actor":{"ID":"net","Attributes":{"name":"bridge"}}}"#,
+                "\n",
+                r#"{"Type":"container","Action":"rename","Actor":{"ID":"abc","Attributes":{"name":"api","oldName":"/web"}}}"#,
+                "\n"
+            )
+            .into(),
+        )
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let result = client.events(tx).await;
+    assert!(matches!(result, Err(Error::DockerCommandFailed)));
+
+    let start = rx.recv().await.unwrap();
+    assert_eq!(
+        (start.name.as_str(), start.action.as_str()),
+        ("web", "start")
+    );
+    let rename = rx.recv().await.unwrap();
+    assert_eq!(rename.name, "api");
+    assert_eq!(rename.old_name.as_deref(), Some("web"));
+    assert!(rx.recv().await.is_none());
+
+    assert_eq!(
+        captured.lock().unwrap()[0].path,
+        "/v1.41/events?filters=%7B%22type%22%3A%5B%22container%22%5D%7D"
+    );
+}
+
+fn exec_config(tty: bool) -> ExecConfig {
+    ExecConfig {
+        cmd: vec!["ls".into(), "-l".into()],
+        env: HashMap::from([("LANG".into(), "C".into())]),
+        working_dir: Some("/srv".into()),
+        user: Some("www-data".into()),
+        tty,
+    }
+}
+
+#[test]
+fn demux_split_separates_stderr() {
+    let raw = b"\x01\0\0\0\0\0\0\x03out\x02\0\0\0\0\0\0\x03err\x01\0\0\0\0\0\0\x01!";
+    let (stdout, stderr) = demux_split(raw);
+    assert_eq!(stdout, b"out!");
+    assert_eq!(stderr, b"err");
+    assert_eq!(demux_split(b"tty output").0, b"tty output");
+}
+
+#[tokio::test]
+async fn exec_collects_output_and_exit_code() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/containers/web/exec") {
+            (201, r#"{"Id":"e1"}"#.into())
+        } else if path.ends_with("/exec/e1/start") {
+            (
+                200,
+                "\u{1}\0\0\0\0\0\0\u{3}out\u{2}\0\0\0\0\0\0\u{3}err".into(),
+            )
+        } else {
+            (200, r#"{"Running":false,"ExitCode":3}"#.into())
+        }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let output = client.exec("web", &exec_config(false)).await.unwrap();
+    assert_eq!(output.stdout, "out");
+    assert_eq!(output.stderr, "err");
+    assert_eq!(output.exit_code, 3);
+
+    let calls = captured.lock().unwrap();
+    let paths: Vec<&str> = calls.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/v1.41/containers/web/exec",
+            "/v1.41/exec/e1/start",
+            "/v1.41/exec/e1/json"
+        ]
+    );
+    let body: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(body["Cmd"], serde_json::json!(["ls", "-l"]));
+    assert_eq!(body["Env"], serde_json::json!(["LANG=C"]));
+    assert_eq!(body["WorkingDir"], "/srv");
+    assert_eq!(body["User"], "www-data");
+    assert_eq!(body["AttachStdin"], false);
+}
+
+#[tokio::test]
+async fn exec_in_stopped_container_is_a_conflict() {
+    let (_dir, socket, _) =
+        stub_engine(|_, _| (409, r#"{"message":"Container abc is not running"}"#.into()));
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let err = client.exec("web", &exec_config(false)).await.unwrap_err();
+    assert!(matches!(err, Error::Conflict(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn exec_session_relays_stdin_and_resizes() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/exec") {
+            (201, r#"{"Id":"e1"}"#.into())
+        } else if path.ends_with("/start") {
+            (101, "$ ".into())
+        } else if path.contains("/resize") {
+            (200, String::new())
+        } else {
+            (200, r#"{"Running":false,"ExitCode":0}"#.into())
+        }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let mut session = client
+        .exec_session("web", &exec_config(true))
+        .await
+        .unwrap();
+    assert_eq!(&session.output.recv().await.unwrap()[..], b"$ ");
+
+    session
+        .input
+        .send(ExecInput::Resize {
+            cols: 120,
+            rows: 40,
+        })
+        .await
+        .unwrap();
+    session
+        .input
+        .send(ExecInput::Data("whoami\n".into()))
+        .await
+        .unwrap();
+    assert_eq!(&session.output.recv().await.unwrap()[..], b"whoami\n");
+
+    drop(session.input);
+    while session.output.recv().await.is_some() {}
+    assert_eq!(session.exit.await.unwrap().unwrap(), 0);
+
+    let calls = captured.lock().unwrap();
+    let body: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(body["Tty"], true);
+    assert_eq!(body["AttachStdin"], true);
+    assert!(calls
+        .iter()
+        .any(|c| c.path == "/v1.41/exec/e1/resize?h=40&w=120"));
+}
+
+#[test]
+fn rfc3339_formats_unix_timestamps() {
+    assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+    assert_eq!(rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+    assert_eq!(rfc3339(1_718_793_045), "2024-06-19T10:30:45Z");
+}
+
+#[tokio::test]
+async fn list_images_maps_engine_summaries() {
+    let (_dir, socket, captured) = stub_engine(|_, _| {
+        (
+            200,
+            r#"[{"Id":"sha256:abc","RepoTags":["nginx:latest"],"Size":1024,"Created":1718793045},
+                {"Id":"sha256:def","RepoTags":["<none>:<none>"],"Size":10,"Created":0}]"#
+                .into(),
+        )
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let images = client.list_images().await.unwrap();
+    assert_eq!(images[0].tags, ["nginx:latest"]);
+    assert_eq!(images[0].size, 1024);
+    assert_eq!(images[0].created, "2024-06-19T10:30:45Z");
+    assert!(images[1].tags.is_empty());
+    assert_eq!(captured.lock().unwrap()[0].path, "/v1.41/images/json");
+}
+
+#[tokio::test]
+async fn missing_image_is_image_not_found() {
+    let (_dir, socket, captured) =
+        stub_engine(|_, _| (404, r#"{"message":"No such image: ghost:latest"}"#.into()));
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    assert!(matches!(
+        client.inspect_image("ghost:latest").await,
+        Err(Error::ImageNotFound)
+    ));
+    assert!(matches!(
+        client.remove_image("ghost:latest").await,
+        Err(Error::ImageNotFound)
+    ));
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].path, "/v1.41/images/ghost%3Alatest/json");
+    assert_eq!(calls[1].method, "DELETE");
+    assert_eq!(calls[1].path, "/v1.41/images/ghost%3Alatest");
+}
+
+#[tokio::test]
+async fn prune_images_reports_removed_images() {
+    let (_dir, socket, captured) = stub_engine(|_, _| {
+        (
+            200,
+            r#"{"ImagesDeleted":[{"Untagged":"redis:7"},{"Deleted":"sha256:abc"}],"SpaceReclaimed":2048}"#
+                .into(),
+        )
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let report = client.prune_images(true).await.unwrap();
+    assert_eq!(report.deleted, ["redis:7", "sha256:abc"]);
+    assert_eq!(report.space_reclaimed, 2048);
+    assert_eq!(
+        captured.lock().unwrap()[0].path,
+        "/v1.41/images/prune?filters=%7B%22dangling%22%3A%5B%22false%22%5D%7D"
+    );
+}
+
+#[tokio::test]
+async fn pull_image_forwards_progress() {
+    let (_dir, socket, captured) = stub_engine(|_, _| {
+        (
+            200,
+            [
+                r#"{"status":"Pulling from library/redis","id":"7"}"#,
+                r#"{"status":"Downloading","progressDetail":{"current":512,"total":1024},"id":"a1b2c3d4e5f6"}"#,
+                r#"{"status":"Status: Downloaded newer image for redis:7"}"#,
+            ]
+            .join("\n"),
+        )
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let (tx, mut rx) = mpsc::channel(16);
+    client.pull_image("redis", None, tx).await.unwrap();
+
+    let mut steps = Vec::new();
+    while let Some(step) = rx.recv().await {
+        steps.push(step);
+    }
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[1].layer.as_deref(), Some("a1b2c3d4e5f6"));
+    assert_eq!(steps[1].current, Some(512));
+    assert_eq!(steps[1].total, Some(1024));
+    assert_eq!(
+        captured.lock().unwrap()[0].path,
+        "/v1.41/images/create?fromImage=redis&tag=latest"
+    );
+}
+
+#[tokio::test]
+async fn pull_image_reports_errors_from_the_stream() {
+    let (_dir, socket, _) = stub_engine(|_, _| {
+        (
+            200,
+            r#"{"status":"Pulling from acme/app"}
+{"error":"manifest for acme/app:2 not found"}"#
+                .into(),
+        )
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let (tx, _rx) = mpsc::channel(16);
+    let err = client.pull_image("acme/app:2", None, tx).await.unwrap_err();
+    assert!(matches!(err, Error::Unexpected(ref m) if m.contains("manifest")));
+}
+
+#[tokio::test]
+async fn pull_image_presents_registry_credentials() {
+    let (_dir, socket, captured) =
+        stub_engine(|_, _| (200, r#"{"status":"Pulling from acme/app"}"#.into()));
+
+    let auth = RegistryAuth {
+        username: "ci".into(),
+        password: "s3cret".into(),
+        server_address: "ghcr.io".into(),
+    };
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let (tx, _rx) = mpsc::channel(16);
+    client
+        .pull_image("ghcr.io/acme/app:1.0", Some(&auth), tx)
+        .await
+        .unwrap();
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].headers["x-registry-auth"], auth.header());
+    assert!(!format!("{auth:?}").contains("s3cret"));
+}
+
+#[tokio::test]
+async fn build_image_streams_output_and_returns_the_image_id() {
+    let (_dir, socket, captured) = stub_engine(|_, _| {
+        (
+            200,
+            [
+                r#"{"stream":"Step 1/2 : FROM ghcr.io/acme/base:1\n"}"#,
+                r#"{"stream":" ---\u003e 4f2a\nStep 2/2 : RUN make\n"}"#,
+                r#"{"aux":{"ID":"sha256:9c1d"}}"#,
+                r#"{"stream":"Successfully tagged api:dev\n"}"#,
+            ]
+            .join("\n"),
+        )
+    });
+
+    let auth = RegistryAuth {
+        username: "ci".into(),
+        password: "s3cret".into(),
+        server_address: "ghcr.io".into(),
+    };
+    let opts = BuildOptions {
+        tag: "api:dev".into(),
+        dockerfile: "docker/Dockerfile".into(),
+        target: Some("release".into()),
+        build_args: BTreeMap::from([("VERSION".to_string(), "1.2".to_string())]),
+    };
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let (tx, mut rx) = mpsc::channel(16);
+    let id = client
+        .build_image("context".into(), &opts, std::slice::from_ref(&auth), tx)
+        .await
+        .unwrap();
+    assert_eq!(id, "sha256:9c1d");
+
+    let mut lines = Vec::new();
+    while let Some(line) = rx.recv().await {
+        lines.push(line);
+    }
+    assert_eq!(
+        lines,
+        [
+            "Step 1/2 : FROM ghcr.io/acme/base:1",
+            " ---> 4f2a",
+            "Step 2/2 : RUN make",
+            "Successfully tagged api:dev"
+        ]
+    );
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(
+        calls[0].path,
+        "/v1.41/build?t=api%3Adev&dockerfile=docker%2FDockerfile&buildargs=%7B%22VERSION%22%3A%221.2%22%7D&target=release"
+    );
+    assert_eq!(calls[0].headers["content-type"], "application/x-tar");
+    assert_eq!(calls[0].body, "context");
+    assert_eq!(
+        calls[0].headers["x-registry-config"],
+        RegistryAuth::config_header(&[auth])
+    );
+}
+
+#[tokio::test]
+async fn build_image_reports_failures_from_the_stream() {
+    let (_dir, socket, _) = stub_engine(|_, _| {
+        (
+            200,
+            r#"{"stream":"Step 1/2 : RUN false\n"}
+{"errorDetail":{"code":1},"error":"The command '/bin/sh -c false' returned a non-zero code: 1"}"#
+                .into(),
+        )
+    });
+
+    let opts = BuildOptions {
+        tag: "api:dev".into(),
+        dockerfile: "Dockerfile".into(),
+        ..Default::default()
+    };
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let (tx, _rx) = mpsc::channel(16);
+    let err = client
+        .build_image("context".into(), &opts, &[], tx)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Unexpected(ref m) if m.contains("non-zero code: 1")));
+
+    let invalid = BuildOptions {
+        dockerfile: "../Dockerfile".into(),
+        ..opts
+    };
+    let (tx, _rx) = mpsc::channel(16);
+    assert!(matches!(
+        client
+            .build_image("context".into(), &invalid, &[], tx)
+            .await,
+        Err(Error::BadRequest(_))
+    ));
+}
+
+#[tokio::test]
+async fn archive_copies_files_to_and_from_containers() {
+    let (_dir, socket, captured) = stub_engine(|method, path| match method {
+        "GET" if path.contains("path=%2Fetc%2Fmissing") => (
+            404,
+            r#"{"message":"Could not find the file /etc/missing in container web"}"#.into(),
+        ),
+        "GET" if path.contains("/containers/ghost/") => {
+            (404, r#"{"message":"No such container: ghost"}"#.into())
+        }
+        "GET" => (200, "tar-bytes".into()),
+        _ => (
+            403,
+            r#"{"message":"container rootfs is marked read-only"}"#.into(),
+        ),
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let archive = client.get_archive("web", "/etc/nginx").await.unwrap();
+    assert_eq!(collect_archive(archive).await.unwrap(), b"tar-bytes");
+    assert!(matches!(
+        client.get_archive("web", "/etc/missing").await,
+        Err(Error::NotFound(ref m)) if m.contains("/etc/missing")
+    ));
+    assert!(matches!(
+        client.get_archive("ghost", "/etc").await,
+        Err(Error::ContainerNotFound)
+    ));
+    assert!(matches!(
+        client.put_archive("web", "/srv", "upload".into()).await,
+        Err(Error::Conflict(_))
+    ));
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].method, "GET");
+    assert_eq!(
+        calls[0].path,
+        "/v1.41/containers/web/archive?path=%2Fetc%2Fnginx"
+    );
+    let put = calls.last().unwrap();
+    assert_eq!(put.method, "PUT");
+    assert_eq!(put.path, "/v1.41/containers/web/archive?path=%2Fsrv");
+    assert_eq!(put.headers["content-type"], "application/x-tar");
+    assert_eq!(put.body, "upload");
+}
+
+#[tokio::test]
+async fn run_attaches_every_network_before_starting() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.contains("/containers/create") {
+            (201, r#"{"Id":"abc123","Warnings":[]}"#.into())
+        } else {
+            (200, String::new())
+        }
+    });
+
+    let networks = [
+        NetworkAttachment {
+            name: "backend".into(),
+            aliases: vec!["db".into()],
+        },
+        NetworkAttachment {
+            name: "monitoring".into(),
+            aliases: vec!["pg".into()],
+        },
+    ];
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let config = ContainerConfig {
+        networks: &networks,
+        ..nginx_config()
+    };
+    client.run(config).await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    let body: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(body["HostConfig"]["NetworkMode"], "backend");
+    assert_eq!(
+        body["NetworkingConfig"]["EndpointsConfig"],
+        serde_json::json!({"backend": {"Aliases": ["db"]}})
+    );
+    assert_eq!(calls[1].path, "/v1.41/networks/monitoring/connect");
+    let connect: Value = serde_json::from_str(&calls[1].body).unwrap();
+    assert_eq!(
+        connect,
+        serde_json::json!({"Container": "abc123", "EndpointConfig": {"Aliases": ["pg"]}})
+    );
+    assert_eq!(calls[2].path, "/v1.41/containers/abc123/start");
+}
+
+#[tokio::test]
+async fn run_on_a_missing_network_does_not_pull() {
+    let (_dir, socket, captured) =
+        stub_engine(|_, _| (404, r#"{"message":"network backend not found"}"#.into()));
+
+    let networks = [NetworkAttachment {
+        name: "backend".into(),
+        aliases: vec![],
+    }];
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let config = ContainerConfig {
+        networks: &networks,
+        ..nginx_config()
+    };
+    assert!(matches!(
+        client.run(config).await,
+        Err(Error::NotFound(ref m)) if m.contains("backend")
+    ));
+    assert_eq!(captured.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn networks_map_to_engine_endpoints() {
+    let (_dir, socket, captured) = stub_engine(|method, path| {
+        match (method, path) {
+        ("POST", _) => (201, r#"{"Id":"net123","Warning":""}"#.into()),
+        ("GET", "/v1.41/networks") => (
+            200,
+            r#"[{"Name":"backend","Id":"net123","Created":"2024-05-01T10:00:00Z","Driver":"bridge","Internal":true,"IPAM":{"Config":[{"Subnet":"10.42.0.0/24"}]}}]"#
+                .into(),
+        ),
+        ("DELETE", _) => (
+            403,
+            r#"{"message":"error while removing network: network backend id net123 has active endpoints"}"#
+                .into(),
+        ),
+        _ => (404, r#"{"message":"network ghost not found"}"#.into()),
+    }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let cfg = NetworkConfig {
+        name: "backend".into(),
+        subnet: Some("10.42.0.0/24".into()),
+        internal: true,
+        ..Default::default()
+    };
+    assert_eq!(client.create_network(&cfg).await.unwrap(), "net123");
+    let networks = client.list_networks().await.unwrap();
+    assert_eq!(networks[0].name, "backend");
+    assert_eq!(networks[0].subnet.as_deref(), Some("10.42.0.0/24"));
+    assert!(networks[0].internal);
+    assert!(matches!(
+        client.inspect_network("ghost").await,
+        Err(Error::NotFound(_))
+    ));
+    assert!(matches!(
+        client.remove_network("backend").await,
+        Err(Error::Conflict(_))
+    ));
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].path, "/v1.41/networks/create");
+    let body: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "Name": "backend",
+            "Driver": "bridge",
+            "Internal": true,
+            "CheckDuplicate": true,
+            "IPAM": {"Config": [{"Subnet": "10.42.0.0/24"}]}
+        })
+    );
+    assert_eq!(calls[3].method, "DELETE");
+    assert_eq!(calls[3].path, "/v1.41/networks/backend");
+}
+
+#[tokio::test]
+async fn volumes_map_to_engine_endpoints() {
+    let (_dir, socket, captured) = stub_engine(|method, path| {
+        match (method, path) {
+        ("POST", _) => (
+            201,
+            r#"{"Name":"pgdata","Driver":"local","Mountpoint":"/var/lib/docker/volumes/pgdata/_data","CreatedAt":"2024-05-01T10:00:00Z","Labels":{"team":"data"},"Options":{"type":"tmpfs"},"Scope":"local"}"#
+                .into(),
+        ),
+        ("GET", "/v1.41/volumes") => (
+            200,
+            r#"{"Volumes":[{"Name":"pgdata","Driver":"local","Mountpoint":"/var/lib/docker/volumes/pgdata/_data","CreatedAt":"2024-05-01T10:00:00Z","Labels":null,"Options":null}],"Warnings":[]}"#
+                .into(),
+        ),
+        ("GET", "/v1.41/system/df") => (
+            200,
+            r#"{"Volumes":[{"Name":"pgdata","UsageData":{"Size":4096,"RefCount":1}},{"Name":"cache","UsageData":{"Size":-1,"RefCount":0}}]}"#
+                .into(),
+        ),
+        ("GET", "/v1.41/containers/json?all=1") => (
+            200,
+            r#"[{"Names":["/db"],"Mounts":[{"Type":"volume","Name":"pgdata","Destination":"/data"},{"Type":"bind","Source":"/srv","Destination":"/srv"}]}]"#
+                .into(),
+        ),
+        ("DELETE", _) => (
+            409,
+            r#"{"message":"remove pgdata: volume is in use - [abc123]"}"#.into(),
+        ),
+        _ => (404, r#"{"message":"get ghost: no such volume"}"#.into()),
+    }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let cfg = VolumeConfig {
+        name: "pgdata".into(),
+        driver_opts: BTreeMap::from([("type".into(), "tmpfs".into())]),
+        labels: BTreeMap::from([("team".into(), "data".into())]),
+        ..Default::default()
+    };
+    let volume = client.create_volume(&cfg).await.unwrap();
+    assert_eq!(volume.mountpoint, "/var/lib/docker/volumes/pgdata/_data");
+    assert_eq!(volume.created, "2024-05-01T10:00:00Z");
+    let volumes = client.list_volumes().await.unwrap();
+    assert_eq!(volumes[0].name, "pgdata");
+    assert_eq!(volumes[0].labels, None);
+
+    let usage = client.volume_usage().await.unwrap();
+    assert_eq!(usage["pgdata"].size, Some(4096));
+    assert_eq!(usage["pgdata"].apps, ["db"]);
+    assert_eq!(usage["cache"].size, None);
+    assert!(usage["cache"].apps.is_empty());
+
+    assert!(matches!(
+        client.inspect_volume("ghost").await,
+        Err(Error::NotFound(_))
+    ));
+    assert!(matches!(
+        client.remove_volume("pgdata").await,
+        Err(Error::Conflict(_))
+    ));
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].path, "/v1.41/volumes/create");
+    let body: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "Name": "pgdata",
+            "Driver": "local",
+            "DriverOpts": {"type": "tmpfs"},
+            "Labels": {"team": "data"}
+        })
+    );
+    assert_eq!(calls.last().unwrap().path, "/v1.41/volumes/pgdata");
+}
+
+#[tokio::test]
+async fn timed_out_engine_calls_are_cancelled() {
+    let (_dir, socket, _) = stub_engine(|_, _| {
+        std::thread::sleep(Duration::from_secs(2));
+        (200, "{}".into())
+    });
+    let client = TimeoutDockerClient::new(
+        Arc::new(EngineDockerClient::new(Endpoint::Unix(socket))),
+        Timeouts {
+            logs: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    );
+
+    let started = Instant::now();
+    let result = client.logs("web").await;
+    assert!(matches!(result, Err(Error::Timeout(ref op)) if op == "logs"));
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn engine_info_reads_unversioned_version_and_info() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        match path {
+        "/version" => (
+            200,
+            r#"{"Version":"20.10.24","ApiVersion":"1.41","MinAPIVersion":"1.12","Os":"linux","Arch":"amd64"}"#
+                .into(),
+        ),
+        _ => (
+            200,
+            r#"{"Driver":"overlay2","CgroupVersion":"2","SecurityOptions":["name=apparmor","name=seccomp,profile=default","name=rootless","name=cgroupns"]}"#
+                .into(),
+        ),
+    }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let info = client.engine_info().await.unwrap();
+    assert_eq!(info.version, "20.10.24");
+    assert_eq!(info.api_version, "1.41");
+    assert_eq!((info.os.as_str(), info.arch.as_str()), ("linux", "amd64"));
+    assert_eq!(info.storage_driver, "overlay2");
+    assert_eq!(info.cgroup_version.as_deref(), Some("2"));
+    assert!(info.rootless);
+    assert_eq!(
+        info.security_options,
+        ["apparmor", "seccomp", "rootless", "cgroupns"]
+    );
+    assert!(info.is_supported());
+
+    let calls = captured.lock().unwrap();
+    let paths: Vec<_> = calls.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(paths, ["/version", "/info"]);
+}
+
+#[tokio::test]
+async fn lifecycle_actions_map_to_engine_endpoints() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.contains("/kill") {
+            (409, r#"{"message":"Container web is not running"}"#.into())
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    client.pause("web").await.unwrap();
+    client.unpause("web").await.unwrap();
+    client.restart("web", Some(5)).await.unwrap();
+    client.restart("web", None).await.unwrap();
+    client.rename("web", "front").await.unwrap();
+    assert!(matches!(
+        client.kill("web", "SIGHUP").await,
+        Err(Error::Conflict(message)) if message == "Container web is not running"
+    ));
+
+    let calls = captured.lock().unwrap();
+    let calls: Vec<_> = calls
+        .iter()
+        .map(|c| (c.method.as_str(), c.path.as_str()))
+        .collect();
+    assert_eq!(
+        calls,
+        [
+            ("POST", "/v1.41/containers/web/pause"),
+            ("POST", "/v1.41/containers/web/unpause"),
+            ("POST", "/v1.41/containers/web/restart?t=5"),
+            ("POST", "/v1.41/containers/web/restart"),
+            ("POST", "/v1.41/containers/web/rename?name=front"),
+            ("POST", "/v1.41/containers/web/kill?signal=SIGHUP"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn wait_reads_exit_code_then_inspects_the_finish() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.contains("/wait") {
+            (200, r#"{"StatusCode":137,"Error":null}"#.into())
+        } else {
+            (
+                200,
+                r#"{"Id":"abc","Name":"/seed","State":{"Status":"exited","Running":false,"ExitCode":137,"OOMKilled":true,"FinishedAt":"2024-05-01T10:00:00Z"}}"#
+                    .into(),
+            )
+        }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let exit = wait_container(&client, "seed", WaitCondition::NextExit)
+        .await
+        .unwrap();
+    assert_eq!(exit.exit_code, 137);
+    assert!(exit.oom_killed);
+    assert_eq!(exit.finished_at.as_deref(), Some("2024-05-01T10:00:00Z"));
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(
+        calls[0].path,
+        "/v1.41/containers/seed/wait?condition=next-exit"
+    );
+    assert_eq!(calls[1].path, "/v1.41/containers/seed/json");
+}
+
+#[tokio::test]
+async fn recreate_keeps_resource_limits() {
+    let (_dir, socket, captured) = stub_engine(|method, path| {
+        match (method, path) {
+        ("GET", _) => (
+            200,
+            r#"{"Id":"abc","Name":"/web","Config":{"Image":"nginx:latest"},"HostConfig":{"RestartPolicy":{"Name":"always"},"NanoCpus":1500000000,"CpuShares":512,"Memory":268435456,"MemoryReservation":0,"MemorySwap":536870912,"PidsLimit":null},"NetworkSettings":{"Ports":{"80/tcp":[{"HostIp":"0.0.0.0","HostPort":"8080"}]}}}"#
+                .into(),
+        ),
+        ("POST", p) if p.contains("/containers/create") => {
+            (201, r#"{"Id":"def456","Warnings":[]}"#.into())
+        }
+        _ => (204, String::new()),
+    }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    assert_eq!(recreate_container(&client, "web").await.unwrap(), "def456");
+
+    let calls = captured.lock().unwrap();
+    let create = calls
+        .iter()
+        .find(|call| call.path.contains("/containers/create"))
+        .unwrap();
+    let body: Value = serde_json::from_str(&create.body).unwrap();
+    let host_config = &body["HostConfig"];
+    assert_eq!(host_config["NanoCpus"], 1_500_000_000);
+    assert_eq!(host_config["CpuShares"], 512);
+    assert_eq!(host_config["Memory"], 268_435_456);
+    assert_eq!(host_config["MemorySwap"], 536_870_912);
+    assert!(host_config.get("MemoryReservation").is_none());
+    assert!(host_config.get("PidsLimit").is_none());
+}
+
+#[tokio::test]
+async fn healthcheck_is_sent_and_kept_on_recreate() {
+    let (_dir, socket, captured) = stub_engine(|method, path| {
+        match (method, path) {
+        ("GET", _) => (
+            200,
+            r#"{"Id":"abc","Name":"/web","Config":{"Image":"nginx:latest","Labels":{"lightshuttle.health.restart-after":"5"},"Healthcheck":{"Test":["CMD-SHELL","curl -f localhost"],"Interval":10000000000,"Retries":4}},"State":{"Status":"running","Running":true,"Health":{"Status":"unhealthy","FailingStreak":6,"Log":[]}},"HostConfig":{"RestartPolicy":{"Name":"always"}},"NetworkSettings":{"Ports":{"80/tcp":null}}}"#
+                .into(),
+        ),
+        ("POST", p) if p.contains("/containers/create") => {
+            (201, r#"{"Id":"def456","Warnings":[]}"#.into())
+        }
+        _ => (204, String::new()),
+    }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let inspected = client.inspect("web").await.unwrap();
+    assert_eq!(
+        HealthStatus::from_state(&inspected.state),
+        Some(HealthStatus::Unhealthy)
+    );
+    let healthcheck = HealthCheck::from_config(&inspected.config).unwrap();
+    assert_eq!(
+        healthcheck,
+        HealthCheck {
+            command: "curl -f localhost".into(),
+            interval: Some(10),
+            retries: Some(4),
+            restart_after: Some(5),
+            ..Default::default()
+        }
+    );
+
+    recreate_container(&client, "web").await.unwrap();
+    let calls = captured.lock().unwrap();
+    let create = calls
+        .iter()
+        .find(|call| call.path.contains("/containers/create"))
+        .unwrap();
+    let body: Value = serde_json::from_str(&create.body).unwrap();
+    assert_eq!(
+        body["Healthcheck"],
+        serde_json::json!({
+            "Test": ["CMD-SHELL", "curl -f localhost"],
+            "Interval": 10_000_000_000_i64,
+            "Timeout": 0,
+            "StartPeriod": 0,
+            "Retries": 4,
+        })
+    );
+    assert_eq!(body["Labels"]["lightshuttle.health.restart-after"], "5");
+}
+
+#[tokio::test]
+async fn recreate_leaves_image_healthchecks_to_the_image() {
+    // A shell-form `HEALTHCHECK` from the Dockerfile, on an app created without one.
+    let (_dir, socket, captured) = stub_engine(|method, path| {
+        match (method, path) {
+        ("GET", _) => (
+            200,
+            r#"{"Id":"abc","Name":"/web","Config":{"Image":"nginx:latest","Healthcheck":{"Test":["CMD-SHELL","curl -f localhost"],"Retries":3}},"State":{"Status":"running","Running":true},"HostConfig":{"RestartPolicy":{"Name":"always"}},"NetworkSettings":{"Ports":{"80/tcp":null}}}"#
+                .into(),
+        ),
+        ("POST", p) if p.contains("/containers/create") => {
+            (201, r#"{"Id":"def456","Warnings":[]}"#.into())
+        }
+        _ => (204, String::new()),
+    }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    let inspected = client.inspect("web").await.unwrap();
+    assert_eq!(HealthCheck::from_config(&inspected.config), None);
+
+    recreate_container(&client, "web").await.unwrap();
+    let calls = captured.lock().unwrap();
+    let create = calls
+        .iter()
+        .find(|call| call.path.contains("/containers/create"))
+        .unwrap();
+    let body: Value = serde_json::from_str(&create.body).unwrap();
+    assert!(body.get("Healthcheck").is_none(), "{body}");
+}
+
+#[tokio::test]
+async fn recreate_keeps_port_mappings() {
+    let (_dir, socket, captured) = stub_engine(|method, path| {
+        match (method, path) {
+        ("GET", _) => (
+            200,
+            r#"{"Id":"abc","Name":"/web","Config":{"Image":"nginx:latest"},"HostConfig":{"RestartPolicy":{"Name":"always"},"PortBindings":{"80/tcp":[{"HostIp":"","HostPort":"8080"}],"514/udp":[{"HostIp":"127.0.0.1","HostPort":"5514"}]}},"NetworkSettings":{"Ports":{"80/tcp":[{"HostIp":"0.0.0.0","HostPort":"8080"},{"HostIp":"::","HostPort":"8080"}],"514/udp":[{"HostIp":"127.0.0.1","HostPort":"5514"}]}}}"#
+                .into(),
+        ),
+        ("POST", p) if p.contains("/containers/create") => {
+            (201, r#"{"Id":"def456","Warnings":[]}"#.into())
+        }
+        _ => (204, String::new()),
+    }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    recreate_container(&client, "web").await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    let create = calls
+        .iter()
+        .find(|call| call.path.contains("/containers/create"))
+        .unwrap();
+    let body: Value = serde_json::from_str(&create.body).unwrap();
+    assert_eq!(
+        body["HostConfig"]["PortBindings"],
+        serde_json::json!({
+            "80/tcp": [{"HostPort": "8080"}],
+            "514/udp": [{"HostIp": "127.0.0.1", "HostPort": "5514"}],
+        })
+    );
+    assert_eq!(
+        body["ExposedPorts"],
+        serde_json::json!({"80/tcp": {}, "514/udp": {}})
+    );
+}
+
+#[tokio::test]
+async fn recreate_keeps_process_overrides() {
+    let (_dir, socket, captured) = stub_engine(|method, path| {
+        match (method, path) {
+        ("GET", p) if p.contains("/containers/worker/") => (
+            200,
+            r#"{"Id":"0123456789abcdef","Name":"/worker","Config":{"Image":"myapp:latest","Cmd":["bundle","exec","sidekiq"],"Entrypoint":["/usr/bin/tini","--"],"WorkingDir":"/srv/app","User":"1000:1000","Hostname":"0123456789ab","Labels":{"lightshuttle.process":"{\"command\":[\"bundle\",\"exec\",\"sidekiq\"],\"entrypoint\":[\"/usr/bin/tini\",\"--\"],\"working_dir\":\"/srv/app\",\"user\":\"1000:1000\"}"}},"HostConfig":{"RestartPolicy":{"Name":"always"}},"NetworkSettings":{"Ports":{}}}"#
+                .into(),
+        ),
+        // Without the label, everything inspect reports comes from the image.
+        ("GET", _) => (
+            200,
+            r#"{"Id":"fedcba9876543210","Name":"/web","Config":{"Image":"myapp:latest","Cmd":["puma"],"Entrypoint":["/usr/bin/tini","--"],"WorkingDir":"/srv/app","User":"app","Hostname":"fedcba987654"},"HostConfig":{"RestartPolicy":{"Name":"always"}},"NetworkSettings":{"Ports":{}}}"#
+                .into(),
+        ),
+        ("POST", p) if p.contains("/containers/create") => {
+            (201, r#"{"Id":"def456","Warnings":[]}"#.into())
+        }
+        _ => (204, String::new()),
+    }
+    });
+
+    let client = EngineDockerClient::new(Endpoint::Unix(socket));
+    recreate_container(&client, "worker").await.unwrap();
+    recreate_container(&client, "web").await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    let mut creates = calls
+        .iter()
+        .filter(|call| call.path.contains("/containers/create"))
+        .map(|call| serde_json::from_str::<Value>(&call.body).unwrap());
+    let body = creates.next().unwrap();
+    assert_eq!(
+        body["Cmd"],
+        serde_json::json!(["bundle", "exec", "sidekiq"])
+    );
+    assert_eq!(
+        body["Entrypoint"],
+        serde_json::json!(["/usr/bin/tini", "--"])
+    );
+    assert_eq!(body["WorkingDir"], "/srv/app");
+    assert_eq!(body["User"], "1000:1000");
+    // The default hostname is the short id of the old container.
+    assert!(body.get("Hostname").is_none());
+
+    // Image defaults are left to the image, which may have changed since.
+    let body = creates.next().unwrap();
+    for field in ["Cmd", "Entrypoint", "WorkingDir", "User", "Hostname"] {
+        assert!(body.get(field).is_none(), "{field}: {}", body[field]);
+    }
+}
+
+#[tokio::test]
+async fn recreate_keeps_security_options_under_the_daemon_seccomp_profile() {
+    let (_dir, socket, captured) = stub_engine(|method, path| {
+        match (method, path) {
+        ("GET", _) => (
+            200,
+            r#"{"Id":"abc","Name":"/web","Config":{"Image":"nginx:latest"},"HostConfig":{"RestartPolicy":{"Name":"always"},"CapAdd":["CAP_NET_BIND_SERVICE"],"CapDrop":["ALL"],"ReadonlyRootfs":true,"Tmpfs":{"/tmp":"size=64m"},"SecurityOpt":["seccomp={\"defaultAction\":\"SCMP_ACT_ERRNO\"}","no-new-privileges:true","apparmor=docker-default"]},"NetworkSettings":{"Ports":{}}}"#
+                .into(),
+        ),
+        ("POST", p) if p.contains("/containers/create") => {
+            (201, r#"{"Id":"def456","Warnings":[]}"#.into())
+        }
+        _ => (204, String::new()),
+    }
+    });
+
+    let client =
+        EngineDockerClient::new(Endpoint::Unix(socket)).with_seccomp(SeccompProfile::Unconfined);
+    let inspected = client.inspect("web").await.unwrap();
+    assert_eq!(
+        SecurityOptions::seccomp(&inspected.host_config).as_deref(),
+        Some("custom")
+    );
+    recreate_container(&client, "web").await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    let create = calls
+        .iter()
+        .find(|call| call.path.contains("/containers/create"))
+        .unwrap();
+    let body: Value = serde_json::from_str(&create.body).unwrap();
+    let host_config = &body["HostConfig"];
+    assert_eq!(
+        host_config["CapAdd"],
+        serde_json::json!(["CAP_NET_BIND_SERVICE"])
+    );
+    assert_eq!(host_config["CapDrop"], serde_json::json!(["ALL"]));
+    assert_eq!(host_config["ReadonlyRootfs"], true);
+    assert_eq!(
+        host_config["Tmpfs"],
+        serde_json::json!({"/tmp": "size=64m"})
+    );
+    // The old profile is replaced by the daemon's current one.
+    assert_eq!(
+        host_config["SecurityOpt"],
+        serde_json::json!([
+            "seccomp=unconfined",
+            "no-new-privileges",
+            "apparmor=docker-default"
+        ])
+    );
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use common::{send_as, setup_with_keys};
+use futures_util::{SinkExt, StreamExt};
+use http_body_util::BodyExt;
+use lightshuttle_core::services::memory::InMemoryDockerClient;
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+use tower::ServiceExt;
+
+/// `operator` may exec, `deployer` may only read and write.
+const KEYS: &str = r#"{
+    "operator": {"name": "ops", "read": true, "write": true, "exec": true},
+    "deployer": {"name": "ci", "read": true, "write": true}
+}"#;
+
+/// Router with a running `web` app.
+fn with_web() -> (Arc<InMemoryDockerClient>, Router) {
+    let (docker, app) = setup_with_keys(KEYS);
+    docker.insert("web", "nginx:latest", "running");
+    (docker, app)
+}
+
+async fn exec(app: &Router, key: &str, name: &str, body: Value) -> (StatusCode, Value) {
+    let uri = format!("/api/v1/apps/{name}/exec");
+    send_as(app, key, "POST", &uri, Some(body)).await
+}
+
+#[tokio::test]
+async fn exec_returns_output() {
+    let (docker, app) = with_web();
+
+    let payload = json!({
+        "cmd": ["ls", "-l", "/srv"],
+        "env": {"LANG": "C"},
+        "working_dir": "/srv",
+        "user": "www-data"
+    });
+    let (status, output) = exec(&app, "operator", "web", payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(output["stdout"], "ls -l /srv\n");
+    assert_eq!(output["stderr"], "");
+    assert_eq!(output["exit_code"], 0);
+
+    let execs = docker.execs("web");
+    assert_eq!(execs.len(), 1);
+    assert_eq!(execs[0].env["LANG"], "C");
+    assert_eq!(execs[0].working_dir.as_deref(), Some("/srv"));
+    assert_eq!(execs[0].user.as_deref(), Some("www-data"));
+    assert!(!execs[0].tty);
+}
+
+#[tokio::test]
+async fn exec_errors_are_mapped() {
+    let (docker, app) = with_web();
+    docker.insert("stopped", "nginx:latest", "exited");
+
+    let (status, _) = exec(&app, "operator", "ghost", json!({"cmd": ["true"]})).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = exec(&app, "operator", "stopped", json!({"cmd": ["true"]})).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = exec(&app, "operator", "web", json!({"cmd": []})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn exec_requires_the_exec_permission() {
+    let (docker, app) = with_web();
+
+    let (status, body) = exec(&app, "deployer", "web", json!({"cmd": ["id"]})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], 403);
+    assert!(docker.execs("web").is_empty());
+
+    let request = Request::builder()
+        .uri("/api/v1/apps/web/exec/ws")
+        .header("x-api-key", "deployer")
+        .header("Connection", "upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+/// `["sh","-c","exec bash -l"]`, URL-encoded.
+const ARGV: &str = "%5B%22sh%22%2C%22-c%22%2C%22exec%20bash%20-l%22%5D";
+
+#[tokio::test]
+async fn websocket_commands_must_be_json_argv() {
+    let (docker, app) = with_web();
+
+    let request = Request::builder()
+        .uri("/api/v1/apps/web/exec/ws?cmd=sh%20-l")
+        .header("x-api-key", "operator")
+        .header("Connection", "upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["details"], "cmd must be a JSON array of strings");
+    assert!(docker.execs("web").is_empty());
+}
+
+#[tokio::test]
+async fn websocket_session_is_interactive() {
+    let (docker, app) = with_web();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut request = format!("ws://{addr}/api/v1/apps/web/exec/ws?cmd={ARGV}&cols=80&rows=24")
+        .into_client_request()
+        .unwrap();
+    request
+        .headers_mut()
+        .insert("x-api-key", "operator".parse().unwrap());
+    let (mut socket, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+
+    socket
+        .send(Message::Text(
+            json!({"type": "resize", "cols": 120, "rows": 40}).to_string(),
+        ))
+        .await
+        .unwrap();
+    socket
+        .send(Message::Binary(b"echo hi\n".to_vec()))
+        .await
+        .unwrap();
+    assert_eq!(
+        socket.next().await.unwrap().unwrap(),
+        Message::Binary(b"echo hi\n".to_vec())
+    );
+    socket
+        .send(Message::Text(
+            json!({"type": "stdin", "data": "\u{4}"}).to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let Message::Text(status) = socket.next().await.unwrap().unwrap() else {
+        panic!("expected the exit status");
+    };
+    assert_eq!(
+        serde_json::from_str::<Value>(&status).unwrap(),
+        json!({"type": "exit", "exit_code": 0})
+    );
+
+    let execs = docker.execs("web");
+    assert_eq!(execs[0].cmd, ["sh", "-c", "exec bash -l"]);
+    assert!(execs[0].tty);
+    assert_eq!(docker.resizes("web"), [(80, 24), (120, 40)]);
+}
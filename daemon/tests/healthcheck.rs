@@ -0,0 +1,125 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::Router;
+use common::send;
+use lightshuttle_core::{
+    api::{routes::router_with_state, state::AppState},
+    docker::HealthCheck,
+    services::{health::HealthSupervisor, memory::InMemoryDockerClient},
+};
+use serde_json::{json, Value};
+
+fn setup() -> (Arc<InMemoryDockerClient>, Router, HealthSupervisor) {
+    let docker = Arc::new(InMemoryDockerClient::new());
+    let state = AppState::new(docker.clone());
+    let supervisor = HealthSupervisor::new(
+        docker.clone(),
+        state.locks.clone(),
+        state.health_restarts.clone(),
+    );
+    (docker, router_with_state(state), supervisor)
+}
+
+/// Like [`common::send`], for requests that must succeed.
+async fn ok(app: &Router, method: &str, uri: &str, body: Option<Value>) -> Value {
+    let (status, body) = send(app, method, uri, body).await;
+    assert!(status.is_success(), "{status}");
+    body
+}
+
+async fn create(app: &Router, name: &str, healthcheck: Value) {
+    let payload = json!({
+        "name": name,
+        "image": "nginx:latest",
+        "ports": [],
+        "container_port": 80,
+        "restart_policy": "unless-stopped",
+        "healthcheck": healthcheck,
+    });
+    ok(app, "POST", "/api/v1/apps", Some(payload)).await;
+}
+
+#[tokio::test]
+async fn unhealthy_app_is_restarted_and_the_reason_recorded() {
+    let (docker, app, supervisor) = setup();
+    create(
+        &app,
+        "web",
+        json!({"command": "curl -f localhost", "retries": 2, "restart_after": 3}),
+    )
+    .await;
+
+    let status = ok(&app, "GET", "/api/v1/apps/web/status", None).await;
+    assert_eq!(status, json!({"status": "running", "health": "starting"}));
+
+    docker.probe("web", 0, "ok");
+    let web = ok(&app, "GET", "/api/v1/apps/web", None).await;
+    assert_eq!(web["health"], "healthy");
+
+    // Unhealthy after 2 failures, restarted after 3.
+    docker.probe("web", 1, "connection refused");
+    docker.probe("web", 1, "connection refused");
+    let status = ok(&app, "GET", "/api/v1/apps/web/status", None).await;
+    assert_eq!(status["health"], "unhealthy");
+    assert!(supervisor.check().await.unwrap().is_empty());
+
+    docker.probe("web", 7, "connection refused\n");
+    assert_eq!(supervisor.check().await.unwrap(), vec!["web"]);
+
+    let status = ok(&app, "GET", "/api/v1/apps/web/status", None).await;
+    assert_eq!(status["health"], "starting");
+    let restart = &status["last_health_restart"];
+    assert_eq!(restart["failing_streak"], 3);
+    assert_eq!(restart["exit_code"], 7);
+    assert_eq!(restart["output"], "connection refused");
+    assert_eq!(restart["count"], 1);
+    assert!(restart["at"].as_str().is_some());
+
+    // The health check and its threshold survive a recreate.
+    ok(&app, "POST", "/api/v1/apps/web/recreate", None).await;
+    for _ in 0..3 {
+        docker.probe("web", 1, "still down");
+    }
+    assert_eq!(supervisor.check().await.unwrap(), vec!["web"]);
+    let status = ok(&app, "GET", "/api/v1/apps/web/status", None).await;
+    assert_eq!(status["last_health_restart"]["count"], 2);
+}
+
+#[tokio::test]
+async fn only_supervised_apps_are_restarted() {
+    let (docker, app, supervisor) = setup();
+    create(
+        &app,
+        "worker",
+        json!({"command": "pgrep worker", "restart_after": 0}),
+    )
+    .await;
+    for _ in 0..5 {
+        docker.probe("worker", 1, "");
+    }
+    // Not created by LightShuttle: left alone whatever its health.
+    docker.insert("db", "postgres:16", "running");
+
+    assert!(supervisor.check().await.unwrap().is_empty());
+    let status = ok(&app, "GET", "/api/v1/apps/worker/status", None).await;
+    assert_eq!(status["health"], "unhealthy");
+    assert!(status.get("last_health_restart").is_none());
+    let status = ok(&app, "GET", "/api/v1/apps/db/status", None).await;
+    assert_eq!(status, json!({"status": "running"}));
+}
+
+#[test]
+fn healthcheck_validation() {
+    let check = |json: Value| {
+        serde_json::from_value::<HealthCheck>(json)
+            .unwrap()
+            .validate()
+            .map_err(|e| e.to_string())
+    };
+    assert!(check(json!({"command": "true", "interval": 5, "retries": 1})).is_ok());
+    assert!(check(json!({"command": " "})).is_err());
+    assert!(check(json!({"command": "true", "timeout": 0})).is_err());
+    assert!(check(json!({"command": "true", "retries": 0})).is_err());
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{json, send, send_text, setup};
+use lightshuttle_core::{docker::ImageSummary, services::docker::DockerClient};
+use serde_json::json;
+
+#[tokio::test]
+async fn list_images_with_their_apps() {
+    let (docker, app) = setup();
+    docker.insert("web", "nginx", "running");
+    docker.insert("old-web", "nginx:latest", "exited");
+    docker.add_image(&["redis:7"], 2048);
+
+    let (status, images) = send(&app, "GET", "/api/v1/images", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(images.as_array().unwrap().len(), 2);
+    assert_eq!(images[0]["tags"], json!(["nginx"]));
+    assert_eq!(images[0]["apps"], json!(["old-web", "web"]));
+    assert_eq!(images[1]["tags"], json!(["redis:7"]));
+    assert_eq!(images[1]["size"], 2048);
+    assert_eq!(images[1]["apps"], json!([]));
+}
+
+#[tokio::test]
+async fn inspect_image_by_reference_or_id() {
+    let (docker, app) = setup();
+    let id = docker.add_image(&["ghcr.io/acme/api:1.0"], 4096);
+    docker.insert("api", "ghcr.io/acme/api:1.0", "running");
+
+    let (status, image) = send(&app, "GET", "/api/v1/images/ghcr.io%2Facme%2Fapi:1.0", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(image["id"], id);
+    assert_eq!(image["size"], 4096);
+    assert_eq!(image["os"], "linux");
+    assert_eq!(image["apps"], json!(["api"]));
+
+    let (status, body) = send(&app, "GET", &format!("/api/v1/images/{}", &id[..19]), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["tags"], json!(["ghcr.io/acme/api:1.0"]));
+
+    let (status, body) = send(&app, "GET", "/api/v1/images/ghost", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["message"], "Image not found");
+}
+
+#[tokio::test]
+async fn images_used_by_apps_are_not_removed() {
+    let (docker, app) = setup();
+    docker.insert("web", "nginx:latest", "exited");
+
+    let (status, body) = send(&app, "DELETE", "/api/v1/images/nginx:latest", None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(body["details"].to_string().contains("used by apps: web"));
+
+    docker.remove("web").await.unwrap();
+    let (status, _) = send(&app, "DELETE", "/api/v1/images/nginx:latest", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "DELETE", "/api/v1/images/nginx:latest", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn prune_removes_dangling_then_unused_images() {
+    let (docker, app) = setup();
+    let dangling = docker.add_image(&[], 100);
+    let unused = docker.add_image(&["redis:7"], 200);
+    docker.insert("web", "nginx:latest", "running");
+
+    let (status, body) = send(&app, "POST", "/api/v1/images/prune", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({"deleted": [dangling], "space_reclaimed": 100}));
+
+    let (_, body) = send(&app, "POST", "/api/v1/images/prune?all=true", None).await;
+    assert_eq!(body, json!({"deleted": [unused], "space_reclaimed": 200}));
+
+    let (_, body) = send(&app, "GET", "/api/v1/images", None).await;
+    let remaining: Vec<ImageSummary> = serde_json::from_value(body).unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert!(remaining[0].matches("nginx"));
+}
+
+#[tokio::test]
+async fn pull_streams_progress_then_done() {
+    let (docker, app) = setup();
+
+    let (status, body) = send_text(
+        &app,
+        None,
+        "POST",
+        "/api/v1/images/pull",
+        Some(json!({"image": "redis:7"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let events: Vec<(&str, &str)> = body
+        .split("\n\n")
+        .filter_map(|event| {
+            let name = event.lines().find_map(|l| l.strip_prefix("event: "))?;
+            let data = event.lines().find_map(|l| l.strip_prefix("data: "))?;
+            Some((name, data))
+        })
+        .collect();
+    let (last, progress) = events.split_last().unwrap();
+    assert!(progress.iter().all(|(name, _)| *name == "progress"));
+    assert!(progress.iter().any(|(_, data)| {
+        let step = json(data);
+        step["layer"] == "0123456789ab" && step["current"] == 512 && step["total"] == 1024
+    }));
+
+    assert_eq!(last.0, "done");
+    let done = json(last.1);
+    assert_eq!(done["image"], "redis:7");
+    let images = docker.list_images().await.unwrap();
+    assert_eq!(done["id"], images[0].id);
+}
+
+#[tokio::test]
+async fn pull_errors_are_plain_http_errors() {
+    let (docker, app) = setup();
+    docker.mark_missing("acme/ghost");
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/v1/images/pull",
+        Some(json!({"image": "acme/ghost:latest"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/v1/images/pull",
+        Some(json!({"image": " "})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,620 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use common::{send, send_text, setup};
+use http_body_util::BodyExt;
+use lightshuttle_core::{
+    api::routes::router_with_client,
+    docker::ContainerStats,
+    services::{docker::DockerClient, memory::InMemoryDockerClient, seccomp::SeccompProfile},
+};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn full_lifecycle_without_docker() {
+    let (docker, app) = setup();
+
+    let payload = json!({
+        "name": "web",
+        "image": "nginx:latest",
+        "ports": [8080],
+        "container_port": 80,
+        "labels": {"tier": "front"},
+        "env": {"MODE": "prod"},
+        "restart_policy": "always"
+    });
+    let (status, _) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, list) = send(&app, "GET", "/api/v1/apps", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list["total"], 1);
+    assert_eq!(list["items"][0]["name"], "web");
+    assert_eq!(list["items"][0]["ports"], json!([8080]));
+
+    let (status, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "running");
+
+    docker.push_log("web", "ready to accept connections");
+    let (status, logs) = send_text(&app, None, "GET", "/api/v1/apps/web/logs", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(logs, "ready to accept connections\n");
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/stop", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web/status", None).await;
+    assert_eq!(body["status"], "exited");
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/start", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(docker.state("web").as_deref(), Some("running"));
+
+    let (status, body) = send(&app, "POST", "/api/v1/apps/web/recreate", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["container_id"].as_str().is_some());
+    assert_eq!(docker.names(), vec!["web"]);
+
+    let (status, _) = send(&app, "DELETE", "/api/v1/apps/web", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(docker.names().is_empty());
+}
+
+#[tokio::test]
+async fn missing_app_returns_404_everywhere() {
+    let (_, app) = setup();
+
+    for (method, uri) in [
+        ("GET", "/api/v1/apps/ghost"),
+        ("GET", "/api/v1/apps/ghost/logs"),
+        ("GET", "/api/v1/apps/ghost/status"),
+        ("POST", "/api/v1/apps/ghost/start"),
+        ("POST", "/api/v1/apps/ghost/stop"),
+        ("POST", "/api/v1/apps/ghost/recreate"),
+        ("POST", "/api/v1/apps/ghost/pause"),
+        ("POST", "/api/v1/apps/ghost/unpause"),
+        ("POST", "/api/v1/apps/ghost/restart"),
+        ("POST", "/api/v1/apps/ghost/kill"),
+        ("DELETE", "/api/v1/apps/ghost"),
+    ] {
+        let (status, _) = send(&app, method, uri, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{method} {uri}");
+    }
+}
+
+#[tokio::test]
+async fn pause_kill_restart_and_rename() {
+    let (docker, app) = setup();
+    docker.insert("web", "nginx:latest", "running");
+    docker.insert("db", "postgres:16", "running");
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/pause", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(docker.state("web").as_deref(), Some("paused"));
+    let (status, body) = send(&app, "POST", "/api/v1/apps/web/pause", None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["details"], "Container web is not running");
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/unpause", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(docker.state("web").as_deref(), Some("running"));
+
+    // SIGHUP reloads: the app keeps running.
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/kill?signal=SIGHUP", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(docker.signals("web"), ["SIGHUP"]);
+    assert_eq!(docker.state("web").as_deref(), Some("running"));
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/kill?signal=HUP;ls", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/kill", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(docker.signals("web"), ["SIGHUP", "SIGKILL"]);
+    assert_eq!(docker.state("web").as_deref(), Some("exited"));
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/restart?timeout=5", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(docker.state("web").as_deref(), Some("running"));
+
+    let rename = |name: &str| Some(json!({ "name": name }));
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/rename", rename("db")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/rename", rename("-web")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/rename", rename("front")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(docker.names(), ["db", "front"]);
+    let (status, body) = send(&app, "GET", "/api/v1/apps/front/status", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "running");
+}
+
+#[tokio::test]
+async fn wait_returns_how_the_app_exited() {
+    let (docker, app) = setup();
+    docker.insert("migrate", "migrations:1", "running");
+
+    let waiting = tokio::spawn({
+        let app = app.clone();
+        async move { send(&app, "POST", "/api/v1/apps/migrate/wait", None).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(!waiting.is_finished());
+    docker.exit("migrate", 3);
+
+    let (status, exit) = waiting.await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(exit["exit_code"], 3);
+    assert_eq!(exit["oom_killed"], false);
+    assert!(exit["finished_at"].as_str().is_some());
+
+    // Already exited: `not-running` answers at once, `next-exit` waits.
+    let (status, body) = send(&app, "POST", "/api/v1/apps/migrate/wait", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["exit_code"], 3);
+    let uri = "/api/v1/apps/migrate/wait?condition=next-exit&timeout=1";
+    let (status, body) = send(&app, "POST", uri, None).await;
+    assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(body["details"], "wait");
+
+    docker.insert("seed", "seeders:1", "running");
+    let waiting = tokio::spawn({
+        let app = app.clone();
+        async move {
+            send(
+                &app,
+                "POST",
+                "/api/v1/apps/seed/wait?condition=next-exit",
+                None,
+            )
+            .await
+        }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    docker.oom_kill("seed");
+    let (status, body) = waiting.await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["exit_code"], 137);
+    assert_eq!(body["oom_killed"], true);
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/ghost/wait", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let uri = "/api/v1/apps/seed/wait?condition=forever";
+    let (status, _) = send(&app, "POST", uri, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn resource_limits_are_applied_shown_and_kept_on_recreate() {
+    let (_docker, app) = setup();
+
+    let payload = json!({
+        "name": "web",
+        "image": "nginx:latest",
+        "ports": [8080],
+        "container_port": 80,
+        "restart_policy": "unless-stopped",
+        "cpus": 1.5,
+        "cpu_shares": 512,
+        "memory": 268435456,
+        "memory_reservation": 134217728,
+        "memory_swap": -1,
+        "pids_limit": 100
+    });
+    let (status, _) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let limits = |app: &Value| {
+        json!({
+            "cpus": app["cpus"],
+            "cpu_shares": app["cpu_shares"],
+            "memory": app["memory"],
+            "memory_reservation": app["memory_reservation"],
+            "memory_swap": app["memory_swap"],
+            "pids_limit": app["pids_limit"],
+        })
+    };
+    let expected = json!({
+        "cpus": 1.5,
+        "cpu_shares": 512,
+        "memory": 268435456,
+        "memory_reservation": 134217728,
+        "memory_swap": -1,
+        "pids_limit": 100,
+    });
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert_eq!(limits(&body), expected);
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/recreate", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert_eq!(limits(&body), expected);
+
+    for (limits, details) in [
+        (json!({"cpus": 0}), "Invalid cpus 0: must be at least 0.01"),
+        (
+            json!({"cpu_shares": 1}),
+            "Invalid cpu_shares 1: must be between 2 and 262144",
+        ),
+        (
+            json!({"memory": 1024}),
+            "Invalid memory 1024: must be at least 6291456 bytes",
+        ),
+        (
+            json!({"memory": 67108864, "memory_reservation": 134217728}),
+            "memory_reservation must not exceed memory",
+        ),
+        (json!({"memory_swap": -1}), "memory_swap requires memory"),
+        (
+            json!({"memory": 67108864, "memory_swap": 1048576}),
+            "Invalid memory_swap 1048576: must be -1 or at least memory",
+        ),
+        (
+            json!({"pids_limit": 0}),
+            "Invalid pids_limit 0: must be at least 1",
+        ),
+    ] {
+        let mut payload = json!({
+            "name": "api",
+            "image": "nginx:latest",
+            "ports": [],
+            "container_port": 80
+        });
+        payload
+            .as_object_mut()
+            .unwrap()
+            .extend(limits.as_object().unwrap().clone());
+        let (status, body) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["details"], details);
+    }
+}
+
+#[tokio::test]
+async fn port_mappings_accept_both_shapes_and_survive_recreate() {
+    let (_docker, app) = setup();
+
+    let payload = json!({
+        "name": "web",
+        "image": "nginx:latest",
+        "ports": [
+            8080,
+            {"host": 8443, "container": 443},
+            {"host": 5514, "container": 514, "protocol": "udp", "host_ip": "127.0.0.1"}
+        ],
+        "container_port": 80
+    });
+    let (status, _) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let expected = json!([
+        {"host": 8080, "container": 80, "protocol": "tcp"},
+        {"host": 8443, "container": 443, "protocol": "tcp"},
+        {"host": 5514, "container": 514, "protocol": "udp", "host_ip": "127.0.0.1"}
+    ]);
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert_eq!(body["port_mappings"], expected);
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/recreate", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert_eq!(body["port_mappings"], expected);
+
+    for (ports, details) in [
+        (
+            json!([8081]),
+            "Host port 8081 needs container_port, or a {host, container} mapping",
+        ),
+        (
+            json!([{"host": 8081, "container": 80}, {"host": 8081, "container": 81}]),
+            "Host port 8081/tcp is published twice",
+        ),
+        (
+            json!([{"host": 8081, "container": 80, "host_ip": "localhost"}]),
+            "Invalid host_ip 'localhost'",
+        ),
+    ] {
+        let payload = json!({"name": "api", "image": "nginx:latest", "ports": ports});
+        let (status, body) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["details"], details);
+    }
+}
+
+#[tokio::test]
+async fn process_overrides_are_applied_shown_and_kept_on_recreate() {
+    let (_docker, app) = setup();
+
+    let payload = json!({
+        "name": "worker",
+        "image": "myapp:latest",
+        "entrypoint": ["/usr/bin/tini", "--"],
+        "command": ["bundle", "exec", "sidekiq"],
+        "working_dir": "/srv/app",
+        "user": "1000:1000",
+        "hostname": "worker-1"
+    });
+    let (status, _) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let process = |app: &Value| {
+        json!({
+            "command": app["command"],
+            "entrypoint": app["entrypoint"],
+            "working_dir": app["working_dir"],
+            "user": app["user"],
+            "hostname": app["hostname"],
+        })
+    };
+    let expected = json!({
+        "command": ["bundle", "exec", "sidekiq"],
+        "entrypoint": ["/usr/bin/tini", "--"],
+        "working_dir": "/srv/app",
+        "user": "1000:1000",
+        "hostname": "worker-1",
+    });
+    let (_, body) = send(&app, "GET", "/api/v1/apps/worker", None).await;
+    assert_eq!(process(&body), expected);
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/worker/recreate", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, "GET", "/api/v1/apps/worker", None).await;
+    assert_eq!(process(&body), expected);
+
+    // The short id engines use as the default hostname is not an override.
+    let payload = json!({"name": "web", "image": "myapp:latest"});
+    let (status, _) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert!(body.get("hostname").is_none());
+
+    for (process, details) in [
+        (json!({"command": []}), "command must not be empty"),
+        (
+            json!({"working_dir": "srv"}),
+            "Invalid working_dir 'srv': must be an absolute path",
+        ),
+        (json!({"user": "app user"}), "Invalid user 'app user'"),
+        (json!({"hostname": "-web"}), "Invalid hostname '-web'"),
+    ] {
+        let mut payload = json!({"name": "api", "image": "myapp:latest"});
+        payload
+            .as_object_mut()
+            .unwrap()
+            .extend(process.as_object().unwrap().clone());
+        let (status, body) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["details"], details);
+    }
+}
+
+#[tokio::test]
+async fn recreate_picks_up_a_new_image_command_unless_overridden() {
+    let (docker, app) = setup();
+    docker.add_image(&["myapp:latest"], 1024);
+    docker.set_image_command("myapp:latest", &["puma"]);
+
+    let payload = json!({"name": "web", "image": "myapp:latest"});
+    let (status, _) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let payload = json!({"name": "worker", "image": "myapp:latest", "command": ["sidekiq"]});
+    let (status, _) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // The image default is what the container runs, not an override.
+    let inspected = docker.inspect("web").await.unwrap();
+    assert_eq!(inspected.config.cmd, Some(vec!["puma".to_string()]));
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert!(body.get("command").is_none());
+
+    // A new version of the image changes its CMD.
+    docker.add_image(&["myapp:latest"], 2048);
+    docker.set_image_command("myapp:latest", &["puma", "-C", "config/puma.rb"]);
+    for name in ["web", "worker"] {
+        let (status, _) = send(&app, "POST", &format!("/api/v1/apps/{name}/recreate"), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let inspected = docker.inspect("web").await.unwrap();
+    assert_eq!(
+        inspected.config.cmd,
+        Some(vec![
+            "puma".to_string(),
+            "-C".to_string(),
+            "config/puma.rb".to_string()
+        ])
+    );
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert!(body.get("command").is_none());
+
+    let inspected = docker.inspect("worker").await.unwrap();
+    assert_eq!(inspected.config.cmd, Some(vec!["sidekiq".to_string()]));
+    let (_, body) = send(&app, "GET", "/api/v1/apps/worker", None).await;
+    assert_eq!(body["command"], json!(["sidekiq"]));
+}
+
+#[tokio::test]
+async fn security_options_are_applied_shown_and_kept_on_recreate() {
+    let profile = concat!(env!("CARGO_MANIFEST_DIR"), "/../seccomp-profile.json");
+    let docker = InMemoryDockerClient::new().with_seccomp(SeccompProfile::load(profile).unwrap());
+    let app = router_with_client(Arc::new(docker));
+
+    let payload = json!({
+        "name": "web",
+        "image": "nginx:latest",
+        "cap_add": ["net_bind_service"],
+        "cap_drop": ["ALL"],
+        "read_only": true,
+        "no_new_privileges": true,
+        "tmpfs": {"/tmp": "size=64m", "/run": ""},
+        "apparmor": "docker-default"
+    });
+    let (status, _) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let security = |app: &Value| {
+        json!({
+            "cap_add": app["cap_add"],
+            "cap_drop": app["cap_drop"],
+            "read_only": app["read_only"],
+            "no_new_privileges": app["no_new_privileges"],
+            "tmpfs": app["tmpfs"],
+            "apparmor": app["apparmor"],
+            "seccomp": app["seccomp"],
+        })
+    };
+    let expected = json!({
+        "cap_add": ["CAP_NET_BIND_SERVICE"],
+        "cap_drop": ["ALL"],
+        "read_only": true,
+        "no_new_privileges": true,
+        "tmpfs": {"/run": "", "/tmp": "size=64m"},
+        "apparmor": "docker-default",
+        "seccomp": "custom",
+    });
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert_eq!(security(&body), expected);
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/recreate", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, "GET", "/api/v1/apps/web", None).await;
+    assert_eq!(security(&body), expected);
+
+    for (security, details) in [
+        (
+            json!({"cap_add": ["NET ADMIN"]}),
+            "Invalid capability 'NET ADMIN'",
+        ),
+        (
+            json!({"tmpfs": {"tmp": ""}}),
+            "Invalid tmpfs path 'tmp': must be an absolute path",
+        ),
+        (json!({"apparmor": ""}), "Invalid apparmor profile ''"),
+    ] {
+        let mut payload = json!({"name": "api", "image": "nginx:latest"});
+        payload
+            .as_object_mut()
+            .unwrap()
+            .extend(security.as_object().unwrap().clone());
+        let (status, body) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["details"], details);
+    }
+}
+
+#[tokio::test]
+async fn duplicate_name_returns_conflict() {
+    let (docker, app) = setup();
+    docker.insert("web", "nginx:latest", "running");
+
+    let payload = json!({
+        "name": "web",
+        "image": "nginx:latest",
+        "ports": [8080],
+        "container_port": 80
+    });
+    let (status, body) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], 409);
+}
+
+#[tokio::test]
+async fn list_only_shows_running_apps_and_filters_by_search() {
+    let (docker, app) = setup();
+    docker.insert("api", "api:1", "running");
+    docker.insert("api-worker", "api:1", "running");
+    docker.insert("old-api", "api:0", "exited");
+
+    let (_, list) = send(&app, "GET", "/api/v1/apps?search=worker", None).await;
+    assert_eq!(list["total"], 1);
+    assert_eq!(list["items"][0]["name"], "api-worker");
+
+    let (_, list) = send(&app, "GET", "/api/v1/apps?page=1&limit=1", None).await;
+    assert_eq!(list["total"], 2);
+    assert_eq!(list["items"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn restart_policy_is_applied_on_exit() {
+    let (docker, app) = setup();
+
+    for (name, policy) in [
+        ("always", "always"),
+        ("once", "no"),
+        ("retry", "on-failure"),
+    ] {
+        let payload = json!({
+            "name": name,
+            "image": "busybox",
+            "ports": [],
+            "container_port": 80,
+            "restart_policy": policy
+        });
+        let (status, _) = send(&app, "POST", "/api/v1/apps", Some(payload)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        docker.exit(name, 1);
+    }
+
+    assert_eq!(docker.state("always").as_deref(), Some("running"));
+    assert_eq!(docker.state("retry").as_deref(), Some("running"));
+    // Without a restart policy apps run with `--rm` and disappear on exit.
+    assert_eq!(docker.state("once"), None);
+
+    docker.exit("retry", 0);
+    assert_eq!(docker.state("retry").as_deref(), Some("exited"));
+}
+
+#[tokio::test]
+async fn system_info_reports_backend() {
+    let (_, app) = setup();
+
+    let (status, info) = send(&app, "GET", "/api/v1/system/info", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(info["docker"]["backend"], "memory");
+    assert!(info["docker"]["endpoint"].is_null());
+}
+
+#[tokio::test]
+async fn stats_snapshot_and_stream() {
+    let (docker, app) = setup();
+    docker.insert("web", "nginx:latest", "running");
+    docker.set_stats(
+        "web",
+        ContainerStats {
+            cpu_percent: 2.5,
+            memory_usage: 4096,
+            memory_limit: 8192,
+            pids: 2,
+            ..Default::default()
+        },
+    );
+
+    let (status, stats) = send(&app, "GET", "/api/v1/apps/web/stats", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stats["cpu_percent"], 2.5);
+    assert_eq!(stats["memory_usage"], 4096);
+    assert_eq!(stats["memory_limit"], 8192);
+    assert_eq!(stats["pids"], 2);
+
+    let (status, _) = send(&app, "GET", "/api/v1/apps/ghost/stats", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let request = Request::builder()
+        .uri("/api/v1/apps/web/stats?stream=true")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    let mut body = response.into_body();
+    let frame = body.frame().await.unwrap().unwrap().into_data().unwrap();
+    let frame = String::from_utf8(frame.to_vec()).unwrap();
+    assert!(frame.starts_with("event: stats\n"), "{frame}");
+    assert!(frame.contains(r#""memory_usage":4096"#), "{frame}");
+}
@@ -0,0 +1,171 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use http_body_util::BodyExt;
+use lightshuttle_core::{
+    api::{routes::router_with_state, state::AppState},
+    services::{locks::AppLocks, memory::InMemoryDockerClient},
+};
+use serde_json::Value;
+use tower::ServiceExt;
+
+/// Router over a Docker client taking `latency` per call.
+fn app(latency: Duration, wait: Duration) -> Router {
+    let docker = InMemoryDockerClient::new().with_latency(latency);
+    let state = AppState::new(Arc::new(docker)).with_locks(Arc::new(AppLocks::new(wait)));
+    router_with_state(state)
+}
+
+fn request(method: &str, uri: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn create(app: &Router, name: &str) {
+    let payload = serde_json::json!({
+        "name": name,
+        "image": "nginx:latest",
+        "ports": [8080],
+        "container_port": 80,
+        "restart_policy": "unless-stopped",
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/apps")
+        .header("Content-Type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn concurrent_recreates_are_serialized() {
+    let app = app(Duration::from_millis(50), Duration::from_secs(10));
+    create(&app, "web").await;
+
+    let recreates: Vec<_> = (0..3)
+        .map(|_| {
+            let app = app.clone();
+            tokio::spawn(async move {
+                app.oneshot(request("POST", "/api/v1/apps/web/recreate"))
+                    .await
+                    .unwrap()
+                    .status()
+            })
+        })
+        .collect();
+    for recreate in recreates {
+        assert_eq!(recreate.await.unwrap(), StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn busy_app_answers_409_with_the_operation_in_flight() {
+    let app = app(Duration::from_millis(500), Duration::from_millis(50));
+    create(&app, "web").await;
+
+    let stop = tokio::spawn(
+        app.clone()
+            .oneshot(request("POST", "/api/v1/apps/web/stop")),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let response = app
+        .clone()
+        .oneshot(request("DELETE", "/api/v1/apps/web"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body: Value =
+        serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    let details = body["details"].as_str().unwrap();
+    assert!(
+        details.starts_with("App web is busy: stop in progress since "),
+        "{details}"
+    );
+
+    // Reads do not wait for the lock, and other apps are not affected.
+    let response = app
+        .clone()
+        .oneshot(request("GET", "/api/v1/apps/web/status"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/apps/other/start"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert_eq!(stop.await.unwrap().unwrap().status(), StatusCode::OK);
+    let response = app
+        .oneshot(request("DELETE", "/api/v1/apps/web"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn uploads_and_exec_wait_for_a_recreate() {
+    let app = app(Duration::from_millis(500), Duration::from_millis(50));
+    create(&app, "web").await;
+
+    let recreate = tokio::spawn(
+        app.clone()
+            .oneshot(request("POST", "/api/v1/apps/web/recreate")),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let exec = Request::builder()
+        .method("POST")
+        .uri("/api/v1/apps/web/exec")
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"cmd":["ls"]}"#))
+        .unwrap();
+    for request in [request("PUT", "/api/v1/apps/web/archive?path=/srv"), exec] {
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body: Value =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        let details = body["details"].as_str().unwrap();
+        assert!(
+            details.starts_with("App web is busy: recreate in progress since "),
+            "{details}"
+        );
+    }
+
+    assert_eq!(recreate.await.unwrap().unwrap().status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn running_exec_does_not_block_lifecycle_operations() {
+    let app = app(Duration::from_millis(300), Duration::from_millis(50));
+    create(&app, "web").await;
+
+    let exec = Request::builder()
+        .method("POST")
+        .uri("/api/v1/apps/web/exec")
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"cmd":["sleep","600"]}"#))
+        .unwrap();
+    let exec = tokio::spawn(app.clone().oneshot(exec));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let response = app
+        .clone()
+        .oneshot(request("POST", "/api/v1/apps/web/pause"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(exec.await.unwrap().unwrap().status(), StatusCode::OK);
+}
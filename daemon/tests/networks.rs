@@ -0,0 +1,222 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{send, setup};
+use lightshuttle_core::{
+    docker::{network_attachments, NetworkAttachment},
+    services::docker::DockerClient,
+};
+use serde_json::{json, Value};
+
+fn app_on(name: &str, image: &str, networks: Value) -> Value {
+    json!({
+        "name": name,
+        "image": image,
+        "ports": [],
+        "container_port": 80,
+        "restart_policy": "always",
+        "networks": networks
+    })
+}
+
+#[tokio::test]
+async fn networks_can_be_created_listed_and_removed() {
+    let (_, app) = setup();
+
+    let (status, network) = send(
+        &app,
+        "POST",
+        "/api/v1/networks",
+        Some(json!({"name": "backend", "subnet": "10.42.0.0/24", "internal": true})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(network["name"], "backend");
+    assert_eq!(network["driver"], "bridge");
+    assert_eq!(network["subnet"], "10.42.0.0/24");
+    assert_eq!(network["internal"], true);
+    assert_eq!(network["apps"], json!([]));
+
+    let (status, networks) = send(&app, "GET", "/api/v1/networks", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = networks
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["bridge", "backend"]);
+
+    let id = network["id"].as_str().unwrap();
+    let (status, by_id) = send(&app, "GET", &format!("/api/v1/networks/{id}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(by_id["name"], "backend");
+
+    let (status, _) = send(&app, "DELETE", "/api/v1/networks/backend", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "GET", "/api/v1/networks/backend", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn invalid_or_duplicate_networks_are_rejected() {
+    let (_, app) = setup();
+
+    for body in [
+        json!({"name": ""}),
+        json!({"name": "-backend"}),
+        json!({"name": "backend", "subnet": "10.42.0.0"}),
+        json!({"name": "backend", "subnet": "10.42.0.0/33"}),
+        json!({"name": "backend", "driver": "bridge;rm"}),
+    ] {
+        let (status, _) = send(&app, "POST", "/api/v1/networks", Some(body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    }
+
+    let body = json!({"name": "backend"});
+    let (status, _) = send(&app, "POST", "/api/v1/networks", Some(body.clone())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = send(&app, "POST", "/api/v1/networks", Some(body)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = send(&app, "DELETE", "/api/v1/networks/bridge", None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = send(&app, "DELETE", "/api/v1/networks/ghost", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn apps_join_networks_with_aliases() {
+    let (docker, app) = setup();
+    for name in ["backend", "frontend"] {
+        send(
+            &app,
+            "POST",
+            "/api/v1/networks",
+            Some(json!({"name": name})),
+        )
+        .await;
+    }
+
+    let networks = json!([
+        {"name": "backend", "aliases": ["db", "postgres"]},
+        {"name": "frontend"}
+    ]);
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/v1/apps",
+        Some(app_on("db", "postgres:16", networks)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let inspect = docker.inspect("db").await.unwrap();
+    assert_eq!(
+        network_attachments(&inspect),
+        [
+            NetworkAttachment {
+                name: "backend".into(),
+                aliases: vec!["db".into(), "postgres".into()],
+            },
+            NetworkAttachment {
+                name: "frontend".into(),
+                aliases: vec![],
+            },
+        ]
+    );
+
+    let (_, backend) = send(&app, "GET", "/api/v1/networks/backend", None).await;
+    assert_eq!(backend["apps"], json!(["db"]));
+
+    let (status, error) = send(&app, "DELETE", "/api/v1/networks/backend", None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(error["details"].as_str().unwrap().contains("db"));
+}
+
+#[tokio::test]
+async fn apps_on_unknown_or_invalid_networks_are_rejected() {
+    let (docker, app) = setup();
+    send(
+        &app,
+        "POST",
+        "/api/v1/networks",
+        Some(json!({"name": "backend"})),
+    )
+    .await;
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/api/v1/apps",
+        Some(app_on("web", "nginx", json!([{"name": "ghost"}]))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    for networks in [
+        json!([{"name": "backend"}, {"name": "backend"}]),
+        json!([{"name": "backend", "aliases": ["bad alias"]}]),
+        json!([{"name": " "}]),
+    ] {
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/api/v1/apps",
+            Some(app_on("web", "nginx", networks.clone())),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{networks}");
+    }
+    assert!(docker.names().is_empty());
+}
+
+#[tokio::test]
+async fn recreate_keeps_network_attachments() {
+    let (docker, app) = setup();
+    send(
+        &app,
+        "POST",
+        "/api/v1/networks",
+        Some(json!({"name": "backend"})),
+    )
+    .await;
+    let networks = json!([{"name": "backend", "aliases": ["api"]}]);
+    send(
+        &app,
+        "POST",
+        "/api/v1/apps",
+        Some(app_on("api", "ghcr.io/acme/api:1", networks)),
+    )
+    .await;
+    send(
+        &app,
+        "POST",
+        "/api/v1/apps",
+        Some(app_on("web", "nginx", Value::Null)),
+    )
+    .await;
+    let before = docker.inspect("api").await.unwrap().id;
+
+    let (status, _) = send(&app, "POST", "/api/v1/apps/api/recreate", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(&app, "POST", "/api/v1/apps/web/recreate", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let api = docker.inspect("api").await.unwrap();
+    assert_ne!(api.id, before);
+    assert_eq!(
+        network_attachments(&api),
+        [NetworkAttachment {
+            name: "backend".into(),
+            aliases: vec!["api".into()],
+        }]
+    );
+    let web = docker.inspect("web").await.unwrap();
+    assert!(network_attachments(&web).is_empty());
+    assert!(web
+        .network_settings
+        .networks
+        .unwrap()
+        .contains_key("bridge"));
+}
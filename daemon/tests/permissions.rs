@@ -0,0 +1,187 @@
+mod common;
+
+use axum::{http::StatusCode, Router};
+use common::{send_as, setup_with_keys};
+use serde_json::{json, Value};
+
+/// `deployer` may change things, `auditor` may only read.
+const KEYS: &str = r#"{
+    "deployer": {"name": "team-a", "read": true, "write": true},
+    "auditor": {"name": "team-a", "read": true, "write": false}
+}"#;
+
+/// Sends every request with the read-only key and expects `403` naming `action`.
+async fn assert_forbidden(app: &Router, requests: &[(&str, &str, Option<Value>, &str)]) {
+    for (method, uri, body, action) in requests {
+        let (status, body) = send_as(app, "auditor", method, uri, body.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{method} {uri}");
+        assert_eq!(
+            body["details"],
+            format!("Namespace team-a is not allowed to {action}"),
+            "{method} {uri}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn image_changes_require_write_permission() {
+    let (docker, app) = setup_with_keys(KEYS);
+    docker.add_image(&["nginx:latest"], 1024);
+
+    assert_forbidden(
+        &app,
+        &[
+            (
+                "DELETE",
+                "/api/v1/images/nginx:latest",
+                None,
+                "remove images",
+            ),
+            ("POST", "/api/v1/images/prune", None, "prune images"),
+            (
+                "POST",
+                "/api/v1/images/pull",
+                Some(json!({"image": "redis:7"})),
+                "pull images",
+            ),
+        ],
+    )
+    .await;
+
+    let (status, _) = send_as(&app, "auditor", "GET", "/api/v1/images", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_as(
+        &app,
+        "deployer",
+        "DELETE",
+        "/api/v1/images/nginx:latest",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn network_changes_require_write_permission() {
+    let (_docker, app) = setup_with_keys(KEYS);
+    let (status, _) = send_as(
+        &app,
+        "deployer",
+        "POST",
+        "/api/v1/networks",
+        Some(json!({"name": "backend"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    assert_forbidden(
+        &app,
+        &[
+            (
+                "POST",
+                "/api/v1/networks",
+                Some(json!({"name": "frontend"})),
+                "create networks",
+            ),
+            (
+                "DELETE",
+                "/api/v1/networks/backend",
+                None,
+                "remove networks",
+            ),
+        ],
+    )
+    .await;
+
+    let (status, _) = send_as(&app, "auditor", "GET", "/api/v1/networks/backend", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn volume_changes_require_write_permission() {
+    let (_docker, app) = setup_with_keys(KEYS);
+    let (status, _) = send_as(
+        &app,
+        "deployer",
+        "POST",
+        "/api/v1/volumes",
+        Some(json!({"name": "data"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    assert_forbidden(
+        &app,
+        &[
+            (
+                "POST",
+                "/api/v1/volumes",
+                Some(json!({"name": "cache"})),
+                "create volumes",
+            ),
+            ("DELETE", "/api/v1/volumes/data", None, "remove volumes"),
+        ],
+    )
+    .await;
+
+    let (status, _) = send_as(&app, "auditor", "GET", "/api/v1/volumes/data", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn app_lifecycle_changes_require_write_permission() {
+    let (docker, app) = setup_with_keys(KEYS);
+    docker.insert("web", "nginx:latest", "running");
+
+    assert_forbidden(
+        &app,
+        &[
+            ("POST", "/api/v1/apps/web/pause", None, "pause apps"),
+            ("POST", "/api/v1/apps/web/unpause", None, "unpause apps"),
+            ("POST", "/api/v1/apps/web/restart", None, "restart apps"),
+            ("POST", "/api/v1/apps/web/kill", None, "kill apps"),
+            (
+                "POST",
+                "/api/v1/apps/web/rename",
+                Some(json!({"name": "site"})),
+                "rename apps",
+            ),
+        ],
+    )
+    .await;
+    assert_eq!(docker.state("web").as_deref(), Some("running"));
+    assert!(docker.signals("web").is_empty());
+
+    let (status, _) = send_as(&app, "deployer", "POST", "/api/v1/apps/web/pause", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn app_changes_require_write_permission() {
+    let (docker, app) = setup_with_keys(KEYS);
+    docker.insert("web", "nginx:latest", "running");
+
+    assert_forbidden(
+        &app,
+        &[
+            (
+                "POST",
+                "/api/v1/apps",
+                Some(json!({"name": "api", "image": "nginx:latest"})),
+                "create apps",
+            ),
+            ("POST", "/api/v1/apps/web/start", None, "start apps"),
+            ("POST", "/api/v1/apps/web/stop", None, "stop apps"),
+            ("POST", "/api/v1/apps/web/recreate", None, "recreate apps"),
+            ("DELETE", "/api/v1/apps/web", None, "delete apps"),
+        ],
+    )
+    .await;
+    assert_eq!(docker.names(), ["web"]);
+    assert_eq!(docker.state("web").as_deref(), Some("running"));
+
+    let (status, _) = send_as(&app, "auditor", "GET", "/api/v1/apps/web", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_as(&app, "deployer", "DELETE", "/api/v1/apps/web", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
@@ -0,0 +1,749 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+
+use common::stub_engine;
+use lightshuttle_core::{
+    docker::{
+        BuildOptions, ContainerConfig, ContainerInspect, ExecConfig, NetworkAttachment,
+        NetworkConfig, PortMapping, PortProtocol, ProcessOverrides, RegistryAuth, ResourceLimits,
+        SecurityOptions, VolumeConfig,
+    },
+    errors::Error,
+    services::{
+        docker::{collect_archive, DockerClient},
+        http::Endpoint,
+        podman::PodmanClient,
+        seccomp::SeccompProfile,
+    },
+};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn run_maps_config_to_libpod_spec() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/containers/create") {
+            (201, r#"{"Id":"abc123","Warnings":[]}"#.into())
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let labels = HashMap::from([("tier".to_string(), "front".to_string())]);
+    let env = HashMap::from([("MODE".to_string(), "prod".to_string())]);
+    let volumes = vec![
+        "/srv/www:/usr/share/nginx/html:ro".to_string(),
+        "cache:/var/cache".to_string(),
+    ];
+    let config = ContainerConfig {
+        name: "web",
+        image: "nginx:latest",
+        ports: &[PortMapping::tcp(8080, 80)],
+        labels: Some(&labels),
+        env: Some(&env),
+        volumes: Some(&volumes),
+        restart_policy: Some("on-failure"),
+        ..Default::default()
+    };
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    assert_eq!(client.run(config).await.unwrap(), "abc123");
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].method, "POST");
+    assert_eq!(calls[0].path, "/v4.0.0/libpod/containers/create");
+    let spec: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(spec["name"], "web");
+    assert_eq!(spec["image"], "nginx:latest");
+    assert_eq!(spec["env"], json!({"MODE": "prod"}));
+    assert_eq!(spec["labels"], json!({"tier": "front"}));
+    assert_eq!(spec["restart_policy"], "on-failure");
+    assert_eq!(spec["remove"], false);
+    assert_eq!(
+        spec["portmappings"],
+        json!([{"host_port": 8080, "container_port": 80, "protocol": "tcp"}])
+    );
+    assert_eq!(
+        spec["mounts"],
+        json!([{"type": "bind", "source": "/srv/www", "destination": "/usr/share/nginx/html", "options": ["ro"]}])
+    );
+    assert_eq!(
+        spec["volumes"],
+        json!([{"Name": "cache", "Dest": "/var/cache", "Options": []}])
+    );
+    assert_eq!(calls[1].path, "/v4.0.0/libpod/containers/abc123/start");
+}
+
+#[tokio::test]
+async fn run_without_restart_policy_is_auto_removed() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/containers/create") {
+            (201, r#"{"Id":"abc123"}"#.into())
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let config = ContainerConfig {
+        name: "job",
+        image: "busybox",
+        labels: None,
+        env: None,
+        volumes: None,
+        restart_policy: None,
+        ..Default::default()
+    };
+    PodmanClient::new(Endpoint::Unix(socket))
+        .run(config)
+        .await
+        .unwrap();
+
+    let spec: Value = serde_json::from_str(&captured.lock().unwrap()[0].body).unwrap();
+    assert_eq!(spec["remove"], true);
+    assert!(spec.get("restart_policy").is_none());
+    assert_eq!(spec["portmappings"], json!([]));
+}
+
+#[tokio::test]
+async fn run_pulls_missing_image_then_retries() {
+    let (_dir, socket, captured) = stub_engine({
+        let creates = std::sync::Mutex::new(0);
+        move |_, path| {
+            if path.ends_with("/containers/create") {
+                let mut n = creates.lock().unwrap();
+                *n += 1;
+                if *n == 1 {
+                    (
+                        404,
+                        r#"{"cause":"no such image","message":"nginx:latest: image not known","response":404}"#.into(),
+                    )
+                } else {
+                    (201, r#"{"Id":"def456"}"#.into())
+                }
+            } else if path.contains("/images/pull") {
+                (
+                    200,
+                    r#"{"stream":"Trying to pull docker.io/library/nginx:latest...\n"}"#.into(),
+                )
+            } else {
+                (204, String::new())
+            }
+        }
+    });
+
+    let config = ContainerConfig {
+        name: "web",
+        image: "nginx:latest",
+        labels: None,
+        env: None,
+        volumes: None,
+        restart_policy: Some("always"),
+        ..Default::default()
+    };
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    assert_eq!(client.run(config).await.unwrap(), "def456");
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(
+        calls[1].path,
+        "/v4.0.0/libpod/images/pull?reference=nginx%3Alatest"
+    );
+    assert_eq!(calls.len(), 4);
+}
+
+#[tokio::test]
+async fn libpod_errors_are_mapped() {
+    let (_dir, socket, _) = stub_engine(|method, path| {
+        match (method, path) {
+        (_, p) if p.contains("/ghost/") || p.contains("/ghost?") => (
+            404,
+            r#"{"cause":"no such container","message":"no container with name or ID \"ghost\" found: no such container","response":404}"#.into(),
+        ),
+        ("POST", p) if p.ends_with("/containers/create") => (
+            500,
+            r#"{"cause":"that name is already in use","message":"creating container storage: the container name \"web\" is already in use","response":500}"#.into(),
+        ),
+        _ => (
+            500,
+            r#"{"cause":"boom","message":"something broke","response":500}"#.into(),
+        ),
+    }
+    });
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+
+    for result in [
+        client.start("ghost").await,
+        client.stop("ghost").await,
+        client.remove("ghost").await,
+        client.logs("ghost").await.map(|_| ()),
+        client.inspect("ghost").await.map(|_| ()),
+    ] {
+        assert!(
+            matches!(result, Err(Error::ContainerNotFound)),
+            "{result:?}"
+        );
+    }
+
+    let config = ContainerConfig {
+        name: "web",
+        image: "nginx:latest",
+        labels: None,
+        env: None,
+        volumes: None,
+        restart_policy: None,
+        ..Default::default()
+    };
+    assert!(matches!(client.run(config).await, Err(Error::Conflict(_))));
+
+    match client.list(true).await {
+        Err(Error::Unexpected(msg)) => assert_eq!(msg, "something broke"),
+        other => panic!("expected unexpected error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn list_and_inspect_read_libpod_output() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.contains("/containers/json?") {
+            (
+                200,
+                r#"[{"Id":"abc","Names":["web"],"Image":"docker.io/library/nginx:latest","State":"running",
+                    "Status":"Up 2 minutes","Ports":[{"host_ip":"","container_port":80,"host_port":8080,"range":1,"protocol":"tcp"}],
+                    "Labels":{"tier":"front"}}]"#
+                    .into(),
+            )
+        } else {
+            (
+                200,
+                r#"{
+                    "Id": "abc",
+                    "Name": "web",
+                    "Created": "2025-08-01T10:00:00Z",
+                    "State": {"Status": "running", "Running": true, "ExitCode": 0, "OciVersion": "1.1.0"},
+                    "Config": {"Image": "docker.io/library/nginx:latest", "Env": ["MODE=prod"], "Labels": {"tier": "front"}},
+                    "HostConfig": {"Binds": ["/srv/www:/usr/share/nginx/html:ro,rprivate,rbind"], "RestartPolicy": {"Name": "always", "MaximumRetryCount": 0}},
+                    "NetworkSettings": {"Ports": {"80/tcp": [{"HostIp": "", "HostPort": "8080"}]}}
+                }"#
+                .into(),
+            )
+        }
+    });
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+
+    let containers = client.list(true).await.unwrap();
+    assert_eq!(containers.len(), 1);
+    assert_eq!(containers[0].name, "web");
+    assert_eq!(containers[0].ports, vec![8080]);
+    assert_eq!(containers[0].labels["tier"], "front");
+
+    let container = client.inspect("web").await.unwrap();
+    assert_eq!(container.state.status, "running");
+    assert_eq!(container.host_config.restart_policy.name, "always");
+    assert_eq!(container.config.env, Some(vec!["MODE=prod".to_string()]));
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].path, "/v4.0.0/libpod/containers/json?all=true");
+    assert_eq!(calls[1].path, "/v4.0.0/libpod/containers/web/json");
+}
+
+#[tokio::test]
+async fn logs_are_demultiplexed() {
+    let (_dir, socket, captured) =
+        stub_engine(|_, _| (200, "\u{1}\0\0\0\0\0\0\u{6}hello\n".into()));
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    assert_eq!(client.logs("web").await.unwrap(), "hello\n");
+    assert_eq!(
+        captured.lock().unwrap()[0].path,
+        "/v4.0.0/libpod/containers/web/logs?stdout=true&stderr=true"
+    );
+    assert_eq!(client.describe().backend, "podman");
+}
+
+#[tokio::test]
+async fn stats_are_read_from_the_libpod_report() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.contains("ghost") {
+            return (200, r#"{"Error": null, "Stats": []}"#.into());
+        }
+        (
+            200,
+            json!({
+                "Error": null,
+                "Stats": [{
+                    "ContainerID": "abc123",
+                    "Name": "web",
+                    "CPU": 12.5,
+                    "MemUsage": 1048576,
+                    "MemLimit": 2097152,
+                    "NetInput": 10,
+                    "NetOutput": 20,
+                    "BlockInput": 30,
+                    "BlockOutput": 40,
+                    "PIDs": 3
+                }]
+            })
+            .to_string(),
+        )
+    });
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    let stats = client.stats("web").await.unwrap();
+    assert_eq!(stats.cpu_percent, 12.5);
+    assert_eq!((stats.memory_usage, stats.memory_limit), (1048576, 2097152));
+    assert_eq!((stats.network_rx, stats.network_tx), (10, 20));
+    assert_eq!((stats.block_read, stats.block_write), (30, 40));
+    assert_eq!(stats.pids, 3);
+    assert_eq!(
+        captured.lock().unwrap()[0].path,
+        "/v4.0.0/libpod/containers/stats?containers=web&stream=false"
+    );
+
+    let err = client.stats("ghost").await.unwrap_err();
+    assert!(matches!(err, Error::ContainerNotFound), "{err:?}");
+}
+
+#[tokio::test]
+async fn exec_uses_libpod_endpoints() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/containers/web/exec") {
+            (201, r#"{"Id":"e1"}"#.into())
+        } else if path.ends_with("/start") {
+            (200, "\u{1}\0\0\0\0\0\0\u{3}hi\n".into())
+        } else {
+            (200, r#"{"Running":false,"ExitCode":0}"#.into())
+        }
+    });
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    let cfg = ExecConfig {
+        cmd: vec!["echo".into(), "hi".into()],
+        ..Default::default()
+    };
+    let output = client.exec("web", &cfg).await.unwrap();
+    assert_eq!(output.stdout, "hi\n");
+    assert_eq!(output.exit_code, 0);
+
+    let calls = captured.lock().unwrap();
+    let paths: Vec<&str> = calls.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/v4.0.0/libpod/containers/web/exec",
+            "/v4.0.0/libpod/exec/e1/start",
+            "/v4.0.0/libpod/exec/e1/json"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn images_use_libpod_endpoints() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.contains("/images/prune") {
+            (
+                200,
+                r#"[{"Id":"abc","Size":300},{"Id":"def","Size":200}]"#.into(),
+            )
+        } else if path.contains("/images/pull") {
+            (
+                200,
+                "{\"stream\":\"Trying to pull docker.io/library/redis:7...\\n\"}\n{\"id\":\"abc\",\"images\":[\"abc\"]}"
+                    .into(),
+            )
+        } else {
+            (
+                404,
+                r#"{"cause":"image not known","message":"ghost: image not known","response":404}"#
+                    .into(),
+            )
+        }
+    });
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    let report = client.prune_images(false).await.unwrap();
+    assert_eq!(report.deleted, ["abc", "def"]);
+    assert_eq!(report.space_reclaimed, 500);
+
+    let auth = RegistryAuth {
+        username: "ci".into(),
+        password: "s3cret".into(),
+        server_address: "docker.io".into(),
+    };
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    client.pull_image("redis:7", Some(&auth), tx).await.unwrap();
+    assert_eq!(
+        rx.recv().await.unwrap().status,
+        "Trying to pull docker.io/library/redis:7..."
+    );
+    assert!(rx.recv().await.is_none());
+
+    assert!(matches!(
+        client.inspect_image("ghost").await,
+        Err(Error::ImageNotFound)
+    ));
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].path, "/v4.0.0/libpod/images/prune?all=false");
+    assert_eq!(
+        calls[1].path,
+        "/v4.0.0/libpod/images/pull?reference=redis%3A7"
+    );
+    assert_eq!(calls[1].headers["x-registry-auth"], auth.header());
+    assert_eq!(calls[2].path, "/v4.0.0/libpod/images/ghost/json");
+}
+
+#[tokio::test]
+async fn build_falls_back_to_inspecting_the_tag() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.contains("/build") {
+            (200, "{\"stream\":\"STEP 1/1: FROM alpine\\n\"}".into())
+        } else {
+            (
+                200,
+                r#"{"Id":"sha256:77aa","RepoTags":["localhost/api:dev"]}"#.into(),
+            )
+        }
+    });
+
+    let opts = BuildOptions {
+        tag: "api:dev".into(),
+        dockerfile: "Dockerfile".into(),
+        ..Default::default()
+    };
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    let id = client
+        .build_image("context".into(), &opts, &[], tx)
+        .await
+        .unwrap();
+    assert_eq!(id, "sha256:77aa");
+    assert_eq!(rx.recv().await.unwrap(), "STEP 1/1: FROM alpine");
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(
+        calls[0].path,
+        "/v4.0.0/libpod/build?t=api%3Adev&dockerfile=Dockerfile&buildargs=%7B%7D"
+    );
+    assert!(!calls[0].headers.contains_key("x-registry-config"));
+    assert_eq!(calls[1].path, "/v4.0.0/libpod/images/api%3Adev/json");
+}
+
+#[tokio::test]
+async fn archive_uses_libpod_endpoints() {
+    let (_dir, socket, captured) = stub_engine(|method, _| match method {
+        "GET" => (200, "tar-bytes".into()),
+        _ => (200, String::new()),
+    });
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    let archive = client.get_archive("web", "/etc/nginx").await.unwrap();
+    assert_eq!(collect_archive(archive).await.unwrap(), b"tar-bytes");
+    client
+        .put_archive("web", "/srv", "upload".into())
+        .await
+        .unwrap();
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(
+        calls[0].path,
+        "/v4.0.0/libpod/containers/web/archive?path=%2Fetc%2Fnginx"
+    );
+    assert_eq!(calls[1].method, "PUT");
+    assert_eq!(
+        calls[1].path,
+        "/v4.0.0/libpod/containers/web/archive?path=%2Fsrv"
+    );
+    assert_eq!(calls[1].headers["content-type"], "application/x-tar");
+    assert_eq!(calls[1].body, "upload");
+}
+
+#[tokio::test]
+async fn networks_use_libpod_endpoints_and_spec() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/containers/create") {
+            (201, r#"{"Id":"abc123","Warnings":[]}"#.into())
+        } else if path.ends_with("/networks/create") {
+            (
+                200,
+                r#"{"name":"backend","id":"net123","driver":"bridge","subnets":[{"subnet":"10.89.0.0/24"}],"internal":false}"#
+                    .into(),
+            )
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    let cfg = NetworkConfig {
+        name: "backend".into(),
+        subnet: Some("10.89.0.0/24".into()),
+        ..Default::default()
+    };
+    assert_eq!(client.create_network(&cfg).await.unwrap(), "net123");
+
+    let networks = [NetworkAttachment {
+        name: "backend".into(),
+        aliases: vec!["db".into()],
+    }];
+    let config = ContainerConfig {
+        name: "db",
+        image: "postgres:16",
+        networks: &networks,
+        ..Default::default()
+    };
+    client.run(config).await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].path, "/v4.0.0/libpod/networks/create");
+    let body: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(
+        body,
+        json!({"name": "backend", "driver": "bridge", "internal": false, "subnets": [{"subnet": "10.89.0.0/24"}]})
+    );
+    let spec: Value = serde_json::from_str(&calls[1].body).unwrap();
+    assert_eq!(spec["netns"], json!({"nsmode": "bridge"}));
+    assert_eq!(spec["networks"], json!({"backend": {"aliases": ["db"]}}));
+}
+
+#[tokio::test]
+async fn volumes_use_libpod_endpoints() {
+    let (_dir, socket, captured) = stub_engine(|method, path| {
+        match (method, path) {
+        ("POST", _) => (
+            201,
+            r#"{"Name":"pgdata","Driver":"local","Mountpoint":"/home/me/.local/share/containers/storage/volumes/pgdata/_data","CreatedAt":"2024-05-01T10:00:00Z","Labels":{},"Options":{}}"#
+                .into(),
+        ),
+        ("GET", "/v4.0.0/libpod/system/df") => (
+            200,
+            r#"{"Volumes":[{"VolumeName":"pgdata","Links":1,"Size":2048,"ReclaimableSize":0}]}"#
+                .into(),
+        ),
+        ("GET", "/v1.41/containers/json?all=1") => (
+            200,
+            r#"[{"Names":["/db"],"Mounts":[{"Type":"volume","Name":"pgdata","Destination":"/data"}]}]"#
+                .into(),
+        ),
+        _ => (204, String::new()),
+    }
+    });
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    let cfg = VolumeConfig {
+        name: "pgdata".into(),
+        labels: BTreeMap::from([("team".into(), "data".into())]),
+        ..Default::default()
+    };
+    assert_eq!(client.create_volume(&cfg).await.unwrap().name, "pgdata");
+    let usage = client.volume_usage().await.unwrap();
+    assert_eq!(usage["pgdata"].size, Some(2048));
+    assert_eq!(usage["pgdata"].apps, ["db"]);
+    client.remove_volume("pgdata").await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    assert_eq!(calls[0].path, "/v4.0.0/libpod/volumes/create");
+    let body: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(
+        body,
+        json!({"Name": "pgdata", "Driver": "local", "Options": {}, "Label": {"team": "data"}})
+    );
+    assert_eq!(calls[3].method, "DELETE");
+    assert_eq!(calls[3].path, "/v4.0.0/libpod/volumes/pgdata");
+}
+
+#[tokio::test]
+async fn resource_limits_map_to_libpod_resources() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/containers/create") {
+            (201, r#"{"Id":"abc123","Warnings":[]}"#.into())
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let limits = ResourceLimits {
+        cpus: Some(0.5),
+        cpu_shares: Some(256),
+        memory: Some(134_217_728),
+        memory_swap: Some(-1),
+        pids_limit: Some(64),
+        ..Default::default()
+    };
+    let config = ContainerConfig {
+        name: "web",
+        image: "nginx:latest",
+        limits: Some(&limits),
+        ..Default::default()
+    };
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    client.run(config).await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    let spec: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(
+        spec["resource_limits"],
+        json!({
+            "cpu": {"period": 100000, "quota": 50000, "shares": 256},
+            "memory": {"limit": 134217728, "swap": -1},
+            "pids": {"limit": 64},
+        })
+    );
+
+    // libpod reports CPU limits as a quota over a period.
+    let inspected: ContainerInspect = serde_json::from_value(json!({
+        "HostConfig": {"CpuQuota": 50000, "CpuPeriod": 100000, "Memory": 134217728, "MemorySwap": -1, "PidsLimit": 64}
+    }))
+    .unwrap();
+    assert_eq!(
+        ResourceLimits::from_host_config(&inspected.host_config),
+        ResourceLimits {
+            cpus: Some(0.5),
+            cpu_shares: None,
+            ..limits
+        }
+    );
+}
+
+#[tokio::test]
+async fn port_mappings_carry_protocol_and_host_ip() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/containers/create") {
+            (201, r#"{"Id":"abc123","Warnings":[]}"#.into())
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let ports = [
+        PortMapping::tcp(8080, 80),
+        PortMapping {
+            host: 5514,
+            container: 514,
+            protocol: PortProtocol::Udp,
+            host_ip: Some("127.0.0.1".into()),
+        },
+    ];
+    let config = ContainerConfig {
+        name: "web",
+        image: "nginx:latest",
+        ports: &ports,
+        ..Default::default()
+    };
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    client.run(config).await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    let spec: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(
+        spec["portmappings"],
+        json!([
+            {"host_port": 8080, "container_port": 80, "protocol": "tcp"},
+            {"host_port": 5514, "container_port": 514, "protocol": "udp", "host_ip": "127.0.0.1"},
+        ])
+    );
+}
+
+#[tokio::test]
+async fn process_overrides_map_to_the_libpod_spec() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/containers/create") {
+            (201, r#"{"Id":"abc123","Warnings":[]}"#.into())
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let process = ProcessOverrides {
+        command: Some(vec!["worker".into(), "--queue=default".into()]),
+        entrypoint: Some(vec!["/entrypoint.sh".into()]),
+        working_dir: Some("/srv/app".into()),
+        user: Some("app".into()),
+        hostname: Some("worker-1".into()),
+    };
+    let config = ContainerConfig {
+        name: "worker",
+        image: "myapp:latest",
+        process: Some(&process),
+        ..Default::default()
+    };
+
+    let client = PodmanClient::new(Endpoint::Unix(socket));
+    client.run(config).await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    let spec: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(spec["command"], json!(["worker", "--queue=default"]));
+    assert_eq!(spec["entrypoint"], json!(["/entrypoint.sh"]));
+    assert_eq!(spec["work_dir"], "/srv/app");
+    assert_eq!(spec["user"], "app");
+    assert_eq!(spec["hostname"], "worker-1");
+
+    // libpod before 5.0 reports the entrypoint as a single string.
+    let inspected: ContainerInspect = serde_json::from_value(json!({
+        "Id": "0123456789abcdef",
+        "Config": {
+            "Cmd": ["worker", "--queue=default"], "Entrypoint": "/entrypoint.sh", "WorkingDir": "/srv/app", "User": "app", "Hostname": "worker-1",
+            "Labels": {"lightshuttle.process": process.to_label()}
+        }
+    }))
+    .unwrap();
+    assert_eq!(
+        inspected.config.entrypoint,
+        Some(vec!["/entrypoint.sh".to_string()])
+    );
+    assert_eq!(ProcessOverrides::from_inspect(&inspected), process);
+}
+
+#[tokio::test]
+async fn security_options_map_to_the_libpod_spec() {
+    let (_dir, socket, captured) = stub_engine(|_, path| {
+        if path.ends_with("/containers/create") {
+            (201, r#"{"Id":"abc123","Warnings":[]}"#.into())
+        } else {
+            (204, String::new())
+        }
+    });
+
+    let profile = concat!(env!("CARGO_MANIFEST_DIR"), "/../seccomp-profile.json");
+    let security = SecurityOptions {
+        cap_add: vec!["CAP_NET_BIND_SERVICE".into()],
+        cap_drop: vec!["ALL".into()],
+        read_only: true,
+        no_new_privileges: true,
+        tmpfs: BTreeMap::from([("/tmp".into(), "size=64m,mode=1777".into())]),
+        apparmor: Some("docker-default".into()),
+    };
+    let config = ContainerConfig {
+        name: "web",
+        image: "nginx:latest",
+        security: Some(&security),
+        ..Default::default()
+    };
+
+    let client = PodmanClient::new(Endpoint::Unix(socket))
+        .with_seccomp(SeccompProfile::load(profile).unwrap());
+    client.run(config).await.unwrap();
+
+    let calls = captured.lock().unwrap();
+    let spec: Value = serde_json::from_str(&calls[0].body).unwrap();
+    assert_eq!(spec["seccomp_profile_path"], profile);
+    assert_eq!(spec["cap_add"], json!(["CAP_NET_BIND_SERVICE"]));
+    assert_eq!(spec["cap_drop"], json!(["ALL"]));
+    assert_eq!(spec["read_only_filesystem"], true);
+    assert_eq!(spec["no_new_privileges"], true);
+    assert_eq!(spec["apparmor_profile"], "docker-default");
+    assert_eq!(
+        spec["mounts"],
+        json!([{
+            "type": "tmpfs",
+            "source": "tmpfs",
+            "destination": "/tmp",
+            "options": ["size=64m", "mode=1777"],
+        }])
+    );
+}
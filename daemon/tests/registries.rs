@@ -0,0 +1,241 @@
+mod common;
+
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE, Engine as _};
+use common::{send_as, send_text, setup_with_keys};
+use lightshuttle_core::{
+    docker::{registry_of, RegistryAuth},
+    services::credentials::{normalize_registry, CredentialStore},
+};
+use serde_json::{json, Value};
+
+/// `alpha` and `beta` are separate namespaces; `auditor` may only read.
+const KEYS: &str = r#"{
+    "alpha": {"name": "team-a", "read": true, "write": true},
+    "beta": {"name": "team-b", "read": true, "write": true},
+    "auditor": {"name": "team-a", "read": true, "write": false}
+}"#;
+
+const KEY: [u8; 32] = [7; 32];
+
+fn login() -> Value {
+    json!({"username": "ci-bot", "password": "hunter2-token"})
+}
+
+#[test]
+fn registries_are_normalized() {
+    assert_eq!(
+        normalize_registry("https://index.docker.io/v1/").unwrap(),
+        "docker.io"
+    );
+    assert_eq!(
+        normalize_registry("Registry.Example.com:5000").unwrap(),
+        "registry.example.com:5000"
+    );
+    assert!(normalize_registry(" ").is_err());
+    assert_eq!(registry_of("nginx"), "docker.io");
+    assert_eq!(registry_of("ghcr.io/acme/api:1.0"), "ghcr.io");
+    assert_eq!(registry_of("localhost:5000/api"), "localhost:5000");
+}
+
+#[test]
+fn registry_auth_header_is_base64url_json() {
+    let auth = RegistryAuth {
+        username: "ci".into(),
+        password: "p?ss>".into(),
+        server_address: "ghcr.io".into(),
+    };
+    let decoded: Value = serde_json::from_slice(&URL_SAFE.decode(auth.header()).unwrap()).unwrap();
+    assert_eq!(
+        decoded,
+        json!({"username": "ci", "password": "p?ss>", "serveraddress": "ghcr.io"})
+    );
+}
+
+#[test]
+fn store_is_encrypted_on_disk() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("registries.json");
+
+    let store = CredentialStore::open(&path, &KEY).unwrap();
+    store
+        .put("team-a", "ghcr.io", "ci-bot", "hunter2-token")
+        .unwrap();
+    let on_disk = std::fs::read_to_string(&path).unwrap();
+    assert!(!on_disk.contains("hunter2-token"));
+    assert!(!on_disk.contains("ci-bot"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let reopened = CredentialStore::open(&path, &KEY).unwrap();
+    let auth = reopened.auth_for("team-a", "ghcr.io/acme/api:1.0").unwrap();
+    assert_eq!(auth.username, "ci-bot");
+    assert_eq!(auth.password, "hunter2-token");
+    assert!(reopened
+        .auth_for("team-b", "ghcr.io/acme/api:1.0")
+        .is_none());
+    assert!(reopened.auth_for("team-a", "nginx").is_none());
+
+    assert!(CredentialStore::open(&path, &[8; 32]).is_err());
+    assert!(CredentialStore::open(&path, &[7; 16]).is_err());
+}
+
+#[tokio::test]
+async fn credentials_are_never_echoed() {
+    let (_, app) = setup_with_keys(KEYS);
+
+    let (status, body) = send_text(
+        &app,
+        Some("alpha"),
+        "PUT",
+        "/api/v1/registries/ghcr.io",
+        Some(login()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(!body.contains("hunter2-token"));
+    let created: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(created["registry"], "ghcr.io");
+    assert_eq!(created["username"], "ci-bot");
+
+    let (status, body) = send_text(
+        &app,
+        Some("alpha"),
+        "PUT",
+        "/api/v1/registries/GHCR.io",
+        Some(login()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body.contains("hunter2-token"));
+
+    let (status, body) = send_text(&app, Some("alpha"), "GET", "/api/v1/registries", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body.contains("hunter2-token"));
+    let list: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    let (status, _) = send_as(
+        &app,
+        "alpha",
+        "PUT",
+        "/api/v1/registries/ghcr.io",
+        Some(json!({"username": "ci-bot", "password": ""})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn credentials_are_scoped_per_namespace() {
+    let (_, app) = setup_with_keys(KEYS);
+    send_as(
+        &app,
+        "alpha",
+        "PUT",
+        "/api/v1/registries/quay.io",
+        Some(login()),
+    )
+    .await;
+
+    let (_, body) = send_as(&app, "beta", "GET", "/api/v1/registries", None).await;
+    assert_eq!(body, json!([]));
+    let (status, _) = send_as(&app, "beta", "DELETE", "/api/v1/registries/quay.io", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_as(
+        &app,
+        "auditor",
+        "DELETE",
+        "/api/v1/registries/quay.io",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_as(
+        &app,
+        "auditor",
+        "PUT",
+        "/api/v1/registries/quay.io",
+        Some(login()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send_as(&app, "alpha", "DELETE", "/api/v1/registries/quay.io", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, body) = send_as(&app, "alpha", "GET", "/api/v1/registries", None).await;
+    assert_eq!(body, json!([]));
+}
+
+#[tokio::test]
+async fn pulls_use_the_namespace_credentials() {
+    let (docker, app) = setup_with_keys(KEYS);
+    send_as(
+        &app,
+        "alpha",
+        "PUT",
+        "/api/v1/registries/ghcr.io",
+        Some(login()),
+    )
+    .await;
+
+    let pull = |key: &'static str, image: &'static str| {
+        let app = app.clone();
+        async move {
+            send_as(
+                &app,
+                key,
+                "POST",
+                "/api/v1/images/pull",
+                Some(json!({"image": image})),
+            )
+            .await
+        }
+    };
+    assert_eq!(
+        pull("alpha", "ghcr.io/acme/api:1.0").await.0,
+        StatusCode::OK
+    );
+    assert_eq!(pull("beta", "ghcr.io/acme/api:1.0").await.0, StatusCode::OK);
+    assert_eq!(pull("alpha", "redis:7").await.0, StatusCode::OK);
+
+    let pulls = docker.pulls();
+    let auth = pulls[0].1.as_ref().unwrap();
+    assert_eq!(auth.username, "ci-bot");
+    assert_eq!(auth.password, "hunter2-token");
+    assert_eq!(auth.server_address, "ghcr.io");
+    assert!(pulls[1].1.is_none());
+    assert!(pulls[2].1.is_none());
+}
+
+#[tokio::test]
+async fn creating_an_app_pulls_private_images_with_credentials() {
+    let (docker, app) = setup_with_keys(KEYS);
+    send_as(
+        &app,
+        "alpha",
+        "PUT",
+        "/api/v1/registries/ghcr.io",
+        Some(login()),
+    )
+    .await;
+
+    let payload = json!({
+        "name": "api",
+        "image": "ghcr.io/acme/api:1.0",
+        "ports": [8080],
+        "container_port": 80
+    });
+    let (status, _) = send_as(&app, "alpha", "POST", "/api/v1/apps", Some(payload)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let pulls = docker.pulls();
+    assert_eq!(pulls.len(), 1);
+    assert_eq!(pulls[0].0, "ghcr.io/acme/api:1.0");
+    assert_eq!(pulls[0].1.as_ref().unwrap().username, "ci-bot");
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{body::Bytes, http::StatusCode, Router};
+use common::send;
+use lightshuttle_core::{
+    api::routes::router_with_client,
+    docker::BuildOptions,
+    errors::Error,
+    services::{
+        docker::DockerClient,
+        memory::InMemoryDockerClient,
+        replay::{Outcome, RecordingDockerClient, ReplayDockerClient},
+    },
+};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+/// Requests exercising the app lifecycle, with the responses they got.
+async fn scenario(app: &Router) -> Vec<(StatusCode, Value)> {
+    let create = json!({
+        "name": "web",
+        "image": "nginx:latest",
+        "ports": [8080],
+        "container_port": 80,
+        "labels": {"team": "front"},
+        "env": {"MODE": "prod"},
+        "restart_policy": "always"
+    });
+    let mut responses = vec![send(app, "POST", "/api/v1/apps", Some(create)).await];
+    for (method, uri) in [
+        ("GET", "/api/v1/apps/web"),
+        ("GET", "/api/v1/apps"),
+        ("POST", "/api/v1/apps/web/stop"),
+        ("GET", "/api/v1/apps/web/status"),
+        ("POST", "/api/v1/apps/ghost/start"),
+        ("DELETE", "/api/v1/apps/web"),
+        ("GET", "/api/v1/apps/web"),
+    ] {
+        responses.push(send(app, method, uri, None).await);
+    }
+    responses
+}
+
+#[tokio::test]
+async fn recorded_calls_replay_the_router_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let fixture = dir.path().join("docker/apps.json");
+
+    let recorder = Arc::new(RecordingDockerClient::new(
+        Arc::new(InMemoryDockerClient::new()),
+        &fixture,
+    ));
+    let recorded = scenario(&router_with_client(recorder.clone())).await;
+    assert_eq!(recorded[0].0, StatusCode::CREATED);
+    assert_eq!(recorded[5].0, StatusCode::NOT_FOUND);
+    assert!(recorder.interactions().iter().any(|i| i.call == "run"));
+    let missing = recorder
+        .interactions()
+        .into_iter()
+        .find(|i| i.call == "start")
+        .unwrap();
+    assert!(matches!(missing.result, Outcome::Err(ref e) if e.kind == "ContainerNotFound"));
+
+    let replay = Arc::new(ReplayDockerClient::open(&fixture).unwrap());
+    let replayed = scenario(&router_with_client(replay.clone())).await;
+    let without_trace = |responses: Vec<(StatusCode, Value)>| -> Vec<(StatusCode, Value)> {
+        responses
+            .into_iter()
+            .map(|(status, mut body)| {
+                if let Some(body) = body.as_object_mut() {
+                    body.remove("trace_id");
+                }
+                (status, body)
+            })
+            .collect()
+    };
+    assert_eq!(without_trace(replayed), without_trace(recorded));
+    replay.assert_done();
+}
+
+/// Tar build context holding a single Dockerfile.
+fn context(dockerfile: &str) -> Bytes {
+    let mut archive = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(dockerfile.len() as u64);
+    header.set_mode(0o644);
+    archive
+        .append_data(&mut header, "Dockerfile", dockerfile.as_bytes())
+        .unwrap();
+    archive.into_inner().unwrap().into()
+}
+
+async fn build(docker: &dyn DockerClient, context: Bytes) -> (String, Vec<String>) {
+    let opts = BuildOptions {
+        tag: "acme/api:1".into(),
+        dockerfile: "Dockerfile".into(),
+        ..Default::default()
+    };
+    let (tx, mut rx) = mpsc::channel(16);
+    let id = docker.build_image(context, &opts, &[], tx).await.unwrap();
+    let mut output = Vec::new();
+    while let Some(line) = rx.recv().await {
+        output.push(line);
+    }
+    (id, output)
+}
+
+#[tokio::test]
+async fn streamed_calls_are_replayed() {
+    let dir = tempfile::tempdir().unwrap();
+    let fixture = dir.path().join("build.json");
+    let context = context("FROM alpine:3.20\nRUN echo hello\n");
+
+    let recorder = RecordingDockerClient::new(Arc::new(InMemoryDockerClient::new()), &fixture);
+    let recorded = build(&recorder, context.clone()).await;
+    assert_eq!(recorded.1[0], "Step 1/2 : FROM alpine:3.20");
+    let interaction = &recorder.interactions()[0];
+    assert_eq!(interaction.stream.len(), recorded.1.len());
+    assert!(interaction.args["context"]
+        .as_str()
+        .unwrap()
+        .starts_with("sha256:"));
+
+    let replay = ReplayDockerClient::open(&fixture).unwrap();
+    assert_eq!(build(&replay, context).await, recorded);
+    replay.assert_done();
+}
+
+#[tokio::test]
+async fn errors_keep_their_kind() {
+    let fixture = json!([
+        {"call": "inspect", "args": {"name": "web"}, "result": {"err": {"kind": "ContainerNotFound", "detail": null}}},
+        {"call": "remove_volume", "args": {"name": "data"}, "result": {"err": {"kind": "Conflict", "detail": "volume is in use"}}}
+    ]);
+    let replay = ReplayDockerClient::new(serde_json::from_value(fixture).unwrap());
+    assert!(matches!(
+        replay.inspect("web").await,
+        Err(Error::ContainerNotFound)
+    ));
+    assert!(matches!(
+        replay.remove_volume("data").await,
+        Err(Error::Conflict(ref m)) if m == "volume is in use"
+    ));
+    replay.assert_done();
+}
+
+#[tokio::test]
+#[should_panic(expected = "Unexpected Docker call stop({\"name\":\"db\"})")]
+async fn unexpected_calls_fail_loudly() {
+    let fixture = json!([{"call": "stop", "args": {"name": "web"}, "result": {"ok": null}}]);
+    let replay = ReplayDockerClient::new(serde_json::from_value(fixture).unwrap());
+    let _ = replay.stop("db").await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "Recorded Docker calls never made: stop({\"name\":\"web\"})")]
+async fn unused_interactions_fail_the_test() {
+    let fixture = json!([
+        {"call": "start", "args": {"name": "web"}, "result": {"ok": null}},
+        {"call": "stop", "args": {"name": "web"}, "result": {"ok": null}}
+    ]);
+    let replay = ReplayDockerClient::new(serde_json::from_value(fixture).unwrap());
+    replay.start("web").await.unwrap();
+    replay.assert_done();
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use lightshuttle_core::{
+    api::routes::router_with_client,
+    docker::{EngineInfo, MIN_API_VERSION},
+    services::{docker::check_engine, memory::InMemoryDockerClient},
+};
+use serde_json::Value;
+use tower::ServiceExt;
+
+fn old_engine() -> EngineInfo {
+    EngineInfo {
+        version: "19.03.15".into(),
+        api_version: "1.40".into(),
+        os: "linux".into(),
+        arch: "amd64".into(),
+        storage_driver: "overlay2".into(),
+        cgroup_version: Some("1".into()),
+        rootless: false,
+        security_options: vec!["apparmor".into(), "seccomp".into()],
+    }
+}
+
+#[tokio::test]
+async fn system_info_reports_engine_capabilities() {
+    let docker = Arc::new(InMemoryDockerClient::new());
+    docker.set_engine_info(old_engine());
+    let app = router_with_client(docker);
+
+    let request = Request::builder()
+        .uri("/api/v1/system/info")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let info: Value =
+        serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes()).unwrap();
+
+    assert_eq!(info["min_api_version"], MIN_API_VERSION);
+    assert!(info["features"]
+        .as_array()
+        .unwrap()
+        .contains(&"testing".into()));
+    let engine = &info["engine"];
+    assert_eq!(engine["version"], "19.03.15");
+    assert_eq!(engine["api_version"], "1.40");
+    assert_eq!(engine["storage_driver"], "overlay2");
+    assert_eq!(engine["cgroup_version"], "1");
+    assert_eq!(engine["rootless"], false);
+    assert_eq!(
+        engine["security_options"],
+        serde_json::json!(["apparmor", "seccomp"])
+    );
+}
+
+#[tokio::test]
+async fn engines_below_the_minimum_api_are_refused_or_warned_about() {
+    let docker = InMemoryDockerClient::new();
+    check_engine(&docker, true).await.unwrap();
+
+    docker.set_engine_info(old_engine());
+    let err = check_engine(&docker, true).await.unwrap_err();
+    assert!(err.to_string().contains("1.40"), "{err}");
+    check_engine(&docker, false).await.unwrap();
+
+    // Versions that cannot be parsed are not trusted either.
+    docker.set_engine_info(EngineInfo {
+        api_version: "unknown".into(),
+        ..old_engine()
+    });
+    assert!(check_engine(&docker, true).await.is_err());
+}
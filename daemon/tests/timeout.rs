@@ -0,0 +1,131 @@
+#![cfg(unix)]
+
+use std::{
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    sync::{Arc, Once},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use lightshuttle_core::{
+    api::routes::router_with_client,
+    errors::Error,
+    services::{
+        docker::{DockerClient, ShellDockerClient},
+        memory::InMemoryDockerClient,
+        timeout::{TimeoutDockerClient, Timeouts},
+    },
+};
+use serde_json::Value;
+use tower::ServiceExt;
+
+fn timeouts(deadline: Duration) -> Timeouts {
+    Timeouts {
+        run: Some(deadline),
+        stop: Some(deadline),
+        inspect: Some(deadline),
+        logs: Some(deadline),
+        pull: Some(deadline),
+    }
+}
+
+static FAKE_DOCKER: Once = Once::new();
+
+/// Puts a `docker` that records its pid and hangs first on the `PATH`;
+/// returns the pid file.
+fn hanging_docker() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lightshuttle-timeout-{}", std::process::id()));
+    let pid = dir.join("pid");
+    FAKE_DOCKER.call_once(|| {
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("docker");
+        std::fs::write(
+            &script,
+            format!("#!/bin/sh\necho $$ > {}\nexec sleep 30\n", pid.display()),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let path = std::env::var("PATH").unwrap_or_default();
+        std::env::set_var("PATH", format!("{}:{path}", dir.display()));
+    });
+    pid
+}
+
+/// `true` once the process is gone or a zombie waiting to be reaped.
+fn is_dead(pid: &str) -> bool {
+    match std::fs::read_to_string(format!("/proc/{pid}/stat")) {
+        Ok(stat) => stat
+            .rsplit_once(") ")
+            .is_some_and(|(_, rest)| rest.starts_with('Z')),
+        Err(_) => true,
+    }
+}
+
+#[tokio::test]
+async fn slow_operations_answer_504_with_the_operation() {
+    let docker = Arc::new(InMemoryDockerClient::new().with_latency(Duration::from_millis(300)));
+    docker.insert("web", "nginx:latest", "running");
+    let client = TimeoutDockerClient::new(docker.clone(), timeouts(Duration::from_millis(50)));
+    let app = router_with_client(Arc::new(client));
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/apps/web/stop")
+        .body(Body::empty())
+        .unwrap();
+    let started = Instant::now();
+    let response = app.oneshot(request).await.unwrap();
+    assert!(started.elapsed() < Duration::from_millis(300));
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json["code"], 504);
+    assert_eq!(json["message"], "Docker operation timed out");
+    assert_eq!(json["details"], "stop");
+
+    // The cancelled stop never reached the container.
+    assert_eq!(docker.state("web").as_deref(), Some("running"));
+}
+
+#[tokio::test]
+async fn operations_within_their_deadline_are_untouched() {
+    let docker = Arc::new(InMemoryDockerClient::new().with_latency(Duration::from_millis(10)));
+    docker.insert("web", "nginx:latest", "running");
+    let client = TimeoutDockerClient::new(
+        docker,
+        Timeouts {
+            logs: None,
+            ..timeouts(Duration::from_secs(5))
+        },
+    );
+    client.stop("web").await.unwrap();
+    assert_eq!(client.inspect("web").await.unwrap().state.status, "exited");
+    assert!(client.logs("web").await.is_ok());
+}
+
+#[tokio::test]
+async fn timed_out_cli_calls_kill_the_docker_process() {
+    let pid_file = hanging_docker();
+    let client = TimeoutDockerClient::new(
+        Arc::new(ShellDockerClient::default()),
+        timeouts(Duration::from_millis(500)),
+    );
+
+    let result = client.inspect("web").await;
+    assert!(matches!(result, Err(Error::Timeout(ref op)) if op == "inspect"));
+
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !is_dead(pid.trim()) {
+        assert!(
+            Instant::now() < deadline,
+            "docker process {pid} still running"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
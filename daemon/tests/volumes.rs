@@ -0,0 +1,162 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{send, setup};
+use lightshuttle_core::services::docker::DockerClient;
+use serde_json::{json, Value};
+
+fn app_with(name: &str, volumes: Value) -> Value {
+    json!({
+        "name": name,
+        "image": "postgres:16",
+        "ports": [],
+        "container_port": 5432,
+        "restart_policy": "always",
+        "volumes": volumes
+    })
+}
+
+#[tokio::test]
+async fn volumes_can_be_created_listed_and_removed() {
+    let (_, app) = setup();
+
+    let (status, volume) = send(
+        &app,
+        "POST",
+        "/api/v1/volumes",
+        Some(json!({
+            "name": "pgdata",
+            "driver_opts": {"type": "tmpfs", "device": "tmpfs"},
+            "labels": {"team": "data"}
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(volume["name"], "pgdata");
+    assert_eq!(volume["driver"], "local");
+    assert_eq!(
+        volume["options"],
+        json!({"type": "tmpfs", "device": "tmpfs"})
+    );
+    assert_eq!(volume["labels"], json!({"team": "data"}));
+    assert_eq!(volume["apps"], json!([]));
+
+    let (status, volumes) = send(&app, "GET", "/api/v1/volumes", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(volumes.as_array().unwrap().len(), 1);
+    assert_eq!(volumes[0]["name"], "pgdata");
+    assert_eq!(volumes[0]["size"], 0);
+
+    let (status, _) = send(&app, "DELETE", "/api/v1/volumes/pgdata", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "GET", "/api/v1/volumes/pgdata", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, "DELETE", "/api/v1/volumes/pgdata", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn invalid_or_duplicate_volumes_are_rejected() {
+    let (_, app) = setup();
+
+    for body in [
+        json!({"name": ""}),
+        json!({"name": "/srv/data"}),
+        json!({"name": "data", "driver": "local;rm"}),
+        json!({"name": "data", "driver_opts": {"o=x": "y"}}),
+        json!({"name": "data", "labels": {"": "x"}}),
+    ] {
+        let (status, _) = send(&app, "POST", "/api/v1/volumes", Some(body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    }
+
+    let body = json!({"name": "data"});
+    let (status, _) = send(&app, "POST", "/api/v1/volumes", Some(body.clone())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = send(&app, "POST", "/api/v1/volumes", Some(body)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn apps_mount_named_volumes_read_only() {
+    let (docker, app) = setup();
+    send(
+        &app,
+        "POST",
+        "/api/v1/volumes",
+        Some(json!({"name": "pgdata"})),
+    )
+    .await;
+
+    let volumes = json!([
+        {"volume": "pgdata", "target": "/var/lib/postgresql/data"},
+        {"volume": "seed", "target": "/docker-entrypoint-initdb.d", "read_only": true},
+        "/srv/conf:/etc/postgresql"
+    ]);
+    let (status, _) = send(&app, "POST", "/api/v1/apps", Some(app_with("db", volumes))).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let binds = docker.inspect("db").await.unwrap().host_config.binds;
+    assert_eq!(
+        binds.unwrap(),
+        [
+            "pgdata:/var/lib/postgresql/data",
+            "seed:/docker-entrypoint-initdb.d:ro",
+            "/srv/conf:/etc/postgresql"
+        ]
+    );
+
+    // Volumes an app references are created on first use.
+    docker.set_volume_size("pgdata", 4096);
+    let (status, pgdata) = send(&app, "GET", "/api/v1/volumes/pgdata", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(pgdata["apps"], json!(["db"]));
+    assert_eq!(pgdata["size"], 4096);
+    let (status, seed) = send(&app, "GET", "/api/v1/volumes/seed", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(seed["apps"], json!(["db"]));
+
+    for volumes in [
+        json!([{"volume": "bad name", "target": "/data"}]),
+        json!([{"volume": "data", "target": "data"}]),
+        json!([{"volume": "data", "target": "/data:rw"}]),
+    ] {
+        let (status, _) = send(
+            &app,
+            "POST",
+            "/api/v1/apps",
+            Some(app_with("web", volumes.clone())),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{volumes}");
+    }
+}
+
+#[tokio::test]
+async fn mounted_volumes_cannot_be_removed() {
+    let (docker, app) = setup();
+    let volumes = json!([{"volume": "pgdata", "target": "/var/lib/postgresql/data"}]);
+    send(&app, "POST", "/api/v1/apps", Some(app_with("db", volumes))).await;
+    send(&app, "POST", "/api/v1/apps/db/stop", None).await;
+    assert_eq!(docker.state("db").as_deref(), Some("exited"));
+    let volumes = json!([{"volume": "pgdata", "target": "/data", "read_only": true}]);
+    send(
+        &app,
+        "POST",
+        "/api/v1/apps",
+        Some(app_with("backup", volumes)),
+    )
+    .await;
+
+    let (status, error) = send(&app, "DELETE", "/api/v1/volumes/pgdata", None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        error["details"],
+        "Volume pgdata is mounted by apps: backup, db"
+    );
+
+    send(&app, "DELETE", "/api/v1/apps/backup", None).await;
+    send(&app, "DELETE", "/api/v1/apps/db", None).await;
+    let (status, _) = send(&app, "DELETE", "/api/v1/volumes/pgdata", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}